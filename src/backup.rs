@@ -0,0 +1,576 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+const BACKUP_EXTENSION: &str = "bak";
+/// Suffix of the files a restore stages and of the scratch directory an
+/// archived snapshot is extracted to.
+const RESTORE_TMP_SUFFIX: &str = "restore-tmp";
+
+/// Finds all `.bak` backups under `root` and pairs each with the file it backs up.
+pub fn find_backups(root: &Path) -> Vec<(PathBuf, PathBuf)> {
+    let mut backups: Vec<(PathBuf, PathBuf)> = WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git")
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| e.path().extension().and_then(|x| x.to_str()) == Some(BACKUP_EXTENSION))
+        .map(|e| {
+            let backup = e.path().to_path_buf();
+            let original = backup.with_extension("");
+            (backup, original)
+        })
+        .collect();
+    backups.sort();
+    backups
+}
+
+fn staging_path(original: &Path) -> PathBuf {
+    let mut name = original.as_os_str().to_owned();
+    name.push(format!(".{RESTORE_TMP_SUFFIX}"));
+    PathBuf::from(name)
+}
+
+/// Default directory (relative to the project root) holding one snapshot per run.
+pub const SNAPSHOT_DIR: &str = ".mule-migrate/backups";
+
+/// File at the root of a snapshot listing the files its run moved and
+/// created.
+const MANIFEST_FILE: &str = ".mule-migrate-manifest.json";
+
+/// The changes of a run that copies of the old files cannot undo. Paths are
+/// relative to the project root.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    renames: Vec<ManifestRename>,
+    #[serde(default)]
+    created: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ManifestRename {
+    from: String,
+    to: String,
+}
+
+/// How a snapshot is stored once its run finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum BackupCompression {
+    /// Plain directory mirroring the project layout
+    #[default]
+    None,
+    /// `<run-id>.zip`
+    Zip,
+    /// `<run-id>.tar.gz`
+    TarGz,
+}
+
+impl BackupCompression {
+    const ARCHIVES: [BackupCompression; 2] = [BackupCompression::Zip, BackupCompression::TarGz];
+
+    fn extension(self) -> Option<&'static str> {
+        match self {
+            BackupCompression::None => None,
+            BackupCompression::Zip => Some("zip"),
+            BackupCompression::TarGz => Some("tar.gz"),
+        }
+    }
+}
+
+/// Resolves the backups directory of a project: `backup_dir` relative to the
+/// project root, or [`SNAPSHOT_DIR`] if not given.
+pub fn backups_dir(project_root: &Path, backup_dir: Option<&str>) -> PathBuf {
+    project_root.join(backup_dir.unwrap_or(SNAPSHOT_DIR))
+}
+
+fn timestamp() -> String {
+    chrono::Utc::now().format("%Y%m%d-%H%M%S").to_string()
+}
+
+/// Copies of the files a run is about to change, stored under
+/// `<backups-dir>/<run-id>/` with the project layout mirrored.
+#[derive(Debug)]
+pub struct Snapshot {
+    project_root: PathBuf,
+    run_id: String,
+    dir: PathBuf,
+}
+
+impl Snapshot {
+    /// Creates an empty snapshot directory for a new run in `backups_dir`,
+    /// named after the current UTC time.
+    pub fn create(project_root: &Path, backups_dir: &Path) -> io::Result<Snapshot> {
+        Self::create_named(project_root, backups_dir, timestamp())
+    }
+
+    /// Like [`Snapshot::create`], appending the first eight characters of the
+    /// migration's run id to the name so the backups can be matched to the
+    /// run's report, e.g. `20250101-120000-3f2a9c1e`.
+    pub fn create_for_run(
+        project_root: &Path,
+        backups_dir: &Path,
+        run_id: &str,
+    ) -> io::Result<Snapshot> {
+        let short: String = run_id.chars().take(8).collect();
+        Self::create_named(
+            project_root,
+            backups_dir,
+            format!("{}-{short}", timestamp()),
+        )
+    }
+
+    fn create_named(project_root: &Path, backups_dir: &Path, base: String) -> io::Result<Snapshot> {
+        fs::create_dir_all(backups_dir)?;
+        // Keep the backups out of git
+        let ignore = backups_dir.join(".gitignore");
+        if !ignore.exists() {
+            fs::write(&ignore, "*\n")?;
+        }
+        let mut run_id = base.clone();
+        let mut n = 1;
+        while find_run(backups_dir, &run_id).is_some() {
+            n += 1;
+            run_id = format!("{base}-{n}");
+        }
+        let dir = backups_dir.join(&run_id);
+        fs::create_dir(&dir)?;
+        Ok(Snapshot {
+            project_root: project_root.to_path_buf(),
+            run_id,
+            dir,
+        })
+    }
+
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Copies `file` (a path under the project root) into the snapshot.
+    pub fn save(&self, file: &Path) -> io::Result<()> {
+        let relative = file.strip_prefix(&self.project_root).unwrap_or(file);
+        let target = self.dir.join(relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(file, target).map(|_| ())
+    }
+
+    fn relative(&self, file: &Path) -> String {
+        let relative = file.strip_prefix(&self.project_root).unwrap_or(file);
+        relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Records the files the run moves (`from`, `to`) and creates, so that
+    /// restoring the snapshot removes them again.
+    pub fn record(&self, renames: &[(&Path, &Path)], created: &[&Path]) -> io::Result<()> {
+        if renames.is_empty() && created.is_empty() {
+            return Ok(());
+        }
+        let manifest = Manifest {
+            renames: renames
+                .iter()
+                .map(|(from, to)| ManifestRename {
+                    from: self.relative(from),
+                    to: self.relative(to),
+                })
+                .collect(),
+            created: created.iter().map(|path| self.relative(path)).collect(),
+        };
+        let data = serde_json::to_string_pretty(&manifest).map_err(io::Error::other)?;
+        fs::write(self.dir.join(MANIFEST_FILE), data)
+    }
+
+    /// Stores the snapshot as requested and returns where it ended up. An
+    /// archive replaces the directory only once it was written completely.
+    pub fn finish(self, compression: BackupCompression) -> io::Result<PathBuf> {
+        let Some(extension) = compression.extension() else {
+            return Ok(self.dir);
+        };
+        let archive = self
+            .dir
+            .with_file_name(format!("{}.{extension}", self.run_id));
+        let files: Vec<(PathBuf, String)> = WalkDir::new(&self.dir)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| {
+                let relative = e.path().strip_prefix(&self.dir).unwrap_or(e.path());
+                let name = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                (e.path().to_path_buf(), name)
+            })
+            .collect();
+        let written = match compression {
+            BackupCompression::Zip => write_zip(&archive, &files),
+            _ => write_tar_gz(&archive, &files),
+        };
+        if let Err(e) = written {
+            fs::remove_file(&archive).ok();
+            return Err(e);
+        }
+        fs::remove_dir_all(&self.dir)?;
+        Ok(archive)
+    }
+}
+
+fn write_zip(archive: &Path, files: &[(PathBuf, String)]) -> io::Result<()> {
+    let mut zip = zip::ZipWriter::new(fs::File::create(archive)?);
+    let options = zip::write::SimpleFileOptions::default();
+    for (path, name) in files {
+        zip.start_file(name.as_str(), options)?;
+        io::copy(&mut fs::File::open(path)?, &mut zip)?;
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+fn write_tar_gz(archive: &Path, files: &[(PathBuf, String)]) -> io::Result<()> {
+    let gz = flate2::write::GzEncoder::new(fs::File::create(archive)?, Default::default());
+    let mut tar = tar::Builder::new(gz);
+    for (path, name) in files {
+        tar.append_path_with_name(path, name)?;
+    }
+    tar.into_inner()?.finish()?;
+    Ok(())
+}
+
+/// Finds the stored snapshot of `run_id`: its directory or its archive.
+fn find_run(backups_dir: &Path, run_id: &str) -> Option<(PathBuf, BackupCompression)> {
+    let dir = backups_dir.join(run_id);
+    if dir.is_dir() {
+        return Some((dir, BackupCompression::None));
+    }
+    BackupCompression::ARCHIVES
+        .into_iter()
+        .find_map(|compression| {
+            let extension = compression.extension()?;
+            let archive = backups_dir.join(format!("{run_id}.{extension}"));
+            archive.is_file().then_some((archive, compression))
+        })
+}
+
+/// Lists the snapshot run ids in `backups_dir`, oldest first.
+pub fn list_runs(backups_dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(backups_dir) else {
+        return Vec::new();
+    };
+    let mut runs: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().to_str()?.to_string();
+            if e.file_type().ok()?.is_dir() {
+                return (!name.ends_with(RESTORE_TMP_SUFFIX)).then_some(name);
+            }
+            BackupCompression::ARCHIVES
+                .into_iter()
+                .find_map(|compression| {
+                    let suffix = format!(".{}", compression.extension()?);
+                    name.strip_suffix(&suffix).map(str::to_string)
+                })
+        })
+        .collect();
+    runs.sort();
+    runs.dedup();
+    runs
+}
+
+/// Deletes all but the `keep` most recent runs in `backups_dir` and returns
+/// the ids of the deleted runs.
+pub fn prune_runs(backups_dir: &Path, keep: usize) -> io::Result<Vec<String>> {
+    let runs = list_runs(backups_dir);
+    let stale = &runs[..runs.len().saturating_sub(keep)];
+    for run in stale {
+        match find_run(backups_dir, run) {
+            Some((dir, BackupCompression::None)) => fs::remove_dir_all(dir)?,
+            Some((archive, _)) => fs::remove_file(archive)?,
+            None => {}
+        }
+    }
+    Ok(stale.to_vec())
+}
+
+/// Restores `(backup, original)` pairs.
+///
+/// All backups are first copied to staging files next to their originals;
+/// only once every copy succeeded are they renamed into place. If staging
+/// fails, nothing is restored and the project is left untouched.
+fn restore_files(
+    backups: &[(PathBuf, PathBuf)],
+    dry_run: bool,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    if dry_run {
+        for (backup, original) in backups {
+            log::info!(
+                "[DRY-RUN] Would restore {} from {}",
+                original.display(),
+                backup.display()
+            );
+        }
+        return Ok(backups
+            .iter()
+            .map(|(_, original)| original.display().to_string())
+            .collect());
+    }
+
+    // 1. Stage every backup next to its original
+    let mut staged = Vec::new();
+    for (backup, original) in backups {
+        let tmp = staging_path(original);
+        if let Err(e) = fs::copy(backup, &tmp) {
+            for (tmp, _) in &staged {
+                fs::remove_file(tmp).ok();
+            }
+            return Err(format!("Failed to stage backup {}: {e}", backup.display()).into());
+        }
+        staged.push((tmp, original));
+    }
+
+    // 2. Move the staged copies into place
+    let mut restored = Vec::new();
+    for (tmp, original) in staged {
+        fs::rename(&tmp, original)
+            .map_err(|e| format!("Failed to restore {}: {e}", original.display()))?;
+        log::info!("Restored {}", original.display());
+        restored.push(original.display().to_string());
+    }
+    Ok(restored)
+}
+
+/// Restores every `.bak` backup under `root` over its original file and
+/// deletes the backups afterwards.
+///
+/// Returns the list of restored files.
+pub fn rollback(root: &Path, dry_run: bool) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let backups = find_backups(root);
+    let restored = restore_files(&backups, dry_run)?;
+    if !dry_run {
+        for (backup, _) in &backups {
+            if let Err(e) = fs::remove_file(backup) {
+                log::warn!("Failed to remove backup {}: {e}", backup.display());
+            }
+        }
+    }
+    Ok(restored)
+}
+
+/// Restores the files of snapshot `run_id` in `backups_dir` over the
+/// project. The snapshot is kept, so the same run can be restored again.
+///
+/// Returns the list of restored files.
+pub fn restore(
+    project_root: &Path,
+    backups_dir: &Path,
+    run_id: &str,
+    dry_run: bool,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let Some((location, compression)) =
+        find_run(backups_dir, run_id).filter(|_| !run_id.is_empty())
+    else {
+        return Err(format!("No backup run '{run_id}' in {}", backups_dir.display()).into());
+    };
+    if compression == BackupCompression::None {
+        return restore_dir(project_root, &location, dry_run);
+    }
+    let dir = backups_dir.join(format!("{run_id}.{RESTORE_TMP_SUFFIX}"));
+    let file = fs::File::open(&location)?;
+    let extracted: Result<(), Box<dyn std::error::Error>> = match compression {
+        BackupCompression::Zip => zip::ZipArchive::new(file)
+            .and_then(|mut zip| zip.extract(&dir))
+            .map_err(Into::into),
+        _ => tar::Archive::new(flate2::read::GzDecoder::new(file))
+            .unpack(&dir)
+            .map_err(Into::into),
+    };
+    let restored = extracted.and_then(|()| restore_dir(project_root, &dir, dry_run));
+    fs::remove_dir_all(&dir).ok();
+    restored
+}
+
+/// Restores every file of the snapshot directory `dir` over the project,
+/// then removes the files the run moved them to or created, as listed in
+/// its manifest.
+fn restore_dir(
+    project_root: &Path,
+    dir: &Path,
+    dry_run: bool,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let manifest_path = dir.join(MANIFEST_FILE);
+    let mut backups: Vec<(PathBuf, PathBuf)> = WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && e.path() != manifest_path)
+        .map(|e| {
+            let relative = e.path().strip_prefix(dir).unwrap_or(e.path());
+            (e.path().to_path_buf(), project_root.join(relative))
+        })
+        .collect();
+    backups.sort();
+    let manifest: Manifest = match fs::read_to_string(&manifest_path) {
+        Ok(data) => serde_json::from_str(&data)
+            .map_err(|e| format!("Invalid manifest {}: {e}", manifest_path.display()))?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Manifest::default(),
+        Err(e) => return Err(format!("Failed to read {}: {e}", manifest_path.display()).into()),
+    };
+    let mut restored = restore_files(&backups, dry_run)?;
+    let added = manifest
+        .renames
+        .iter()
+        .map(|r| &r.to)
+        .chain(&manifest.created)
+        .map(|relative| project_root.join(relative))
+        // A file that existed before the run was restored above
+        .filter(|path| !backups.iter().any(|(_, original)| original == path));
+    for path in added {
+        if dry_run {
+            log::info!("[DRY-RUN] Would remove {}", path.display());
+        } else {
+            match fs::remove_file(&path) {
+                Ok(()) => log::info!("Removed {}", path.display()),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(format!("Failed to remove {}: {e}", path.display()).into()),
+            }
+        }
+        restored.push(format!("{} (removed)", path.display()));
+    }
+    Ok(restored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_rollback_restores_and_removes_backups() {
+        let dir = tempdir().unwrap();
+        let flows = dir.path().join("src/main/mule");
+        fs::create_dir_all(&flows).unwrap();
+        fs::write(dir.path().join("pom.xml"), "new pom").unwrap();
+        fs::write(dir.path().join("pom.xml.bak"), "old pom").unwrap();
+        fs::write(flows.join("flow.xml"), "new flow").unwrap();
+        fs::write(flows.join("flow.xml.bak"), "old flow").unwrap();
+
+        let restored = rollback(dir.path(), false).unwrap();
+        assert_eq!(restored.len(), 2);
+        assert_eq!(
+            fs::read_to_string(dir.path().join("pom.xml")).unwrap(),
+            "old pom"
+        );
+        assert_eq!(
+            fs::read_to_string(flows.join("flow.xml")).unwrap(),
+            "old flow"
+        );
+        assert!(find_backups(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_rollback_dry_run_keeps_files() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("pom.xml"), "new pom").unwrap();
+        fs::write(dir.path().join("pom.xml.bak"), "old pom").unwrap();
+
+        let restored = rollback(dir.path(), true).unwrap();
+        assert_eq!(restored.len(), 1);
+        assert_eq!(
+            fs::read_to_string(dir.path().join("pom.xml")).unwrap(),
+            "new pom"
+        );
+        assert!(dir.path().join("pom.xml.bak").exists());
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let dir = tempdir().unwrap();
+        let backups = backups_dir(dir.path(), None);
+        let flows = dir.path().join("src/main/mule");
+        fs::create_dir_all(&flows).unwrap();
+        fs::write(flows.join("flow.xml"), "old flow").unwrap();
+
+        let snapshot =
+            Snapshot::create_for_run(dir.path(), &backups, "3f2a9c1e-0d4b-4c6e-9a71-5b8e2f0c7d13")
+                .unwrap();
+        snapshot.save(&flows.join("flow.xml")).unwrap();
+        fs::write(flows.join("flow.xml"), "new flow").unwrap();
+        let run = snapshot.run_id().to_string();
+        assert!(run.ends_with("-3f2a9c1e"));
+        assert!(snapshot.dir().join("src/main/mule/flow.xml").exists());
+        snapshot.finish(BackupCompression::None).unwrap();
+        assert_eq!(list_runs(&backups), vec![run.clone()]);
+
+        let restored = restore(dir.path(), &backups, &run, false).unwrap();
+        assert_eq!(restored.len(), 1);
+        assert_eq!(
+            fs::read_to_string(flows.join("flow.xml")).unwrap(),
+            "old flow"
+        );
+        assert!(restore(dir.path(), &backups, "19700101-000000", false).is_err());
+    }
+
+    #[test]
+    fn test_restore_undoes_renames_and_created_files() {
+        let dir = tempdir().unwrap();
+        let backups = backups_dir(dir.path(), None);
+        let old = dir.path().join("src/main/resources/mule-app.properties");
+        let new = dir.path().join("src/main/resources/config.yaml");
+        let created = dir.path().join("src/main/resources/log4j2-test.xml");
+        fs::create_dir_all(old.parent().unwrap()).unwrap();
+        fs::write(&old, "http.port=8081\n").unwrap();
+
+        let snapshot = Snapshot::create(dir.path(), &backups).unwrap();
+        snapshot.save(&old).unwrap();
+        snapshot.record(&[(&old, &new)], &[&created]).unwrap();
+        fs::rename(&old, &new).unwrap();
+        fs::write(&created, "<Configuration/>").unwrap();
+        let run = snapshot.run_id().to_string();
+        snapshot.finish(BackupCompression::Zip).unwrap();
+
+        let restored = restore(dir.path(), &backups, &run, false).unwrap();
+        assert_eq!(restored.len(), 3);
+        assert_eq!(fs::read_to_string(&old).unwrap(), "http.port=8081\n");
+        assert!(!new.exists());
+        assert!(!created.exists());
+        assert!(!dir.path().join(MANIFEST_FILE).exists());
+    }
+
+    #[test]
+    fn test_compressed_snapshots_restore_and_prune() {
+        let dir = tempdir().unwrap();
+        let backups = backups_dir(dir.path(), Some("backups"));
+        fs::write(dir.path().join("pom.xml"), "old pom").unwrap();
+
+        let mut runs = Vec::new();
+        for compression in [BackupCompression::Zip, BackupCompression::TarGz] {
+            let snapshot = Snapshot::create(dir.path(), &backups).unwrap();
+            snapshot.save(&dir.path().join("pom.xml")).unwrap();
+            runs.push(snapshot.run_id().to_string());
+            let archive = snapshot.finish(compression).unwrap();
+            assert!(archive.is_file());
+        }
+        assert_eq!(list_runs(&backups), runs);
+
+        for run in &runs {
+            fs::write(dir.path().join("pom.xml"), "new pom").unwrap();
+            restore(dir.path(), &backups, run, false).unwrap();
+            assert_eq!(
+                fs::read_to_string(dir.path().join("pom.xml")).unwrap(),
+                "old pom"
+            );
+        }
+        assert_eq!(prune_runs(&backups, 1).unwrap(), vec![runs[0].clone()]);
+        assert_eq!(list_runs(&backups), vec![runs[1].clone()]);
+    }
+}
@@ -0,0 +1,270 @@
+use crate::file_ops::ALWAYS_SKIPPED_DIRS;
+use crate::report::{ReportFormat, EXIT_CHANGES, EXIT_FAILURE, EXIT_UP_TO_DATE};
+use crate::{is_mule_project, run_migration, MigrateError, MigrationOptions, MigrationReport};
+use colored::*;
+use indicatif::HumanBytes;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// List of projects to migrate together, e.g. `{"projects": ["apps/orders"]}`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    /// Project roots, relative to the manifest's directory unless absolute.
+    pub projects: Vec<PathBuf>,
+}
+
+impl Manifest {
+    /// Reads a JSON manifest and resolves its project roots.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Vec<PathBuf>, MigrateError> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path).map_err(|source| MigrateError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let manifest: Manifest =
+            serde_json::from_str(&data).map_err(|source| MigrateError::Json {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        let base = path.parent().unwrap_or(Path::new(""));
+        Ok(manifest
+            .projects
+            .into_iter()
+            .map(|project| base.join(project))
+            .collect())
+    }
+}
+
+/// Finds the Mule projects under `workspace`, sorted by path.
+///
+/// Build output and VCS directories (`target/`, `.git/`, ...) are skipped,
+/// and the search does not descend into a project once found.
+pub fn find_mule_projects<P: AsRef<Path>>(workspace: P) -> Vec<PathBuf> {
+    let mut projects = Vec::new();
+    let mut walker = WalkDir::new(workspace).sort_by_file_name().into_iter();
+    while let Some(entry) = walker.next() {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if !entry.file_type().is_dir() {
+            continue;
+        }
+        let skipped = entry.depth() > 0
+            && entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| ALWAYS_SKIPPED_DIRS.contains(&name));
+        if skipped {
+            walker.skip_current_dir();
+        } else if is_mule_project(entry.path()) {
+            projects.push(entry.into_path());
+            walker.skip_current_dir();
+        }
+    }
+    projects
+}
+
+/// Outcome of one project in a batch.
+#[derive(Debug, Serialize)]
+pub struct ProjectOutcome {
+    pub project: String,
+    pub exit_code: i32,
+    /// Why the migration of this project stopped, if it did.
+    pub error: Option<String>,
+    pub report: Option<MigrationReport>,
+}
+
+/// Totals over the projects of a batch.
+#[derive(Debug, Default, Serialize)]
+pub struct BatchTotals {
+    pub files_scanned: usize,
+    pub files_changed: usize,
+    pub bytes_written: u64,
+    pub replacements: usize,
+    pub total_millis: u64,
+}
+
+/// Consolidated report of a batch migration.
+#[derive(Debug, Default, Serialize)]
+pub struct BatchReport {
+    pub projects: Vec<ProjectOutcome>,
+    pub totals: BatchTotals,
+}
+
+impl BatchReport {
+    /// Failures win over changes, as for a single project.
+    pub fn exit_code(&self) -> i32 {
+        let codes = || self.projects.iter().map(|p| p.exit_code);
+        if codes().any(|c| c == EXIT_FAILURE) {
+            EXIT_FAILURE
+        } else if codes().any(|c| c == EXIT_CHANGES) {
+            EXIT_CHANGES
+        } else {
+            EXIT_UP_TO_DATE
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Failed to serialize report")
+    }
+
+    /// Renders one line per project with its result.
+    pub fn to_text(&self) -> String {
+        let mut out = format!(
+            "\n{}\n",
+            "================= BATCH SUMMARY =================="
+                .bold()
+                .blue()
+        );
+        for outcome in &self.projects {
+            let changed = outcome.report.as_ref().map_or(0, |r| r.changed_files.len());
+            let line = match (&outcome.error, outcome.exit_code) {
+                (Some(e), _) => format!("FAILED     {}: {e}", outcome.project).red(),
+                (None, EXIT_FAILURE) => {
+                    format!("ERRORS     {} ({changed} file(s))", outcome.project).red()
+                }
+                (None, EXIT_CHANGES) => {
+                    format!("CHANGED    {} ({changed} file(s))", outcome.project).green()
+                }
+                _ => format!("UP TO DATE {}", outcome.project).blue(),
+            };
+            out.push_str(&format!("  {line}\n"));
+        }
+        let count = |code| self.projects.iter().filter(|p| p.exit_code == code).count();
+        out.push_str(&format!(
+            "{} project(s): {} changed, {} up to date, {} failed\n",
+            self.projects.len(),
+            count(EXIT_CHANGES),
+            count(EXIT_UP_TO_DATE),
+            count(EXIT_FAILURE)
+        ));
+        let totals = &self.totals;
+        out.push_str(&format!(
+            "{} file(s) scanned, {} changed, {} written, {} replacement(s) in {}ms\n",
+            totals.files_scanned,
+            totals.files_changed,
+            HumanBytes(totals.bytes_written),
+            totals.replacements,
+            totals.total_millis
+        ));
+        out
+    }
+}
+
+/// Migrates every project with the same options, continuing past failures.
+///
+/// Each project prints its own text summary; `opts.project_root`,
+/// `opts.report_format` and `opts.report_file` are ignored in favor of the
+/// consolidated report returned here.
+pub fn run_batch(projects: &[PathBuf], opts: &MigrationOptions) -> BatchReport {
+    let mut batch = BatchReport::default();
+    for project in projects {
+        let project = project.display().to_string();
+        log::info!("Migrating {project}");
+        let project_opts = MigrationOptions {
+            project_root: &project,
+            report_format: ReportFormat::Text,
+            report_file: None,
+            ..opts.clone()
+        };
+        let outcome = match run_migration(&project_opts) {
+            Ok(report) => ProjectOutcome {
+                exit_code: report.exit_code(),
+                error: None,
+                report: Some(report),
+                project,
+            },
+            Err(e) => {
+                log::error!("Migration of {project} failed: {e}");
+                ProjectOutcome {
+                    exit_code: EXIT_FAILURE,
+                    error: Some(e.to_string()),
+                    report: None,
+                    project,
+                }
+            }
+        };
+        if let Some(report) = &outcome.report {
+            let stats = &report.stats;
+            batch.totals.files_scanned += stats.files_scanned;
+            batch.totals.files_changed += stats.files_changed;
+            batch.totals.bytes_written += stats.bytes_written;
+            batch.totals.replacements += stats.replacements;
+            batch.totals.total_millis += stats.total_millis;
+        }
+        batch.projects.push(outcome);
+    }
+    batch
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write_project(root: &Path) {
+        fs::create_dir_all(root).unwrap();
+        fs::write(root.join("pom.xml"), "<project/>").unwrap();
+        fs::write(root.join("mule-artifact.json"), "{}").unwrap();
+    }
+
+    #[test]
+    fn test_find_mule_projects_skips_target_copies() {
+        let dir = tempdir().unwrap();
+        write_project(&dir.path().join("orders"));
+        write_project(&dir.path().join("orders/target/classes"));
+        write_project(&dir.path().join("team/customers"));
+        fs::create_dir_all(dir.path().join("docs")).unwrap();
+
+        let projects: Vec<_> = find_mule_projects(dir.path())
+            .into_iter()
+            .map(|p| p.strip_prefix(dir.path()).unwrap().to_path_buf())
+            .collect();
+        assert_eq!(
+            projects,
+            vec![PathBuf::from("orders"), PathBuf::from("team/customers")]
+        );
+    }
+
+    #[test]
+    fn test_run_batch_continues_past_failures() {
+        let dir = tempdir().unwrap();
+        let app = dir.path().join("apps/orders");
+        fs::create_dir_all(&app).unwrap();
+        fs::write(
+            app.join("pom.xml"),
+            "<project><properties><app.runtime>4.4.0</app.runtime></properties></project>",
+        )
+        .unwrap();
+        fs::write(
+            app.join("mule-artifact.json"),
+            r#"{"minMuleVersion":"4.4.0","javaSpecificationVersions":["1.8"]}"#,
+        )
+        .unwrap();
+        let manifest = dir.path().join("manifest.json");
+        fs::write(
+            &manifest,
+            r#"{"projects": ["apps/orders", "apps/missing"]}"#,
+        )
+        .unwrap();
+
+        let projects = Manifest::from_file(&manifest).unwrap();
+        let opts = MigrationOptions {
+            dry_run: true,
+            report_format: ReportFormat::Text,
+            ..crate::tests::test_options(".")
+        };
+        let batch = run_batch(&projects, &opts);
+        assert_eq!(batch.projects.len(), 2);
+        assert_eq!(batch.projects[0].exit_code, EXIT_CHANGES);
+        assert!(batch.projects[1]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("not a Mule project"));
+        assert_eq!(batch.exit_code(), EXIT_FAILURE);
+    }
+}
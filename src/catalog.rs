@@ -0,0 +1,160 @@
+use crate::version::{self, Version};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::cmp::Ordering;
+
+/// Minimum version of a connector/module required from a given runtime on.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ConnectorRequirement {
+    pub group_id: String,
+    pub artifact_id: String,
+    /// Lowest target runtime version the requirement applies to.
+    pub min_runtime: String,
+    /// Only applies when this Java version is among the target
+    /// `javaSpecificationVersions`.
+    #[serde(default)]
+    pub java: Option<String>,
+    /// Minimum compatible connector version.
+    pub min_version: String,
+    /// Name of the connector's XML namespace below
+    /// `http://www.mulesoft.org/schema/mule/`, e.g. `http`.
+    #[serde(default)]
+    pub schema: Option<String>,
+}
+
+/// (groupId, artifactId, min runtime, java, min version)
+#[rustfmt::skip]
+const BUILTIN_CATALOG: &[(&str, &str, &str, Option<&str>, &str)] = &[
+    ("org.mule.connectors",      "mule-http-connector",                 "4.6.0", Some("17"), "1.8.0"),
+    ("org.mule.connectors",      "mule-sockets-connector",              "4.6.0", Some("17"), "1.2.4"),
+    ("org.mule.connectors",      "mule-db-connector",                   "4.6.0", Some("17"), "1.14.0"),
+    ("org.mule.connectors",      "mule-file-connector",                 "4.6.0", Some("17"), "1.5.0"),
+    ("org.mule.connectors",      "mule-ftp-connector",                  "4.6.0", Some("17"), "1.8.0"),
+    ("org.mule.connectors",      "mule-sftp-connector",                 "4.6.0", Some("17"), "2.0.0"),
+    ("org.mule.connectors",      "mule-email-connector",                "4.6.0", Some("17"), "1.7.0"),
+    ("org.mule.connectors",      "mule-jms-connector",                  "4.6.0", Some("17"), "1.9.0"),
+    ("org.mule.connectors",      "mule-objectstore-connector",          "4.6.0", Some("17"), "1.2.2"),
+    ("org.mule.connectors",      "mule-vm-connector",                   "4.6.0", Some("17"), "2.0.1"),
+    ("com.mulesoft.connectors",  "mule-salesforce-connector",           "4.6.0", Some("17"), "10.18.0"),
+    ("org.mule.modules",         "mule-validation-module",              "4.6.0", Some("17"), "2.0.5"),
+    ("org.mule.modules",         "mule-apikit-module",                  "4.6.0", Some("17"), "1.10.0"),
+    ("org.mule.modules",         "mule-scripting-module",               "4.6.0", Some("17"), "2.1.0"),
+];
+
+/// XML namespace names of the connectors in the built-in catalog.
+const BUILTIN_SCHEMAS: &[(&str, &str)] = &[
+    ("mule-http-connector", "http"),
+    ("mule-sockets-connector", "sockets"),
+    ("mule-db-connector", "db"),
+    ("mule-file-connector", "file"),
+    ("mule-ftp-connector", "ftp"),
+    ("mule-sftp-connector", "sftp"),
+    ("mule-email-connector", "email"),
+    ("mule-jms-connector", "jms"),
+    ("mule-objectstore-connector", "os"),
+    ("mule-vm-connector", "vm"),
+    ("mule-salesforce-connector", "salesforce"),
+    ("mule-validation-module", "validation"),
+    ("mule-apikit-module", "mule-apikit"),
+    ("mule-scripting-module", "scripting"),
+];
+
+/// Returns the built-in connector catalog.
+pub fn builtin_catalog() -> Vec<ConnectorRequirement> {
+    BUILTIN_CATALOG
+        .iter()
+        .map(
+            |(group_id, artifact_id, min_runtime, java, min_version)| ConnectorRequirement {
+                group_id: group_id.to_string(),
+                artifact_id: artifact_id.to_string(),
+                min_runtime: min_runtime.to_string(),
+                java: java.map(str::to_string),
+                min_version: min_version.to_string(),
+                schema: BUILTIN_SCHEMAS
+                    .iter()
+                    .find(|(a, _)| a == artifact_id)
+                    .map(|(_, schema)| schema.to_string()),
+            },
+        )
+        .collect()
+}
+
+/// Resolves the minimum connector versions for a target runtime and Java
+/// versions. When several requirements apply to the same connector, the
+/// highest minimum wins.
+///
+/// Returns `(groupId, artifactId, minimum version)` triples.
+pub fn minimum_versions(
+    catalog: &[ConnectorRequirement],
+    runtime_version: &str,
+    java_versions: &[String],
+) -> Vec<(String, String, String)> {
+    let mut minimums: Vec<(String, String, String)> = Vec::new();
+    for req in catalog {
+        let runtime_applies = matches!(
+            version::compare(runtime_version, &req.min_runtime),
+            Some(Ordering::Greater | Ordering::Equal)
+        );
+        let java_applies = req
+            .java
+            .as_ref()
+            .is_none_or(|java| java_versions.contains(java));
+        if !runtime_applies || !java_applies {
+            continue;
+        }
+        match minimums
+            .iter_mut()
+            .find(|(g, a, _)| *g == req.group_id && *a == req.artifact_id)
+        {
+            Some((_, _, current)) => {
+                if Version::parse(&req.min_version) > Version::parse(current) {
+                    *current = req.min_version.clone();
+                }
+            }
+            None => minimums.push((
+                req.group_id.clone(),
+                req.artifact_id.clone(),
+                req.min_version.clone(),
+            )),
+        }
+    }
+    minimums
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minimum_versions_for_java_17() {
+        let catalog = builtin_catalog();
+        let mins = minimum_versions(&catalog, "4.9.4", &["17".to_string()]);
+        assert!(mins
+            .iter()
+            .any(|(_, a, v)| a == "mule-http-connector" && v == "1.8.0"));
+        let mins = minimum_versions(&catalog, "4.9.4", &["1.8".to_string()]);
+        assert!(mins.is_empty());
+        let mins = minimum_versions(&catalog, "4.4.0", &["17".to_string()]);
+        assert!(mins.is_empty());
+    }
+
+    #[test]
+    fn test_minimum_versions_highest_minimum_wins() {
+        let mut catalog = builtin_catalog();
+        catalog.push(ConnectorRequirement {
+            group_id: "org.mule.connectors".to_string(),
+            artifact_id: "mule-http-connector".to_string(),
+            min_runtime: "4.9.0".to_string(),
+            java: None,
+            min_version: "1.10.0".to_string(),
+            schema: None,
+        });
+        let mins = minimum_versions(&catalog, "4.9.4", &["17".to_string()]);
+        let http = mins
+            .iter()
+            .find(|(_, a, _)| a == "mule-http-connector")
+            .unwrap();
+        assert_eq!(http.2, "1.10.0");
+    }
+}
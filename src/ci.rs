@@ -0,0 +1,232 @@
+use crate::config::ReplacementRule;
+use crate::error::MigrateError;
+use crate::file_ops::{self, CompiledRules, FileFilter};
+use crate::steps::{FilePlan, MigrationStep, StepContext};
+use crate::{template, yaml_props};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// CI files updated when `ci_files.files` is empty: Jenkins pipelines,
+/// GitHub workflows and Azure Pipelines definitions.
+pub const DEFAULT_CI_FILES: &[&str] = &[
+    "Jenkinsfile",
+    ".github/workflows/*.{yml,yaml}",
+    "azure-pipelines.{yml,yaml}",
+];
+
+/// Updates of CI definitions, e.g. a pinned runtime version or Maven image.
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CiFilesConfig {
+    /// Globs (relative to the project root) of the CI files to update;
+    /// empty means `Jenkinsfile`, `.github/workflows/*.{yml,yaml}` and
+    /// `azure-pipelines.{yml,yaml}`.
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Text replacements, with the options of the top-level `replacements`.
+    #[serde(default)]
+    pub replacements: Vec<ReplacementRule>,
+    /// Scalar values to set in YAML CI files, keyed by dotted path, e.g.
+    /// `jobs.build.container.image`.
+    #[serde(default)]
+    pub yaml: BTreeMap<String, String>,
+}
+
+impl CiFilesConfig {
+    pub fn is_empty(&self) -> bool {
+        self.replacements.is_empty() && self.yaml.is_empty()
+    }
+
+    /// The config with the template variables in the replacement values and
+    /// `yaml` values resolved.
+    pub fn render(&self, vars: &BTreeMap<&'static str, String>) -> CiFilesConfig {
+        CiFilesConfig {
+            files: self.files.clone(),
+            replacements: template::render_rules(&self.replacements, vars),
+            yaml: self
+                .yaml
+                .iter()
+                .map(|(key, value)| (key.clone(), template::render(value, vars)))
+                .collect(),
+        }
+    }
+
+    /// Globs of the CI files to update.
+    pub fn globs(&self) -> Vec<String> {
+        if self.files.is_empty() {
+            DEFAULT_CI_FILES.iter().map(|g| g.to_string()).collect()
+        } else {
+            self.files.clone()
+        }
+    }
+}
+
+fn is_yaml(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e == "yml" || e == "yaml")
+}
+
+/// Applies the replacements, and in YAML files the `yaml` updates, to the
+/// content of the CI file at `relative` to the project root.
+///
+/// Returns the new content, a description of each change and the YAML keys
+/// that were not found (always empty for other files).
+pub fn plan_ci_update<'a>(
+    content: &str,
+    relative: &Path,
+    replacements: &CompiledRules,
+    yaml: &'a BTreeMap<String, String>,
+) -> (String, Vec<String>, Vec<&'a str>) {
+    let (mut content, matched) = replacements.replace_in_content(content, relative);
+    let mut changes: Vec<String> = matched
+        .iter()
+        .map(|rule| format!("'{}' -> '{}'", rule.from, rule.to))
+        .collect();
+    let mut missing = Vec::new();
+    if is_yaml(relative) && !yaml.is_empty() {
+        let updates: Vec<(&str, &str)> = yaml
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect();
+        let (updated, yaml_changes, not_found) = yaml_props::plan_yaml_update(&content, &updates);
+        content = updated;
+        changes.extend(yaml_changes);
+        missing = not_found;
+    }
+    (content, changes, missing)
+}
+
+/// Step updating the CI files of a project as its `ci_files` config says.
+#[derive(Debug, Clone)]
+pub struct CiFilesUpdater<'a> {
+    project_root: PathBuf,
+    ci: &'a CiFilesConfig,
+    replacements: CompiledRules<'a>,
+    filter: FileFilter,
+    ci_files: FileFilter,
+}
+
+impl<'a> CiFilesUpdater<'a> {
+    /// Updates the CI files among the files `filter` selects, as `ci` (with
+    /// its variables resolved, see [`CiFilesConfig::render`]) says.
+    ///
+    /// # Errors
+    /// Returns an error if a glob of `ci.files` is invalid.
+    pub fn new<P: AsRef<Path>>(
+        project_root: P,
+        ci: &'a CiFilesConfig,
+        filter: FileFilter,
+    ) -> Result<Self, MigrateError> {
+        Ok(CiFilesUpdater {
+            project_root: project_root.as_ref().to_path_buf(),
+            ci,
+            replacements: CompiledRules::new(&ci.replacements),
+            filter,
+            ci_files: FileFilter::new(&ci.globs(), &[])?,
+        })
+    }
+}
+
+impl MigrationStep for CiFilesUpdater<'_> {
+    fn name(&self) -> &str {
+        "ci"
+    }
+
+    fn files(&self) -> Vec<PathBuf> {
+        file_ops::walk_files(&self.project_root, &self.filter)
+            .filter(|p| {
+                let relative = p.strip_prefix(&self.project_root).unwrap_or(p);
+                self.ci_files.is_selected(relative)
+            })
+            .collect()
+    }
+
+    fn plan(&self, ctx: &StepContext) -> Result<Vec<FilePlan>, MigrateError> {
+        let yaml = &self.ci.yaml;
+        let mut found_keys = HashSet::new();
+        let mut yaml_files = 0;
+        let mut plans = Vec::new();
+        for (path, original) in ctx.read_each(self.files()) {
+            let relative = path.strip_prefix(&self.project_root).unwrap_or(&path);
+            let (content, changes, missing) =
+                plan_ci_update(&original, relative, &self.replacements, yaml);
+            if is_yaml(relative) {
+                yaml_files += 1;
+                found_keys.extend(
+                    yaml.keys()
+                        .map(String::as_str)
+                        .filter(|k| !missing.contains(k))
+                        .map(str::to_string),
+                );
+            }
+            if !changes.is_empty() {
+                plans.push(FilePlan::for_file(path, original, content, changes));
+            }
+        }
+        if yaml_files > 0 {
+            for key in yaml.keys().filter(|k| !found_keys.contains(k.as_str())) {
+                log::warn!("CI key '{key}' not found in any YAML CI file");
+            }
+        }
+        Ok(plans)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ConfigFormat, MigrationConfig};
+
+    #[test]
+    fn test_plan_ci_update_replaces_and_sets_yaml_paths() {
+        let config = MigrationConfig::from_str_with_format(
+            r#"
+app_runtime_version: 4.9.4
+mule_maven_plugin_version: 4.3.1
+munit_version: 3.4.0
+mule_artifact:
+  min_mule_version: 4.9.0
+  java_specification_versions: ["17"]
+replacements: []
+ci_files:
+  replacements:
+    - from: "-Dmule.version=4.4.0"
+      to: "-Dmule.version=4.9.4"
+  yaml:
+    jobs.build.container.image: maven:3.9-eclipse-temurin-17
+"#,
+            ConfigFormat::Yaml,
+        )
+        .unwrap();
+        let ci = &config.ci_files;
+        let replacements = CompiledRules::new(&ci.replacements);
+        let workflow = "jobs:\n  build:\n    container:\n      image: maven:3.8-openjdk-8 # pinned\n    steps:\n      - run: mvn -Dmule.version=4.4.0 test\n";
+        let (content, changes, missing) = plan_ci_update(
+            workflow,
+            Path::new(".github/workflows/build.yml"),
+            &replacements,
+            &ci.yaml,
+        );
+        assert_eq!(
+            content,
+            "jobs:\n  build:\n    container:\n      image: maven:3.9-eclipse-temurin-17 # pinned\n    steps:\n      - run: mvn -Dmule.version=4.9.4 test\n"
+        );
+        assert_eq!(changes.len(), 2);
+        assert!(missing.is_empty());
+
+        let jenkinsfile = "sh 'mvn -Dmule.version=4.4.0 deploy'\n";
+        let (content, changes, missing) = plan_ci_update(
+            jenkinsfile,
+            Path::new("Jenkinsfile"),
+            &replacements,
+            &ci.yaml,
+        );
+        assert_eq!(content, "sh 'mvn -Dmule.version=4.9.4 deploy'\n");
+        assert_eq!(
+            changes,
+            vec!["'-Dmule.version=4.4.0' -> '-Dmule.version=4.9.4'"]
+        );
+        assert!(missing.is_empty());
+    }
+}
@@ -0,0 +1,113 @@
+use crate::config::MigrationConfig;
+use crate::java_compat;
+use crate::version::Version;
+
+/// Lowest Mule runtime supporting each Java version.
+const JAVA_MIN_RUNTIME: &[(u32, &str)] = &[(11, "4.2.0"), (17, "4.6.0")];
+
+/// Lowest Mule runtime supported by MUnit 3.
+const MUNIT_3_MIN_RUNTIME: &str = "4.4.0";
+
+/// Lowest mule-maven-plugin able to package for Java 17.
+const JAVA_17_MIN_MAVEN_PLUGIN: &str = "4.0.0";
+
+/// Lowest Mule runtime supported by mule-maven-plugin 4.
+const MAVEN_PLUGIN_4_MIN_RUNTIME: &str = "4.3.0";
+
+fn below(version: &Version, min: &str) -> bool {
+    Version::parse(min).is_some_and(|min| *version < min)
+}
+
+/// Cross-checks the versions a config migrates to. Returns one explanation
+/// per combination that would not build or run.
+pub fn check_config(config: &MigrationConfig) -> Vec<String> {
+    let (Some(runtime), Some(min_mule), Some(munit), Some(plugin)) = (
+        Version::parse(&config.app_runtime_version),
+        Version::parse(&config.mule_artifact.min_mule_version),
+        Version::parse(&config.munit_version),
+        Version::parse(&config.mule_maven_plugin_version),
+    ) else {
+        // Malformed versions are already rejected by config validation
+        return Vec::new();
+    };
+
+    let mut problems = Vec::new();
+    if min_mule > runtime {
+        problems.push(format!(
+            "min_mule_version {min_mule} is newer than app_runtime_version {runtime}; the app could not be deployed to its own runtime"
+        ));
+    }
+    let java = java_compat::highest_java_version(&config.mule_artifact.java_specification_versions);
+    for (java_version, min_runtime) in JAVA_MIN_RUNTIME {
+        if java >= Some(*java_version) && below(&runtime, min_runtime) {
+            problems.push(format!(
+                "Java {java_version} requires Mule runtime {min_runtime} or later, but app_runtime_version is {runtime}"
+            ));
+        }
+    }
+    if java >= Some(17) && below(&plugin, JAVA_17_MIN_MAVEN_PLUGIN) {
+        problems.push(format!(
+            "Java 17 requires mule-maven-plugin {JAVA_17_MIN_MAVEN_PLUGIN} or later, but mule_maven_plugin_version is {plugin}"
+        ));
+    }
+    if munit.major() >= 3 && below(&runtime, MUNIT_3_MIN_RUNTIME) {
+        problems.push(format!(
+            "MUnit {munit} requires Mule runtime {MUNIT_3_MIN_RUNTIME} or later, but app_runtime_version is {runtime}; use MUnit 2.x"
+        ));
+    }
+    if plugin.major() >= 4 && below(&runtime, MAVEN_PLUGIN_4_MIN_RUNTIME) {
+        problems.push(format!(
+            "mule-maven-plugin {plugin} requires Mule runtime {MAVEN_PLUGIN_4_MIN_RUNTIME} or later, but app_runtime_version is {runtime}; use mule-maven-plugin 3.x"
+        ));
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigFormat;
+
+    fn config(
+        runtime: &str,
+        min_mule: &str,
+        munit: &str,
+        plugin: &str,
+        java: &str,
+    ) -> MigrationConfig {
+        let json = format!(
+            r#"{{
+                "app_runtime_version": "{runtime}",
+                "mule_maven_plugin_version": "{plugin}",
+                "munit_version": "{munit}",
+                "mule_artifact": {{
+                    "min_mule_version": "{min_mule}",
+                    "java_specification_versions": ["{java}"]
+                }},
+                "replacements": []
+            }}"#
+        );
+        MigrationConfig::from_str_with_format(&json, ConfigFormat::Json).unwrap()
+    }
+
+    #[test]
+    fn test_check_config_explains_incompatible_versions() {
+        assert!(check_config(&config("4.9.4", "4.9.0", "3.4.0", "4.3.1", "17")).is_empty());
+
+        let problems = check_config(&config("4.5.0", "4.6.0", "3.4.0", "3.8.0", "17"));
+        assert_eq!(
+            problems,
+            vec![
+                "min_mule_version 4.6.0 is newer than app_runtime_version 4.5.0; the app could not be deployed to its own runtime",
+                "Java 17 requires Mule runtime 4.6.0 or later, but app_runtime_version is 4.5.0",
+                "Java 17 requires mule-maven-plugin 4.0.0 or later, but mule_maven_plugin_version is 3.8.0",
+            ]
+        );
+
+        let problems = check_config(&config("4.3.0", "4.3.0", "3.0.0", "3.8.0", "1.8"));
+        assert_eq!(
+            problems,
+            vec!["MUnit 3.0.0 requires Mule runtime 4.4.0 or later, but app_runtime_version is 4.3.0; use MUnit 2.x"]
+        );
+    }
+}
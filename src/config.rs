@@ -1,34 +1,921 @@
+use crate::catalog::ConnectorRequirement;
+use crate::ci::CiFilesConfig;
+use crate::dataweave::DataWeaveConfig;
+use crate::deploy::DeployConfig;
+use crate::dockerfile::DockerfileConfig;
+use crate::exchange::{ExchangeConfig, ExchangeJsonConfig};
+use crate::java_compat;
+use crate::log4j2::Log4j2Edit;
+use crate::lookup::VersionLookupConfig;
+use crate::maven::MavenConfig;
+use crate::maven_plugin::{ConfigurationEdit, MavenPluginConfig};
+use crate::mule_xml::MuleXmlRule;
+use crate::parent_pom::InheritedProperties;
+use crate::version::{self, VersionBump, VersionRange};
+use crate::yaml_props::YamlPropertyRule;
+use crate::{http, lockfile, lookup, template};
+use regex::Regex;
+use schemars::JsonSchema;
 use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct MigrationConfig {
+    /// JSON Schema the file is written against, for editors; ignored.
+    #[serde(default, rename = "$schema")]
+    pub schema: Option<String>,
+    /// Target Mule runtime, set as the `app.runtime` property of pom.xml.
     pub app_runtime_version: String,
+    /// Version of the mule-maven-plugin, set as `mule.maven.plugin.version`.
     pub mule_maven_plugin_version: String,
+    /// MUnit version, set as `munit.version`.
     pub munit_version: String,
+    /// Fields to set in mule-artifact.json.
     pub mule_artifact: MuleArtifactConfig,
+    /// Text replacements in the project's source files, applied in order.
     pub replacements: Vec<ReplacementRule>,
+    /// Versions to pin for specific `<dependency>`/`<plugin>` coordinates in pom.xml.
+    #[serde(default)]
+    pub dependencies: Vec<DependencyVersion>,
+    /// Raise connector versions to the minimums of the connector catalog.
+    #[serde(default)]
+    pub use_connector_catalog: bool,
+    /// Migrate MUnit declarations in pom.xml and `munit:config` in the test suites.
+    #[serde(default)]
+    pub migrate_munit: bool,
+    /// Additional connector catalog entries, on top of the built-in catalog.
+    #[serde(default)]
+    pub connector_catalog: Vec<ConnectorRequirement>,
+    /// Repositories and caching for `--latest-connectors` lookups.
+    #[serde(default)]
+    pub version_lookup: VersionLookupConfig,
+    /// Point the `xsi:schemaLocation` entries of runtime and catalog
+    /// connector namespaces to the `current` schemas.
+    #[serde(default)]
+    pub update_schema_locations: bool,
+    /// Also check that Mule configs changed by replacements still declare an
+    /// XSD for every namespace they use.
+    #[serde(default)]
+    pub validate_mule_schemas: bool,
+    /// Exchange API spec dependencies to bump, and where to look them up.
+    #[serde(default)]
+    pub exchange: ExchangeConfig,
+    /// New `<version>` of the project in pom.xml: `major`, `minor`, `patch`
+    /// or an explicit version, so the migrated artifact is told apart.
+    #[serde(default)]
+    pub project_version: Option<VersionBump>,
+    /// Version changes of the asset's own `exchange.json`.
+    #[serde(default)]
+    pub exchange_json: ExchangeJsonConfig,
+    /// Structural attribute edits for Mule config files under `src/main/mule`.
+    #[serde(default)]
+    pub mule_xml_rules: Vec<MuleXmlRule>,
+    /// Import and function renames in DataWeave (`.dwl`) scripts.
+    #[serde(default)]
+    pub dataweave: DataWeaveConfig,
+    /// Declare `app.runtime`, `mule.maven.plugin.version` and `munit.version`
+    /// in pom.xml if it neither declares nor inherits them.
+    #[serde(default)]
+    pub add_missing_properties: bool,
+    /// Add the mule-maven-plugin to `<build><plugins>` of Mule projects that
+    /// do not declare it.
+    #[serde(default)]
+    pub add_mule_maven_plugin: bool,
+    /// Java version set in the `maven.compiler.source`/`target`/`release`
+    /// properties (default: the highest `javaSpecificationVersions` entry).
+    #[serde(default)]
+    pub maven_compiler_version: Option<String>,
+    /// JVM arguments the MUnit plugins must pass to the test JVM, added to
+    /// their `argLines` if missing.
+    #[serde(default)]
+    pub munit_jvm_args: Vec<String>,
+    /// Repository URLs to replace in `<repositories>`, `<pluginRepositories>`
+    /// and `<distributionManagement>` of pom.xml.
+    #[serde(default)]
+    pub repositories: Vec<RepositoryMapping>,
+    /// Edits of the mule-maven-plugin declaration, e.g. its `<configuration>`.
+    #[serde(default)]
+    pub maven_plugin: MavenPluginConfig,
+    /// What to do about version properties pom.xml inherits from a parent pom.
+    #[serde(default)]
+    pub inherited_properties: InheritedProperties,
+    /// Goals, extra arguments and timeout of Maven runs.
+    #[serde(default)]
+    pub maven: MavenConfig,
+    /// Properties to set in YAML property files.
+    #[serde(default)]
+    pub yaml_properties: Vec<YamlPropertyRule>,
+    /// Values to set for existing keys of the `.properties` files under
+    /// `src/main/resources`, e.g. `anypoint.platform.client_id`.
+    #[serde(default)]
+    pub properties_updates: BTreeMap<String, String>,
+    /// Edits of `src/main/resources/log4j2.xml`.
+    #[serde(default)]
+    pub log4j2: Vec<Log4j2Edit>,
+    /// Replacements and YAML updates for CI definitions (Jenkinsfile, GitHub
+    /// workflows, azure-pipelines.yml).
+    #[serde(default)]
+    pub ci_files: CiFilesConfig,
+    /// Base image tags and `ENV` values to set in Dockerfiles.
+    #[serde(default)]
+    pub dockerfile: DockerfileConfig,
+    /// Files to rename or move, applied after every other change.
+    #[serde(default)]
+    pub file_renames: Vec<FileRename>,
+    /// Files to delete, as paths or globs relative to the project root, e.g.
+    /// an obsolete `src/main/resources/log4j.xml`.
+    #[serde(default)]
+    pub files_to_delete: Vec<String>,
+    /// Files to create if they do not exist yet.
+    #[serde(default)]
+    pub files_to_create: Vec<NewFile>,
+    /// Shell commands run in the project root before any file is changed.
+    #[serde(default)]
+    pub pre_hooks: Vec<String>,
+    /// Shell commands run in the project root after the changes are written.
+    #[serde(default)]
+    pub post_hooks: Vec<String>,
+    /// Where `--deploy` redeploys the migrated app after the run.
+    #[serde(default)]
+    pub deploy: Option<DeployConfig>,
+    /// External step plugins run after the built-in steps: names of
+    /// `mule-migrate-step-<name>` executables on the PATH, or paths relative
+    /// to the project root.
+    #[serde(default)]
+    pub plugins: Vec<String>,
+    /// Globs (relative to the project root) of files to consider for replacements.
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Globs (relative to the project root) of files to never touch.
+    #[serde(default)]
+    pub exclude: Vec<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct MuleArtifactConfig {
+    /// Value of `minMuleVersion`.
     pub min_mule_version: String,
+    /// Value of `javaSpecificationVersions`, e.g. `["17"]`.
     pub java_specification_versions: Vec<String>,
+    /// Further fields to set, keyed by JSON pointer (e.g. `/secureProperties`).
+    #[serde(default)]
+    pub extra_fields: BTreeMap<String, serde_json::Value>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct ReplacementRule {
+    /// Text (or pattern, with `regex`) to find.
     pub from: String,
+    /// Replacement text. `{{name}}` references the project variables
+    /// `app_runtime_version`, `mule_maven_plugin_version`, `munit_version`,
+    /// `min_mule_version`, `group_id`, `artifact_id`, `version` and
+    /// `project_name`.
     pub to: String,
+    /// Treat `from` as a regular expression; `to` may reference capture groups (`$1`, `${name}`).
+    #[serde(default)]
+    pub regex: bool,
+    /// Globs (relative to the project root) of the files the rule applies to;
+    /// empty means every file.
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// Only replace matches not adjacent to a letter, digit, `_`, `-` or `.`,
+    /// so `4.4.0` does not match inside `14.4.0` or `4.4.0-SNAPSHOT`.
+    #[serde(default)]
+    pub whole_word: bool,
+    /// Match `from` regardless of case.
+    #[serde(default)]
+    pub case_insensitive: bool,
+    /// Replace at most this many matches per file.
+    #[serde(default)]
+    pub max_replacements: Option<usize>,
+    /// Only replace inside the values of this XML attribute, named as written
+    /// (e.g. `doc:name`, `config-ref`), in `.xml` files.
+    #[serde(default)]
+    pub attribute: Option<String>,
+    /// Leave DataWeave scripts in `.xml` files alone: CDATA sections and the
+    /// bodies of `ee:` elements such as `<ee:set-payload>`.
+    #[serde(default)]
+    pub skip_dataweave: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DependencyVersion {
+    pub group_id: String,
+    pub artifact_id: String,
+    /// Version to set in the `<version>` of the declaration.
+    pub version: String,
+}
+
+/// A Maven repository URL to replace. `from` also matches URLs below it,
+/// e.g. `https://repository.mulesoft.org/nexus/content/repositories` matches
+/// `https://repository.mulesoft.org/nexus/content/repositories/public`.
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RepositoryMapping {
+    /// URL to replace, including the URLs below it.
+    pub from: String,
+    /// URL to put in its place.
+    pub to: String,
+}
+
+/// A file rename or move. Paths are relative to the project root and
+/// `/`-separated. If `from` is a glob, `to` is the directory the matching
+/// files are moved to, keeping their path below the part of `from` before
+/// the first wildcard: `src/main/mule/legacy/**/*.xml` to `src/main/mule`
+/// moves `legacy/api/orders.xml` to `src/main/mule/api/orders.xml`.
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct FileRename {
+    /// File, or glob of the files, to move.
+    pub from: String,
+    /// New path of the file, or the directory to move the files matching
+    /// the glob to.
+    pub to: String,
+}
+
+/// A file to create, with its parent directories.
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct NewFile {
+    /// Path relative to the project root, `/`-separated.
+    pub path: String,
+    /// Content of the file. `{{name}}` references the same project variables
+    /// as replacement values, e.g. `{{artifact_id}}`.
+    pub content: String,
+}
+
+/// Serialization format of a migration config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl ConfigFormat {
+    /// Detects the config format from the file extension, defaulting to JSON.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        match path
+            .as_ref()
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("yaml") | Some("yml") => ConfigFormat::Yaml,
+            Some("toml") => ConfigFormat::Toml,
+            _ => ConfigFormat::Json,
+        }
+    }
+}
+
+/// Returns true if the config source is an `http://` or `https://` URL.
+pub fn is_url(source: &str) -> bool {
+    source.starts_with("https://") || source.starts_with("http://")
+}
+
+/// Lowercase hex SHA-256 of `data`.
+fn sha256_hex(data: &str) -> String {
+    Sha256::digest(data.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn check_sha256(source: &str, data: &str, sha256: Option<&str>) -> Result<(), String> {
+    let Some(expected) = sha256 else {
+        return Ok(());
+    };
+    let actual = sha256_hex(data);
+    if actual.eq_ignore_ascii_case(expected.trim()) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Checksum mismatch for config {source}: expected sha256 {expected}, got {actual}"
+        ))
+    }
+}
+
+/// Downloads a remote config, caching it in the user's cache directory.
+///
+/// A cached copy matching the pinned checksum is used without going to the
+/// network; any cached copy is used when the download fails.
+fn fetch_config(url: &str, sha256: Option<&str>) -> Result<String, Box<dyn std::error::Error>> {
+    let cache_path = lookup::cache_dir().map(|dir| {
+        dir.join("configs")
+            .join(lockfile::content_hash(url.as_bytes()))
+    });
+    let cached = cache_path
+        .as_ref()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .filter(|data| check_sha256(url, data, sha256).is_ok());
+    if let Some(data) = cached.as_ref().filter(|_| sha256.is_some()) {
+        log::debug!("Using cached config for {url}");
+        return Ok(data.clone());
+    }
+    match http::get_string(url) {
+        Ok(data) => {
+            check_sha256(url, &data, sha256)?;
+            if let Some(path) = &cache_path {
+                let written = path
+                    .parent()
+                    .map_or(Ok(()), fs::create_dir_all)
+                    .and_then(|_| fs::write(path, &data));
+                if let Err(e) = written {
+                    log::debug!("Failed to cache config {url}: {e}");
+                }
+            }
+            Ok(data)
+        }
+        Err(e) => match cached {
+            Some(data) => {
+                log::warn!("Failed to fetch config {url} ({e}); using the cached copy");
+                Ok(data)
+            }
+            None => Err(format!("Failed to fetch config {url}: {e}").into()),
+        },
+    }
+}
+
+/// Reads a config file or downloads a config URL, checking its SHA-256 if
+/// pinned.
+fn read_source(source: &str, sha256: Option<&str>) -> Result<String, Box<dyn std::error::Error>> {
+    if is_url(source) {
+        return fetch_config(source, sha256);
+    }
+    let data = fs::read_to_string(source)?;
+    check_sha256(source, &data, sha256)?;
+    Ok(data)
+}
+
+/// Config format of a path or URL, from its extension.
+fn source_format(source: &str) -> ConfigFormat {
+    ConfigFormat::from_path(source.split(['?', '#']).next().unwrap_or(source))
+}
+
+/// Parses config text of any format into a generic value, for merging.
+fn parse_value(
+    data: &str,
+    format: ConfigFormat,
+) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    Ok(match format {
+        ConfigFormat::Json => serde_json::from_str(data)?,
+        ConfigFormat::Yaml => serde_yaml::from_str(data)?,
+        ConfigFormat::Toml => toml::from_str(data)?,
+    })
+}
+
+/// Matches the `${env:NAME}` and `${var.NAME}` placeholders of config values.
+/// Other `${...}` references (e.g. Maven properties) are left alone.
+const PLACEHOLDER: &str = r"\$\{(env:|var\.)([A-Za-z0-9_.\-]+)\}";
+
+/// Replaces the placeholders in every string of `value` with the named
+/// environment variable or `vars` entry.
+fn interpolate(value: &mut serde_json::Value, vars: &[(String, String)]) -> Result<(), String> {
+    match value {
+        serde_json::Value::String(text) => {
+            let placeholder = Regex::new(PLACEHOLDER).expect("Invalid placeholder pattern");
+            let mut missing = None;
+            let resolved = placeholder.replace_all(text, |caps: &regex::Captures| {
+                let name = &caps[2];
+                let found = if &caps[1] == "env:" {
+                    std::env::var(name).ok()
+                } else {
+                    vars.iter()
+                        .rev()
+                        .find(|(k, _)| k == name)
+                        .map(|(_, v)| v.clone())
+                };
+                found.unwrap_or_else(|| {
+                    missing.get_or_insert_with(|| match &caps[1] {
+                        "env:" => format!("environment variable {name} is not set"),
+                        _ => format!("variable '{name}' is not set (pass --set {name}=<value>)"),
+                    });
+                    String::new()
+                })
+            });
+            if let Some(missing) = missing {
+                return Err(format!("Cannot resolve '{text}': {missing}"));
+            }
+            *text = resolved.into_owned();
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                interpolate(item, vars)?;
+            }
+        }
+        serde_json::Value::Object(members) => {
+            for member in members.values_mut() {
+                interpolate(member, vars)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Resolves an `extends` reference against the config that contains it:
+/// URLs and absolute paths are kept, relative paths are taken from the
+/// directory (or URL) of `source`.
+fn resolve_base(source: &str, extends: &str) -> String {
+    if is_url(extends) || Path::new(extends).is_absolute() {
+        return extends.to_string();
+    }
+    if is_url(source) {
+        let dir = &source[..source.rfind('/').map_or(source.len(), |i| i + 1)];
+        return format!("{dir}{extends}");
+    }
+    let path = Path::new(source)
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(extends);
+    // Canonical, so that cycles through `..` are detected
+    fs::canonicalize(&path)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Merges `overlay` into `base`: objects are merged key by key, any other
+/// value (including arrays) in the overlay replaces the base's.
+fn merge_values(
+    base: &mut serde_json::Value,
+    overlay: serde_json::Value,
+    path: &str,
+    origin: &str,
+) {
+    match (base, overlay) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                let key_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                match base.get_mut(&key) {
+                    Some(existing) => merge_values(existing, value, &key_path, origin),
+                    None => {
+                        log::debug!("Config {origin} adds {key_path}");
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => {
+            if *base != overlay {
+                log::debug!("Config {origin} overrides {path}");
+            }
+            *base = overlay;
+        }
+    }
+}
+
+/// Replaces the `extends` of `value` (the config loaded from `source`) by
+/// the base config it names, merged under `value`. `chain` holds the
+/// configs being resolved, to detect cycles; `vars` resolve placeholders in
+/// the base configs.
+fn resolve_extends(
+    source: &str,
+    mut value: serde_json::Value,
+    chain: &mut Vec<String>,
+    vars: &[(String, String)],
+) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    let Some(extends) = value.as_object_mut().and_then(|o| o.remove("extends")) else {
+        return Ok(value);
+    };
+    let extends = extends
+        .as_str()
+        .ok_or_else(|| format!("'extends' in {source} must be a path or URL"))?;
+    let base_source = resolve_base(source, extends);
+    if chain.contains(&base_source) {
+        return Err(format!(
+            "Config inheritance cycle: {} -> {base_source}",
+            chain.join(" -> ")
+        )
+        .into());
+    }
+    log::debug!("Config {source} extends {base_source}");
+    chain.push(base_source.clone());
+    let base_data = read_source(&base_source, None)?;
+    let mut base_value = parse_value(&base_data, source_format(&base_source))
+        .map_err(|e| format!("Invalid base config {base_source}: {e}"))?;
+    interpolate(&mut base_value, vars)?;
+    let mut merged = resolve_extends(&base_source, base_value, chain, vars)?;
+    chain.pop();
+    merge_values(&mut merged, value, "", source);
+    Ok(merged)
+}
+
+/// Returns the 1-based line and column of `value` in `source`, searching
+/// after the first occurrence of `key`.
+fn locate(source: &str, key: &str, value: &str) -> Option<(usize, usize)> {
+    let key_pos = source.find(key)?;
+    let offset = key_pos + source[key_pos..].find(value)?;
+    let line = source[..offset].matches('\n').count() + 1;
+    let line_start = source[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    Some((line, source[line_start..offset].chars().count() + 1))
 }
 
 impl MigrationConfig {
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
-        let data = fs::read_to_string(path)?;
-        let config: MigrationConfig = serde_json::from_str(&data)?;
+        Self::load(&path.as_ref().to_string_lossy(), None, &[])
+    }
+
+    /// Downloads and parses a config from an `http(s)://` URL. The format is
+    /// detected from the extension of the URL path.
+    ///
+    /// # Errors
+    /// Returns an error if the config cannot be downloaded (and is not
+    /// cached), does not match `sha256`, or is invalid.
+    pub fn from_url(url: &str, sha256: Option<&str>) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::load(url, sha256, &[])
+    }
+
+    /// Loads a config from a file path or URL, checking its SHA-256 if pinned,
+    /// resolving its `extends` chain and its `${env:NAME}` and `${var.NAME}`
+    /// placeholders (the latter from `vars`).
+    pub fn load(
+        source: &str,
+        sha256: Option<&str>,
+        vars: &[(String, String)],
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let data = read_source(source, sha256)?;
+        Self::from_source(source, &data, vars)
+            .map_err(|e| format!("Invalid config {source}: {e}").into())
+    }
+
+    /// Parses the config text loaded from `source`, merging it over the
+    /// configs it extends.
+    fn from_source(
+        source: &str,
+        data: &str,
+        vars: &[(String, String)],
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let format = source_format(source);
+        let mut value = parse_value(data, format)?;
+        let templated = Regex::new(PLACEHOLDER)
+            .expect("Invalid placeholder pattern")
+            .is_match(data);
+        if value.get("extends").is_none() && !templated {
+            return Self::from_str_with_format(data, format);
+        }
+        interpolate(&mut value, vars)?;
+        let key = fs::canonicalize(source)
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| source.to_string());
+        let merged = resolve_extends(source, value, &mut vec![key], vars)?;
+        let config: MigrationConfig = serde_json::from_value(merged)?;
+        config.validate(data)?;
         Ok(config)
     }
+
+    pub fn from_str_with_format(
+        data: &str,
+        format: ConfigFormat,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let config: MigrationConfig = match format {
+            ConfigFormat::Json => serde_json::from_str(data)?,
+            ConfigFormat::Yaml => serde_yaml::from_str(data)?,
+            ConfigFormat::Toml => toml::from_str(data)?,
+        };
+        config.validate(data)?;
+        Ok(config)
+    }
+
+    /// Checks values serde cannot: version strings must be `MAJOR.MINOR.PATCH`,
+    /// Java versions must be numeric and regex rules must compile. `source` is the raw config text, used to
+    /// report line/column positions.
+    ///
+    /// # Errors
+    /// Returns one message listing every problem found.
+    pub fn validate(&self, source: &str) -> Result<(), String> {
+        let mut versions: Vec<(&str, &str)> = vec![
+            ("app_runtime_version", &self.app_runtime_version),
+            ("mule_maven_plugin_version", &self.mule_maven_plugin_version),
+            ("munit_version", &self.munit_version),
+            ("min_mule_version", &self.mule_artifact.min_mule_version),
+        ];
+        versions.extend(
+            self.dependencies
+                .iter()
+                .map(|d| ("version", d.version.as_str())),
+        );
+        versions.extend(
+            self.exchange
+                .api_specs
+                .iter()
+                .filter_map(|s| s.version.as_deref())
+                .map(|v| ("version", v)),
+        );
+        for req in &self.connector_catalog {
+            versions.push(("min_runtime", &req.min_runtime));
+            versions.push(("min_version", &req.min_version));
+        }
+
+        let mut problems = Vec::new();
+        for (key, value) in versions {
+            if !version::is_semver(value) {
+                let location = locate(source, key, value)
+                    .map(|(line, col)| format!("line {line}, column {col}: "))
+                    .unwrap_or_default();
+                problems.push(format!(
+                    "{location}{key} '{value}' is not a valid version (expected MAJOR.MINOR.PATCH)"
+                ));
+            }
+        }
+        if let Some(java) = &self.maven_compiler_version {
+            if java_compat::highest_java_version(std::slice::from_ref(java)).is_none() {
+                problems.push(format!(
+                    "maven_compiler_version '{java}' is not a Java version (e.g. \"1.8\", \"17\")"
+                ));
+            }
+        }
+        for java in &self.mule_artifact.java_specification_versions {
+            if java_compat::highest_java_version(std::slice::from_ref(java)).is_none() {
+                let location = locate(source, "java_specification_versions", java)
+                    .map(|(line, col)| format!("line {line}, column {col}: "))
+                    .unwrap_or_default();
+                problems.push(format!(
+                    "{location}java_specification_versions entry '{java}' is not a Java version (e.g. \"1.8\", \"17\")"
+                ));
+            }
+        }
+        for rule in self.replacements.iter().filter(|r| r.regex) {
+            if let Err(e) = Regex::new(&rule.from) {
+                problems.push(format!(
+                    "Invalid regex in replacement rule '{}': {e}",
+                    rule.from
+                ));
+            }
+        }
+        for rule in &self.replacements {
+            if rule
+                .attribute
+                .as_deref()
+                .is_some_and(|a| a.trim().is_empty())
+            {
+                problems.push(format!(
+                    "attribute of replacement rule '{}' must not be empty",
+                    rule.from
+                ));
+            }
+            for name in template::unknown_variables(&rule.to) {
+                problems.push(format!(
+                    "Unknown variable {{{{{name}}}}} in replacement rule '{}' (known: {})",
+                    rule.from,
+                    template::VARIABLES.join(", ")
+                ));
+            }
+            if rule.max_replacements == Some(0) {
+                problems.push(format!(
+                    "max_replacements of replacement rule '{}' must be at least 1",
+                    rule.from
+                ));
+            }
+            for pattern in &rule.paths {
+                if let Err(e) = globset::Glob::new(pattern) {
+                    problems.push(format!(
+                        "Invalid path glob in replacement rule '{}': {e}",
+                        rule.from
+                    ));
+                }
+            }
+        }
+        for rule in &self.ci_files.replacements {
+            if rule.regex {
+                if let Err(e) = Regex::new(&rule.from) {
+                    problems.push(format!(
+                        "Invalid regex in ci_files replacement rule '{}': {e}",
+                        rule.from
+                    ));
+                }
+            }
+            for name in template::unknown_variables(&rule.to) {
+                problems.push(format!(
+                    "Unknown variable {{{{{name}}}}} in ci_files replacement rule '{}' (known: {})",
+                    rule.from,
+                    template::VARIABLES.join(", ")
+                ));
+            }
+        }
+        for (key, value) in &self.ci_files.yaml {
+            for name in template::unknown_variables(value) {
+                problems.push(format!(
+                    "Unknown variable {{{{{name}}}}} in ci_files.yaml '{key}' (known: {})",
+                    template::VARIABLES.join(", ")
+                ));
+            }
+        }
+        for (field, bump) in [
+            ("project_version", &self.project_version),
+            ("exchange_json.version", &self.exchange_json.version),
+            ("exchange_json.api_version", &self.exchange_json.api_version),
+        ] {
+            if matches!(bump, Some(VersionBump::Set(v)) if v.trim().is_empty()) {
+                problems.push(format!("{field} must not be empty"));
+            }
+        }
+        for (kind, key, value) in self
+            .dockerfile
+            .base_images
+            .iter()
+            .map(|(k, v)| ("base_images", k, v))
+            .chain(self.dockerfile.env.iter().map(|(k, v)| ("env", k, v)))
+        {
+            for name in template::unknown_variables(value) {
+                problems.push(format!(
+                    "Unknown variable {{{{{name}}}}} in dockerfile.{kind} '{key}' (known: {})",
+                    template::VARIABLES.join(", ")
+                ));
+            }
+        }
+        for glob in self.ci_files.files.iter().chain(&self.dockerfile.files) {
+            if let Err(e) = globset::Glob::new(glob) {
+                problems.push(format!(
+                    "Invalid glob '{glob}' in ci_files or dockerfile files: {e}"
+                ));
+            }
+        }
+        for (kind, rule) in self
+            .dataweave
+            .imports
+            .iter()
+            .map(|r| ("imports", r))
+            .chain(self.dataweave.functions.iter().map(|r| ("functions", r)))
+        {
+            if rule.from.trim().is_empty() {
+                problems.push(format!(
+                    "dataweave.{kind} rule to '{}' has an empty 'from'",
+                    rule.to
+                ));
+            }
+        }
+        for mapping in &self.repositories {
+            if mapping.from.trim_end_matches('/').is_empty() {
+                problems.push(format!(
+                    "repositories mapping to '{}' has an empty 'from'",
+                    mapping.to
+                ));
+            }
+        }
+        for edit in &self.maven_plugin.configuration {
+            match edit {
+                ConfigurationEdit::Set { path, .. } if path.trim_matches('/').is_empty() => {
+                    problems
+                        .push("maven_plugin.configuration set edit has an empty path".to_string())
+                }
+                ConfigurationEdit::Insert { xml, .. } => {
+                    if let Err(e) = roxmltree::Document::parse(xml) {
+                        problems.push(format!(
+                            "maven_plugin.configuration insert edit has invalid XML: {e}"
+                        ));
+                    }
+                }
+                _ => {}
+            }
+        }
+        for (name, value) in self.maven_plugin.deployment.elements() {
+            if value.trim().is_empty() {
+                problems.push(format!("maven_plugin.deployment {name} is empty"));
+            }
+            for var in template::unknown_variables(value) {
+                problems.push(format!(
+                    "Unknown variable {{{{{var}}}}} in maven_plugin.deployment {name} (known: {})",
+                    template::VARIABLES.join(", ")
+                ));
+            }
+        }
+        let escapes = |path: &str| {
+            path.trim().is_empty()
+                || Path::new(path).is_absolute()
+                || Path::new(path)
+                    .components()
+                    .any(|c| c == std::path::Component::ParentDir)
+        };
+        for rename in &self.file_renames {
+            if escapes(&rename.from) || escapes(&rename.to) {
+                problems.push(format!(
+                    "file_renames entry '{}' -> '{}' must use non-empty paths inside the project",
+                    rename.from, rename.to
+                ));
+            } else if let Err(e) = globset::Glob::new(&rename.from) {
+                problems.push(format!(
+                    "Invalid glob in file_renames entry '{}': {e}",
+                    rename.from
+                ));
+            }
+        }
+        for pattern in &self.files_to_delete {
+            if escapes(pattern) {
+                problems.push(format!(
+                    "files_to_delete entry '{pattern}' must be a non-empty path inside the project"
+                ));
+            } else if let Err(e) = globset::Glob::new(pattern) {
+                problems.push(format!("Invalid glob in files_to_delete: {e}"));
+            }
+        }
+        for file in &self.files_to_create {
+            if escapes(&file.path) {
+                problems.push(format!(
+                    "files_to_create entry '{}' must be a non-empty path inside the project",
+                    file.path
+                ));
+            }
+            for name in template::unknown_variables(&file.content) {
+                problems.push(format!(
+                    "Unknown variable {{{{{name}}}}} in the content of '{}' (known: {})",
+                    file.path,
+                    template::VARIABLES.join(", ")
+                ));
+            }
+        }
+        if self.plugins.iter().any(|p| p.trim().is_empty()) {
+            problems.push("plugins must not contain empty names".to_string());
+        }
+        if self.maven.goals.is_empty() {
+            problems.push("maven.goals must name at least one goal".to_string());
+        }
+        if self.maven.test_goals.is_empty() {
+            problems.push("maven.test_goals must name at least one goal".to_string());
+        }
+        for (field, patterns) in [
+            ("update_includes", &self.maven.update_includes),
+            ("update_excludes", &self.maven.update_excludes),
+        ] {
+            for pattern in patterns {
+                if pattern.trim().is_empty() || pattern.contains([',', ' ']) {
+                    problems.push(format!(
+                        "maven.{field} pattern '{pattern}' must be one groupId:artifactId[:type[:classifier[:version]]] pattern"
+                    ));
+                }
+            }
+        }
+        if let Some(proxy) = &self.version_lookup.proxy {
+            if let Err(e) = ureq::Proxy::new(proxy) {
+                problems.push(format!("version_lookup.proxy '{proxy}' is invalid: {e}"));
+            }
+        }
+        for (artifact, range) in &self.version_lookup.ranges {
+            if artifact.split(':').count() != 2 || artifact.split(':').any(str::is_empty) {
+                problems.push(format!(
+                    "version_lookup.ranges key '{artifact}' must be groupId:artifactId"
+                ));
+            }
+            if VersionRange::parse(range).is_none() {
+                problems.push(format!(
+                    "version_lookup.ranges '{range}' for {artifact} is not a Maven version range like [1.0,2.0)"
+                ));
+            }
+        }
+        for credentials in &self.version_lookup.credentials {
+            if credentials.token.is_none() && credentials.username.is_none() {
+                problems.push(format!(
+                    "version_lookup.credentials for '{}' need a token or a username",
+                    credentials.repository
+                ));
+            }
+        }
+        if let Some(deploy) = &self.deploy {
+            for (field, value) in [
+                ("organization_id", &deploy.organization_id),
+                ("environment_id", &deploy.environment_id),
+                ("application", &deploy.application),
+                ("runtime_version", &deploy.runtime_version),
+            ] {
+                if value.trim().is_empty() {
+                    problems.push(format!("deploy.{field} is empty"));
+                }
+                for name in template::unknown_variables(value) {
+                    problems.push(format!(
+                        "Unknown variable {{{{{name}}}}} in deploy.{field} (known: {})",
+                        template::VARIABLES.join(", ")
+                    ));
+                }
+            }
+            if !deploy.url.starts_with("https://") && !deploy.url.starts_with("http://") {
+                problems.push(format!("deploy.url '{}' is not an http(s) URL", deploy.url));
+            }
+        }
+        for pointer in self.mule_artifact.extra_fields.keys() {
+            if !pointer.starts_with('/') {
+                problems.push(format!(
+                    "extra_fields key '{pointer}' is not a JSON pointer (e.g. \"/secureProperties\")"
+                ));
+            }
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems.join("\n  "))
+        }
+    }
 }
 
 #[cfg(test)]
@@ -64,5 +951,191 @@ mod tests {
         assert_eq!(config.mule_artifact.java_specification_versions, vec!["17"]);
         assert_eq!(config.replacements[0].from, "foo");
         assert_eq!(config.replacements[0].to, "bar");
+        assert!(!config.replacements[0].regex);
+        assert!(config.dependencies.is_empty());
+    }
+
+    #[test]
+    fn test_migration_config_from_yaml_file() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test_config.yml");
+        let yaml = r#"
+app_runtime_version: "4.9.4"
+mule_maven_plugin_version: "4.3.1"
+munit_version: "3.4.0"
+mule_artifact:
+  min_mule_version: "4.9.0"
+  java_specification_versions: ["17"]
+replacements:
+  - from: foo
+    to: bar
+dependencies:
+  - group_id: org.mule.connectors
+    artifact_id: mule-http-connector
+    version: "1.10.3"
+"#;
+        let mut file = File::create(&file_path).unwrap();
+        file.write_all(yaml.as_bytes()).unwrap();
+        let config = MigrationConfig::from_file(&file_path).unwrap();
+        assert_eq!(config.app_runtime_version, "4.9.4");
+        assert_eq!(config.mule_artifact.java_specification_versions, vec!["17"]);
+        assert_eq!(config.replacements[0].to, "bar");
+        assert_eq!(config.dependencies[0].artifact_id, "mule-http-connector");
+        assert_eq!(config.dependencies[0].version, "1.10.3");
+    }
+
+    #[test]
+    fn test_migration_config_from_toml_file() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test_config.toml");
+        let toml = r#"
+app_runtime_version = "4.9.4"
+mule_maven_plugin_version = "4.3.1"
+munit_version = "3.4.0"
+
+[mule_artifact]
+min_mule_version = "4.9.0"
+java_specification_versions = ["17"]
+
+[[replacements]]
+from = "foo"
+to = "bar"
+"#;
+        let mut file = File::create(&file_path).unwrap();
+        file.write_all(toml.as_bytes()).unwrap();
+        let config = MigrationConfig::from_file(&file_path).unwrap();
+        assert_eq!(config.munit_version, "3.4.0");
+        assert_eq!(config.mule_artifact.min_mule_version, "4.9.0");
+        assert_eq!(config.replacements[0].from, "foo");
+    }
+
+    #[test]
+    fn test_migration_config_rejects_invalid_regex_rule() {
+        let json = r#"{
+            "app_runtime_version": "4.9.4",
+            "mule_maven_plugin_version": "4.3.1",
+            "munit_version": "3.4.0",
+            "mule_artifact": {
+                "min_mule_version": "4.9.0",
+                "java_specification_versions": ["17"]
+            },
+            "replacements": [
+                {"from": "config-ref=\"(.*-old\"", "to": "$1", "regex": true}
+            ]
+        }"#;
+        let err = MigrationConfig::from_str_with_format(json, ConfigFormat::Json).unwrap_err();
+        assert!(err.to_string().contains("Invalid regex"));
+    }
+
+    #[test]
+    fn test_migration_config_reports_unknown_fields_and_bad_versions() {
+        let json = r#"{
+            "app_runtime_version": "4.9.4",
+            "mule_maven_plugin_version": "4.3.1",
+            "munit_version": "3.4.0",
+            "mule_artifact": {
+                "min_mule_version": "4.9.0",
+                "java_specification_versions": ["17"]
+            },
+            "replacments": []
+        }"#;
+        let err = MigrationConfig::from_str_with_format(json, ConfigFormat::Json).unwrap_err();
+        assert!(err.to_string().contains("unknown field `replacments`"));
+        assert!(err.to_string().contains("line 9"));
+
+        let json = json
+            .replace("replacments", "replacements")
+            .replace("\"4.3.1\"", "\"latest\"");
+        let err = MigrationConfig::from_str_with_format(&json, ConfigFormat::Json).unwrap_err();
+        assert!(err.to_string().contains(
+            "line 3, column 43: mule_maven_plugin_version 'latest' is not a valid version"
+        ));
+    }
+
+    #[test]
+    fn test_check_sha256() {
+        let data = "app_runtime_version: 4.9.4\n";
+        let sha = sha256_hex(data);
+        assert_eq!(sha.len(), 64);
+        assert!(check_sha256("c.yaml", data, None).is_ok());
+        assert!(check_sha256("c.yaml", data, Some(&sha.to_uppercase())).is_ok());
+        let err = check_sha256("c.yaml", "changed", Some(&sha)).unwrap_err();
+        assert!(err.starts_with("Checksum mismatch for config c.yaml"));
+        assert!(is_url("https://example.com/4.9.json"));
+        assert!(!is_url("configs/4.9.json"));
+    }
+
+    #[test]
+    fn test_extends_merges_base_config() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("org")).unwrap();
+        fs::write(
+            dir.path().join("org/base.yaml"),
+            "app_runtime_version: 4.9.4\n\
+             mule_maven_plugin_version: 4.3.1\n\
+             munit_version: 3.4.0\n\
+             mule_artifact:\n  min_mule_version: 4.9.0\n  java_specification_versions: ['17']\n\
+             replacements:\n  - from: foo\n    to: bar\n",
+        )
+        .unwrap();
+        let project = dir.path().join("migration.json");
+        fs::write(
+            &project,
+            r#"{
+                "extends": "org/base.yaml",
+                "munit_version": "3.5.0",
+                "mule_artifact": {"min_mule_version": "4.9.4"},
+                "replacements": [{"from": "baz", "to": "qux"}]
+            }"#,
+        )
+        .unwrap();
+
+        let config = MigrationConfig::from_file(&project).unwrap();
+        assert_eq!(config.app_runtime_version, "4.9.4");
+        assert_eq!(config.munit_version, "3.5.0");
+        assert_eq!(config.mule_artifact.min_mule_version, "4.9.4");
+        assert_eq!(config.mule_artifact.java_specification_versions, vec!["17"]);
+        assert_eq!(config.replacements.len(), 1);
+        assert_eq!(config.replacements[0].from, "baz");
+
+        fs::write(
+            dir.path().join("org/base.yaml"),
+            "extends: ../migration.json\n",
+        )
+        .unwrap();
+        let err = MigrationConfig::from_file(&project)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("inheritance cycle"), "{err}");
+    }
+
+    #[test]
+    fn test_placeholders_resolve_from_env_and_vars() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("migration.yaml");
+        fs::write(
+            &path,
+            "app_runtime_version: ${var.runtime}\n\
+             mule_maven_plugin_version: 4.3.1\n\
+             munit_version: ${env:MLM_TEST_MUNIT_VERSION}\n\
+             mule_artifact:\n  min_mule_version: ${var.runtime}\n  java_specification_versions: ['17']\n\
+             replacements:\n  - from: foo\n    to: '${project.version}'\n",
+        )
+        .unwrap();
+        let path = path.to_str().unwrap();
+        std::env::set_var("MLM_TEST_MUNIT_VERSION", "3.4.0");
+        let vars = [("runtime".to_string(), "4.9.4".to_string())];
+
+        let config = MigrationConfig::load(path, None, &vars).unwrap();
+        assert_eq!(config.app_runtime_version, "4.9.4");
+        assert_eq!(config.mule_artifact.min_mule_version, "4.9.4");
+        assert_eq!(config.munit_version, "3.4.0");
+        // Maven properties are not placeholders
+        assert_eq!(config.replacements[0].to, "${project.version}");
+
+        let err = MigrationConfig::load(path, None, &[])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("pass --set runtime=<value>"), "{err}");
     }
 }
@@ -0,0 +1,203 @@
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::ops::Range;
+use std::path::Path;
+
+/// `dataweave` section of the migration config: renames applied to the
+/// code of `.dwl` files, outside comments and string literals.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DataWeaveConfig {
+    /// Modules to rename in imports and qualified references, e.g.
+    /// `dw::Crypto` in `import * from dw::Crypto` and `dw::Crypto::MD5(...)`.
+    #[serde(default)]
+    pub imports: Vec<DataWeaveRename>,
+    /// Functions to rename at their call sites, e.g. `MD5` in `MD5(payload)`.
+    #[serde(default)]
+    pub functions: Vec<DataWeaveRename>,
+}
+
+impl DataWeaveConfig {
+    pub fn is_empty(&self) -> bool {
+        self.imports.is_empty() && self.functions.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DataWeaveRename {
+    /// Current name.
+    pub from: String,
+    /// New name.
+    pub to: String,
+}
+
+/// Checks whether the DataWeave rules apply to a file.
+pub fn is_dataweave(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e == "dwl")
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Byte ranges of the comments and string literals of a DataWeave script.
+fn literal_ranges(script: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut chars = script.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let end = match c {
+            '/' if chars.peek().is_some_and(|&(_, n)| n == '/') => chars
+                .find(|&(_, n)| n == '\n')
+                .map_or(script.len(), |(i, _)| i),
+            '/' if chars.peek().is_some_and(|&(_, n)| n == '*') => {
+                chars.next();
+                let mut prev = ' ';
+                chars
+                    .find(|&(_, n)| std::mem::replace(&mut prev, n) == '*' && n == '/')
+                    .map_or(script.len(), |(i, _)| i + 1)
+            }
+            '"' | '\'' | '`' => {
+                let mut escaped = false;
+                chars
+                    .find(|&(_, n)| {
+                        let closes = !escaped && n == c;
+                        escaped = !escaped && n == '\\';
+                        closes
+                    })
+                    .map_or(script.len(), |(i, _)| i + 1)
+            }
+            _ => continue,
+        };
+        ranges.push(start..end);
+    }
+    ranges
+}
+
+/// Renames the occurrences of `from` in `code` that `accept` allows, given
+/// the text before and after the match.
+fn rename(
+    code: &str,
+    from: &str,
+    to: &str,
+    accept: impl Fn(&str, &str) -> bool,
+) -> (String, usize) {
+    let mut out = String::with_capacity(code.len());
+    let mut last = 0;
+    let mut count = 0;
+    for (start, _) in code.match_indices(from) {
+        let end = start + from.len();
+        if start < last || !accept(&code[..start], &code[end..]) {
+            continue;
+        }
+        out.push_str(&code[last..start]);
+        out.push_str(to);
+        last = end;
+        count += 1;
+    }
+    out.push_str(&code[last..]);
+    (out, count)
+}
+
+/// A module reference is not part of a longer name; `::` may follow it.
+fn is_module_ref(before: &str, after: &str) -> bool {
+    !before.ends_with(|c: char| is_ident_char(c) || c == ':') && !after.starts_with(is_ident_char)
+}
+
+/// A function call: the name is followed by `(`, possibly after whitespace.
+fn is_call(before: &str, after: &str) -> bool {
+    !before.ends_with(is_ident_char) && after.trim_start().starts_with('(')
+}
+
+/// Applies the DataWeave renames to the code of a `.dwl` script, leaving
+/// comments and string literals untouched.
+///
+/// Returns the new content and a description of each rename that matched.
+pub fn plan_dataweave_update(content: &str, config: &DataWeaveConfig) -> (String, Vec<String>) {
+    let rules = config
+        .imports
+        .iter()
+        .map(|r| (r, "import", is_module_ref as fn(&str, &str) -> bool))
+        .chain(
+            config
+                .functions
+                .iter()
+                .map(|r| (r, "function", is_call as _)),
+        );
+    let mut content = content.to_string();
+    let mut changes = Vec::new();
+    for (rule, kind, accept) in rules {
+        let mut out = String::with_capacity(content.len());
+        let mut last = 0;
+        let mut total = 0;
+        let literals = literal_ranges(&content);
+        let gaps = literals
+            .iter()
+            .map(|r| (r.start, r.end))
+            .chain([(content.len(), content.len())]);
+        for (start, end) in gaps {
+            let (code, count) = rename(&content[last..start], &rule.from, &rule.to, accept);
+            out.push_str(&code);
+            out.push_str(&content[start..end]);
+            total += count;
+            last = end;
+        }
+        if total > 0 {
+            changes.push(format!("{kind} '{}' -> '{}' ({total})", rule.from, rule.to));
+            content = out;
+        }
+    }
+    (content, changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_dataweave_update_skips_comments_and_strings() {
+        let script = r#"%dw 2.0
+import * from dw::Crypto
+// MD5(payload) from dw::Crypto is deprecated
+output application/json
+---
+{
+  hash: MD5 (payload.id),
+  qualified: dw::Crypto::MD5(payload.id),
+  label: "MD5(dw::Crypto)",
+  other: dw::CryptoUtils::MD5Sum(payload)
+}"#;
+        let config = DataWeaveConfig {
+            imports: vec![DataWeaveRename {
+                from: "dw::Crypto".to_string(),
+                to: "dw::crypto::Crypto".to_string(),
+            }],
+            functions: vec![DataWeaveRename {
+                from: "MD5".to_string(),
+                to: "hashWith".to_string(),
+            }],
+        };
+        let (content, changes) = plan_dataweave_update(script, &config);
+        assert_eq!(
+            content,
+            r#"%dw 2.0
+import * from dw::crypto::Crypto
+// MD5(payload) from dw::Crypto is deprecated
+output application/json
+---
+{
+  hash: hashWith (payload.id),
+  qualified: dw::crypto::Crypto::hashWith(payload.id),
+  label: "MD5(dw::Crypto)",
+  other: dw::CryptoUtils::MD5Sum(payload)
+}"#
+        );
+        assert_eq!(
+            changes,
+            vec![
+                "import 'dw::Crypto' -> 'dw::crypto::Crypto' (2)",
+                "function 'MD5' -> 'hashWith' (2)"
+            ]
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use crate::error::MigrateError;
+use crate::http;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+
+/// Anypoint Platform control plane (US).
+pub const ANYPOINT_URL: &str = "https://anypoint.mulesoft.com";
+
+/// Environment variable holding an Anypoint access token by default.
+const DEFAULT_TOKEN_ENV: &str = "ANYPOINT_TOKEN";
+
+/// Connected app credentials, used when no access token is set.
+pub const CLIENT_ID_ENV: &str = "ANYPOINT_CLIENT_ID";
+pub const CLIENT_SECRET_ENV: &str = "ANYPOINT_CLIENT_SECRET";
+
+/// Pause between two polls of the deployment status.
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// `deploy` section of the migration config: where `--deploy` redeploys the
+/// migrated app through the Runtime Manager API (CloudHub 2.0 and Runtime
+/// Fabric deployments).
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DeployConfig {
+    /// Anypoint Platform base URL, e.g. `https://eu1.anypoint.mulesoft.com`.
+    #[serde(default = "default_url")]
+    pub url: String,
+    /// Organization (business group) id.
+    pub organization_id: String,
+    /// Id of the (sandbox) environment the app is deployed to.
+    pub environment_id: String,
+    /// Name of the deployment to redeploy.
+    #[serde(default = "default_application")]
+    pub application: String,
+    /// Runtime version to redeploy with.
+    #[serde(default = "default_runtime_version")]
+    pub runtime_version: String,
+    /// Java version to run on, e.g. `"17"`; unchanged if not set.
+    #[serde(default)]
+    pub java_version: Option<String>,
+    /// Release channel of the runtime, e.g. `LTS` or `EDGE`; unchanged if
+    /// not set.
+    #[serde(default)]
+    pub release_channel: Option<String>,
+    /// Environment variable holding the access token. Without one, a token
+    /// is requested with the connected app credentials in
+    /// `ANYPOINT_CLIENT_ID` and `ANYPOINT_CLIENT_SECRET`.
+    #[serde(default = "default_token_env")]
+    pub token_env: String,
+    /// Seconds to wait for the redeployment to finish; 0 to only start it.
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_url() -> String {
+    ANYPOINT_URL.to_string()
+}
+
+fn default_application() -> String {
+    "{{artifact_id}}".to_string()
+}
+
+fn default_runtime_version() -> String {
+    "{{app_runtime_version}}".to_string()
+}
+
+fn default_token_env() -> String {
+    DEFAULT_TOKEN_ENV.to_string()
+}
+
+fn default_timeout_secs() -> u64 {
+    600
+}
+
+impl DeployConfig {
+    fn deployments_url(&self) -> String {
+        format!(
+            "{}/amc/application-manager/api/v2/organizations/{}/environments/{}/deployments",
+            self.url.trim_end_matches('/'),
+            self.organization_id,
+            self.environment_id
+        )
+    }
+}
+
+/// Outcome of a redeployment, as shown in the report.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeploymentStatus {
+    pub application: String,
+    pub environment_id: String,
+    pub runtime_version: String,
+    /// Deployment status reported by Runtime Manager, e.g. `APPLIED`.
+    pub status: String,
+    /// Application status, e.g. `RUNNING`, if reported.
+    pub application_status: Option<String>,
+    pub succeeded: bool,
+}
+
+/// Request body changing the runtime of a deployment.
+pub fn redeploy_body(runtime_version: &str, java: Option<&str>, channel: Option<&str>) -> Value {
+    let mut runtime = json!({ "version": runtime_version });
+    if let Some(java) = java {
+        runtime["java"] = json!(java);
+    }
+    if let Some(channel) = channel {
+        runtime["releaseChannel"] = json!(channel);
+    }
+    json!({ "target": { "deploymentSettings": { "runtime": runtime } } })
+}
+
+/// Id of the deployment named `name` in a deployment list response.
+pub fn find_deployment(list: &Value, name: &str) -> Option<String> {
+    list.get("items")?
+        .as_array()?
+        .iter()
+        .find(|d| d.get("name").and_then(Value::as_str) == Some(name))?
+        .get("id")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// Deployment and application status of a deployment response. The
+/// deployment is settled once it is no longer being applied.
+pub fn deployment_state(deployment: &Value) -> (String, Option<String>) {
+    let status = deployment
+        .get("status")
+        .and_then(Value::as_str)
+        .unwrap_or("UNKNOWN")
+        .to_string();
+    let application = deployment
+        .pointer("/application/status")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    (status, application)
+}
+
+fn settled(status: &str) -> bool {
+    !matches!(status, "APPLYING" | "UNKNOWN")
+}
+
+/// The access token from `token_env`, else one requested with the connected
+/// app credentials.
+fn access_token(config: &DeployConfig, proxy: Option<&str>) -> Result<String, String> {
+    if let Some(token) = std::env::var(&config.token_env)
+        .ok()
+        .filter(|t| !t.is_empty())
+    {
+        return Ok(token);
+    }
+    let (Ok(id), Ok(secret)) = (
+        std::env::var(CLIENT_ID_ENV),
+        std::env::var(CLIENT_SECRET_ENV),
+    ) else {
+        return Err(format!(
+            "no credentials: set {} or {CLIENT_ID_ENV} and {CLIENT_SECRET_ENV}",
+            config.token_env
+        ));
+    };
+    let url = format!(
+        "{}/accounts/api/v2/oauth2/token",
+        config.url.trim_end_matches('/')
+    );
+    let body = json!({
+        "grant_type": "client_credentials",
+        "client_id": id,
+        "client_secret": secret,
+    });
+    let response = http::send_json("POST", &url, None, proxy, &body)
+        .map_err(|e| format!("failed to get an access token: {e}"))?;
+    serde_json::from_str::<Value>(&response)
+        .ok()
+        .and_then(|v| v.get("access_token")?.as_str().map(str::to_string))
+        .ok_or_else(|| "the token response has no access_token".to_string())
+}
+
+fn get_json(url: &str, token: &str, proxy: Option<&str>) -> Result<Value, String> {
+    let auth = http::Auth::Bearer(token.to_string());
+    let body = http::get_string_with(url, Some(&auth), proxy, None).map_err(|e| e.to_string())?;
+    serde_json::from_str(&body).map_err(|e| format!("invalid response from {url}: {e}"))
+}
+
+/// Redeploys `application` on `runtime_version` and waits for it to settle,
+/// for at most `timeout_secs`. Requests go through `proxy` if given.
+///
+/// # Errors
+/// Returns an error if there are no credentials, the deployment does not
+/// exist or a request fails. A redeployment that fails or does not settle in
+/// time is returned as an unsuccessful status.
+pub fn redeploy(
+    config: &DeployConfig,
+    application: &str,
+    runtime_version: &str,
+    proxy: Option<&str>,
+) -> Result<DeploymentStatus, MigrateError> {
+    let fail = |reason: String| MigrateError::Deploy {
+        application: application.to_string(),
+        reason,
+    };
+    let token = access_token(config, proxy).map_err(fail)?;
+    let list = get_json(&config.deployments_url(), &token, proxy).map_err(fail)?;
+    let id = find_deployment(&list, application).ok_or_else(|| {
+        fail(format!(
+            "no deployment of that name in environment {}",
+            config.environment_id
+        ))
+    })?;
+    let url = format!("{}/{id}", config.deployments_url());
+    log::info!(
+        "Redeploying {application} on runtime {runtime_version} in environment {}",
+        config.environment_id
+    );
+    let body = redeploy_body(
+        runtime_version,
+        config.java_version.as_deref(),
+        config.release_channel.as_deref(),
+    );
+    let response = http::send_json("PATCH", &url, Some(&token), proxy, &body)
+        .map_err(|e| fail(format!("redeploy request failed: {e}")))?;
+    let mut deployment: Value = serde_json::from_str(&response).unwrap_or(Value::Null);
+    let deadline = Instant::now() + Duration::from_secs(config.timeout_secs);
+    let (mut status, mut application_status) = deployment_state(&deployment);
+    while config.timeout_secs > 0 && !settled(&status) && Instant::now() < deadline {
+        std::thread::sleep(POLL_INTERVAL);
+        deployment = get_json(&url, &token, proxy).map_err(fail)?;
+        (status, application_status) = deployment_state(&deployment);
+        log::debug!("Deployment {application}: {status}");
+    }
+    if config.timeout_secs > 0 && !settled(&status) {
+        log::warn!(
+            "Deployment {application} still {status} after {}s",
+            config.timeout_secs
+        );
+    }
+    let succeeded = if config.timeout_secs == 0 {
+        status != "FAILED"
+    } else {
+        status == "APPLIED" && application_status.as_deref().is_none_or(|s| s == "RUNNING")
+    };
+    Ok(DeploymentStatus {
+        application: application.to_string(),
+        environment_id: config.environment_id.clone(),
+        runtime_version: runtime_version.to_string(),
+        status,
+        application_status,
+        succeeded,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redeploy_request_and_responses() {
+        assert_eq!(
+            redeploy_body("4.9.4", Some("17"), None),
+            json!({ "target": { "deploymentSettings": { "runtime": { "version": "4.9.4", "java": "17" } } } })
+        );
+        let list = json!({ "total": 2, "items": [
+            { "id": "a1", "name": "customers-api", "status": "APPLIED" },
+            { "id": "b2", "name": "orders-api", "status": "APPLIED" }
+        ] });
+        assert_eq!(find_deployment(&list, "orders-api").as_deref(), Some("b2"));
+        assert_eq!(find_deployment(&list, "payments-api"), None);
+        let deployment =
+            json!({ "id": "b2", "status": "APPLYING", "application": { "status": "NOT_RUNNING" } });
+        let (status, application) = deployment_state(&deployment);
+        assert!(!settled(&status));
+        assert_eq!(application.as_deref(), Some("NOT_RUNNING"));
+    }
+}
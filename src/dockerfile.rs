@@ -0,0 +1,396 @@
+use crate::config::MigrationConfig;
+use crate::error::MigrateError;
+use crate::file_ops::{self, FileFilter};
+use crate::steps::{FilePlan, MigrationStep, StepContext};
+use crate::template;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Dockerfiles updated when `dockerfile.files` is empty.
+pub const DEFAULT_DOCKERFILES: &[&str] = &["**/Dockerfile", "**/Dockerfile.*", "**/*.Dockerfile"];
+
+/// Updates of the base images and environment of Dockerfiles.
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DockerfileConfig {
+    /// Globs (relative to the project root) of the Dockerfiles to update;
+    /// empty means `Dockerfile`, `Dockerfile.*` and `*.Dockerfile` files
+    /// anywhere in the project.
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Tags to set in `FROM` lines, keyed by image name as written without
+    /// its tag, e.g. `eclipse-temurin: 17-jre`.
+    #[serde(default)]
+    pub base_images: BTreeMap<String, String>,
+    /// Values to set for variables already declared with `ENV`, e.g.
+    /// `JAVA_VERSION: "17"`.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+impl DockerfileConfig {
+    pub fn is_empty(&self) -> bool {
+        self.base_images.is_empty() && self.env.is_empty()
+    }
+
+    /// Globs of the Dockerfiles to update.
+    pub fn globs(&self) -> Vec<String> {
+        if self.files.is_empty() {
+            DEFAULT_DOCKERFILES.iter().map(|g| g.to_string()).collect()
+        } else {
+            self.files.clone()
+        }
+    }
+}
+
+/// Splits an image reference into its name, tag and digest, e.g.
+/// `registry:5000/mule/runtime:4.4.0@sha256:ab` into `registry:5000/mule/runtime`,
+/// `4.4.0` and `sha256:ab`.
+fn split_image(image: &str) -> (&str, Option<&str>, Option<&str>) {
+    let (reference, digest) = match image.split_once('@') {
+        Some((reference, digest)) => (reference, Some(digest)),
+        None => (image, None),
+    };
+    let name_start = reference.rfind('/').map_or(0, |i| i + 1);
+    match reference[name_start..].rfind(':') {
+        Some(i) => (
+            &reference[..name_start + i],
+            Some(&reference[name_start + i + 1..]),
+            digest,
+        ),
+        None => (reference, None, digest),
+    }
+}
+
+/// Byte range of the first whitespace-separated word of `line` starting at
+/// or after `from`.
+fn next_word(line: &str, from: usize) -> Option<std::ops::Range<usize>> {
+    let start = from + line[from..].find(|c: char| !c.is_whitespace())?;
+    let end = line[start..]
+        .find(char::is_whitespace)
+        .map_or(line.len(), |i| start + i);
+    Some(start..end)
+}
+
+/// Rewrites the image of a `FROM` line; `rest` starts after the keyword.
+fn update_from(
+    line: &str,
+    rest: usize,
+    images: &BTreeMap<String, String>,
+    changes: &mut Vec<String>,
+) -> Option<String> {
+    let mut word = next_word(line, rest)?;
+    while line[word.clone()].starts_with("--") {
+        word = next_word(line, word.end)?;
+    }
+    let image = &line[word.clone()];
+    let (name, tag, digest) = split_image(image);
+    let new_tag = images.get(name)?;
+    if tag == Some(new_tag.as_str()) && digest.is_none() {
+        return None;
+    }
+    if digest.is_some() {
+        log::warn!("Dropping the digest of {image}, it pins the old tag");
+    }
+    let new_image = format!("{name}:{new_tag}");
+    changes.push(format!("FROM {image} -> {new_image}"));
+    Some(format!(
+        "{}{new_image}{}",
+        &line[..word.start],
+        &line[word.end..]
+    ))
+}
+
+/// End of an `ENV` value starting at `start`: a quoted string, or up to the
+/// next unescaped whitespace.
+fn value_end(line: &str, start: usize) -> usize {
+    let mut chars = line[start..].char_indices();
+    let quote = line[start..]
+        .chars()
+        .next()
+        .filter(|c| *c == '"' || *c == '\'');
+    if quote.is_some() {
+        chars.next();
+    }
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (_, '\\') => {
+                chars.next();
+            }
+            (Some(q), c) if c == q => return start + i + 1,
+            (None, c) if c.is_whitespace() => return start + i,
+            _ => {}
+        }
+    }
+    line.len()
+}
+
+/// Renders `value` in the quoting style of `old`, quoting values with
+/// whitespace.
+fn quote_like(old: &str, value: &str) -> String {
+    if old.starts_with('\'') {
+        format!("'{value}'")
+    } else if old.starts_with('"') || value.contains(char::is_whitespace) || value.is_empty() {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+        .unwrap_or(value)
+}
+
+/// Sets the values of `KEY=value` pairs of an `ENV` instruction (or its
+/// continuation line), or of the legacy `ENV KEY value` form; `rest` is
+/// where the pairs start.
+fn update_env(
+    line: &str,
+    rest: usize,
+    legacy_allowed: bool,
+    env: &BTreeMap<String, String>,
+    found: &mut [bool],
+    changes: &mut Vec<String>,
+) -> Option<String> {
+    let body = line.trim_end();
+    let body = body.strip_suffix('\\').unwrap_or(body);
+    let mut out = String::new();
+    let mut last = 0;
+    let mut pos = rest;
+    while let Some(word) = next_word(body, pos) {
+        let (key, value_start, end) = match body[word.clone()].find('=') {
+            Some(eq) => {
+                let value_start = word.start + eq + 1;
+                (
+                    &body[word.start..value_start - 1],
+                    value_start,
+                    value_end(body, value_start),
+                )
+            }
+            None if legacy_allowed && pos == rest => {
+                // `ENV KEY value`: the value is the rest of the line
+                let value = next_word(body, word.end)?;
+                (&body[word.clone()], value.start, body.trim_end().len())
+            }
+            None => break,
+        };
+        if let Some((i, (_, new))) = env.iter().enumerate().find(|(_, (k, _))| *k == key) {
+            found[i] = true;
+            let old = &line[value_start..end];
+            if unquote(old) != new {
+                out.push_str(&line[last..value_start]);
+                out.push_str(&quote_like(old, new));
+                last = end;
+                changes.push(format!("ENV {key}: '{}' -> '{new}'", unquote(old)));
+            }
+        }
+        pos = end;
+    }
+    if last == 0 {
+        return None;
+    }
+    out.push_str(&line[last..]);
+    Some(out)
+}
+
+/// Sets the tags of the `FROM` images and the values of the `ENV` variables
+/// named in `images` and `env`, leaving every other line as it is.
+///
+/// Returns the new content, a description of each change and the `env`
+/// keys that were not declared.
+pub fn plan_dockerfile_update<'a>(
+    content: &str,
+    images: &BTreeMap<String, String>,
+    env: &'a BTreeMap<String, String>,
+) -> (String, Vec<String>, Vec<&'a str>) {
+    let mut changes = Vec::new();
+    let mut found = vec![false; env.len()];
+    let mut out = String::with_capacity(content.len());
+    let mut in_env = false;
+    for line in content.split_inclusive('\n') {
+        let (text, newline) = match line.strip_suffix('\n') {
+            Some(text) => match text.strip_suffix('\r') {
+                Some(text) => (text, "\r\n"),
+                None => (text, "\n"),
+            },
+            None => (line, ""),
+        };
+        let trimmed = text.trim_start();
+        let indent = text.len() - trimmed.len();
+        let keyword = trimmed.split_whitespace().next().unwrap_or("");
+        let updated = if in_env && !trimmed.starts_with('#') {
+            update_env(text, indent, false, env, &mut found, &mut changes)
+        } else if keyword.eq_ignore_ascii_case("FROM") {
+            update_from(text, indent + keyword.len(), images, &mut changes)
+        } else if keyword.eq_ignore_ascii_case("ENV") {
+            update_env(
+                text,
+                indent + keyword.len(),
+                true,
+                env,
+                &mut found,
+                &mut changes,
+            )
+        } else {
+            None
+        };
+        if !trimmed.starts_with('#') {
+            in_env = (in_env || keyword.eq_ignore_ascii_case("ENV")) && text.ends_with('\\');
+        }
+        out.push_str(updated.as_deref().unwrap_or(text));
+        out.push_str(newline);
+    }
+    let missing = env
+        .keys()
+        .zip(found)
+        .filter(|(_, found)| !found)
+        .map(|(key, _)| key.as_str())
+        .collect();
+    (out, changes, missing)
+}
+
+/// Step updating the Dockerfiles of a project as its `dockerfile` config
+/// says.
+#[derive(Debug, Clone)]
+pub struct DockerfileUpdater<'a> {
+    project_root: PathBuf,
+    config: &'a MigrationConfig,
+    filter: FileFilter,
+    dockerfiles: FileFilter,
+}
+
+impl<'a> DockerfileUpdater<'a> {
+    /// Updates the Dockerfiles among the files `filter` selects.
+    ///
+    /// # Errors
+    /// Returns an error if a glob of `dockerfile.files` is invalid.
+    pub fn new<P: AsRef<Path>>(
+        project_root: P,
+        config: &'a MigrationConfig,
+        filter: FileFilter,
+    ) -> Result<Self, MigrateError> {
+        Ok(DockerfileUpdater {
+            project_root: project_root.as_ref().to_path_buf(),
+            config,
+            filter,
+            dockerfiles: FileFilter::new(&config.dockerfile.globs(), &[])?,
+        })
+    }
+}
+
+impl MigrationStep for DockerfileUpdater<'_> {
+    fn name(&self) -> &str {
+        "dockerfile"
+    }
+
+    fn files(&self) -> Vec<PathBuf> {
+        file_ops::walk_files(&self.project_root, &self.filter)
+            .filter(|p| {
+                let relative = p.strip_prefix(&self.project_root).unwrap_or(p);
+                self.dockerfiles.is_selected(relative)
+            })
+            .collect()
+    }
+
+    fn plan(&self, ctx: &StepContext) -> Result<Vec<FilePlan>, MigrateError> {
+        let vars = ctx.project_variables(self.config, &self.project_root);
+        let render = |values: &BTreeMap<String, String>| {
+            values
+                .iter()
+                .map(|(key, value)| (key.clone(), template::render(value, &vars)))
+                .collect::<BTreeMap<_, _>>()
+        };
+        let images = render(&self.config.dockerfile.base_images);
+        let env = render(&self.config.dockerfile.env);
+        let mut found_keys = HashSet::new();
+        let mut scanned = 0;
+        let mut plans = Vec::new();
+        for (path, original) in ctx.read_each(self.files()) {
+            scanned += 1;
+            let (content, changes, missing) = plan_dockerfile_update(&original, &images, &env);
+            found_keys.extend(
+                env.keys()
+                    .map(String::as_str)
+                    .filter(|k| !missing.contains(k))
+                    .map(str::to_string),
+            );
+            if !changes.is_empty() {
+                plans.push(FilePlan::for_file(path, original, content, changes));
+            }
+        }
+        if scanned > 0 {
+            for key in env.keys().filter(|k| !found_keys.contains(k.as_str())) {
+                log::warn!("ENV variable '{key}' not declared in any Dockerfile");
+            }
+        }
+        Ok(plans)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_dockerfile_update_sets_tags_and_env() {
+        let content = "FROM --platform=linux/amd64 eclipse-temurin:8-jre AS base\n\
+# FROM eclipse-temurin:8-jre\n\
+FROM registry:5000/mule/runtime:4.4.0@sha256:abc\n\
+ENV JAVA_VERSION=8 MULE_HOME=\"/opt/mule\" \\\n    MULE_VERSION=4.4.0\n\
+ENV LANG C.UTF-8\n\
+RUN echo JAVA_VERSION=8\n";
+        let images = map(&[
+            ("eclipse-temurin", "17-jre"),
+            ("registry:5000/mule/runtime", "4.9.4"),
+        ]);
+        let env = map(&[
+            ("JAVA_VERSION", "17"),
+            ("MULE_HOME", "/opt/mule 4"),
+            ("MULE_VERSION", "4.9.4"),
+            ("LANG", "C.UTF-8"),
+            ("TZ", "UTC"),
+        ]);
+        let (out, changes, missing) = plan_dockerfile_update(content, &images, &env);
+        assert_eq!(
+            out,
+            "FROM --platform=linux/amd64 eclipse-temurin:17-jre AS base\n\
+# FROM eclipse-temurin:8-jre\n\
+FROM registry:5000/mule/runtime:4.9.4\n\
+ENV JAVA_VERSION=17 MULE_HOME=\"/opt/mule 4\" \\\n    MULE_VERSION=4.9.4\n\
+ENV LANG C.UTF-8\n\
+RUN echo JAVA_VERSION=8\n"
+        );
+        assert_eq!(changes.len(), 5);
+        assert_eq!(
+            changes[0],
+            "FROM eclipse-temurin:8-jre -> eclipse-temurin:17-jre"
+        );
+        assert_eq!(missing, vec!["TZ"]);
+    }
+
+    #[test]
+    fn test_split_image() {
+        assert_eq!(split_image("maven"), ("maven", None, None));
+        assert_eq!(
+            split_image("localhost:5000/app:1.0"),
+            ("localhost:5000/app", Some("1.0"), None)
+        );
+        assert_eq!(
+            split_image("app@sha256:ff"),
+            ("app", None, Some("sha256:ff"))
+        );
+    }
+}
@@ -0,0 +1,255 @@
+use crate::config::MigrationConfig;
+use crate::profiles;
+use serde_json::{json, Map, Value};
+
+/// Profile shown as the example in the config reference.
+const EXAMPLE_PROFILE: &str = "4.4-to-4.9-java17";
+
+/// JSON Schema of the migration config, derived from [`MigrationConfig`].
+pub fn config_schema() -> Value {
+    schemars::schema_for!(MigrationConfig).to_value()
+}
+
+/// JSON Schema for editors to validate and complete config files with.
+/// It also knows `extends`, which is resolved before the config is parsed,
+/// and only requires the top-level keys of configs that extend no other one.
+pub fn config_json_schema() -> Value {
+    let mut schema = config_schema();
+    let Some(root) = schema.as_object_mut() else {
+        return schema;
+    };
+    if let Some(properties) = root.get_mut("properties").and_then(Value::as_object_mut) {
+        properties.insert(
+            "extends".to_string(),
+            json!({
+                "description": "Path (relative to this config) or URL of a base config to merge this one over.",
+                "type": "string"
+            }),
+        );
+    }
+    if let Some(required) = root.remove("required") {
+        root.insert("if".to_string(), json!({ "required": ["extends"] }));
+        root.insert("else".to_string(), json!({ "required": required }));
+    }
+    schema
+}
+
+/// A documented config key, e.g. `replacements[].from`.
+#[derive(Debug)]
+struct Field {
+    path: String,
+    kind: String,
+    required: bool,
+    default: Option<String>,
+    description: Option<String>,
+}
+
+fn resolve<'a>(schema: &'a Value, defs: &'a Map<String, Value>) -> &'a Value {
+    match schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|r| r.strip_prefix("#/$defs/"))
+    {
+        Some(name) => defs.get(name).map_or(schema, |def| resolve(def, defs)),
+        None => schema,
+    }
+}
+
+/// Strips the `null` alternative schemars adds for `Option` fields.
+fn non_null<'a>(schema: &'a Value, defs: &'a Map<String, Value>) -> &'a Value {
+    let schema = resolve(schema, defs);
+    let alternatives = schema.get("anyOf").and_then(Value::as_array);
+    match alternatives.map(|a| a.as_slice()) {
+        Some([a, b]) if b.get("type") == Some(&Value::from("null")) => resolve(a, defs),
+        _ => schema,
+    }
+}
+
+fn type_name(schema: &Value, defs: &Map<String, Value>) -> String {
+    let schema = non_null(schema, defs);
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        return one_of(values.iter());
+    }
+    if let Some(variants) = schema.get("oneOf").and_then(Value::as_array) {
+        if variants.iter().all(|v| v.get("const").is_some()) {
+            return one_of(variants.iter().filter_map(|v| v.get("const")));
+        }
+        return "object, see the variants below".to_string();
+    }
+    if let Some(alternatives) = schema.get("anyOf").and_then(Value::as_array) {
+        let names: Vec<String> = alternatives.iter().map(|a| type_name(a, defs)).collect();
+        return names.join(" or ");
+    }
+    let types: Vec<&str> = match schema.get("type") {
+        Some(Value::String(t)) => vec![t.as_str()],
+        Some(Value::Array(types)) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .filter(|t| *t != "null")
+            .collect(),
+        _ => return "any".to_string(),
+    };
+    match types.as_slice() {
+        ["array"] => match schema.get("items") {
+            Some(items) => format!("list of {}", type_name(items, defs)),
+            None => "list".to_string(),
+        },
+        ["object"] => match schema.get("additionalProperties") {
+            Some(values) if schema.get("properties").is_none() => {
+                format!("map of string to {}", type_name(values, defs))
+            }
+            _ => "object".to_string(),
+        },
+        ["integer"] => "integer".to_string(),
+        types => types.join(" or "),
+    }
+}
+
+fn one_of<'a>(values: impl Iterator<Item = &'a Value>) -> String {
+    let values: Vec<String> = values.map(Value::to_string).collect();
+    format!("one of {}", values.join(", "))
+}
+
+/// Collects the fields of an object schema, required ones first, and
+/// descends into nested objects and lists of objects.
+fn collect_fields(schema: &Value, defs: &Map<String, Value>, prefix: &str, out: &mut Vec<Field>) {
+    let schema = non_null(schema, defs);
+    if let Some(variants) = schema.get("oneOf").and_then(Value::as_array) {
+        for variant in variants {
+            let tag = variant
+                .get("properties")
+                .and_then(Value::as_object)
+                .and_then(|p| p.iter().find_map(|(name, s)| Some((name, s.get("const")?))));
+            if let Some((tag_name, tag_value)) = tag {
+                out.push(Field {
+                    path: format!("{} ({tag_name}: {tag_value})", prefix.trim_end_matches('.')),
+                    kind: "object".to_string(),
+                    required: false,
+                    default: None,
+                    description: description(variant),
+                });
+                collect_fields(variant, defs, prefix, out);
+            }
+        }
+        return;
+    }
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return;
+    };
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let mut names: Vec<&String> = properties.keys().collect();
+    names.sort_by_key(|name| !required.contains(&name.as_str()));
+    for name in names {
+        let property = &properties[name.as_str()];
+        if property.get("const").is_some() {
+            // The tag of an internally tagged enum, listed with the variant
+            continue;
+        }
+        let path = format!("{prefix}{name}");
+        let target = non_null(property, defs);
+        out.push(Field {
+            path: path.clone(),
+            kind: type_name(property, defs),
+            required: required.contains(&name.as_str()),
+            default: property
+                .get("default")
+                .filter(|d| !d.is_null())
+                .map(Value::to_string),
+            description: description(property).or_else(|| description(target)),
+        });
+        let items = target.get("items").map(|items| non_null(items, defs));
+        match items {
+            Some(items) => collect_fields(items, defs, &format!("{path}[]."), out),
+            None => collect_fields(target, defs, &format!("{path}."), out),
+        }
+    }
+}
+
+fn description(schema: &Value) -> Option<String> {
+    schema
+        .get("description")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+/// Renders the config reference: every key with its type, whether it is
+/// required, its default and its description, followed by an example config.
+pub fn config_reference() -> String {
+    let schema = config_schema();
+    let empty = Map::new();
+    let defs = schema
+        .get("$defs")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+    let mut fields = Vec::new();
+    collect_fields(&schema, defs, "", &mut fields);
+
+    let mut out = String::from(
+        "MIGRATION CONFIG REFERENCE\n\n\
+         Config files are JSON, YAML (.yaml, .yml) or TOML (.toml). Keys of\n\
+         nested objects are written as `parent.key`, keys of list entries as\n\
+         `list[].key`. Besides the keys below, a config may name a base config\n\
+         to inherit from in `extends`, and use ${env:NAME} and ${var.NAME}\n\
+         placeholders in any string.\n\n",
+    );
+    for field in &fields {
+        out.push_str(&field.path);
+        out.push_str(&format!(" ({}", field.kind));
+        if field.required {
+            out.push_str(", required");
+        }
+        if let Some(default) = &field.default {
+            out.push_str(&format!(", default: {default}"));
+        }
+        out.push_str(")\n");
+        if let Some(description) = &field.description {
+            for line in description.lines() {
+                out.push_str(&format!("    {line}\n"));
+            }
+        }
+        out.push('\n');
+    }
+    if let Some(example) = profiles::source(EXAMPLE_PROFILE) {
+        out.push_str(&format!(
+            "EXAMPLE (the built-in profile {EXAMPLE_PROFILE})\n\n{}\n",
+            example.trim_end()
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_reference_lists_nested_fields() {
+        let reference = config_reference();
+        assert!(reference.contains("\napp_runtime_version (string, required)\n"));
+        assert!(reference.contains("\nreplacements[].from (string, required)\n"));
+        assert!(reference.contains("\nreplacements[].regex (boolean, default: false)\n"));
+        assert!(reference.contains("\nversion_lookup.cache_ttl_hours (integer"));
+        assert!(reference.contains("\nlog4j2[] (action: \"insert\") (object)\n"));
+        assert!(reference.contains("\nlog4j2[].parent (string, required)\n"));
+        assert!(reference.contains("EXAMPLE (the built-in profile 4.4-to-4.9-java17)"));
+    }
+
+    #[test]
+    fn test_json_schema_allows_extends_without_required_keys() {
+        let schema = config_json_schema();
+        assert_eq!(schema["additionalProperties"], false);
+        assert_eq!(schema["properties"]["extends"]["type"], "string");
+        assert!(schema["properties"]["$schema"].is_object());
+        assert!(schema.get("required").is_none());
+        let required = schema["else"]["required"].as_array().unwrap();
+        assert!(required.contains(&json!("app_runtime_version")));
+        assert_eq!(
+            schema["$defs"]["ReplacementRule"]["properties"]["from"]["type"],
+            "string"
+        );
+    }
+}
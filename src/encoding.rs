@@ -0,0 +1,195 @@
+use std::borrow::Cow;
+
+/// Character encodings source files are read and written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    /// ISO-8859-1, common in legacy `.properties` files and XML declaring
+    /// `encoding="ISO-8859-1"`.
+    Latin1,
+}
+
+impl Encoding {
+    pub fn name(self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Latin1 => "ISO-8859-1",
+        }
+    }
+
+    /// Maps the `encoding` of an XML declaration to a supported encoding.
+    fn from_label(label: &str) -> Option<Encoding> {
+        match label.to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" | "us-ascii" | "ascii" => Some(Encoding::Utf8),
+            "iso-8859-1" | "iso8859-1" | "iso_8859-1" | "latin1" | "latin-1" => {
+                Some(Encoding::Latin1)
+            }
+            _ => None,
+        }
+    }
+}
+
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+
+/// How a text file is stored on disk, so it can be written back the same way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextFormat {
+    pub encoding: Encoding,
+    /// The file starts with a UTF-8 byte order mark.
+    pub bom: bool,
+    /// Every line ends with `\r\n` rather than `\n`.
+    pub crlf: bool,
+}
+
+impl Default for TextFormat {
+    /// UTF-8 without BOM and with `\n` line endings, for new files.
+    fn default() -> Self {
+        TextFormat {
+            encoding: Encoding::Utf8,
+            bom: false,
+            crlf: false,
+        }
+    }
+}
+
+/// Control characters other than tab, line feed, form feed and carriage
+/// return do not occur in text files.
+fn is_text_byte(b: u8) -> bool {
+    b >= 0x20 || matches!(b, b'\t' | b'\n' | b'\x0c' | b'\r')
+}
+
+/// Encoding named by the XML declaration at the start of `bytes`, if any.
+fn declared_encoding(bytes: &[u8]) -> Option<Encoding> {
+    let rest = bytes.strip_prefix(b"<?xml")?;
+    let end = rest.windows(2).position(|w| w == b"?>")?;
+    let declaration = std::str::from_utf8(&rest[..end]).ok()?;
+    let value = declaration.split_once("encoding")?.1;
+    let value = value.trim_start().strip_prefix('=')?.trim_start();
+    let quote = value.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+    let label = value[1..].split(quote).next()?;
+    Encoding::from_label(label)
+}
+
+/// Detects how file content is stored. The encoding is the one an XML
+/// declaration names, else UTF-8 if the content is valid UTF-8, else
+/// ISO-8859-1 if every byte is a printable Latin-1 character. Returns `None`
+/// for binary content (NUL or other control bytes) or content that is not
+/// valid in its declared encoding.
+pub fn detect(bytes: &[u8]) -> Option<TextFormat> {
+    let bom = bytes.starts_with(UTF8_BOM);
+    let body = if bom { &bytes[UTF8_BOM.len()..] } else { bytes };
+    if !body.iter().copied().all(is_text_byte) {
+        return None;
+    }
+    let is_utf8 = std::str::from_utf8(body).is_ok();
+    let encoding = match declared_encoding(body) {
+        Some(Encoding::Utf8) if !is_utf8 => return None,
+        Some(declared) if !bom => declared,
+        _ if is_utf8 => Encoding::Utf8,
+        // 0x7F-0x9F are controls in Latin-1 but printable in Windows-1252; a
+        // guess either way could corrupt the file
+        _ if !bom && !body.iter().any(|b| (0x7f..=0x9f).contains(b)) => Encoding::Latin1,
+        _ => return None,
+    };
+    // Files mixing line endings are left as they are
+    let mut line_ends = body
+        .iter()
+        .enumerate()
+        .filter(|&(_, &b)| b == b'\n')
+        .map(|(i, _)| i > 0 && body[i - 1] == b'\r')
+        .peekable();
+    let crlf = line_ends.peek().is_some() && line_ends.all(|crlf| crlf);
+    Some(TextFormat {
+        encoding,
+        bom,
+        crlf,
+    })
+}
+
+/// Decodes file content to a string without the BOM, see [`detect`].
+pub fn decode(bytes: &[u8]) -> Option<(String, TextFormat)> {
+    let format = detect(bytes)?;
+    let body = if format.bom {
+        &bytes[UTF8_BOM.len()..]
+    } else {
+        bytes
+    };
+    let text = match format.encoding {
+        Encoding::Utf8 => String::from_utf8_lossy(body).into_owned(),
+        Encoding::Latin1 => body.iter().map(|&b| char::from(b)).collect(),
+    };
+    Some((text, format))
+}
+
+/// Encodes `text` in `format`, restoring the BOM and turning lines added
+/// with `\n` into `\r\n` in CRLF files. Returns `None` if `text` contains
+/// characters the encoding cannot represent.
+pub fn encode(text: &str, format: TextFormat) -> Option<Vec<u8>> {
+    let text = if format.crlf {
+        Cow::Owned(text.replace("\r\n", "\n").replace('\n', "\r\n"))
+    } else {
+        Cow::Borrowed(text)
+    };
+    let mut bytes = if format.bom {
+        UTF8_BOM.to_vec()
+    } else {
+        Vec::new()
+    };
+    match format.encoding {
+        Encoding::Utf8 => bytes.extend_from_slice(text.as_bytes()),
+        Encoding::Latin1 => {
+            for c in text.chars() {
+                bytes.push(u8::try_from(c).ok()?);
+            }
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_detects_latin1_and_binary() {
+        assert_eq!(
+            decode("café".as_bytes()),
+            Some(("café".to_string(), TextFormat::default()))
+        );
+        let latin1 = b"greeting=caf\xe9\n";
+        let (text, format) = decode(latin1).unwrap();
+        assert_eq!(text, "greeting=café\n");
+        assert_eq!(format.encoding, Encoding::Latin1);
+        assert_eq!(encode("greeting=café\n", format).unwrap(), latin1);
+        assert_eq!(encode("€", format), None);
+
+        assert_eq!(decode(b"PK\x03\x04\x14\x00\x00\x00"), None);
+        assert_eq!(decode(b"caf\x80"), None);
+    }
+
+    #[test]
+    fn test_round_trip_keeps_declared_encoding_bom_and_crlf() {
+        // ASCII-only, so only the declaration tells it is ISO-8859-1
+        let xml = b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?>\r\n<mule/>\r\n";
+        let (text, format) = decode(xml).unwrap();
+        assert_eq!(
+            format,
+            TextFormat {
+                encoding: Encoding::Latin1,
+                bom: false,
+                crlf: true
+            }
+        );
+        let edited = text.replace("<mule/>", "<mule>\n  <flow name=\"café\"/>\n</mule>");
+        assert_eq!(
+            encode(&edited, format).unwrap(),
+            b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?>\r\n<mule>\r\n  <flow name=\"caf\xe9\"/>\r\n</mule>\r\n"
+        );
+
+        let bom = b"\xef\xbb\xbfname=orders\n";
+        let (text, format) = decode(bom).unwrap();
+        assert_eq!(text, "name=orders\n");
+        assert!(format.bom && !format.crlf);
+        assert_eq!(encode(&text, format).unwrap(), bom);
+    }
+}
@@ -0,0 +1,82 @@
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Errors raised while migrating a project.
+#[derive(Debug, Error)]
+pub enum MigrateError {
+    #[error("'{0}' is not a Mule project (pom.xml or mule-artifact.json missing)")]
+    NotMuleProject(String),
+    #[error("{0}")]
+    Config(String),
+    #[error("Incompatible versions in the config (pass --force to migrate anyway):\n  {}", .0.join("\n  "))]
+    Incompatible(Vec<String>),
+    #[error("Offline mode, but the run needs the network or external programs:\n  {}", .0.join("\n  "))]
+    Offline(Vec<String>),
+    #[error("Invalid file pattern: {0}")]
+    Glob(#[from] globset::Error),
+    #[error("Failed to read {}: {source}", path.display())]
+    Read { path: PathBuf, source: io::Error },
+    #[error("Skipped {}: binary or in an unsupported encoding", path.display())]
+    NotText { path: PathBuf },
+    #[error("Failed to traverse the project: {0}")]
+    Walk(#[from] ignore::Error),
+    #[error("{0} file(s) could not be processed (--strict)")]
+    FileErrors(usize),
+    #[error("Failed to write {}: {source}", path.display())]
+    Write { path: PathBuf, source: io::Error },
+    #[error("Failed to create backup of {}: {source}; no files were changed", path.display())]
+    Backup { path: PathBuf, source: io::Error },
+    #[error("Failed to write {}: {source}; all changes were rolled back", path.display())]
+    RolledBack { path: PathBuf, source: io::Error },
+    #[error("Failed to parse {}: {source}", path.display())]
+    Xml {
+        path: PathBuf,
+        source: roxmltree::Error,
+    },
+    #[error("Failed to parse {}: {source}", path.display())]
+    Json {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error("Cannot update {}: {reason}", path.display())]
+    JsonField { path: PathBuf, reason: String },
+    #[error("Cannot bump the project version in {}: {reason}", path.display())]
+    ProjectVersion { path: PathBuf, reason: String },
+    #[error("Invalid plan {}: {reason}", path.display())]
+    Plan { path: PathBuf, reason: String },
+    #[error("{} changed since the plan was made; make a new plan", path.display())]
+    PlanOutdated { path: PathBuf },
+    #[error("{} is held by {holder}; wait for that run to finish", path.display())]
+    Locked { path: PathBuf, holder: String },
+    #[error("'{0}' has uncommitted changes; commit or stash them, or pass --allow-dirty")]
+    DirtyWorkingTree(String),
+    #[error("{0}")]
+    Git(io::Error),
+    #[error("Maven not found: no Maven wrapper in {} and no mvn on the PATH (pass --maven-bin)", .0.display())]
+    MavenNotFound(PathBuf),
+    #[error("Hook '{command}' failed: {reason}")]
+    Hook { command: String, reason: String },
+    #[error("Plugin '{name}' failed: {reason}")]
+    Plugin { name: String, reason: String },
+    #[error("Redeploy of '{application}' failed: {reason}")]
+    Deploy { application: String, reason: String },
+    #[error("{} is broken after editing: {reason}; no files were changed", path.display())]
+    PomVerification { path: PathBuf, reason: String },
+    #[error("Interactive review failed: {0}")]
+    Interactive(io::Error),
+    #[error("Failed to write the report: {0}")]
+    Report(io::Error),
+}
+
+impl MigrateError {
+    /// Classifies a failed read of `path`: invalid data means the file is
+    /// binary (or in an unsupported encoding) rather than unreadable.
+    pub fn from_read(path: PathBuf, source: io::Error) -> Self {
+        if source.kind() == io::ErrorKind::InvalidData {
+            MigrateError::NotText { path }
+        } else {
+            MigrateError::Read { path, source }
+        }
+    }
+}
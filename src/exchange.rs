@@ -0,0 +1,205 @@
+use crate::lookup::{self, VersionCache, VersionLookupConfig};
+use crate::version::{self, VersionBump};
+use crate::xml;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::cmp::Ordering;
+
+/// Maven facade of Anypoint Exchange.
+pub const EXCHANGE_REPOSITORY: &str = "https://maven.anypoint.mulesoft.com/api/v3/maven";
+
+/// Environment variable holding the Anypoint access token by default.
+const DEFAULT_TOKEN_ENV: &str = "ANYPOINT_TOKEN";
+
+/// Changes of the asset's own `exchange.json`, made when a migration run
+/// changes the project.
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ExchangeJsonConfig {
+    /// New asset `version`: `major`, `minor`, `patch` or an explicit version.
+    #[serde(default)]
+    pub version: Option<VersionBump>,
+    /// New `apiVersion`, e.g. `v2`: `major`, `minor`, `patch` or an
+    /// explicit value.
+    #[serde(default)]
+    pub api_version: Option<VersionBump>,
+}
+
+impl ExchangeJsonConfig {
+    pub fn is_empty(&self) -> bool {
+        self.version.is_none() && self.api_version.is_none()
+    }
+}
+
+/// `exchange` section of the migration config.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ExchangeConfig {
+    /// Maven repository base URL of Exchange.
+    #[serde(default = "default_repository")]
+    pub repository: String,
+    /// Environment variable holding the access token for Exchange lookups.
+    #[serde(default = "default_token_env")]
+    pub token_env: String,
+    /// API spec dependencies (RAML/OAS assets) to bump.
+    #[serde(default)]
+    pub api_specs: Vec<ApiSpec>,
+}
+
+impl Default for ExchangeConfig {
+    fn default() -> Self {
+        ExchangeConfig {
+            repository: default_repository(),
+            token_env: default_token_env(),
+            api_specs: Vec::new(),
+        }
+    }
+}
+
+fn default_repository() -> String {
+    EXCHANGE_REPOSITORY.to_string()
+}
+
+fn default_token_env() -> String {
+    DEFAULT_TOKEN_ENV.to_string()
+}
+
+/// An Exchange asset dependency, e.g. `<classifier>raml</classifier>`.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ApiSpec {
+    /// Exchange groupId, usually the organization id.
+    pub group_id: String,
+    pub artifact_id: String,
+    /// Version to set; without one, the latest release in Exchange.
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+/// Resolves the target version of each API spec pom.xml declares. Specs
+/// without a configured version are looked up in Exchange and only raised,
+/// never lowered.
+///
+/// Returns `(groupId, artifactId, version)` triples.
+pub fn resolve_api_spec_versions(
+    content: &str,
+    config: &ExchangeConfig,
+    lookup: &VersionLookupConfig,
+) -> Result<Vec<(String, String, String)>, roxmltree::Error> {
+    let declared = xml::declared_artifacts(content)?;
+    // The proxy and cache settings of the other lookups apply to Exchange too
+    let lookup_config = VersionLookupConfig {
+        repositories: vec![config.repository.clone()],
+        ..lookup.clone()
+    };
+    let token = std::env::var(&config.token_env).ok();
+    let mut cache = None;
+    let mut versions = Vec::new();
+    for spec in &config.api_specs {
+        let Some(artifact) = declared
+            .iter()
+            .find(|a| a.group_id == spec.group_id && a.artifact_id == spec.artifact_id)
+        else {
+            log::debug!(
+                "API spec {}:{} is not declared in pom.xml",
+                spec.group_id,
+                spec.artifact_id
+            );
+            continue;
+        };
+        let version = match &spec.version {
+            Some(version) => version.clone(),
+            None => {
+                let cache = cache.get_or_insert_with(VersionCache::load_default);
+                let range = lookup_config.range_for(
+                    &spec.group_id,
+                    &spec.artifact_id,
+                    artifact.version.as_deref(),
+                );
+                let Some(latest) = lookup::lookup_latest_authorized(
+                    &spec.group_id,
+                    &spec.artifact_id,
+                    &lookup_config,
+                    cache,
+                    token.as_deref(),
+                    range.as_ref(),
+                ) else {
+                    continue;
+                };
+                let current = artifact.version.as_deref().unwrap_or("");
+                if version::compare(current, &latest) != Some(Ordering::Less) {
+                    continue;
+                }
+                latest
+            }
+        };
+        versions.push((spec.group_id.clone(), spec.artifact_id.clone(), version));
+    }
+    if let Some(cache) = cache {
+        cache.save();
+    }
+    Ok(versions)
+}
+
+/// Sets the `<version>` of the API spec dependencies in pom.xml content.
+///
+/// Returns the new content and a description of each updated dependency.
+pub fn update_api_spec_versions(
+    content: &str,
+    versions: &[(String, String, String)],
+) -> Result<(String, Vec<String>), roxmltree::Error> {
+    xml::edit_declared_versions(content, "API spec", |group_id, artifact_id, _| {
+        versions
+            .iter()
+            .find(|(g, a, _)| g == group_id && a == artifact_id)
+            .map(|(_, _, v)| v.clone())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_spec_versions_are_updated() {
+        let pom = r#"<project>
+  <dependencies>
+    <dependency>
+      <groupId>4f6c2b3a-0d1e-4b5c-9a8f-1e2d3c4b5a69</groupId>
+      <artifactId>orders-api</artifactId>
+      <version>1.0.2</version>
+      <classifier>raml</classifier>
+      <type>zip</type>
+    </dependency>
+    <dependency>
+      <groupId>4f6c2b3a-0d1e-4b5c-9a8f-1e2d3c4b5a69</groupId>
+      <artifactId>customers-api</artifactId>
+      <version>2.1.0</version>
+      <classifier>oas</classifier>
+      <type>zip</type>
+    </dependency>
+  </dependencies>
+</project>"#;
+        let config: ExchangeConfig = serde_yaml::from_str(
+            r#"
+api_specs:
+  - group_id: 4f6c2b3a-0d1e-4b5c-9a8f-1e2d3c4b5a69
+    artifact_id: orders-api
+    version: 1.1.0
+  - group_id: 4f6c2b3a-0d1e-4b5c-9a8f-1e2d3c4b5a69
+    artifact_id: payments-api
+    version: 3.0.0
+"#,
+        )
+        .unwrap();
+        let versions =
+            resolve_api_spec_versions(pom, &config, &VersionLookupConfig::default()).unwrap();
+        let (out, changes) = update_api_spec_versions(pom, &versions).unwrap();
+        assert_eq!(
+            changes,
+            vec!["API spec 4f6c2b3a-0d1e-4b5c-9a8f-1e2d3c4b5a69:orders-api: '1.0.2' -> '1.1.0'"]
+        );
+        assert!(out.contains("<version>1.1.0</version>\n      <classifier>raml</classifier>"));
+        assert!(out.contains("<version>2.1.0</version>"));
+    }
+}
@@ -1,136 +1,1009 @@
-use crate::config::ReplacementRule;
+use crate::backup::Snapshot;
+use crate::config::{FileRename, ReplacementRule};
+use crate::encoding::{self, Encoding};
+use crate::error::MigrateError;
+use crate::xml;
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
 use log;
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use std::collections::HashSet;
 use std::fs;
-use std::io::{Read, Write};
-use walkdir::WalkDir;
+use std::io::{self, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 
 const FILE_EXTENSIONS: &[&str] = &["xml", "dwl"]; // Extend as needed
 
+/// Directories that never contain migratable sources.
+pub(crate) const ALWAYS_SKIPPED_DIRS: &[&str] =
+    &[".git", ".mule-migrate", "target", "node_modules"];
+
+/// Include/exclude glob filter on paths relative to the project root.
+///
+/// A file is selected if it matches no exclude glob and, when include globs
+/// are given, at least one of them.
+#[derive(Debug, Clone, Default)]
+pub struct FileFilter {
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    skip: HashSet<PathBuf>,
+}
+
+fn build_glob_set(patterns: &[String]) -> Result<Option<GlobSet>, globset::Error> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern)?);
+    }
+    Ok(Some(builder.build()?))
+}
+
+impl FileFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self, globset::Error> {
+        Ok(FileFilter {
+            include: build_glob_set(include)?,
+            exclude: build_glob_set(exclude)?,
+            skip: HashSet::new(),
+        })
+    }
+
+    /// Leaves out these exact paths (relative to the project root) as well.
+    pub fn skip_files(&mut self, paths: impl IntoIterator<Item = PathBuf>) {
+        self.skip.extend(paths);
+    }
+
+    /// Checks a path relative to the project root.
+    pub fn is_selected(&self, relative: &Path) -> bool {
+        if self.skip.contains(relative) {
+            return false;
+        }
+        if self.exclude.as_ref().is_some_and(|g| g.is_match(relative)) {
+            return false;
+        }
+        self.include.as_ref().is_none_or(|g| g.is_match(relative))
+    }
+}
+
+/// Walks the files under `root`, honoring `.gitignore`/`.ignore` files,
+/// skipping build output and VCS directories, and applying `filter`.
+///
+/// Entries that cannot be read (e.g. permission denied) are yielded as errors.
+pub fn walk_entries<'a>(
+    root: &'a Path,
+    filter: &'a FileFilter,
+) -> impl Iterator<Item = Result<PathBuf, ignore::Error>> + 'a {
+    WalkBuilder::new(root)
+        .hidden(false)
+        .require_git(false)
+        .filter_entry(|e| {
+            !(e.file_type().is_some_and(|t| t.is_dir())
+                && ALWAYS_SKIPPED_DIRS
+                    .iter()
+                    .any(|d| e.file_name() == std::ffi::OsStr::new(d)))
+        })
+        .build()
+        .filter(|e| {
+            e.as_ref()
+                .map_or(true, |e| e.file_type().is_some_and(|t| t.is_file()))
+        })
+        .map(|e| e.map(|e| e.into_path()))
+        .filter(move |path| match path {
+            Ok(path) => filter.is_selected(path.strip_prefix(root).unwrap_or(path)),
+            Err(_) => true,
+        })
+}
+
+/// Like [`walk_entries`], silently skipping unreadable entries.
+pub fn walk_files<'a>(
+    root: &'a Path,
+    filter: &'a FileFilter,
+) -> impl Iterator<Item = PathBuf> + 'a {
+    walk_entries(root, filter).filter_map(|e| e.ok())
+}
+
+/// Reads a text file in the encoding it declares or is detected in, without
+/// its BOM. Binary content is an `InvalidData` error.
+pub fn read_text<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    let path = path.as_ref();
+    let (text, format) = encoding::decode(&fs::read(path)?)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "binary content"))?;
+    if format.encoding != Encoding::Utf8 {
+        log::debug!("Reading {} as {}", path.display(), format.encoding.name());
+    }
+    Ok(text)
+}
+
+/// Writes a text file the way it is already stored: same encoding, BOM and
+/// line endings (UTF-8 with `\n` for new files).
+///
+/// # Errors
+/// Returns an `InvalidData` error, leaving the file untouched, if `content`
+/// cannot be represented in the file's encoding.
+pub fn write_text<P: AsRef<Path>>(path: P, content: &str) -> io::Result<()> {
+    let path = path.as_ref();
+    let format = fs::read(path)
+        .ok()
+        .and_then(|bytes| encoding::detect(&bytes))
+        .unwrap_or_default();
+    let bytes = encoding::encode(content, format).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "new content cannot be encoded as {}",
+                format.encoding.name()
+            ),
+        )
+    })?;
+    write_atomic(path, &bytes)
+}
+
+/// Writes `bytes` to a temporary file next to `path` and renames it over
+/// `path`, so a crash never leaves a truncated file behind. The permissions
+/// of an existing file are kept.
+pub(crate) fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a file path"))?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp = path.with_file_name(temp_name);
+    let result = (|| {
+        let mut file = fs::File::create(&temp)?;
+        file.write_all(bytes)?;
+        if let Ok(metadata) = fs::metadata(path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.sync_all()?;
+        fs::rename(&temp, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Reads a text file, attaching its path to the error.
+pub(crate) fn read_file<P: AsRef<Path>>(path: P) -> Result<String, MigrateError> {
+    let path = path.as_ref();
+    read_text(path).map_err(|source| MigrateError::from_read(path.to_path_buf(), source))
+}
+
+/// Writes a text file, attaching its path to the error.
+pub(crate) fn write_file<P: AsRef<Path>>(path: P, content: &str) -> Result<(), MigrateError> {
+    let path = path.as_ref();
+    write_text(path, content).map_err(|source| MigrateError::Write {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Saves a file to `snapshot` before it is changed.
+pub(crate) fn backup_file(snapshot: &Snapshot, path: &Path) -> Result<(), MigrateError> {
+    snapshot.save(path).map_err(|source| MigrateError::Backup {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// A replacement rule with its pattern and path globs (if any) compiled once up front.
+#[derive(Debug, Clone)]
+struct CompiledRule<'a> {
+    rule: &'a ReplacementRule,
+    /// `from` as a regex; plain rules are escaped.
+    pattern: Regex,
+    paths: Option<GlobSet>,
+}
+
+impl CompiledRule<'_> {
+    /// Checks whether the rule applies to a path relative to the project root.
+    /// Attribute rules only apply to XML files.
+    fn applies_to(&self, relative: &Path) -> bool {
+        (self.rule.attribute.is_none() || is_xml(relative))
+            && self.paths.as_ref().is_none_or(|g| g.is_match(relative))
+    }
+}
+
+fn compile_rules(replacements: &[ReplacementRule]) -> Vec<CompiledRule<'_>> {
+    replacements
+        .iter()
+        .filter_map(|rule| {
+            let paths = match build_glob_set(&rule.paths) {
+                Ok(paths) => paths,
+                Err(e) => {
+                    log::error!("Skipping rule '{}' with invalid path glob: {e}", rule.from);
+                    return None;
+                }
+            };
+            let source = if rule.regex {
+                rule.from.clone()
+            } else {
+                regex::escape(&rule.from)
+            };
+            match RegexBuilder::new(&source)
+                .case_insensitive(rule.case_insensitive)
+                .build()
+            {
+                Ok(pattern) => Some(CompiledRule {
+                    rule,
+                    pattern,
+                    paths,
+                }),
+                Err(e) => {
+                    log::error!("Skipping invalid regex rule '{}': {e}", rule.from);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+fn is_xml(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e == "xml")
+}
+
+/// Characters that make a match part of a larger token for `whole_word` rules.
+fn is_token_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.')
+}
+
+/// Checks that `content[start..end]` is not adjacent to a token character.
+fn is_whole_word(content: &str, start: usize, end: usize) -> bool {
+    !content[..start]
+        .chars()
+        .next_back()
+        .is_some_and(is_token_char)
+        && !content[end..].chars().next().is_some_and(is_token_char)
+}
+
+/// Replaces the matches of a rule in `text`, at most `limit` of them.
+fn replace_matches(text: &str, compiled: &CompiledRule, limit: Option<usize>) -> (String, usize) {
+    let rule = compiled.rule;
+    let mut replaced = String::new();
+    let mut last = 0;
+    let mut count = 0;
+    for caps in compiled.pattern.captures_iter(text) {
+        if limit.is_some_and(|max| count >= max) {
+            break;
+        }
+        let m = caps.get(0).expect("Match without a group 0");
+        if rule.whole_word && !is_whole_word(text, m.start(), m.end()) {
+            continue;
+        }
+        replaced.push_str(&text[last..m.start()]);
+        if rule.regex {
+            caps.expand(&rule.to, &mut replaced);
+        } else {
+            replaced.push_str(&rule.to);
+        }
+        last = m.end();
+        count += 1;
+    }
+    if count == 0 {
+        return (text.to_string(), 0);
+    }
+    replaced.push_str(&text[last..]);
+    (replaced, count)
+}
+
+/// Replaces the matches of a rule in `content` outside the sorted,
+/// non-overlapping `skipped` ranges.
+fn replace_outside(
+    content: &str,
+    skipped: &[Range<usize>],
+    compiled: &CompiledRule,
+) -> (String, usize) {
+    let mut replaced = String::new();
+    let mut last = 0;
+    let mut total = 0;
+    let gaps = skipped
+        .iter()
+        .map(|r| (r.start, r.end))
+        .chain([(content.len(), content.len())]);
+    for (start, end) in gaps {
+        let limit = compiled
+            .rule
+            .max_replacements
+            .map(|max| max.saturating_sub(total));
+        let (segment, count) = replace_matches(&content[last..start], compiled, limit);
+        replaced.push_str(&segment);
+        replaced.push_str(&content[start..end]);
+        total += count;
+        last = end;
+    }
+    (replaced, total)
+}
+
+/// Applies a single rule to `content`, returning the new content and the number of matches.
+fn apply_rule(content: &str, compiled: &CompiledRule, is_xml: bool) -> (String, usize) {
+    let rule = compiled.rule;
+    let Some(attribute) = &rule.attribute else {
+        if rule.skip_dataweave && is_xml {
+            return match xml::dataweave_ranges(content) {
+                Ok(scripts) => replace_outside(content, &scripts, compiled),
+                Err(e) => {
+                    log::warn!(
+                        "Skipping DataWeave-aware rule '{}' on unparsable XML: {e}",
+                        rule.from
+                    );
+                    (content.to_string(), 0)
+                }
+            };
+        }
+        return replace_matches(content, compiled, rule.max_replacements);
+    };
+    let mut total = 0;
+    let rewritten = xml::rewrite_attribute_values(content, attribute, |value| {
+        let limit = rule.max_replacements.map(|max| max.saturating_sub(total));
+        let (value, count) = replace_matches(value, compiled, limit);
+        total += count;
+        (value, count)
+    });
+    match rewritten {
+        Ok(result) => result,
+        Err(e) => {
+            log::warn!(
+                "Skipping attribute rule '{}' on unparsable XML: {e}",
+                rule.from
+            );
+            (content.to_string(), 0)
+        }
+    }
+}
+
+/// Replacement rules compiled once, to apply to the content of many files.
+#[derive(Debug, Clone)]
+pub struct CompiledRules<'a>(Vec<CompiledRule<'a>>);
+
+impl<'a> CompiledRules<'a> {
+    /// Compiles `rules`, skipping (and logging) those with an invalid regex or
+    /// path glob.
+    pub fn new(rules: &'a [ReplacementRule]) -> Self {
+        CompiledRules(compile_rules(rules))
+    }
+
+    /// Applies the rules to the content of one file, at `relative` to the
+    /// project root, returning the new content and the rules that matched.
+    pub fn replace_in_content(
+        &self,
+        content: &str,
+        relative: &Path,
+    ) -> (String, Vec<&'a ReplacementRule>) {
+        let mut content = content.to_string();
+        let mut matched = Vec::new();
+        for compiled in self.0.iter().filter(|c| c.applies_to(relative)) {
+            let (replaced, count) = apply_rule(&content, compiled, is_xml(relative));
+            if count > 0 {
+                matched.push(compiled.rule);
+                content = replaced;
+            }
+        }
+        (content, matched)
+    }
+}
+
 pub fn traverse_and_replace(
-    root: &str,
+    root: &Path,
     replacements: &[ReplacementRule],
     dry_run: bool,
-    backup: bool,
-) {
+    backup: Option<&Snapshot>,
+) -> Result<(), MigrateError> {
     log::info!("🔍 Scanning for files with extensions: {FILE_EXTENSIONS:?}");
     log::info!("📝 Replacement rules to apply:");
     for (i, rule) in replacements.iter().enumerate() {
         log::info!("  {}. '{}' -> '{}'", i + 1, rule.from, rule.to);
     }
 
+    let rules = compile_rules(replacements);
     let mut files_processed = 0;
     let mut files_updated = 0;
 
-    for entry in WalkDir::new(root).into_iter().filter_map(|e| e.ok()) {
-        let path = entry.path();
-        if path.is_file() {
-            if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-                if FILE_EXTENSIONS.contains(&ext) {
-                    files_processed += 1;
-                    log::info!("📄 Processing: {}", path.display());
-
-                    let mut content = String::new();
-                    if let Ok(mut file) = fs::File::open(path) {
-                        if file.read_to_string(&mut content).is_ok() {
-                            let mut new_content = content.clone();
-                            let mut replacements_made = 0;
-
-                            for rule in replacements {
-                                let count = new_content.matches(&rule.from).count();
-                                if count > 0 {
-                                    log::info!(
-                                        "    🔄 Replacing '{}' with '{}' ({} occurrences)",
-                                        rule.from,
-                                        rule.to,
-                                        count
-                                    );
-                                    new_content = new_content.replace(&rule.from, &rule.to);
-                                    replacements_made += count;
-                                }
+    let filter = FileFilter::default();
+    for path in walk_files(root, &filter) {
+        let path = path.as_path();
+        if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+            if FILE_EXTENSIONS.contains(&ext) {
+                files_processed += 1;
+                log::info!("📄 Processing: {}", path.display());
+
+                match read_text(path) {
+                    Ok(content) => {
+                        let mut new_content = content.clone();
+                        let mut replacements_made = 0;
+
+                        let relative = path.strip_prefix(root).unwrap_or(path);
+                        for compiled in rules.iter().filter(|r| r.applies_to(relative)) {
+                            let (replaced, count) =
+                                apply_rule(&new_content, compiled, is_xml(path));
+                            if count > 0 {
+                                log::info!(
+                                    "    🔄 Replacing '{}' with '{}' ({} occurrences)",
+                                    compiled.rule.from,
+                                    compiled.rule.to,
+                                    count
+                                );
+                                new_content = replaced;
+                                replacements_made += count;
                             }
+                        }
 
-                            if new_content != content {
-                                files_updated += 1;
-                                if backup {
-                                    let backup_path = path.with_extension(format!("{ext}.bak"));
-                                    fs::copy(path, &backup_path).expect("Failed to create backup");
-                                    log::info!("    💾 Backup created: {}", backup_path.display());
-                                }
-                                if dry_run {
-                                    log::info!(
-                                        "    [DRY-RUN] Would update {} ({} replacements)",
-                                        path.display(),
-                                        replacements_made
-                                    );
-                                } else {
-                                    let mut file =
-                                        fs::File::create(path).expect("Failed to write file");
-                                    file.write_all(new_content.as_bytes())
-                                        .expect("Failed to write file");
-                                    log::info!(
-                                        "    ✅ Updated {} ({} replacements)",
-                                        path.display(),
-                                        replacements_made
-                                    );
-                                }
+                        if new_content != content {
+                            files_updated += 1;
+                            if let Some(snapshot) = backup {
+                                backup_file(snapshot, path)?;
+                                log::info!("    💾 Backup saved to {}", snapshot.dir().display());
+                            }
+                            if dry_run {
+                                log::info!(
+                                    "    [DRY-RUN] Would update {} ({} replacements)",
+                                    path.display(),
+                                    replacements_made
+                                );
                             } else {
-                                log::info!("    ✅ No changes needed");
+                                write_file(path, &new_content)?;
+                                log::info!(
+                                    "    ✅ Updated {} ({} replacements)",
+                                    path.display(),
+                                    replacements_made
+                                );
                             }
+                        } else {
+                            log::info!("    ✅ No changes needed");
                         }
                     }
+                    Err(e) => log::warn!("    ⚠️ Skipping {}: {e}", path.display()),
                 }
             }
         }
     }
 
     log::info!("📊 Summary: Processed {files_processed} files, updated {files_updated} files");
+    Ok(())
+}
+
+const SUMMARY_FILE_EXTENSIONS: &[&str] = &[
+    "xml",
+    "yaml",
+    "yml",
+    "properties",
+    "txt",
+    "java",
+    "groovy",
+    "json",
+];
+
+/// A source file whose content would change after applying the replacement rules.
+#[derive(Debug)]
+pub struct PlannedReplacement {
+    pub path: PathBuf,
+    pub original: String,
+    pub content: String,
+    /// One `path: 'from' -> 'to'` entry per rule that matched.
+    pub summary: Vec<String>,
+}
+
+/// How often a replacement rule matched across the project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuleImpact {
+    pub from: String,
+    pub to: String,
+    /// Total number of matches.
+    pub matches: usize,
+    /// Number of files with at least one match.
+    pub files: usize,
+}
+
+/// Replacements planned for a project, plus the files that could not be
+/// processed.
+#[derive(Debug, Default)]
+pub struct ReplacementPlan {
+    pub planned: Vec<PlannedReplacement>,
+    pub errors: Vec<MigrateError>,
+    /// One entry per rule, in config order, including rules that never matched.
+    pub impact: Vec<RuleImpact>,
+}
+
+/// Computes the replacements for every source file under `root` without
+/// touching the files.
+///
+/// File content is obtained through `read`, so callers can supply content
+/// staged by earlier migration steps instead of what is on disk. Files that
+/// cannot be read, or are not UTF-8 text, are collected in `errors`.
+pub fn plan_replacements<F>(
+    root: &Path,
+    replacements: &[ReplacementRule],
+    filter: &FileFilter,
+    mut read: F,
+) -> ReplacementPlan
+where
+    F: FnMut(&Path) -> std::io::Result<String>,
+{
+    let rules = compile_rules(replacements);
+    let mut plan = ReplacementPlan {
+        impact: rules
+            .iter()
+            .map(|compiled| RuleImpact {
+                from: compiled.rule.from.clone(),
+                to: compiled.rule.to.clone(),
+                matches: 0,
+                files: 0,
+            })
+            .collect(),
+        ..Default::default()
+    };
+    for entry in walk_entries(root, filter) {
+        let path = match entry {
+            Ok(path) => path,
+            Err(e) => {
+                plan.errors.push(e.into());
+                continue;
+            }
+        };
+        let path = path.as_path();
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        if !SUMMARY_FILE_EXTENSIONS.contains(&ext) {
+            continue;
+        }
+        let original = match read(path) {
+            Ok(original) => original,
+            Err(e) => {
+                plan.errors
+                    .push(MigrateError::from_read(path.to_path_buf(), e));
+                continue;
+            }
+        };
+        let mut content = original.clone();
+        let mut summary = Vec::new();
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        for (compiled, impact) in rules.iter().zip(&mut plan.impact) {
+            if !compiled.applies_to(relative) {
+                continue;
+            }
+            let (replaced, count) = apply_rule(&content, compiled, is_xml(path));
+            if count > 0 {
+                impact.matches += count;
+                impact.files += 1;
+                summary.push(format!(
+                    "{}: '{}' -> '{}'",
+                    path.display(),
+                    compiled.rule.from,
+                    compiled.rule.to
+                ));
+                content = replaced;
+            }
+        }
+        if !summary.is_empty() {
+            plan.planned.push(PlannedReplacement {
+                path: path.to_path_buf(),
+                original,
+                content,
+                summary,
+            });
+        }
+    }
+    plan
+}
+
+/// File moves planned for a project by its `file_renames` rules.
+#[derive(Debug, Default)]
+pub struct RenamePlan {
+    /// Current and new path of each file to move.
+    pub renames: Vec<(PathBuf, PathBuf)>,
+    /// Files left in place because their new path is already taken.
+    pub conflicts: Vec<String>,
+    /// Indices of the rules that matched no file.
+    pub unmatched: Vec<usize>,
+}
+
+fn has_wildcard(component: &str) -> bool {
+    component.contains(['*', '?', '[', '{'])
+}
+
+/// Computes the new path of every file under `root` matched by `rules`
+/// without touching the files. A file matched by several rules is moved by
+/// the first one.
+///
+/// # Errors
+/// Returns an error if a `from` glob is invalid.
+pub fn plan_file_renames(
+    root: &Path,
+    rules: &[FileRename],
+    filter: &FileFilter,
+) -> Result<RenamePlan, MigrateError> {
+    let mut plan = RenamePlan::default();
+    if rules.is_empty() {
+        return Ok(plan);
+    }
+    let mut matchers = Vec::new();
+    for rule in rules {
+        let from = rule.from.trim_matches('/');
+        let prefix: PathBuf = from.split('/').take_while(|c| !has_wildcard(c)).collect();
+        let matcher = GlobBuilder::new(from)
+            .literal_separator(true)
+            .build()?
+            .compile_matcher();
+        matchers.push((matcher, prefix, from.split('/').any(has_wildcard)));
+    }
+    let mut matched = vec![false; rules.len()];
+    let mut taken = HashSet::new();
+    for path in walk_files(root, filter) {
+        let relative = path.strip_prefix(root).unwrap_or(&path);
+        let Some(i) = matchers.iter().position(|(m, ..)| m.is_match(relative)) else {
+            continue;
+        };
+        matched[i] = true;
+        let (_, prefix, glob) = &matchers[i];
+        let target = root.join(rules[i].to.trim_matches('/'));
+        let target = if *glob {
+            target.join(relative.strip_prefix(prefix).unwrap_or(relative))
+        } else {
+            target
+        };
+        if target == path {
+            continue;
+        }
+        if target.exists() || !taken.insert(target.clone()) {
+            plan.conflicts.push(format!(
+                "{}: {} already exists",
+                path.display(),
+                target.display()
+            ));
+            continue;
+        }
+        plan.renames.push((path, target));
+    }
+    plan.unmatched = (0..rules.len()).filter(|i| !matched[*i]).collect();
+    Ok(plan)
+}
+
+/// Finds the files under `root` matching any of the `patterns` (paths or
+/// globs relative to the root), for deletion.
+///
+/// # Errors
+/// Returns an error if a glob is invalid.
+pub fn plan_file_deletes(
+    root: &Path,
+    patterns: &[String],
+    filter: &FileFilter,
+) -> Result<Vec<PathBuf>, MigrateError> {
+    if patterns.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(
+            GlobBuilder::new(pattern.trim_matches('/'))
+                .literal_separator(true)
+                .build()?,
+        );
+    }
+    let globs = builder.build()?;
+    Ok(walk_files(root, filter)
+        .filter(|path| globs.is_match(path.strip_prefix(root).unwrap_or(path)))
+        .collect())
 }
 
 pub fn traverse_and_replace_summary(
-    root: &str,
-    replacements: &Vec<(String, String)>,
+    root: &Path,
+    replacements: &[ReplacementRule],
     dry_run: bool,
-    backup: bool,
-) -> Vec<String> {
+    backup: Option<&Snapshot>,
+) -> Result<Vec<String>, MigrateError> {
     let mut summary = Vec::new();
-    for entry in WalkDir::new(root).into_iter().filter_map(|e| e.ok()) {
-        if entry.file_type().is_file() {
-            let path = entry.path();
-            let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-            if [
-                "xml",
-                "yaml",
-                "yml",
-                "properties",
-                "txt",
-                "java",
-                "groovy",
-                "json",
-            ]
-            .contains(&ext)
-            {
-                let content = fs::read_to_string(path);
-                if let Ok(mut content) = content {
-                    let mut changed = false;
-                    for (from, to) in replacements {
-                        if content.contains(from) {
-                            summary.push(format!("{}: '{}' -> '{}'", path.display(), from, to));
-                            content = content.replace(from, to);
-                            changed = true;
-                        }
-                    }
-                    if changed {
-                        if backup {
-                            let backup_path = format!("{}.bak", path.display());
-                            fs::copy(path, &backup_path).ok();
-                        }
-                        if !dry_run {
-                            fs::write(path, content).ok();
-                        }
-                    }
-                }
-            }
+    let filter = FileFilter::default();
+    let plan = plan_replacements(root, replacements, &filter, |p| read_text(p));
+    for planned in plan.planned {
+        if let Some(snapshot) = backup {
+            backup_file(snapshot, &planned.path)?;
         }
+        if !dry_run {
+            write_file(&planned.path, &planned.content)?;
+        }
+        summary.extend(planned.summary);
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn rule(from: &str, to: &str, regex: bool) -> ReplacementRule {
+        ReplacementRule {
+            from: from.to_string(),
+            to: to.to_string(),
+            regex,
+            paths: Vec::new(),
+            whole_word: false,
+            case_insensitive: false,
+            max_replacements: None,
+            attribute: None,
+            skip_dataweave: false,
+        }
+    }
+
+    #[test]
+    fn test_traverse_and_replace_summary_plain() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("flow.xml");
+        fs::write(&file_path, "<set-payload value=\"#[error.muleMessage]\"/>").unwrap();
+        let summary = traverse_and_replace_summary(
+            dir.path(),
+            &[rule("error.muleMessage", "error.errorMessage", false)],
+            false,
+            None,
+        )
+        .unwrap();
+        assert_eq!(summary.len(), 1);
+        let content = fs::read_to_string(&file_path).unwrap();
+        assert!(content.contains("error.errorMessage"));
+    }
+
+    #[test]
+    fn test_traverse_and_replace_summary_regex_capture_groups() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("flow.xml");
+        fs::write(
+            &file_path,
+            r#"<http:request config-ref="orders-old"/><http:request config-ref="users-old"/>"#,
+        )
+        .unwrap();
+        let summary = traverse_and_replace_summary(
+            dir.path(),
+            &[rule(
+                r#"config-ref="([^"]*)-old""#,
+                r#"config-ref="$1-new""#,
+                true,
+            )],
+            false,
+            None,
+        )
+        .unwrap();
+        assert_eq!(summary.len(), 1);
+        let content = fs::read_to_string(&file_path).unwrap();
+        assert_eq!(
+            content,
+            r#"<http:request config-ref="orders-new"/><http:request config-ref="users-new"/>"#
+        );
+    }
+
+    #[test]
+    fn test_plan_replacements_respects_gitignore_and_globs() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        for sub in [
+            "src/main/mule",
+            "src/test/resources",
+            "target/classes",
+            "generated",
+        ] {
+            fs::create_dir_all(root.join(sub)).unwrap();
+            fs::write(root.join(sub).join("a.xml"), "old").unwrap();
+        }
+        fs::write(root.join(".gitignore"), "generated/\n").unwrap();
+
+        let rules = [rule("old", "new", false)];
+        let filter = FileFilter::new(&[], &["src/test/**".to_string()]).unwrap();
+        let planned = plan_replacements(root, &rules, &filter, |p| fs::read_to_string(p));
+        let paths: Vec<_> = planned
+            .planned
+            .iter()
+            .map(|p| p.path.strip_prefix(root).unwrap().to_path_buf())
+            .collect();
+        assert_eq!(paths, vec![PathBuf::from("src/main/mule/a.xml")]);
+
+        let filter = FileFilter::new(&["generated/**".to_string()], &[]).unwrap();
+        let planned = plan_replacements(root, &rules, &filter, |p| fs::read_to_string(p));
+        assert!(planned.planned.is_empty());
+    }
+
+    #[test]
+    fn test_plan_replacements_collects_file_errors() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("flow.xml"), "old").unwrap();
+        fs::write(root.join("blob.xml"), [0x6f, 0x6c, 0x64, 0xff, 0xfe]).unwrap();
+
+        let rules = [rule("old", "new", false)];
+        let plan = plan_replacements(root, &rules, &FileFilter::default(), |p| {
+            fs::read_to_string(p)
+        });
+        assert_eq!(plan.planned.len(), 1);
+        assert_eq!(plan.errors.len(), 1);
+        assert!(
+            matches!(&plan.errors[0], MigrateError::NotText { path } if path.ends_with("blob.xml"))
+        );
+    }
+
+    #[test]
+    fn test_plan_replacements_counts_matches_per_rule() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("a.xml"), "old old").unwrap();
+        fs::write(root.join("b.xml"), "old").unwrap();
+
+        let rules = [rule("old", "new", false), rule("olld", "new", false)];
+        let plan = plan_replacements(root, &rules, &FileFilter::default(), |p| {
+            fs::read_to_string(p)
+        });
+        let counts: Vec<_> = plan.impact.iter().map(|i| (i.matches, i.files)).collect();
+        assert_eq!(counts, vec![(3, 2), (0, 0)]);
+    }
+
+    #[test]
+    fn test_plan_replacements_rule_paths() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        for sub in ["src/main/mule", "src/test/resources"] {
+            fs::create_dir_all(root.join(sub)).unwrap();
+            fs::write(root.join(sub).join("a.xml"), "old").unwrap();
+        }
+
+        let mut scoped = rule("old", "new", false);
+        scoped.paths = vec!["src/main/mule/**/*.xml".to_string()];
+        let plan = plan_replacements(root, &[scoped], &FileFilter::default(), |p| {
+            fs::read_to_string(p)
+        });
+        let paths: Vec<_> = plan
+            .planned
+            .iter()
+            .map(|p| p.path.strip_prefix(root).unwrap().to_path_buf())
+            .collect();
+        assert_eq!(paths, vec![PathBuf::from("src/main/mule/a.xml")]);
+    }
+
+    #[test]
+    fn test_apply_rule_whole_word_case_and_limit() {
+        let content = "4.4.0 14.4.0 4.4.0-SNAPSHOT <v>4.4.0</v>";
+        let mut versions = rule("4.4.0", "4.9.0", false);
+        versions.whole_word = true;
+        let compiled = compile_rules(std::slice::from_ref(&versions));
+        assert_eq!(
+            apply_rule(content, &compiled[0], false),
+            ("4.9.0 14.4.0 4.4.0-SNAPSHOT <v>4.9.0</v>".to_string(), 2)
+        );
+
+        versions.max_replacements = Some(1);
+        let compiled = compile_rules(std::slice::from_ref(&versions));
+        assert_eq!(
+            apply_rule(content, &compiled[0], false),
+            ("4.9.0 14.4.0 4.4.0-SNAPSHOT <v>4.4.0</v>".to_string(), 1)
+        );
+
+        let mut names = rule("Error.MuleMessage", "error.errorMessage", false);
+        names.case_insensitive = true;
+        let compiled = compile_rules(std::slice::from_ref(&names));
+        assert_eq!(
+            apply_rule("#[error.muleMessage]", &compiled[0], false),
+            ("#[error.errorMessage]".to_string(), 1)
+        );
+    }
+
+    #[test]
+    fn test_apply_rule_scoped_to_attribute() {
+        let content = r#"<mule xmlns:doc="http://www.mulesoft.org/schema/mule/documentation"
+      xmlns:http="http://www.mulesoft.org/schema/mule/http"
+      xmlns:ee="http://www.mulesoft.org/schema/mule/ee/core">
+  <flow name="orders-old" doc:name="orders-old">
+    <http:request config-ref="orders-old-config" doc:name="Call orders-old"/>
+    <ee:set-payload><![CDATA[%dw 2.0
+output application/json
+---
+{ flow: "orders-old" }]]></ee:set-payload>
+  </flow>
+</mule>"#;
+        let mut names = rule("orders-old", "orders & co", false);
+        names.attribute = Some("doc:name".to_string());
+        let compiled = compile_rules(std::slice::from_ref(&names));
+        let (replaced, count) = apply_rule(content, &compiled[0], true);
+        assert_eq!(count, 2);
+        assert_eq!(
+            replaced,
+            content
+                .replace(r#"doc:name="orders-old""#, r#"doc:name="orders &amp; co""#)
+                .replace(r#""Call orders-old""#, r#""Call orders &amp; co""#)
+        );
+        assert!(compiled[0].applies_to(Path::new("src/main/mule/orders.xml")));
+        assert!(!compiled[0].applies_to(Path::new("src/main/resources/config.yaml")));
+    }
+
+    #[test]
+    fn test_apply_rule_skips_dataweave() {
+        let content = r#"<mule xmlns:ee="http://www.mulesoft.org/schema/mule/ee/core">
+  <set-variable variableName="version" value="4.4.0"/>
+  <ee:transform>
+    <ee:message>
+      <ee:set-payload>%dw 2.0 output application/json --- { runtime: "4.4.0" }</ee:set-payload>
+    </ee:message>
+  </ee:transform>
+  <logger message="4.4.0"/>
+  <scripting:script xmlns:scripting="http://www.mulesoft.org/schema/mule/scripting"><![CDATA[version = "4.4.0"]]></scripting:script>
+</mule>"#;
+        let mut versions = rule("4.4.0", "4.9.0", false);
+        versions.skip_dataweave = true;
+        let compiled = compile_rules(std::slice::from_ref(&versions));
+        let (replaced, count) = apply_rule(content, &compiled[0], true);
+        assert_eq!(count, 2);
+        assert_eq!(
+            replaced,
+            content
+                .replace(r#"value="4.4.0""#, r#"value="4.9.0""#)
+                .replace(r#"message="4.4.0""#, r#"message="4.9.0""#)
+        );
+        assert_eq!(
+            apply_rule("version=4.4.0", &compiled[0], false),
+            ("version=4.9.0".to_string(), 1)
+        );
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_file_and_keeps_permissions() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("pom.xml");
+        fs::write(&path, "old").unwrap();
+        let mut permissions = fs::metadata(&path).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&path, permissions).unwrap();
+
+        write_atomic(&path, b"new").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert!(fs::metadata(&path).unwrap().permissions().readonly());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_plan_file_renames_moves_glob_matches_below_prefix() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        for file in [
+            "src/main/mule/legacy/api/orders.xml",
+            "src/main/mule/legacy/notes.txt",
+            "src/main/app/mule-app.properties",
+            "src/main/resources/config.yaml",
+            "src/main/resources/old.properties",
+        ] {
+            fs::create_dir_all(root.join(file).parent().unwrap()).unwrap();
+            fs::write(root.join(file), "").unwrap();
+        }
+        let renames = |from: &str, to: &str| FileRename {
+            from: from.to_string(),
+            to: to.to_string(),
+        };
+        let rules = [
+            renames("src/main/mule/legacy/**/*.xml", "src/main/mule"),
+            renames(
+                "src/main/app/mule-app.properties",
+                "src/main/resources/app.properties",
+            ),
+            renames(
+                "src/main/resources/old.properties",
+                "src/main/resources/config.yaml",
+            ),
+            renames("src/main/mule/*.xml", "src/main/mule/flows/"),
+        ];
+        let plan = plan_file_renames(root, &rules, &FileFilter::default()).unwrap();
+        let mut renames = plan.renames;
+        renames.sort();
+        assert_eq!(
+            renames,
+            vec![
+                (
+                    root.join("src/main/app/mule-app.properties"),
+                    root.join("src/main/resources/app.properties")
+                ),
+                (
+                    root.join("src/main/mule/legacy/api/orders.xml"),
+                    root.join("src/main/mule/api/orders.xml")
+                ),
+            ]
+        );
+        assert_eq!(plan.conflicts.len(), 1);
+        assert!(plan.conflicts[0].contains("config.yaml already exists"));
+        assert_eq!(plan.unmatched, vec![3]);
     }
-    summary
 }
@@ -0,0 +1,140 @@
+use crate::report::MigrationReport;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+/// Runs `git` in the project root and returns its stdout.
+fn git(project_root: &str, args: &[&str]) -> io::Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(project_root)
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Returns true if the working tree has uncommitted changes or untracked files.
+///
+/// # Errors
+/// Returns an error if git is not installed or the project is not in a git repository.
+pub fn is_dirty(project_root: &str) -> io::Result<bool> {
+    Ok(!git(project_root, &["status", "--porcelain"])?
+        .trim()
+        .is_empty())
+}
+
+/// Creates `branch` from the current HEAD and switches to it.
+pub fn create_branch(project_root: &str, branch: &str) -> io::Result<()> {
+    git(project_root, &["checkout", "-b", branch])?;
+    Ok(())
+}
+
+/// Stages `files` (as reported, i.e. prefixed with the project root) and
+/// commits them with `message`.
+///
+/// Returns the short hash of the new commit.
+pub fn commit_files(project_root: &str, files: &[String], message: &str) -> io::Result<String> {
+    let relative: Vec<&str> = files
+        .iter()
+        .map(|f| {
+            Path::new(f)
+                .strip_prefix(project_root)
+                .ok()
+                .and_then(|p| p.to_str())
+                .unwrap_or(f)
+        })
+        .collect();
+    let mut add = vec!["add", "--"];
+    add.extend(&relative);
+    git(project_root, &add)?;
+    git(project_root, &["commit", "-q", "-m", message])?;
+    Ok(git(project_root, &["rev-parse", "--short", "HEAD"])?
+        .trim()
+        .to_string())
+}
+
+/// Builds a commit message listing the changes of a migration run, with
+/// trailers identifying the run and the tool version.
+pub fn commit_message(runtime_version: &str, report: &MigrationReport) -> String {
+    let mut message = format!("Migrate to Mule runtime {runtime_version}\n");
+    let sections = [
+        ("Moved files", &report.renamed_files),
+        ("Created files", &report.created_files),
+        ("Deleted files", &report.deleted_files),
+        ("Updated properties", &report.changed_properties),
+        ("Updated mule-artifact.json", &report.changed_json),
+        ("Updated Mule XML", &report.changed_xml),
+        ("Updated DataWeave", &report.changed_dataweave),
+        ("Plugin changes", &report.plugin_changes),
+        ("String replacements", &report.replacements),
+        ("CI files", &report.ci_changes),
+        ("Dockerfiles", &report.dockerfile_changes),
+        ("Resolved dependencies", &report.dependency_changes),
+    ];
+    for (title, changes) in sections {
+        if changes.is_empty() {
+            continue;
+        }
+        message.push_str(&format!("\n{title}:\n"));
+        for change in changes {
+            message.push_str(&format!("- {change}\n"));
+        }
+    }
+    message.push_str(&format!(
+        "\nMigration-Run: {}\nMigrated-By: mule-lazy-migrate {}\n",
+        report.run_id, report.tool_version
+    ));
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_commit_message_lists_changes() {
+        let mut report = MigrationReport::new(false);
+        report
+            .changed_properties
+            .push("app.runtime: '4.4.0' -> '4.9.4'".to_string());
+        report.run_id = "3f2a9c1e-0d4b-4c6e-9a71-5b8e2f0c7d13".to_string();
+        report.tool_version = "0.1.3".to_string();
+        let message = commit_message("4.9.4", &report);
+        assert_eq!(
+            message,
+            "Migrate to Mule runtime 4.9.4\n\nUpdated properties:\n- app.runtime: '4.4.0' -> '4.9.4'\n\
+             \nMigration-Run: 3f2a9c1e-0d4b-4c6e-9a71-5b8e2f0c7d13\nMigrated-By: mule-lazy-migrate 0.1.3\n"
+        );
+    }
+
+    #[test]
+    fn test_branch_and_commit() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_str().unwrap();
+        git(root, &["init", "-q"]).unwrap();
+        git(root, &["config", "user.email", "test@example.com"]).unwrap();
+        git(root, &["config", "user.name", "Test"]).unwrap();
+        fs::write(dir.path().join("pom.xml"), "<project/>").unwrap();
+        assert!(is_dirty(root).unwrap());
+        commit_files(root, &[format!("{root}/pom.xml")], "Initial").unwrap();
+        assert!(!is_dirty(root).unwrap());
+
+        create_branch(root, "migrate").unwrap();
+        fs::write(dir.path().join("pom.xml"), "<project></project>").unwrap();
+        fs::write(dir.path().join("pom.xml.bak"), "<project/>").unwrap();
+        let hash = commit_files(root, &[format!("{root}/pom.xml")], "Migrate").unwrap();
+        assert!(!hash.is_empty());
+        let branch = git(root, &["rev-parse", "--abbrev-ref", "HEAD"]).unwrap();
+        assert_eq!(branch.trim(), "migrate");
+        // Only the migrated file is committed, backups stay untracked
+        assert!(is_dirty(root).unwrap());
+    }
+}
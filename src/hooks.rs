@@ -0,0 +1,112 @@
+use crate::error::MigrateError;
+use serde::Serialize;
+use std::path::Path;
+use std::process::Command;
+
+/// Output of a hook command run during a migration.
+#[derive(Debug, Clone, Serialize)]
+pub struct HookRun {
+    /// `pre` or `post`.
+    pub phase: String,
+    pub command: String,
+    /// Exit code, or `None` if the command was killed by a signal.
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl HookRun {
+    pub fn succeeded(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Runs a command through the platform shell in `root`, capturing its output.
+fn run_hook(root: &Path, phase: &str, command: &str) -> std::io::Result<HookRun> {
+    let output = if cfg!(windows) {
+        Command::new("cmd")
+            .args(["/C", command])
+            .current_dir(root)
+            .output()?
+    } else {
+        Command::new("sh")
+            .args(["-c", command])
+            .current_dir(root)
+            .output()?
+    };
+    Ok(HookRun {
+        phase: phase.to_string(),
+        command: command.to_string(),
+        exit_code: output.status.code(),
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+    })
+}
+
+/// Runs the hook commands of one phase in order, stopping at the first one
+/// that cannot be started or exits unsuccessfully.
+///
+/// Returns the runs so far and the error that stopped them, if any.
+pub fn run_hooks(
+    root: &str,
+    phase: &str,
+    commands: &[String],
+) -> (Vec<HookRun>, Option<MigrateError>) {
+    let mut runs = Vec::new();
+    for command in commands {
+        log::info!("Running {phase} hook: {command}");
+        match run_hook(Path::new(root), phase, command) {
+            Ok(run) if run.succeeded() => runs.push(run),
+            Ok(run) => {
+                let reason = match run.exit_code {
+                    Some(code) => format!("exited with status {code}"),
+                    None => "terminated by a signal".to_string(),
+                };
+                runs.push(run);
+                return (
+                    runs,
+                    Some(MigrateError::Hook {
+                        command: command.clone(),
+                        reason,
+                    }),
+                );
+            }
+            Err(e) => {
+                return (
+                    runs,
+                    Some(MigrateError::Hook {
+                        command: command.clone(),
+                        reason: e.to_string(),
+                    }),
+                )
+            }
+        }
+    }
+    (runs, None)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_run_hooks_captures_output_and_stops_on_failure() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("marker"), "").unwrap();
+        let commands = [
+            "ls; echo oops >&2".to_string(),
+            "exit 3".to_string(),
+            "echo never".to_string(),
+        ];
+        let (runs, err) = run_hooks(dir.path().to_str().unwrap(), "pre", &commands);
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].stdout, "marker\n");
+        assert_eq!(runs[0].stderr, "oops\n");
+        assert_eq!(runs[1].exit_code, Some(3));
+        assert_eq!(
+            err.unwrap().to_string(),
+            "Hook 'exit 3' failed: exited with status 3"
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use crate::report::MigrationReport;
+
+const STYLE: &str = "\
+body{font-family:system-ui,sans-serif;margin:2rem auto;max-width:70rem;color:#222}\
+h1{font-size:1.5rem}h2{font-size:1.15rem;margin-top:2rem}\
+table{border-collapse:collapse}td,th{border:1px solid #ccc;padding:.3rem .8rem;text-align:left}\
+td.count{text-align:right}tr.problem td{background:#fdecea;color:#a40000;font-weight:bold}\
+.errors{background:#fdecea;border-left:4px solid #c00;padding:.5rem 1rem}\
+.badge{background:#2962ff;color:#fff;border-radius:3px;padding:.1rem .4rem;font-size:.8rem}\
+details{margin:.4rem 0}summary{cursor:pointer;font-family:monospace}\
+pre{background:#f6f8fa;padding:.5rem;overflow-x:auto;font-size:.85rem}\
+.add{color:#116329;background:#dafbe1}.del{color:#82071e;background:#ffebe9}.hunk{color:#6639ba}";
+
+/// Escapes text for use in HTML content or attribute values.
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn list(out: &mut String, title: &str, entries: &[String]) {
+    if entries.is_empty() {
+        return;
+    }
+    out.push_str(&format!("<h2>{}</h2>\n<ul>\n", escape(title)));
+    for entry in entries {
+        out.push_str(&format!("<li>{}</li>\n", escape(entry)));
+    }
+    out.push_str("</ul>\n");
+}
+
+/// Renders a unified diff with one highlighted line per change.
+fn diff_block(diff: &str) -> String {
+    let mut out = String::from("<pre>");
+    for line in diff.lines() {
+        let class = if line.starts_with("@@") {
+            Some("hunk")
+        } else if line.starts_with('+') && !line.starts_with("+++") {
+            Some("add")
+        } else if line.starts_with('-') && !line.starts_with("---") {
+            Some("del")
+        } else {
+            None
+        };
+        match class {
+            Some(class) => out.push_str(&format!(
+                "<span class=\"{class}\">{}</span>\n",
+                escape(line)
+            )),
+            None => out.push_str(&format!("{}\n", escape(line))),
+        }
+    }
+    out.push_str("</pre>");
+    out
+}
+
+/// Renders the report as a standalone HTML page: a summary table, errors
+/// highlighted at the top, the change lists and a collapsible diff per file.
+pub fn render_report(report: &MigrationReport) -> String {
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str("<title>Mule migration report</title>\n");
+    out.push_str(&format!("<style>{STYLE}</style>\n</head>\n<body>\n"));
+    out.push_str("<h1>Mule migration report");
+    if report.dry_run {
+        out.push_str(" <span class=\"badge\">dry run</span>");
+    }
+    out.push_str("</h1>\n");
+    out.push_str(&format!(
+        "<p>Started {} &middot; finished {}</p>\n",
+        escape(&report.started_at),
+        escape(&report.finished_at)
+    ));
+
+    let rows = [
+        ("Changed files", report.changed_files.len(), false),
+        ("Moved files", report.renamed_files.len(), false),
+        ("Created files", report.created_files.len(), false),
+        ("Deleted files", report.deleted_files.len(), false),
+        ("Updated properties", report.changed_properties.len(), false),
+        ("Updated JSON fields", report.changed_json.len(), false),
+        ("Updated Mule XML", report.changed_xml.len(), false),
+        ("Updated DataWeave", report.changed_dataweave.len(), false),
+        ("Plugin changes", report.plugin_changes.len(), false),
+        ("String replacements", report.replacements.len(), false),
+        ("CI files", report.ci_changes.len(), false),
+        ("Dockerfiles", report.dockerfile_changes.len(), false),
+        (
+            "Resolved dependencies",
+            report.dependency_changes.len(),
+            false,
+        ),
+        ("Skipped files", report.skipped_files.len(), false),
+        ("Skipped steps", report.skipped_steps.len(), false),
+        (
+            "Compatibility warnings",
+            report.compatibility_warnings.len(),
+            false,
+        ),
+        ("File errors", report.file_errors.len(), true),
+        ("Errors", report.errors.len(), true),
+    ];
+    out.push_str("<table>\n<tr><th>Category</th><th>Count</th></tr>\n");
+    for (label, count, is_problem) in rows {
+        let class = if is_problem && count > 0 {
+            " class=\"problem\""
+        } else {
+            ""
+        };
+        out.push_str(&format!(
+            "<tr{class}><td>{label}</td><td class=\"count\">{count}</td></tr>\n"
+        ));
+    }
+    out.push_str("</table>\n");
+
+    if !report.errors.is_empty() || !report.file_errors.is_empty() {
+        out.push_str("<div class=\"errors\">\n");
+        list(&mut out, "Errors", &report.errors);
+        list(&mut out, "File errors", &report.file_errors);
+        out.push_str("</div>\n");
+    }
+    list(&mut out, "Moved files", &report.renamed_files);
+    list(&mut out, "Created files", &report.created_files);
+    list(&mut out, "Deleted files", &report.deleted_files);
+    list(&mut out, "Updated properties", &report.changed_properties);
+    list(&mut out, "Updated JSON fields", &report.changed_json);
+    list(&mut out, "Updated Mule XML", &report.changed_xml);
+    list(&mut out, "Updated DataWeave", &report.changed_dataweave);
+    list(&mut out, "Plugin changes", &report.plugin_changes);
+    list(&mut out, "String replacements", &report.replacements);
+    list(&mut out, "CI files", &report.ci_changes);
+    list(&mut out, "Dockerfiles", &report.dockerfile_changes);
+    list(
+        &mut out,
+        "Resolved dependencies",
+        &report.dependency_changes,
+    );
+    list(&mut out, "Skipped files", &report.skipped_files);
+    list(&mut out, "Skipped steps", &report.skipped_steps);
+    list(
+        &mut out,
+        "Compatibility warnings",
+        &report.compatibility_warnings,
+    );
+
+    let hooks: Vec<String> = report
+        .hooks
+        .iter()
+        .map(|run| match run.exit_code {
+            Some(code) => format!("[{}] {} (exit {code})", run.phase, run.command),
+            None => format!("[{}] {} (killed)", run.phase, run.command),
+        })
+        .collect();
+    list(&mut out, "Hooks", &hooks);
+
+    if !report.diffs.is_empty() {
+        out.push_str("<h2>Changes per file</h2>\n");
+        for file in &report.diffs {
+            out.push_str(&format!(
+                "<details>\n<summary>{}</summary>\n{}\n</details>\n",
+                escape(&file.path),
+                diff_block(&file.diff)
+            ));
+        }
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::FileDiff;
+
+    #[test]
+    fn test_render_report_escapes_and_highlights() {
+        let mut report = MigrationReport::new(true);
+        report.errors.push("Failed to parse <pom.xml>".to_string());
+        report.diffs.push(FileDiff {
+            path: "src/main/mule/flow.xml".to_string(),
+            diff: "@@ -1 +1 @@\n-<a x=\"1\"/>\n+<a x=\"2\"/>\n".to_string(),
+        });
+        let html = render_report(&report);
+        assert!(html.contains("<span class=\"badge\">dry run</span>"));
+        assert!(html.contains("<li>Failed to parse &lt;pom.xml&gt;</li>"));
+        assert!(
+            html.contains("<tr class=\"problem\"><td>Errors</td><td class=\"count\">1</td></tr>")
+        );
+        assert!(html.contains("<summary>src/main/mule/flow.xml</summary>"));
+        assert!(html.contains("<span class=\"add\">+&lt;a x=&quot;2&quot;/&gt;</span>"));
+    }
+}
@@ -0,0 +1,139 @@
+use crate::json_ops;
+use crate::xml;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+/// Starter config written by `init-config`. Field order matches the
+/// documented config layout.
+#[derive(Serialize)]
+struct StarterConfig {
+    app_runtime_version: String,
+    mule_maven_plugin_version: String,
+    munit_version: String,
+    mule_artifact: StarterMuleArtifact,
+    use_connector_catalog: bool,
+    dependencies: Vec<StarterDependency>,
+    replacements: Vec<()>,
+}
+
+#[derive(Serialize)]
+struct StarterMuleArtifact {
+    min_mule_version: String,
+    java_specification_versions: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct StarterDependency {
+    group_id: String,
+    artifact_id: String,
+    version: String,
+}
+
+/// Placeholder for a target value, showing the project's current value.
+/// Placeholders fail config validation until they are replaced.
+fn placeholder(current: Option<&str>) -> String {
+    match current {
+        Some(v) => format!("TODO (currently {v})"),
+        None => "TODO".to_string(),
+    }
+}
+
+/// Builds a starter migration config (pretty-printed JSON) from the
+/// project's pom.xml and mule-artifact.json.
+///
+/// Every target version is a placeholder naming the current value, and every
+/// Mule connector gets a `dependencies` entry to pin or delete.
+///
+/// # Errors
+/// Returns an error if pom.xml or mule-artifact.json cannot be read or parsed.
+pub fn starter_config(project_root: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let pom = fs::read_to_string(Path::new(project_root).join("pom.xml"))?;
+    let artifact = fs::read_to_string(Path::new(project_root).join("mule-artifact.json"))?;
+
+    let versions = xml::read_pom_versions(&pom)?;
+    let runtime = versions.app_runtime.or(versions.mule_version);
+    let plugin = versions.mule_maven_plugin_version;
+    let munit = versions.munit_version;
+    let artifacts = xml::declared_artifacts(&pom)?;
+    let (min_mule_version, java_versions) = json_ops::read_mule_artifact_versions(&artifact)?;
+
+    let dependencies = artifacts
+        .iter()
+        .filter(|a| a.is_mule_plugin())
+        .filter_map(|a| {
+            let version = a.version.as_deref().filter(|v| !v.starts_with("${"))?;
+            Some(StarterDependency {
+                group_id: a.group_id.clone(),
+                artifact_id: a.artifact_id.clone(),
+                version: placeholder(Some(version)),
+            })
+        })
+        .collect();
+
+    let config = StarterConfig {
+        app_runtime_version: placeholder(runtime.as_deref()),
+        mule_maven_plugin_version: placeholder(plugin.as_deref()),
+        munit_version: placeholder(munit.as_deref()),
+        mule_artifact: StarterMuleArtifact {
+            min_mule_version: placeholder(min_mule_version.as_deref()),
+            java_specification_versions: vec![placeholder(
+                Some(java_versions.join(", ").as_str()).filter(|v| !v.is_empty()),
+            )],
+        },
+        use_connector_catalog: true,
+        dependencies,
+        replacements: Vec::new(),
+    };
+    Ok(serde_json::to_string_pretty(&config)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ConfigFormat, MigrationConfig};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_starter_config_from_project() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("pom.xml"),
+            r#"<project>
+  <properties>
+    <app.runtime>4.4.0</app.runtime>
+    <munit.version>2.3.0</munit.version>
+  </properties>
+  <dependencies>
+    <dependency>
+      <groupId>org.mule.connectors</groupId>
+      <artifactId>mule-http-connector</artifactId>
+      <version>1.5.0</version>
+      <classifier>mule-plugin</classifier>
+    </dependency>
+  </dependencies>
+  <build><plugins><plugin>
+    <groupId>org.mule.tools.maven</groupId>
+    <artifactId>mule-maven-plugin</artifactId>
+    <version>3.8.0</version>
+  </plugin></plugins></build>
+</project>"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("mule-artifact.json"),
+            r#"{"minMuleVersion":"4.4.0","javaSpecificationVersions":["1.8"]}"#,
+        )
+        .unwrap();
+
+        let json = starter_config(dir.path().to_str().unwrap()).unwrap();
+        assert!(json.contains(r#""app_runtime_version": "TODO (currently 4.4.0)""#));
+        assert!(json.contains(r#""mule_maven_plugin_version": "TODO (currently 3.8.0)""#));
+        assert!(json.contains(r#""version": "TODO (currently 1.5.0)""#));
+        assert!(json.contains(r#""TODO (currently 1.8)""#));
+
+        // Placeholders are rejected until they are filled in
+        let err = MigrationConfig::from_str_with_format(&json, ConfigFormat::Json).unwrap_err();
+        assert!(err.to_string().contains("line 2, column 27"));
+    }
+}
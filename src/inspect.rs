@@ -0,0 +1,163 @@
+use crate::json_ops;
+use crate::xml;
+use colored::*;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+/// A Mule connector or module the project depends on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Connector {
+    pub group_id: String,
+    pub artifact_id: String,
+    /// Declared version, possibly a `${property}` reference.
+    pub version: Option<String>,
+}
+
+/// The versions a project is on today, read without any config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProjectInventory {
+    pub project: String,
+    /// `app.runtime`, else `mule.version`.
+    pub runtime_version: Option<String>,
+    pub min_mule_version: Option<String>,
+    pub java_versions: Vec<String>,
+    pub munit_version: Option<String>,
+    pub mule_maven_plugin_version: Option<String>,
+    pub connectors: Vec<Connector>,
+}
+
+/// Reads the runtime, Java, MUnit and plugin versions and the connectors of
+/// the project from its pom.xml and mule-artifact.json.
+///
+/// # Errors
+/// Returns an error if pom.xml or mule-artifact.json cannot be read or parsed.
+pub fn inspect_project(project_root: &str) -> Result<ProjectInventory, Box<dyn std::error::Error>> {
+    let pom = fs::read_to_string(Path::new(project_root).join("pom.xml"))?;
+    let artifact = fs::read_to_string(Path::new(project_root).join("mule-artifact.json"))?;
+
+    let versions = xml::read_pom_versions(&pom)?;
+    let (min_mule_version, java_versions) = json_ops::read_mule_artifact_versions(&artifact)?;
+    let connectors = xml::declared_artifacts(&pom)?
+        .into_iter()
+        .filter(|a| a.is_mule_plugin())
+        .map(|a| Connector {
+            group_id: a.group_id,
+            artifact_id: a.artifact_id,
+            version: a.version,
+        })
+        .collect();
+    Ok(ProjectInventory {
+        project: project_root.to_string(),
+        runtime_version: versions.app_runtime.or(versions.mule_version),
+        min_mule_version,
+        java_versions,
+        munit_version: versions.munit_version,
+        mule_maven_plugin_version: versions.mule_maven_plugin_version,
+        connectors,
+    })
+}
+
+impl ProjectInventory {
+    /// Serializes the inventory as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Failed to serialize inventory")
+    }
+
+    /// Renders the inventory as a colorized text table.
+    pub fn to_text(&self) -> String {
+        let value = |v: &Option<String>| v.clone().unwrap_or_else(|| "-".to_string());
+        let java = if self.java_versions.is_empty() {
+            "-".to_string()
+        } else {
+            self.java_versions.join(", ")
+        };
+        let mut out = format!("{}\n", self.project.bold());
+        for (name, v) in [
+            ("Runtime", value(&self.runtime_version)),
+            ("minMuleVersion", value(&self.min_mule_version)),
+            ("Java", java),
+            ("MUnit", value(&self.munit_version)),
+            ("mule-maven-plugin", value(&self.mule_maven_plugin_version)),
+        ] {
+            out.push_str(&format!("  {name:18} {v}\n"));
+        }
+        out.push_str(&format!("  {}\n", "Connectors:".bold()));
+        for c in &self.connectors {
+            out.push_str(&format!(
+                "    {}:{} {}\n",
+                c.group_id,
+                c.artifact_id,
+                value(&c.version)
+            ));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_inspect_project() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("pom.xml"),
+            r#"<project>
+  <properties>
+    <mule.version>4.4.0</mule.version>
+    <munit.version>2.3.0</munit.version>
+  </properties>
+  <dependencies>
+    <dependency>
+      <groupId>org.mule.connectors</groupId>
+      <artifactId>mule-http-connector</artifactId>
+      <version>1.7.1</version>
+      <classifier>mule-plugin</classifier>
+    </dependency>
+    <dependency>
+      <groupId>junit</groupId>
+      <artifactId>junit</artifactId>
+      <version>4.13.2</version>
+    </dependency>
+  </dependencies>
+  <build>
+    <plugins>
+      <plugin>
+        <groupId>org.mule.tools.maven</groupId>
+        <artifactId>mule-maven-plugin</artifactId>
+        <version>3.8.0</version>
+      </plugin>
+    </plugins>
+  </build>
+</project>"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("mule-artifact.json"),
+            r#"{"minMuleVersion": "4.4.0", "javaSpecificationVersions": ["1.8", "11"]}"#,
+        )
+        .unwrap();
+
+        let root = dir.path().to_str().unwrap();
+        let inventory = inspect_project(root).unwrap();
+        assert_eq!(
+            inventory,
+            ProjectInventory {
+                project: root.to_string(),
+                runtime_version: Some("4.4.0".to_string()),
+                min_mule_version: Some("4.4.0".to_string()),
+                java_versions: vec!["1.8".to_string(), "11".to_string()],
+                munit_version: Some("2.3.0".to_string()),
+                mule_maven_plugin_version: Some("3.8.0".to_string()),
+                connectors: vec![Connector {
+                    group_id: "org.mule.connectors".to_string(),
+                    artifact_id: "mule-http-connector".to_string(),
+                    version: Some("1.7.1".to_string()),
+                }],
+            }
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use crate::transaction::Transaction;
+use colored::*;
+use similar::{ChangeTag, DiffOp, DiffTag, TextDiff};
+use std::io::{self, BufRead, Write};
+
+const CONTEXT_LINES: usize = 3;
+
+/// Answer to a "apply this hunk?" prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    /// Apply this hunk
+    Yes,
+    /// Skip this hunk
+    No,
+    /// Apply this and every remaining hunk
+    All,
+    /// Skip this and every remaining hunk
+    Quit,
+}
+
+/// Rebuilds `new` from `old`, keeping only the hunks `decide` accepts.
+///
+/// `decide` receives each hunk rendered as a unified-diff snippet.
+pub fn select_hunks<F>(old: &str, new: &str, mut decide: F) -> String
+where
+    F: FnMut(&str) -> bool,
+{
+    let diff = TextDiff::from_lines(old, new);
+    let mut rejected: Vec<DiffOp> = Vec::new();
+    for group in diff.grouped_ops(CONTEXT_LINES) {
+        let mut hunk = String::new();
+        for op in &group {
+            for change in diff.iter_changes(op) {
+                let sign = match change.tag() {
+                    ChangeTag::Delete => "-",
+                    ChangeTag::Insert => "+",
+                    ChangeTag::Equal => " ",
+                };
+                hunk.push_str(&format!("{sign}{change}"));
+                if change.missing_newline() {
+                    hunk.push('\n');
+                }
+            }
+        }
+        if !decide(&hunk) {
+            rejected.extend(group.into_iter().filter(|op| op.tag() != DiffTag::Equal));
+        }
+    }
+
+    let mut out = String::new();
+    for op in diff.ops() {
+        let lines = if op.tag() == DiffTag::Equal || rejected.contains(op) {
+            &diff.old_slices()[op.old_range()]
+        } else {
+            &diff.new_slices()[op.new_range()]
+        };
+        out.extend(lines.iter().copied());
+    }
+    out
+}
+
+fn colorize_hunk(hunk: &str) -> String {
+    hunk.lines()
+        .map(|line| match line.chars().next() {
+            Some('+') => line.green().to_string(),
+            Some('-') => line.red().to_string(),
+            _ => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn prompt<R: BufRead, W: Write>(input: &mut R, output: &mut W) -> io::Result<Decision> {
+    loop {
+        write!(
+            output,
+            "{}",
+            "Apply this change [y,n,a,q,?]? ".blue().bold()
+        )?;
+        output.flush()?;
+        let mut answer = String::new();
+        if input.read_line(&mut answer)? == 0 {
+            // EOF: treat as quit so nothing is applied unintentionally
+            return Ok(Decision::Quit);
+        }
+        match answer.trim() {
+            "y" => return Ok(Decision::Yes),
+            "n" => return Ok(Decision::No),
+            "a" => return Ok(Decision::All),
+            "q" => return Ok(Decision::Quit),
+            _ => writeln!(
+                output,
+                "y - apply this change\nn - skip this change\na - apply this and all remaining changes\nq - skip this and all remaining changes"
+            )?,
+        }
+    }
+}
+
+/// Walks every staged change hunk by hunk and asks whether to apply it.
+///
+/// Returns a transaction containing only the approved changes, plus the
+/// paths of files whose changes were skipped entirely. Staged file moves
+/// and removals are kept as they are.
+pub fn review_transaction<R: BufRead, W: Write>(
+    tx: Transaction,
+    input: &mut R,
+    output: &mut W,
+) -> io::Result<(Transaction, Vec<String>)> {
+    let mut reviewed = Transaction::new();
+    for rename in tx.renames() {
+        reviewed.stage_rename(&rename.from, &rename.to);
+    }
+    for path in tx.deletes() {
+        reviewed.stage_delete(path);
+    }
+    let mut skipped = Vec::new();
+    // Some(true) = accept everything from now on, Some(false) = reject everything
+    let mut remaining: Option<bool> = None;
+    let mut error = None;
+
+    for write in tx.into_writes() {
+        writeln!(
+            output,
+            "\n{}",
+            format!("--- {}", write.path.display()).bold()
+        )?;
+        let content = select_hunks(&write.original, &write.content, |hunk| {
+            if let Some(accept) = remaining {
+                return accept;
+            }
+            if let Err(e) = writeln!(output, "{}", colorize_hunk(hunk)) {
+                error = Some(e);
+                remaining = Some(false);
+                return false;
+            }
+            match prompt(input, output) {
+                Ok(Decision::Yes) => true,
+                Ok(Decision::No) => false,
+                Ok(Decision::All) => {
+                    remaining = Some(true);
+                    true
+                }
+                Ok(Decision::Quit) => {
+                    remaining = Some(false);
+                    false
+                }
+                Err(e) => {
+                    error = Some(e);
+                    remaining = Some(false);
+                    false
+                }
+            }
+        });
+        if let Some(e) = error.take() {
+            return Err(e);
+        }
+        if content == write.original {
+            skipped.push(write.path.display().to_string());
+        } else if write.created {
+            reviewed.stage_new(&write.path, content);
+        } else {
+            reviewed.stage(&write.path, write.original, content);
+        }
+    }
+    Ok((reviewed, skipped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_select_hunks_keeps_only_accepted() {
+        let old = (1..=20).map(|i| format!("line {i}\n")).collect::<String>();
+        let new = old
+            .replace("line 2\n", "line two\n")
+            .replace("line 18\n", "line eighteen\n");
+        let mut seen = 0;
+        let out = select_hunks(&old, &new, |_| {
+            seen += 1;
+            seen == 2
+        });
+        assert_eq!(seen, 2);
+        assert!(out.contains("line 2\n"));
+        assert!(out.contains("line eighteen\n"));
+    }
+
+    #[test]
+    fn test_review_transaction_with_scripted_answers() {
+        let mut tx = Transaction::new();
+        tx.stage("a.xml", "a\n".to_string(), "A\n".to_string());
+        tx.stage("b.xml", "b\n".to_string(), "B\n".to_string());
+        tx.stage("c.xml", "c\n".to_string(), "C\n".to_string());
+        let mut input = Cursor::new("n\na\n");
+        let mut output = Vec::new();
+        let (reviewed, skipped) = review_transaction(tx, &mut input, &mut output).unwrap();
+        assert_eq!(skipped, vec!["a.xml".to_string()]);
+        assert_eq!(reviewed.staged_content("b.xml"), Some("B\n"));
+        assert_eq!(reviewed.staged_content("c.xml"), Some("C\n"));
+    }
+}
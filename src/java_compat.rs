@@ -0,0 +1,127 @@
+use crate::file_ops::{walk_files, FileFilter};
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+
+/// Location of custom Java sources in a Mule project.
+pub const JAVA_SOURCE_DIR: &str = "src/main/java";
+
+/// (pattern, Java version it breaks on, explanation)
+#[rustfmt::skip]
+const INCOMPATIBILITIES: &[(&str, u32, &str)] = &[
+    (r"\bjavax\.xml\.bind\b", 11, "JAXB (javax.xml.bind) was removed from the JDK; add jakarta.xml.bind-api or the JAXB runtime as a dependency"),
+    (r"\bjavax\.activation\b", 11, "JavaBeans Activation Framework (javax.activation) was removed from the JDK; add jakarta.activation as a dependency"),
+    (r"\bjavax\.xml\.ws\b|\bjavax\.jws\b", 11, "JAX-WS (javax.xml.ws, javax.jws) was removed from the JDK; add a JAX-WS implementation as a dependency"),
+    (r"\bjavax\.annotation\.(PostConstruct|PreDestroy|Resource|Generated)\b", 11, "Common annotations (javax.annotation) were removed from the JDK; add jakarta.annotation-api as a dependency"),
+    (r"\borg\.omg\.|\bjavax\.rmi\.CORBA\b", 11, "CORBA (org.omg, javax.rmi.CORBA) was removed from the JDK"),
+    (r"\bjdk\.nashorn\b|getEngineByName\(\s*\x22(nashorn|javascript|js)\x22", 15, "The Nashorn JavaScript engine was removed; use GraalJS or the standalone Nashorn dependency"),
+    (r"\bsun\.misc\.BASE64(En|De)coder\b", 9, "sun.misc.BASE64Encoder/Decoder were removed; use java.util.Base64"),
+    (r"\bsun\.misc\.Unsafe\b", 17, "sun.misc.Unsafe is a JDK internal API and may be inaccessible under strong encapsulation"),
+    (r"\bcom\.sun\.org\.apache\.|\bsun\.security\.|\bsun\.reflect\.", 17, "JDK internal APIs are strongly encapsulated since Java 17; use public APIs or --add-opens"),
+    (r"\bThread\.currentThread\(\)\.stop\(|\.stop\(\s*new\s+\w*Throwable", 11, "Thread.stop(Throwable) was removed"),
+    (r"\bRuntime\.runFinalizersOnExit\b|\bSystem\.runFinalizersOnExit\b", 11, "runFinalizersOnExit was removed"),
+    (r"\bSecurityManager\b|\bSystem\.setSecurityManager\b", 17, "The Security Manager is deprecated for removal and disabled by default at runtime"),
+];
+
+/// Returns the highest Java feature version among the target
+/// `javaSpecificationVersions` (`1.8` counts as 8).
+pub fn highest_java_version(java_versions: &[String]) -> Option<u32> {
+    java_versions
+        .iter()
+        .filter_map(|v| {
+            let v = v.trim();
+            let v = v.strip_prefix("1.").unwrap_or(v);
+            v.split('.').next()?.parse::<u32>().ok()
+        })
+        .max()
+}
+
+/// Scans Java source content for APIs that break on `target_java`.
+///
+/// Returns `(line number, explanation)` pairs.
+pub fn scan_java_source(content: &str, target_java: u32) -> Vec<(usize, &'static str)> {
+    let rules: Vec<(Regex, &'static str)> = INCOMPATIBILITIES
+        .iter()
+        .filter(|(_, since, _)| target_java >= *since)
+        .map(|(pattern, _, message)| (Regex::new(pattern).expect("Invalid pattern"), *message))
+        .collect();
+    let mut findings = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("//") || trimmed.starts_with('*') {
+            continue;
+        }
+        for (re, message) in &rules {
+            if re.is_match(line) {
+                findings.push((i + 1, *message));
+            }
+        }
+    }
+    findings
+}
+
+/// Scans `src/main/java` of the project for Java incompatibilities with the
+/// target Java version.
+///
+/// Returns one `path:line: explanation` warning per finding.
+pub fn scan_project(project_root: &Path, target_java: u32) -> Vec<String> {
+    let java_root = project_root.join(JAVA_SOURCE_DIR);
+    if !java_root.is_dir() {
+        return Vec::new();
+    }
+    let filter = FileFilter::default();
+    let mut warnings = Vec::new();
+    for path in walk_files(&java_root, &filter) {
+        if path.extension().and_then(|e| e.to_str()) != Some("java") {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        for (line, message) in scan_java_source(&content, target_java) {
+            warnings.push(format!("{}:{line}: {message}", path.display()));
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_scan_java_source_flags_removed_apis() {
+        let source = r#"package com.example;
+import javax.xml.bind.JAXBContext;
+// import jdk.nashorn.api.scripting.NashornScriptEngine;
+import java.util.List;
+public class Foo {
+    Object e = new javax.script.ScriptEngineManager().getEngineByName("nashorn");
+}
+"#;
+        let findings = scan_java_source(source, 17);
+        let lines: Vec<usize> = findings.iter().map(|(l, _)| *l).collect();
+        assert_eq!(lines, vec![2, 6]);
+        assert!(scan_java_source(source, 8).is_empty());
+    }
+
+    #[test]
+    fn test_scan_project_and_java_version() {
+        assert_eq!(
+            highest_java_version(&["1.8".to_string(), "17".to_string()]),
+            Some(17)
+        );
+        let dir = tempdir().unwrap();
+        let pkg = dir.path().join("src/main/java/com/example");
+        fs::create_dir_all(&pkg).unwrap();
+        fs::write(
+            pkg.join("Codec.java"),
+            "String s = new sun.misc.BASE64Encoder().encode(b);\n",
+        )
+        .unwrap();
+        let warnings = scan_project(dir.path(), 17);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("Codec.java:1: "));
+    }
+}
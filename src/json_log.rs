@@ -0,0 +1,121 @@
+use crate::observer::{MigrationEvent, MigrationObserver};
+use serde_json::{json, Map, Value};
+use std::io::Write;
+
+/// Format of the log output on stderr.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable log lines
+    #[default]
+    Text,
+    /// One JSON object per line, for log shippers
+    Json,
+}
+
+fn timestamp() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
+
+/// Formats a log record as a JSON line, for `env_logger::Builder::format`.
+pub fn format_record(buf: &mut impl Write, record: &log::Record) -> std::io::Result<()> {
+    let line = json!({
+        "timestamp": timestamp(),
+        "level": record.level().to_string(),
+        "target": record.target(),
+        "message": record.args().to_string(),
+    });
+    writeln!(buf, "{line}")
+}
+
+/// Splits a change description ending in `'old' -> 'new'` into its subject
+/// and the two values, e.g. `app.runtime: '4.4.0' -> '4.9.4'`.
+fn split_change(description: &str) -> Option<(&str, &str, &str)> {
+    let body = description.strip_suffix('\'')?;
+    let (before, new) = body.rsplit_once(" -> '")?;
+    let before = before.strip_suffix('\'')?;
+    let (subject, old) = before.rsplit_once(": '")?;
+    Some((subject, old, new))
+}
+
+/// Converts a migration event to a JSON object. `step` is the step the
+/// event happened in.
+pub fn event_to_json(event: &MigrationEvent, step: &str) -> Value {
+    let mut fields = Map::new();
+    fields.insert("timestamp".into(), timestamp().into());
+    let action = match event {
+        MigrationEvent::StepStarted { step } => {
+            fields.insert("step".into(), (*step).into());
+            "step_started"
+        }
+        MigrationEvent::FileScanned { path } => {
+            fields.insert("file".into(), path.display().to_string().into());
+            "file_scanned"
+        }
+        MigrationEvent::PropertyUpdated { description } => {
+            fields.insert("description".into(), (*description).into());
+            if let Some((subject, old, new)) = split_change(description) {
+                fields.insert("subject".into(), subject.into());
+                fields.insert("old".into(), old.into());
+                fields.insert("new".into(), new.into());
+            }
+            "change"
+        }
+        MigrationEvent::FileChanged { path } => {
+            fields.insert("file".into(), path.display().to_string().into());
+            "file_changed"
+        }
+        MigrationEvent::Error { message } => {
+            fields.insert("message".into(), (*message).into());
+            "error"
+        }
+    };
+    fields.insert("action".into(), action.into());
+    fields.entry("step").or_insert_with(|| step.into());
+    Value::Object(fields)
+}
+
+/// Writes every migration event to stderr as a JSON line.
+#[derive(Debug, Default)]
+pub struct JsonLogObserver {
+    step: String,
+}
+
+impl JsonLogObserver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl MigrationObserver for JsonLogObserver {
+    fn on_event(&mut self, event: &MigrationEvent) {
+        if let MigrationEvent::StepStarted { step } = event {
+            self.step = step.to_string();
+        }
+        eprintln!("{}", event_to_json(event, &self.step));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_change_event_has_old_and_new() {
+        let event = MigrationEvent::PropertyUpdated {
+            description: "app.runtime: '4.4.0' -> '4.9.4'",
+        };
+        let value = event_to_json(&event, "pom.xml");
+        assert_eq!(value["action"], "change");
+        assert_eq!(value["step"], "pom.xml");
+        assert_eq!(value["subject"], "app.runtime");
+        assert_eq!(value["old"], "4.4.0");
+        assert_eq!(value["new"], "4.9.4");
+
+        let event = MigrationEvent::PropertyUpdated {
+            description: "munit.version: added '3.4.0'",
+        };
+        let value = event_to_json(&event, "pom.xml");
+        assert_eq!(value["description"], "munit.version: added '3.4.0'");
+        assert!(value.get("old").is_none());
+    }
+}
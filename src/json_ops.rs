@@ -1,135 +1,546 @@
+use crate::backup::Snapshot;
+use crate::error::MigrateError;
+use crate::exchange::ExchangeJsonConfig;
+use crate::file_ops::{backup_file, read_file, write_file};
+use crate::version::VersionBump;
+use crate::xml::{apply_edits, TextEdit};
 use log;
+use serde::Serialize;
 use serde_json::Value;
-use std::fs;
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::Path;
+
+fn parse_error(path: &Path) -> impl FnOnce(serde_json::Error) -> MigrateError + '_ {
+    move |source| MigrateError::Json {
+        path: path.to_path_buf(),
+        source,
+    }
+}
 
-// Placeholder for JSON operations
 pub fn update_mule_artifact_json(
-    path: &str,
+    path: &Path,
     min_mule_version: &str,
     java_versions: &[String],
     dry_run: bool,
-    backup: bool,
-) {
-    log::info!("Reading mule-artifact.json from {path}");
-    let json_data = fs::read_to_string(path).expect("Failed to read mule-artifact.json");
-    let mut v: Value =
-        serde_json::from_str(&json_data).expect("Failed to parse mule-artifact.json");
-
-    let mut changed = false;
-
-    // Check minMuleVersion
-    let current_min_version = v["minMuleVersion"].as_str().unwrap_or("not set");
-    if current_min_version != min_mule_version {
-        log::info!("  Updating minMuleVersion: '{current_min_version}' -> '{min_mule_version}'");
-        v["minMuleVersion"] = Value::String(min_mule_version.to_string());
-        changed = true;
-    } else {
-        log::info!("  minMuleVersion already at '{min_mule_version}'");
-    }
+    backup: Option<&Snapshot>,
+) -> Result<(), MigrateError> {
+    log::info!("Reading mule-artifact.json from {}", path.display());
+    let json_data = read_file(path)?;
+    let (new_content, updated_fields) =
+        plan_mule_artifact_json_update(&json_data, min_mule_version, java_versions)
+            .map_err(parse_error(path))?;
 
-    // Check javaSpecificationVersions
-    let new_java_versions = Value::Array(
-        java_versions
-            .iter()
-            .map(|s| Value::String(s.clone()))
-            .collect(),
-    );
-    let current_java_versions = &v["javaSpecificationVersions"];
-    if current_java_versions != &new_java_versions {
+    if updated_fields.is_empty() {
         log::info!(
-            "  Updating javaSpecificationVersions: {current_java_versions:?} -> {new_java_versions:?}"
+            "✅ No changes needed for mule-artifact.json - all values are already up to date"
         );
-        v["javaSpecificationVersions"] = new_java_versions;
-        changed = true;
+        return Ok(());
+    }
+    for field in &updated_fields {
+        log::info!("  Updating {field}");
+    }
+    if let Some(snapshot) = backup {
+        backup_file(snapshot, path)?;
+        log::info!("Backup saved to {}", snapshot.dir().display());
+    }
+    if dry_run {
+        log::info!("[DRY-RUN] Would update mule-artifact.json with the above changes");
     } else {
-        log::info!("  javaSpecificationVersions already at {new_java_versions:?}");
+        log::info!("Writing updated mule-artifact.json...");
+        write_file(path, &new_content)?;
+        log::info!("✅ Successfully updated mule-artifact.json");
     }
+    Ok(())
+}
 
-    if changed {
-        if backup {
-            let backup_path = format!("{path}.bak");
-            fs::copy(path, &backup_path).expect("Failed to create backup");
-            log::info!("Backup created: {backup_path}");
+/// Reads `minMuleVersion` and `javaSpecificationVersions` from
+/// mule-artifact.json content.
+pub fn read_mule_artifact_versions(
+    content: &str,
+) -> Result<(Option<String>, Vec<String>), serde_json::Error> {
+    let json_data: Value = serde_json::from_str(content)?;
+    let min_mule_version = json_data
+        .get("minMuleVersion")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let java_versions = json_data
+        .get("javaSpecificationVersions")
+        .and_then(Value::as_array)
+        .map(|versions| {
+            versions
+                .iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok((min_mule_version, java_versions))
+}
+
+/// A member of a JSON object: its key and the byte span of its value.
+struct Member {
+    key: String,
+    key_start: usize,
+    value: Range<usize>,
+}
+
+fn skip_ws(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+        i += 1;
+    }
+    i
+}
+
+/// Returns the index just past the string literal opening at `i`.
+fn skip_string(bytes: &[u8], mut i: usize) -> usize {
+    i += 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return i + 1,
+            _ => i += 1,
         }
-        if dry_run {
-            log::info!("[DRY-RUN] Would update mule-artifact.json with the above changes");
-        } else {
-            log::info!("Writing updated mule-artifact.json...");
-            let out = serde_json::to_string_pretty(&v).expect("Failed to serialize JSON");
-            fs::write(path, out).expect("Failed to write mule-artifact.json");
-            log::info!("✅ Successfully updated mule-artifact.json");
+    }
+    i
+}
+
+/// Returns the index just past the JSON value starting at `i`.
+fn skip_value(bytes: &[u8], mut i: usize) -> usize {
+    match bytes.get(i) {
+        Some(b'"') => skip_string(bytes, i),
+        Some(b'{' | b'[') => {
+            let mut depth = 0;
+            while i < bytes.len() {
+                match bytes[i] {
+                    b'"' => {
+                        i = skip_string(bytes, i);
+                        continue;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return i + 1;
+                        }
+                    }
+                    _ => {}
+                }
+                i += 1;
+            }
+            i
+        }
+        _ => {
+            while i < bytes.len() && !matches!(bytes[i], b',' | b'}' | b']') {
+                i += 1;
+            }
+            while i > 0 && bytes[i - 1].is_ascii_whitespace() {
+                i -= 1;
+            }
+            i
         }
-    } else {
-        log::info!(
-            "✅ No changes needed for mule-artifact.json - all values are already up to date"
-        );
     }
 }
 
-pub fn update_mule_artifact_json_summary(
-    path: &str,
+/// The members of a JSON object and the positions of its braces.
+struct Object {
+    open: usize,
+    members: Vec<Member>,
+    close: usize,
+}
+
+/// Locates the members of the object whose `{` is at `open`.
+///
+/// Expects content that already parsed as JSON; returns `None` if there is
+/// no object at `open`.
+fn parse_object(content: &str, open: usize) -> Option<Object> {
+    let bytes = content.as_bytes();
+    if bytes.get(open) != Some(&b'{') {
+        return None;
+    }
+    let mut members = Vec::new();
+    let mut i = skip_ws(bytes, open + 1);
+    while bytes.get(i) == Some(&b'"') {
+        let key_start = i;
+        let key_end = skip_string(bytes, i);
+        let key = serde_json::from_str(&content[i..key_end]).ok()?;
+        i = skip_ws(bytes, skip_ws(bytes, key_end) + 1);
+        let value_end = skip_value(bytes, i);
+        members.push(Member {
+            key,
+            key_start,
+            value: i..value_end,
+        });
+        i = skip_ws(bytes, value_end);
+        if bytes.get(i) == Some(&b',') {
+            i = skip_ws(bytes, i + 1);
+        }
+    }
+    (bytes.get(i) == Some(&b'}')).then_some(Object {
+        open,
+        members,
+        close: i,
+    })
+}
+
+/// Spans of the items of the array whose `[` is at `open`.
+fn parse_array(content: &str, open: usize) -> Vec<Range<usize>> {
+    let bytes = content.as_bytes();
+    let mut items = Vec::new();
+    let mut i = skip_ws(bytes, open + 1);
+    while i < bytes.len() && bytes[i] != b']' {
+        let end = skip_value(bytes, i);
+        items.push(i..end);
+        i = skip_ws(bytes, end);
+        if bytes.get(i) == Some(&b',') {
+            i = skip_ws(bytes, i + 1);
+        }
+    }
+    items
+}
+
+/// Whitespace before `pos` if `pos` is the first non-blank character of its line.
+fn line_indent(content: &str, pos: usize) -> Option<&str> {
+    let line_start = content[..pos].rfind('\n').map_or(0, |i| i + 1);
+    let prefix = &content[line_start..pos];
+    prefix.trim().is_empty().then_some(prefix)
+}
+
+/// Serializes `value` on one line, with `", "` between array items.
+fn render_inline(value: &Value) -> String {
+    match value {
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(render_inline).collect();
+            format!("[{}]", items.join(", "))
+        }
+        _ => value.to_string(),
+    }
+}
+
+/// Serializes `value` over several lines, indented by `unit` per level and
+/// starting at `indent`. Scalars and arrays of scalars stay on one line.
+fn render_pretty(value: &Value, unit: &str, indent: &str) -> String {
+    let nested = match value {
+        Value::Object(fields) => !fields.is_empty(),
+        Value::Array(items) => items.iter().any(|i| i.is_object() || i.is_array()),
+        _ => false,
+    };
+    if !nested {
+        return render_inline(value);
+    }
+    let mut out = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(unit.as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(&mut out, formatter);
+    value
+        .serialize(&mut serializer)
+        .expect("Failed to serialize JSON value");
+    String::from_utf8(out)
+        .expect("serde_json writes UTF-8")
+        .replace('\n', &format!("\n{indent}"))
+}
+
+/// Indentation unit of a multi-line container: the indentation of its first
+/// item beyond the indentation of its closing bracket.
+fn indent_unit(item_indent: &str, close_indent: &str) -> String {
+    item_indent
+        .strip_prefix(close_indent)
+        .filter(|unit| !unit.is_empty())
+        .unwrap_or("  ")
+        .to_string()
+}
+
+/// Serializes `value` to replace the text at `span`, keeping a multi-line
+/// value multi-line with the original indentation.
+fn render_like(content: &str, span: &Range<usize>, value: &Value) -> String {
+    let original = &content[span.clone()];
+    if !original.contains('\n') {
+        return render_inline(value);
+    }
+    let first = skip_ws(content.as_bytes(), span.start + 1);
+    let close_indent = line_indent(content, span.end - 1).unwrap_or("");
+    let item_indent = line_indent(content, first)
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{close_indent}  "));
+    match value {
+        Value::Array(items)
+            if !items.is_empty() && !items.iter().any(|i| i.is_object() || i.is_array()) =>
+        {
+            let body: Vec<String> = items.iter().map(|i| format!("{item_indent}{i}")).collect();
+            format!("[\n{}\n{close_indent}]", body.join(",\n"))
+        }
+        _ => render_pretty(
+            value,
+            &indent_unit(&item_indent, close_indent),
+            close_indent,
+        ),
+    }
+}
+
+/// Computes the updated mule-artifact.json content without touching the file.
+///
+/// Only the spans of changed values are rewritten and missing fields are
+/// appended after the last member, so key order, indentation and the
+/// trailing newline of the original file are kept.
+///
+/// Returns the new content and a description of each updated field.
+pub fn plan_mule_artifact_json_update(
+    content: &str,
     min_mule_version: &str,
     java_spec_versions: &[String],
-    dry_run: bool,
-    backup: bool,
-) -> (bool, Vec<String>) {
-    let mut changed = false;
+) -> Result<(String, Vec<String>), serde_json::Error> {
+    let json_data: Value = serde_json::from_str(content)?;
+    let Some(object) = parse_object(content, skip_ws(content.as_bytes(), 0)) else {
+        return Ok((content.to_string(), Vec::new()));
+    };
     let mut updated_fields = Vec::new();
-    let mut json_data: Value =
-        serde_json::from_str(&fs::read_to_string(path).expect("Failed to read mule-artifact.json"))
-            .expect("Invalid JSON");
-
-    if let Some(obj) = json_data.as_object_mut() {
-        // Ensure minMuleVersion exists and is correct
-        match obj.get_mut("minMuleVersion") {
-            Some(v) => {
-                if v != min_mule_version {
-                    updated_fields.push(format!("minMuleVersion: '{v}' -> '{min_mule_version}'"));
-                    *v = Value::String(min_mule_version.to_string());
-                    changed = true;
-                }
-            }
-            None => {
-                obj.insert(
-                    "minMuleVersion".to_string(),
-                    Value::String(min_mule_version.to_string()),
-                );
-                updated_fields.push(format!("minMuleVersion: <missing> -> '{min_mule_version}'"));
-                changed = true;
-            }
+    let mut edits = Vec::new();
+    let mut missing = Vec::new();
+
+    let new_min = Value::String(min_mule_version.to_string());
+    match json_data.get("minMuleVersion") {
+        Some(v) if v == &new_min => {}
+        Some(v) => {
+            let current = v.as_str().map_or_else(|| v.to_string(), str::to_string);
+            updated_fields.push(format!(
+                "minMuleVersion: '{current}' -> '{min_mule_version}'"
+            ));
         }
-        // Only flat javaSpecificationVersions
-        let new_val = Value::Array(
-            java_spec_versions
-                .iter()
-                .map(|s| Value::String(s.clone()))
-                .collect(),
-        );
-        if let Some(jv) = obj.get_mut("javaSpecificationVersions") {
-            if jv != &new_val {
-                updated_fields.push("javaSpecificationVersions".to_string());
-                *jv = new_val.clone();
-                changed = true;
-            }
-        } else {
-            obj.insert("javaSpecificationVersions".to_string(), new_val.clone());
+        None => {
+            updated_fields.push(format!("minMuleVersion: <missing> -> '{min_mule_version}'"));
+            missing.push(("minMuleVersion".to_string(), new_min.clone()));
+        }
+    }
+    // Only flat javaSpecificationVersions
+    let new_java = Value::Array(
+        java_spec_versions
+            .iter()
+            .map(|s| Value::String(s.clone()))
+            .collect(),
+    );
+    match json_data.get("javaSpecificationVersions") {
+        Some(v) if v == &new_java => {}
+        Some(_) => updated_fields.push("javaSpecificationVersions".to_string()),
+        None => {
             updated_fields.push(format!(
                 "javaSpecificationVersions: <missing> -> {java_spec_versions:?}"
             ));
-            changed = true;
+            missing.push(("javaSpecificationVersions".to_string(), new_java.clone()));
+        }
+    }
+    if updated_fields.is_empty() {
+        return Ok((content.to_string(), updated_fields));
+    }
+
+    for member in &object.members {
+        let new_value = match member.key.as_str() {
+            "minMuleVersion" => &new_min,
+            "javaSpecificationVersions" => &new_java,
+            _ => continue,
+        };
+        let current: Value = serde_json::from_str(&content[member.value.clone()])?;
+        if &current != new_value {
+            edits.push(TextEdit {
+                range: member.value.clone(),
+                text: render_like(content, &member.value, new_value),
+            });
+        }
+    }
+    if !missing.is_empty() {
+        edits.push(insert_members(content, &object, &missing));
+    }
+    Ok((apply_edits(content, edits), updated_fields))
+}
+
+/// Appends `missing` members after the last member of `object`, following
+/// the layout of the existing members.
+fn insert_members(content: &str, object: &Object, missing: &[(String, Value)]) -> TextEdit {
+    let close_indent = line_indent(content, object.close).unwrap_or("");
+    let member_indent = match object.members.first() {
+        Some(first) => line_indent(content, first.key_start).map(str::to_string),
+        None => Some(format!("{close_indent}  ")),
+    };
+    let render = |(key, value): &(String, Value)| match &member_indent {
+        Some(indent) => format!(
+            "{}: {}",
+            Value::from(key.as_str()),
+            render_pretty(value, &indent_unit(indent, close_indent), indent)
+        ),
+        None => format!("{}: {}", Value::from(key.as_str()), render_inline(value)),
+    };
+    let Some(last) = object.members.last() else {
+        let indent = member_indent.as_deref().unwrap_or("");
+        let body: Vec<String> = missing
+            .iter()
+            .map(|m| format!("{indent}{}", render(m)))
+            .collect();
+        return TextEdit {
+            range: object.open + 1..object.close,
+            text: format!("\n{}\n{close_indent}", body.join(",\n")),
+        };
+    };
+    let separator = match &member_indent {
+        Some(indent) => format!(",\n{indent}"),
+        None => ", ".to_string(),
+    };
+    let text: String = missing
+        .iter()
+        .map(|m| format!("{separator}{}", render(m)))
+        .collect();
+    TextEdit {
+        range: last.value.end..last.value.end,
+        text,
+    }
+}
+
+/// Splits a JSON pointer (RFC 6901) into its unescaped reference tokens.
+fn pointer_tokens(pointer: &str) -> Option<Vec<String>> {
+    let rest = pointer.strip_prefix('/')?;
+    Some(
+        rest.split('/')
+            .map(|t| t.replace("~1", "/").replace("~0", "~"))
+            .collect(),
+    )
+}
+
+/// Sets the value at `pointer`, creating missing object members along the
+/// way. Returns the edited content and the change description, or `None`
+/// if the value is already set.
+fn set_pointer(
+    content: &str,
+    pointer: &str,
+    value: &Value,
+) -> Result<Option<(String, String)>, String> {
+    let tokens =
+        pointer_tokens(pointer).ok_or_else(|| format!("'{pointer}' is not a JSON pointer"))?;
+    let bytes = content.as_bytes();
+    let start = skip_ws(bytes, 0);
+    let mut span = start..skip_value(bytes, start);
+    for (depth, token) in tokens.iter().enumerate() {
+        let parent = format!("/{}", tokens[..depth].join("/"));
+        if let Some(object) = parse_object(content, span.start) {
+            match object.members.iter().find(|m| &m.key == token) {
+                Some(member) => span = member.value.clone(),
+                None => {
+                    let nested = tokens[depth + 1..]
+                        .iter()
+                        .rev()
+                        .fold(value.clone(), |inner, key| {
+                            Value::Object([(key.clone(), inner)].into_iter().collect())
+                        });
+                    let edit = insert_members(content, &object, &[(token.clone(), nested)]);
+                    let change = format!("{pointer}: <missing> -> {}", render_inline(value));
+                    return Ok(Some((apply_edits(content, vec![edit]), change)));
+                }
+            }
+        } else if bytes[span.start] == b'[' {
+            let items = parse_array(content, span.start);
+            span = token
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| items.get(index).cloned())
+                .ok_or_else(|| format!("{pointer}: no item '{token}' in array '{parent}'"))?;
+        } else {
+            return Err(format!("{pointer}: '{parent}' is not an object or array"));
         }
     }
+    let current: Value = serde_json::from_str(&content[span.clone()]).map_err(|e| e.to_string())?;
+    if &current == value {
+        return Ok(None);
+    }
+    let edit = TextEdit {
+        range: span.clone(),
+        text: render_like(content, &span, value),
+    };
+    let change = format!(
+        "{pointer}: {} -> {}",
+        render_inline(&current),
+        render_inline(value)
+    );
+    Ok(Some((apply_edits(content, vec![edit]), change)))
+}
+
+/// Applies the `mule_artifact.extra_fields` of the config: each key is a JSON
+/// pointer (e.g. `/classLoaderModelLoaderDescriptor/attributes/exportedResources`)
+/// and each value the JSON value to set there. Missing object members are
+/// created; array items must already exist.
+///
+/// Returns the new content and a description of each updated field, or a
+/// message naming the first pointer that cannot be set.
+pub fn plan_mule_artifact_extra_fields(
+    content: &str,
+    extra_fields: &BTreeMap<String, Value>,
+) -> Result<(String, Vec<String>), String> {
+    let mut content = content.to_string();
+    let mut changes = Vec::new();
+    for (pointer, value) in extra_fields {
+        if let Some((updated, change)) = set_pointer(&content, pointer, value)? {
+            content = updated;
+            changes.push(change);
+        }
+    }
+    Ok((content, changes))
+}
+
+/// Computes the updated `exchange.json` content: the asset `version` and
+/// `apiVersion` changed as configured, each only if `exchange.json` has it.
+/// Increments (`major`, `minor`, `patch`) are only made with
+/// `allow_increments`, so a re-run that migrates nothing does not bump
+/// again.
+///
+/// Returns the new content and a description of each updated field, or a
+/// message if a field to increment does not hold a version.
+pub fn plan_exchange_json_update(
+    content: &str,
+    config: &ExchangeJsonConfig,
+    allow_increments: bool,
+) -> Result<(String, Vec<String>), String> {
+    let json: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let mut content = content.to_string();
+    let mut changes = Vec::new();
+    for (field, bump) in [
+        ("version", &config.version),
+        ("apiVersion", &config.api_version),
+    ] {
+        let Some(bump) = bump else { continue };
+        if !allow_increments && !matches!(bump, VersionBump::Set(_)) {
+            continue;
+        }
+        let Some(current) = json.get(field).and_then(Value::as_str) else {
+            continue;
+        };
+        let new = bump
+            .apply(current)
+            .ok_or_else(|| format!("{field} '{current}' is not a version to increment"))?;
+        if let Some((updated, change)) =
+            set_pointer(&content, &format!("/{field}"), &Value::from(new))?
+        {
+            content = updated;
+            changes.push(change);
+        }
+    }
+    Ok((content, changes))
+}
+
+pub fn update_mule_artifact_json_summary(
+    path: &Path,
+    min_mule_version: &str,
+    java_spec_versions: &[String],
+    dry_run: bool,
+    backup: Option<&Snapshot>,
+) -> Result<(bool, Vec<String>), MigrateError> {
+    let content = read_file(path)?;
+    let (new_content, updated_fields) =
+        plan_mule_artifact_json_update(&content, min_mule_version, java_spec_versions)
+            .map_err(parse_error(path))?;
+    let changed = !updated_fields.is_empty();
     if changed {
-        if backup {
-            let backup_path = format!("{path}.bak");
-            fs::copy(path, &backup_path).expect("Failed to create backup");
+        if let Some(snapshot) = backup {
+            backup_file(snapshot, path)?;
         }
         if !dry_run {
-            fs::write(path, serde_json::to_string_pretty(&json_data).unwrap())
-                .expect("Failed to write mule-artifact.json");
+            write_file(path, &new_content)?;
         }
     }
-    (changed, updated_fields)
+    Ok((changed, updated_fields))
 }
 
 #[cfg(test)]
@@ -150,12 +561,13 @@ mod tests {
         let mut file = File::create(&file_path).unwrap();
         file.write_all(json.as_bytes()).unwrap();
         let (changed, fields) = update_mule_artifact_json_summary(
-            file_path.to_str().unwrap(),
+            &file_path,
             "4.9.0",
             &["17".to_string()],
             false,
-            false,
-        );
+            None,
+        )
+        .unwrap();
         assert!(changed);
         assert!(fields.iter().any(|f| f.contains("minMuleVersion")));
         assert!(fields
@@ -174,12 +586,13 @@ mod tests {
         let mut file = File::create(&file_path).unwrap();
         file.write_all(json.as_bytes()).unwrap();
         let (changed, fields) = update_mule_artifact_json_summary(
-            file_path.to_str().unwrap(),
+            &file_path,
             "4.9.0",
             &["17".to_string()],
             false,
-            false,
-        );
+            None,
+        )
+        .unwrap();
         assert!(!changed);
         assert!(fields.is_empty());
     }
@@ -192,16 +605,99 @@ mod tests {
         let mut file = File::create(&file_path).unwrap();
         file.write_all(json.as_bytes()).unwrap();
         let (changed, fields) = update_mule_artifact_json_summary(
-            file_path.to_str().unwrap(),
+            &file_path,
             "4.9.0",
             &["17".to_string()],
             false,
-            false,
-        );
+            None,
+        )
+        .unwrap();
         assert!(changed);
         assert!(fields.iter().any(|f| f.contains("minMuleVersion")));
         assert!(fields
             .iter()
             .any(|f| f.contains("javaSpecificationVersions")));
     }
+
+    #[test]
+    fn test_plan_mule_artifact_json_update_preserves_layout() {
+        let json = "{\n    \"name\": \"orders-api\",\n    \"minMuleVersion\": \"4.4.0\",\n    \"javaSpecificationVersions\": [\n        \"1.8\"\n    ],\n    \"secureProperties\": []\n}\n";
+        let (content, fields) =
+            plan_mule_artifact_json_update(json, "4.9.0", &["17".to_string()]).unwrap();
+        assert_eq!(
+            content,
+            "{\n    \"name\": \"orders-api\",\n    \"minMuleVersion\": \"4.9.0\",\n    \"javaSpecificationVersions\": [\n        \"17\"\n    ],\n    \"secureProperties\": []\n}\n"
+        );
+        assert_eq!(fields[0], "minMuleVersion: '4.4.0' -> '4.9.0'");
+
+        let json = "{\n\t\"name\": \"orders-api\"\n}\n";
+        let (content, _) =
+            plan_mule_artifact_json_update(json, "4.9.0", &["17".to_string()]).unwrap();
+        assert_eq!(
+            content,
+            "{\n\t\"name\": \"orders-api\",\n\t\"minMuleVersion\": \"4.9.0\",\n\t\"javaSpecificationVersions\": [\"17\"]\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_plan_mule_artifact_extra_fields() {
+        let json = "{\n  \"minMuleVersion\": \"4.9.0\",\n  \"secureProperties\": [],\n  \"configs\": [\"a.xml\"]\n}\n";
+        let fields: BTreeMap<String, Value> = serde_json::from_str(
+            r#"{
+                "/secureProperties": ["db.password"],
+                "/configs/0": "orders.xml",
+                "/classLoaderModelLoaderDescriptor/attributes/exportedResources": ["x.dwl"]
+            }"#,
+        )
+        .unwrap();
+        let (content, changes) = plan_mule_artifact_extra_fields(json, &fields).unwrap();
+        assert_eq!(
+            content,
+            "{\n  \"minMuleVersion\": \"4.9.0\",\n  \"secureProperties\": [\"db.password\"],\n  \"configs\": [\"orders.xml\"],\n  \"classLoaderModelLoaderDescriptor\": {\n    \"attributes\": {\n      \"exportedResources\": [\n        \"x.dwl\"\n      ]\n    }\n  }\n}\n"
+        );
+        assert_eq!(
+            changes,
+            vec![
+                "/classLoaderModelLoaderDescriptor/attributes/exportedResources: <missing> -> [\"x.dwl\"]",
+                "/configs/0: \"a.xml\" -> \"orders.xml\"",
+                "/secureProperties: [] -> [\"db.password\"]",
+            ]
+        );
+        let (_, again) = plan_mule_artifact_extra_fields(&content, &fields).unwrap();
+        assert!(again.is_empty());
+
+        let bad: BTreeMap<String, Value> = [("/minMuleVersion/x".to_string(), Value::from(1))]
+            .into_iter()
+            .collect();
+        let err = plan_mule_artifact_extra_fields(json, &bad).unwrap_err();
+        assert!(
+            err.contains("'/minMuleVersion' is not an object or array"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_plan_exchange_json_update_bumps_only_with_changes() {
+        use crate::version::BumpKind;
+        let content = "{\n  \"main\": \"orders-api.raml\",\n  \"version\": \"1.2.3\",\n  \"apiVersion\": \"v1\"\n}\n";
+        let config = ExchangeJsonConfig {
+            version: Some(VersionBump::Bump(BumpKind::Minor)),
+            api_version: Some(VersionBump::Set("v2".to_string())),
+        };
+        let (out, changes) = plan_exchange_json_update(content, &config, true).unwrap();
+        assert_eq!(
+            out,
+            "{\n  \"main\": \"orders-api.raml\",\n  \"version\": \"1.3.0\",\n  \"apiVersion\": \"v2\"\n}\n"
+        );
+        assert_eq!(
+            changes,
+            vec![
+                "/version: \"1.2.3\" -> \"1.3.0\"",
+                "/apiVersion: \"v1\" -> \"v2\""
+            ]
+        );
+
+        let (_, changes) = plan_exchange_json_update(content, &config, false).unwrap();
+        assert_eq!(changes, vec!["/apiVersion: \"v1\" -> \"v2\""]);
+    }
 }
@@ -1,161 +1,1315 @@
+pub mod backup;
+pub mod batch;
+pub mod catalog;
+pub mod ci;
+pub mod compat;
 pub mod config;
+pub mod dataweave;
+pub mod deploy;
+pub mod dockerfile;
+pub mod docs;
+pub mod encoding;
+pub mod error;
+pub mod exchange;
 pub mod file_ops;
+pub mod git;
+pub mod hooks;
+pub mod html;
+pub mod http;
+pub mod init;
+pub mod inspect;
+pub mod interactive;
+pub mod java_compat;
+pub mod json_log;
 pub mod json_ops;
+pub mod lockfile;
+pub mod log4j2;
+pub mod lookup;
+pub mod matrix;
+pub mod maven;
+pub mod maven_plugin;
+pub mod mule_xml;
+pub mod munit;
+pub mod observer;
+pub mod parent_pom;
+pub mod plan;
+pub mod plugins;
+pub mod profiles;
+pub mod progress;
+pub mod properties;
+pub mod report;
+pub mod run_lock;
+pub mod sarif;
+pub mod self_update;
+pub mod steps;
+pub mod support;
+pub mod surefire;
+pub mod template;
+pub mod transaction;
+pub mod version;
+pub mod watch;
 pub mod xml;
+pub mod yaml_props;
 
-use colored::*;
+pub use batch::find_mule_projects;
 use config::MigrationConfig;
-use std::path::Path;
-use std::process::Command;
+pub use error::MigrateError;
+use lockfile::Lockfile;
+pub use observer::{MigrationEvent, MigrationObserver};
+use parent_pom::InheritedProperties;
+pub use report::{MigrationReport, ReportFormat};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use steps::{MigrationStep, StepContext, StepKind};
+use transaction::Transaction;
 
 /// Migration options for running the migration tool.
+#[derive(Clone)]
 pub struct MigrationOptions<'a> {
-    /// Path to the migration config JSON file.
-    pub config_path: &'a str,
+    /// Path or `http(s)://` URL of the migration config file (JSON, YAML or TOML).
+    pub config_path: Option<&'a str>,
+    /// Expected SHA-256 of the config file.
+    pub config_sha256: Option<&'a str>,
+    /// Values of the config's `${var.NAME}` placeholders.
+    pub config_vars: &'a [(String, String)],
+    /// Name of a built-in profile, used when no config file is given.
+    pub profile: Option<&'a str>,
     /// Path to the Mule project root directory.
     pub project_root: &'a str,
     /// If true, perform a dry run without making changes.
     pub dry_run: bool,
-    /// If true, create backup files before modifying.
+    /// If true, save the files about to change to a snapshot of the run.
     pub backup: bool,
+    /// Directory for the backup snapshots, relative to the project root
+    /// (default: `.mule-migrate/backups`).
+    pub backup_dir: Option<&'a str>,
+    /// How each snapshot is stored.
+    pub backup_compression: backup::BackupCompression,
+    /// Keep only this many most recent snapshots.
+    pub keep_backups: Option<usize>,
     /// If true, update Maven dependencies to latest releases before migration.
     pub update_maven_deps: bool,
+    /// If true, raise Mule connectors in pom.xml to their latest releases.
+    pub latest_connectors: bool,
+    /// If true, migrate to the newest supported LTS runtime instead of the
+    /// config's `app_runtime_version`.
+    pub latest_lts: bool,
+    /// If true, migrate to the latest runtime, mule-maven-plugin and MUnit
+    /// releases, keeping configured versions that cannot be looked up.
+    pub latest: bool,
     /// If true, build the Mule project after migration.
     pub build_mule_project: bool,
+    /// If true, run the project's tests after migration and summarize them.
+    pub run_tests: bool,
+    /// If true, redeploy the app as the config's `deploy` section says after
+    /// a successful run.
+    pub deploy: bool,
+    /// Maven executable to use instead of the project's `mvnw` or `mvn` on the PATH.
+    pub maven_bin: Option<&'a str>,
+    /// Lines of Maven output kept in the report.
+    pub maven_output_lines: usize,
+    /// Build goals replacing the config's `maven.goals`, if not empty.
+    pub maven_goals: &'a [String],
+    /// Extra Maven arguments, on top of the config's `maven.args`.
+    pub maven_args: &'a [String],
+    /// Maven timeout in seconds, replacing the config's `maven.timeout_secs`.
+    pub maven_timeout_secs: Option<u64>,
+    /// Extra include globs for source files, on top of the config's `include`.
+    pub include: &'a [String],
+    /// Extra exclude globs for source files, on top of the config's `exclude`.
+    pub exclude: &'a [String],
+    /// If true, ask for approval of each change hunk before writing.
+    pub interactive: bool,
+    /// Format of the final migration report.
+    pub report_format: ReportFormat,
+    /// Optional file to write the report to.
+    pub report_file: Option<&'a str>,
+    /// Create and switch to this git branch before migrating.
+    pub git_branch: Option<&'a str>,
+    /// Commit the migrated files with a generated message.
+    pub git_commit: bool,
+    /// Allow git operations on a working tree with uncommitted changes.
+    pub allow_dirty: bool,
+    /// Fail without writing anything if any source file could not be processed.
+    pub strict: bool,
+    /// Migrate even if the config's target versions are incompatible.
+    pub force: bool,
+    /// Process every file, even those the lockfile records as already migrated.
+    pub ignore_lock: bool,
+    /// In a dry run, write every intended edit to this plan file.
+    pub plan_out: Option<&'a str>,
+    /// Run only these steps, if not empty.
+    pub only: &'a [steps::StepKind],
+    /// Steps not to run.
+    pub skip: &'a [steps::StepKind],
+    /// Fail up front if the run would use the network or run external
+    /// programs. Requests are blocked with [`http::set_offline`].
+    pub offline: bool,
+}
+
+/// Loads the migration config from a file or URL, or from a built-in
+/// profile, falling back to the built-in defaults when neither is given.
+/// A `config_sha256` pins the file's checksum and `vars` resolve its
+/// `${var.NAME}` placeholders.
+///
+/// # Errors
+/// Returns an error if the checksum does not match or the config is invalid.
+pub fn load_config(
+    config_path: Option<&str>,
+    profile: Option<&str>,
+    config_sha256: Option<&str>,
+    vars: &[(String, String)],
+) -> Result<MigrationConfig, MigrateError> {
+    let config = match (config_path, profile) {
+        (Some(path), _) => {
+            log::info!("Loading migration config from {path}");
+            MigrationConfig::load(path, config_sha256, vars)
+        }
+        (None, Some(name)) => {
+            log::info!("Using built-in profile {name}");
+            profiles::load(name)
+        }
+        (None, None) => {
+            log::info!("No config given, using the built-in defaults");
+            profiles::default_config()
+        }
+    };
+    config.map_err(|e| MigrateError::Config(e.to_string()))
 }
 
 /// Runs the migration process for a Mule 4 project using the provided options.
 ///
 /// This function will:
 /// - Check if the target directory is a Mule project
-/// - Load the migration config
+/// - Load the migration config (or a built-in profile)
 /// - Optionally update Maven dependencies
 /// - Update pom.xml and mule-artifact.json
+/// - Apply structural attribute edits to Mule config files
+/// - Set properties in YAML property files
 /// - Perform string replacements in source files
+/// - Write all changes at once, rolling back if any write fails
+/// - Run the config's pre hooks before and post hooks after the changes
+/// - Optionally create a git branch first and commit the changes
+/// - Flag custom Java code using APIs removed in the target JDK
 /// - Optionally build the project
-/// - Print a colorized summary of changes
+/// - Optionally redeploy the app on the new runtime
+/// - Print a colorized summary of changes (or a JSON report)
+///
+/// Returns the emitted report.
+///
+/// # Errors
+/// Returns an error if the project is not valid or migration fails.
+pub fn run_migration(opts: &MigrationOptions) -> Result<MigrationReport, MigrateError> {
+    run_migration_with_observer(opts, |_: &MigrationEvent| {})
+}
+
+/// Runs the migration like [`run_migration`], reporting progress to
+/// `observer` as it happens.
 ///
 /// # Errors
 /// Returns an error if the project is not valid or migration fails.
-pub fn run_migration(opts: &MigrationOptions) -> Result<(), Box<dyn std::error::Error>> {
-    let mut changed_files = Vec::new();
-    let mut changed_properties = Vec::new();
-    let mut changed_json = Vec::new();
-    let mut replacements_summary = Vec::new();
-    let mut errors = Vec::new();
+pub fn run_migration_with_observer(
+    opts: &MigrationOptions,
+    mut observer: impl MigrationObserver,
+) -> Result<MigrationReport, MigrateError> {
+    let observer: &mut dyn MigrationObserver = &mut observer;
+    let mut report = MigrationReport::new(opts.dry_run);
+    report.project_root = opts.project_root.to_string();
 
     log::info!("Checking if '{}' is a Mule project...", opts.project_root);
-    if !is_mule_project(opts.project_root) {
-        let msg = format!(
-            "'{}' is not a Mule project (pom.xml or mule-artifact.json missing)",
-            opts.project_root
-        );
-        log::error!("{msg}");
-        errors.push(msg.clone());
-        print_summary(
-            &changed_files,
-            &changed_properties,
-            &changed_json,
-            &replacements_summary,
-            &errors,
-            opts.dry_run,
+    if !is_mule_project(Path::new(opts.project_root)) {
+        let err = MigrateError::NotMuleProject(opts.project_root.to_string());
+        log::error!("{err}");
+        notify_error(&mut report, observer, err.to_string());
+        report.finish();
+        report::emit_report(&report, opts.report_format, opts.report_file)
+            .map_err(MigrateError::Report)?;
+        return Err(err);
+    }
+    // Held until the run returns, so concurrent runs cannot interleave writes
+    let _lock = if opts.dry_run {
+        None
+    } else {
+        Some(run_lock::RunLock::acquire(Path::new(opts.project_root))?)
+    };
+    let mut config = load_config(
+        opts.config_path,
+        opts.profile,
+        opts.config_sha256,
+        opts.config_vars,
+    )?;
+    if opts.latest {
+        let mut cache = lookup::VersionCache::load_default();
+        for change in lookup::apply_latest_versions(&mut config, &mut cache, opts.latest_lts) {
+            log::info!("Latest release: {change}");
+        }
+        cache.save();
+    }
+    let today = chrono::Local::now().date_naive();
+    if opts.latest_lts {
+        let Some(lts) = support::latest_lts(today) else {
+            return Err(MigrateError::Config(
+                "--latest-lts: no LTS runtime known to this version of the tool is still supported; set app_runtime_version instead".to_string(),
+            ));
+        };
+        log::info!(
+            "Newest supported LTS runtime: {} (instead of {})",
+            lts.release,
+            config.app_runtime_version
         );
-        return Err(msg.into());
+        config.app_runtime_version = lts.release.to_string();
+    }
+    if opts.offline {
+        let needs = offline_conflicts(opts, &config);
+        if !needs.is_empty() {
+            return Err(MigrateError::Offline(needs));
+        }
+    }
+    if opts.deploy && config.deploy.is_none() {
+        return Err(MigrateError::Config(
+            "--deploy needs a 'deploy' section in the config".to_string(),
+        ));
+    }
+    let incompatible = compat::check_config(&config);
+    if !incompatible.is_empty() {
+        if !opts.force {
+            return Err(MigrateError::Incompatible(incompatible));
+        }
+        for problem in &incompatible {
+            log::warn!("Incompatible versions (--force): {problem}");
+        }
+        report.compatibility_warnings.extend(incompatible);
+    }
+    if let Some(warning) = support::runtime_warning(&config.app_runtime_version, today) {
+        log::warn!("{warning}");
+        report.compatibility_warnings.push(warning);
     }
-    log::info!("Loading migration config from {}", opts.config_path);
-    let config = MigrationConfig::from_file(opts.config_path)?;
     let project_root = opts.project_root;
 
-    if opts.update_maven_deps {
-        update_maven_dependencies(project_root);
+    report.versions = matrix::version_matrix(Path::new(project_root), &config);
+    log::info!("Versions (current -> target):");
+    for (row, line) in report
+        .versions
+        .iter()
+        .zip(matrix::render_matrix(&report.versions))
+    {
+        if row.downgrade {
+            log::warn!("  {line}");
+        } else {
+            log::info!("  {line}");
+        }
+    }
+
+    let use_git = !opts.dry_run && (opts.git_branch.is_some() || opts.git_commit);
+    if use_git && !opts.allow_dirty && git::is_dirty(project_root).map_err(MigrateError::Git)? {
+        return Err(MigrateError::DirtyWorkingTree(project_root.to_string()));
+    }
+    if let Some(branch) = opts.git_branch.filter(|_| !opts.dry_run) {
+        log::info!("Creating git branch {branch}");
+        git::create_branch(project_root, branch).map_err(MigrateError::Git)?;
     }
 
-    if opts.build_mule_project {
-        build_mule_project(project_root);
+    if !config.pre_hooks.is_empty() && !opts.dry_run {
+        let (runs, err) = hooks::run_hooks(project_root, "pre", &config.pre_hooks);
+        report.hooks.extend(runs);
+        if let Some(err) = err {
+            log::error!("{err}");
+            notify_error(&mut report, observer, err.to_string());
+            report.finish();
+            report::emit_report(&report, opts.report_format, opts.report_file)
+                .map_err(MigrateError::Report)?;
+            return Err(err);
+        }
+    }
+
+    let maven = (opts.update_maven_deps || opts.build_mule_project || opts.run_tests)
+        .then(|| maven::Maven::locate(Path::new(project_root), opts.maven_bin))
+        .transpose()
+        .map(|maven| {
+            maven.map(|maven| {
+                let args = config.maven.args.iter().chain(opts.maven_args).cloned();
+                let timeout = opts.maven_timeout_secs.or(config.maven.timeout_secs);
+                maven.with_settings(args.collect(), timeout.map(Duration::from_secs))
+            })
+        })
+        .unwrap_or_else(|e| {
+            log::error!("{e}");
+            notify_error(&mut report, observer, e.to_string());
+            None
+        });
+    // Snapshot of the resolved dependencies, compared with the one after the
+    // run so reviewers see every library that moved
+    let tree_before = maven
+        .as_ref()
+        .filter(|_| opts.update_maven_deps)
+        .and_then(|maven| capture_dependency_tree(maven, opts, &report.run_id));
+    if let Some(maven) = maven.as_ref().filter(|_| opts.update_maven_deps) {
+        update_maven_dependencies(&mut report, observer, opts, maven, &config.maven);
+    }
+
+    // All modifications are staged first and written together at the end
+    let mut tx = Transaction::new();
+    let selected = |step: StepKind| step.is_selected(opts.only, opts.skip);
+    report.skipped_steps = StepKind::ALL
+        .into_iter()
+        .filter(|&step| !selected(step))
+        .map(|step| step.name().to_string())
+        .collect();
+    if !report.skipped_steps.is_empty() {
+        log::info!("Skipping steps: {}", report.skipped_steps.join(", "));
     }
 
     // 1. Update pom.xml
-    let pom_path = Path::new(project_root).join("pom.xml");
-    if pom_path.exists() {
-        log::info!("Updating pom.xml at {}", pom_path.display());
-        let (changed, props) = xml::update_pom_xml_summary(
-            pom_path.to_str().unwrap(),
-            &config.app_runtime_version,
-            &config.mule_maven_plugin_version,
-            &config.munit_version,
-            opts.dry_run,
-            opts.backup,
-        );
-        if changed {
-            changed_files.push(pom_path.display().to_string());
-            changed_properties.extend(props);
+    if selected(StepKind::Pom) {
+        start_step(&mut report, observer, "pom.xml");
+        let pom_path = Path::new(project_root).join("pom.xml");
+        if pom_path.exists() {
+            notify_scanned(&mut report, observer, &pom_path);
+            log::info!("Updating pom.xml at {}", pom_path.display());
+            let step = steps::PomUpdater::new(project_root, &config)
+                .latest_connectors(opts.latest_connectors);
+            match stage_step(&step, &mut tx) {
+                Ok(changes) => {
+                    notify_changes(observer, &changes);
+                    report.changed_properties.extend(changes);
+                    if config.inherited_properties == InheritedProperties::Warn {
+                        let content = file_ops::read_file(&pom_path).unwrap_or_default();
+                        for (p, target) in step.inherited_overrides(&content) {
+                            let msg = format!(
+                                "{} is inherited from {} ('{}'): set it to '{target}' there, or use inherited_properties: override",
+                                p.name,
+                                p.source.display(),
+                                p.value
+                            );
+                            log::warn!("{msg}");
+                            report.compatibility_warnings.push(msg);
+                        }
+                    }
+                }
+                Err(e) => {
+                    log::error!("{e}");
+                    notify_error(&mut report, observer, e.to_string());
+                }
+            }
+        } else {
+            let msg = format!("No pom.xml found at {}", pom_path.display());
+            log::warn!("{msg}");
+            notify_error(&mut report, observer, msg);
         }
-    } else {
-        let msg = format!("No pom.xml found at {}", pom_path.display());
-        log::warn!("{msg}");
-        errors.push(msg);
     }
 
     // 2. Update mule-artifact.json
-    let artifact_path = Path::new(project_root).join("mule-artifact.json");
-    if artifact_path.exists() {
-        log::info!("Updating mule-artifact.json at {}", artifact_path.display());
-        let (changed, json_fields) = json_ops::update_mule_artifact_json_summary(
-            artifact_path.to_str().unwrap(),
-            &config.mule_artifact.min_mule_version,
-            &config.mule_artifact.java_specification_versions[..],
-            opts.dry_run,
-            opts.backup,
-        );
-        if changed {
-            changed_files.push(artifact_path.display().to_string());
-            changed_json.extend(json_fields);
+    if selected(StepKind::Artifact) {
+        start_step(&mut report, observer, "mule-artifact.json");
+        let artifact_path = Path::new(project_root).join("mule-artifact.json");
+        if artifact_path.exists() {
+            notify_scanned(&mut report, observer, &artifact_path);
+            log::info!("Updating mule-artifact.json at {}", artifact_path.display());
+            let step = steps::ArtifactJsonUpdater::new(project_root, &config);
+            match stage_step(&step, &mut tx) {
+                Ok(changes) => {
+                    notify_changes(observer, &changes);
+                    report.changed_json.extend(changes);
+                }
+                Err(e) => {
+                    log::error!("{e}");
+                    notify_error(&mut report, observer, e.to_string());
+                }
+            }
+        } else {
+            let msg = format!("No mule-artifact.json found at {}", artifact_path.display());
+            log::warn!("{msg}");
+            notify_error(&mut report, observer, msg);
+        }
+    }
+
+    let include: Vec<String> = config.include.iter().chain(opts.include).cloned().collect();
+    let mut exclude: Vec<String> = config.exclude.iter().chain(opts.exclude).cloned().collect();
+    // Never migrate the backups themselves
+    if let Some(dir) = opts.backup_dir.filter(|d| Path::new(d).is_relative()) {
+        exclude.push(format!("{}/**", dir.trim_end_matches(['/', '\\'])));
+    }
+    let mut filter = file_ops::FileFilter::new(&include, &exclude)?;
+    filter.skip_files([PathBuf::from(lockfile::LOCKFILE_NAME)]);
+    let all_files = filter.clone();
+    let lock_hash = lockfile::config_hash(&config, &include, &exclude);
+    if let Some(lock) = Lockfile::load(Path::new(project_root)).filter(|_| !opts.ignore_lock) {
+        let unchanged = lock.unchanged_files(Path::new(project_root), &lock_hash);
+        if !unchanged.is_empty() {
+            log::info!(
+                "Skipping {} file(s) unchanged since the last run (see {})",
+                unchanged.len(),
+                lockfile::LOCKFILE_NAME
+            );
+        }
+        filter.skip_files(unchanged);
+    }
+
+    // 3. Structural edits of Mule config files and, if enabled, MUnit suites
+    if selected(StepKind::MuleXml) {
+        start_step(&mut report, observer, "mule-xml");
+        let munit_rules = if config.migrate_munit {
+            munit::test_rules(&config.mule_artifact.min_mule_version)
+        } else {
+            Vec::new()
+        };
+        if !config.mule_xml_rules.is_empty() || !munit_rules.is_empty() {
+            log::info!(
+                "Applying Mule XML rules in {} and {}",
+                mule_xml::MULE_CONFIG_DIR,
+                munit::MUNIT_TEST_DIR
+            );
+            let xml_files =
+                file_ops::walk_files(Path::new(project_root), &filter).filter_map(|p| {
+                    let relative = p.strip_prefix(project_root).unwrap_or(&p);
+                    let rules = if mule_xml::is_mule_config(relative) {
+                        &config.mule_xml_rules
+                    } else if munit::is_munit_test(relative) {
+                        &munit_rules
+                    } else {
+                        return None;
+                    };
+                    (!rules.is_empty()).then_some((p, rules))
+                });
+            for (path, rules) in xml_files {
+                notify_scanned(&mut report, observer, &path);
+                let planned = file_ops::read_file(&path).and_then(|original| {
+                    mule_xml::plan_mule_xml_update(&original, rules)
+                        .map(|(content, changes)| (original, content, changes))
+                        .map_err(|source| MigrateError::Xml {
+                            path: path.clone(),
+                            source,
+                        })
+                });
+                match planned {
+                    Ok((original, content, changes)) if !changes.is_empty() => {
+                        let changes: Vec<String> = changes
+                            .into_iter()
+                            .map(|c| format!("{}: {c}", path.display()))
+                            .collect();
+                        notify_changes(observer, &changes);
+                        report.changed_xml.extend(changes);
+                        tx.stage(&path, original, content);
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        log::warn!("{e}");
+                        notify_file_error(&mut report, observer, e.to_string());
+                    }
+                }
+            }
         }
-    } else {
-        let msg = format!("No mule-artifact.json found at {}", artifact_path.display());
-        log::warn!("{msg}");
-        errors.push(msg);
     }
 
-    // 3. Traverse and replace in source files
-    let replacements_vec: Vec<(String, String)> = config
-        .replacements
+    // 4. Point schema locations of Mule configs and MUnit suites to the current schemas
+    if selected(StepKind::SchemaLocations) && config.update_schema_locations {
+        start_step(&mut report, observer, "schema-locations");
+        let step = mule_xml::SchemaLocationUpdater::new(project_root, &config, filter.clone());
+        match stage_files_step(&step, &mut tx, &mut report, observer) {
+            Ok(changes) => {
+                notify_changes(observer, &changes);
+                report.changed_xml.extend(changes);
+            }
+            Err(e) => {
+                log::error!("{e}");
+                notify_error(&mut report, observer, e.to_string());
+            }
+        }
+    }
+
+    // 5. Set properties in YAML property files
+    if selected(StepKind::YamlProperties) {
+        start_step(&mut report, observer, "yaml-properties");
+        if !config.yaml_properties.is_empty() {
+            let matchers = config
+                .yaml_properties
+                .iter()
+                .map(|rule| globset::Glob::new(&rule.files).map(|g| g.compile_matcher()))
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut found_keys = std::collections::HashSet::new();
+            for path in file_ops::walk_files(Path::new(project_root), &filter) {
+                let relative = path.strip_prefix(project_root).unwrap_or(&path);
+                let updates: Vec<(&str, &str)> = config
+                    .yaml_properties
+                    .iter()
+                    .zip(&matchers)
+                    .filter(|(_, m)| m.is_match(relative))
+                    .map(|(rule, _)| (rule.key.as_str(), rule.value.as_str()))
+                    .collect();
+                if updates.is_empty() {
+                    continue;
+                }
+                notify_scanned(&mut report, observer, &path);
+                let original = match tx.staged_content(&path) {
+                    Some(content) => Ok(content.to_string()),
+                    None => file_ops::read_file(&path),
+                };
+                let original = match original {
+                    Ok(original) => original,
+                    Err(e) => {
+                        log::warn!("{e}");
+                        notify_file_error(&mut report, observer, e.to_string());
+                        continue;
+                    }
+                };
+                let (content, changes, missing) = yaml_props::plan_yaml_update(&original, &updates);
+                found_keys.extend(
+                    updates
+                        .iter()
+                        .map(|(k, _)| *k)
+                        .filter(|k| !missing.contains(k)),
+                );
+                if !changes.is_empty() {
+                    let changes: Vec<String> = changes
+                        .into_iter()
+                        .map(|c| format!("{}: {c}", path.display()))
+                        .collect();
+                    notify_changes(observer, &changes);
+                    report.changed_properties.extend(changes);
+                    tx.stage(&path, original, content);
+                }
+            }
+            for rule in &config.yaml_properties {
+                if !found_keys.contains(rule.key.as_str()) {
+                    log::warn!(
+                        "YAML property '{}' not found in any file matching {}",
+                        rule.key,
+                        rule.files
+                    );
+                }
+            }
+        }
+    }
+
+    // 6. Set existing keys in .properties files
+    if selected(StepKind::Properties) {
+        start_step(&mut report, observer, "properties");
+        if !config.properties_updates.is_empty() {
+            let step = properties::PropertiesUpdater::new(
+                project_root,
+                &config.properties_updates,
+                filter.clone(),
+            )?;
+            match stage_files_step(&step, &mut tx, &mut report, observer) {
+                Ok(changes) => {
+                    notify_changes(observer, &changes);
+                    report.changed_properties.extend(changes);
+                }
+                Err(e) => {
+                    log::error!("{e}");
+                    notify_error(&mut report, observer, e.to_string());
+                }
+            }
+        }
+    }
+
+    // 7. Structural edits of log4j2.xml
+    if selected(StepKind::Log4j2) {
+        start_step(&mut report, observer, "log4j2");
+        let log4j2_path = Path::new(project_root).join(log4j2::LOG4J2_PATH);
+        if !config.log4j2.is_empty()
+            && log4j2_path.exists()
+            && filter.is_selected(Path::new(log4j2::LOG4J2_PATH))
+        {
+            log::info!("Updating {}", log4j2_path.display());
+            notify_scanned(&mut report, observer, &log4j2_path);
+            let planned = file_ops::read_file(&log4j2_path).and_then(|original| {
+                log4j2::plan_log4j2_update(&original, &config.log4j2)
+                    .map(|(content, changes)| (original, content, changes))
+                    .map_err(|source| MigrateError::Xml {
+                        path: log4j2_path.clone(),
+                        source,
+                    })
+            });
+            match planned {
+                Ok((original, content, changes)) if !changes.is_empty() => {
+                    let changes: Vec<String> = changes
+                        .into_iter()
+                        .map(|c| format!("{}: {c}", log4j2_path.display()))
+                        .collect();
+                    notify_changes(observer, &changes);
+                    report.changed_xml.extend(changes);
+                    tx.stage(&log4j2_path, original, content);
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    log::warn!("{e}");
+                    notify_file_error(&mut report, observer, e.to_string());
+                }
+            }
+        } else if !config.log4j2.is_empty() && !log4j2_path.exists() {
+            log::warn!("No log4j2.xml found at {}", log4j2_path.display());
+        }
+    }
+
+    // 8. Rename imports and functions in DataWeave scripts
+    if selected(StepKind::Dataweave) {
+        start_step(&mut report, observer, "dataweave");
+        if !config.dataweave.is_empty() {
+            let scripts = file_ops::walk_files(Path::new(project_root), &filter)
+                .filter(|p| dataweave::is_dataweave(p));
+            for path in scripts {
+                notify_scanned(&mut report, observer, &path);
+                let original = match file_ops::read_file(&path) {
+                    Ok(original) => original,
+                    Err(e) => {
+                        log::warn!("{e}");
+                        notify_file_error(&mut report, observer, e.to_string());
+                        continue;
+                    }
+                };
+                let (content, changes) =
+                    dataweave::plan_dataweave_update(&original, &config.dataweave);
+                if !changes.is_empty() {
+                    let changes: Vec<String> = changes
+                        .into_iter()
+                        .map(|c| format!("{}: {c}", path.display()))
+                        .collect();
+                    notify_changes(observer, &changes);
+                    report.changed_dataweave.extend(changes);
+                    tx.stage(&path, original, content);
+                }
+            }
+        }
+    }
+
+    let pom_path = Path::new(project_root).join("pom.xml");
+    // pom.xml before and after the text replacements, to check they kept its structure
+    let mut replaced_pom = None;
+
+    // 9. Traverse and replace in source files
+    if selected(StepKind::Replacements) {
+        start_step(&mut report, observer, "replacements");
+        let pom = tx
+            .staged_content(&pom_path)
+            .map(str::to_string)
+            .or_else(|| file_ops::read_text(&pom_path).ok());
+        let vars = template::project_variables(&config, pom.as_deref(), Path::new(project_root));
+        let replacements = template::render_rules(&config.replacements, &vars);
+        let plan =
+            file_ops::plan_replacements(Path::new(project_root), &replacements, &filter, |p| {
+                notify_scanned(&mut report, observer, p);
+                match tx.staged_content(p) {
+                    Some(content) => Ok(content.to_string()),
+                    None => file_ops::read_text(p),
+                }
+            });
+        report.rule_impact = plan.impact;
+        for planned in plan.planned {
+            // pom.xml has a stricter check of its own below
+            if planned.path != pom_path && planned.path.extension().is_some_and(|e| e == "xml") {
+                let before = tx
+                    .staged_content(&planned.path)
+                    .unwrap_or(&planned.original);
+                if let Some(problem) = mule_xml::check_replaced_xml(
+                    before,
+                    &planned.content,
+                    config.validate_mule_schemas,
+                ) {
+                    let message = format!(
+                        "{}: replacements left it broken ({problem}), so it was not changed",
+                        planned.path.display()
+                    );
+                    log::warn!("{message}");
+                    notify_file_error(&mut report, observer, message);
+                    continue;
+                }
+            }
+            if planned.path == pom_path {
+                let before = tx
+                    .staged_content(&pom_path)
+                    .unwrap_or(&planned.original)
+                    .to_string();
+                replaced_pom = Some((before, planned.content.clone()));
+            }
+            tx.stage(&planned.path, planned.original, planned.content);
+            notify_changes(observer, &planned.summary);
+            report.replacements.extend(planned.summary);
+        }
+        for e in plan.errors {
+            log::warn!("{e}");
+            notify_file_error(&mut report, observer, e.to_string());
+        }
+    }
+
+    // 10. Update pinned versions and images in CI definitions
+    if selected(StepKind::Ci) {
+        start_step(&mut report, observer, "ci");
+        if !config.ci_files.is_empty() {
+            let vars = StepContext::new(&tx).project_variables(&config, Path::new(project_root));
+            let ci = config.ci_files.render(&vars);
+            let step = ci::CiFilesUpdater::new(project_root, &ci, filter.clone())?;
+            match stage_files_step(&step, &mut tx, &mut report, observer) {
+                Ok(changes) => {
+                    notify_changes(observer, &changes);
+                    report.ci_changes.extend(changes);
+                }
+                Err(e) => {
+                    log::error!("{e}");
+                    notify_error(&mut report, observer, e.to_string());
+                }
+            }
+        }
+    }
+
+    // 11. Bump base images and environment variables in Dockerfiles
+    if selected(StepKind::Dockerfile) {
+        start_step(&mut report, observer, "dockerfile");
+        if !config.dockerfile.is_empty() {
+            let step = dockerfile::DockerfileUpdater::new(project_root, &config, filter.clone())?;
+            match stage_files_step(&step, &mut tx, &mut report, observer) {
+                Ok(changes) => {
+                    notify_changes(observer, &changes);
+                    report.dockerfile_changes.extend(changes);
+                }
+                Err(e) => {
+                    log::error!("{e}");
+                    notify_error(&mut report, observer, e.to_string());
+                }
+            }
+        }
+    }
+
+    // 12. Run external step plugins on top of the built-in changes
+    if selected(StepKind::Plugins) {
+        start_step(&mut report, observer, "plugins");
+        for name in &config.plugins {
+            let step = plugins::ExternalStep::resolve(
+                name,
+                Path::new(project_root),
+                &config,
+                opts.dry_run,
+            );
+            match step.and_then(|step| stage_step(&step, &mut tx)) {
+                Ok(changes) => {
+                    notify_changes(observer, &changes);
+                    report.plugin_changes.extend(changes);
+                }
+                Err(e) => {
+                    log::error!("{e}");
+                    notify_error(&mut report, observer, e.to_string());
+                }
+            }
+        }
+    }
+
+    // 13. Rename and move files; content staged for them moves along
+    if selected(StepKind::FileRenames) {
+        start_step(&mut report, observer, "file-renames");
+        if !config.file_renames.is_empty() {
+            match file_ops::plan_file_renames(
+                Path::new(project_root),
+                &config.file_renames,
+                &filter,
+            ) {
+                Ok(plan) => {
+                    let changes: Vec<String> = plan
+                        .renames
+                        .iter()
+                        .map(|(from, to)| format!("{} -> {}", from.display(), to.display()))
+                        .collect();
+                    notify_changes(observer, &changes);
+                    report.renamed_files.extend(changes);
+                    for (from, to) in plan.renames {
+                        tx.stage_rename(from, to);
+                    }
+                    for conflict in plan.conflicts {
+                        let msg = format!("Not moving {conflict}");
+                        log::warn!("{msg}");
+                        notify_file_error(&mut report, observer, msg);
+                    }
+                    for i in plan.unmatched {
+                        let rule = &config.file_renames[i];
+                        if Path::new(project_root).join(&rule.to).exists() {
+                            log::debug!("{} was already moved to {}", rule.from, rule.to);
+                        } else {
+                            log::warn!("File rename '{}' matched no file", rule.from);
+                        }
+                    }
+                }
+                Err(e) => {
+                    log::error!("{e}");
+                    notify_error(&mut report, observer, e.to_string());
+                }
+            }
+        }
+    }
+
+    // 14. Delete obsolete files and create new ones
+    if selected(StepKind::Files) {
+        start_step(&mut report, observer, "files");
+        match file_ops::plan_file_deletes(Path::new(project_root), &config.files_to_delete, &filter)
+        {
+            Ok(paths) => {
+                for path in paths {
+                    if tx.renames().iter().any(|r| r.from == path) {
+                        log::debug!("Not deleting {}, it is moved", path.display());
+                        continue;
+                    }
+                    let change = path.display().to_string();
+                    notify_changes(observer, std::slice::from_ref(&change));
+                    report.deleted_files.push(change);
+                    tx.stage_delete(path);
+                }
+            }
+            Err(e) => {
+                log::error!("{e}");
+                notify_error(&mut report, observer, e.to_string());
+            }
+        }
+        if !config.files_to_create.is_empty() {
+            let pom = tx
+                .staged_content(&pom_path)
+                .map(str::to_string)
+                .or_else(|| file_ops::read_text(&pom_path).ok());
+            let vars =
+                template::project_variables(&config, pom.as_deref(), Path::new(project_root));
+            for file in &config.files_to_create {
+                let path = Path::new(project_root).join(file.path.trim_matches('/'));
+                if path.exists() || tx.renames().iter().any(|r| r.to == path) {
+                    log::debug!("{} already exists", path.display());
+                    continue;
+                }
+                let change = path.display().to_string();
+                notify_changes(observer, std::slice::from_ref(&change));
+                report.created_files.push(change);
+                tx.stage_new(&path, template::render(&file.content, &vars));
+            }
+        }
+    }
+
+    // 15. Bump the project version in pom.xml, once per run that changes
+    // the project
+    if selected(StepKind::ProjectVersion) {
+        start_step(&mut report, observer, "project-version");
+        if let Some(bump) = &config.project_version {
+            let step = steps::ProjectVersionUpdater::new(project_root, bump);
+            match stage_step(&step, &mut tx) {
+                Ok(changes) => {
+                    notify_changes(observer, &changes);
+                    report.changed_properties.extend(changes);
+                }
+                Err(e) => {
+                    log::error!("{e}");
+                    notify_error(&mut report, observer, e.to_string());
+                }
+            }
+        }
+    }
+
+    // 16. Bump the asset version in exchange.json, once per run that changes
+    // the project
+    if selected(StepKind::ExchangeJson) {
+        start_step(&mut report, observer, "exchange.json");
+        let path = Path::new(project_root).join("exchange.json");
+        if !config.exchange_json.is_empty() && path.is_file() {
+            notify_scanned(&mut report, observer, &path);
+            let step = steps::ExchangeJsonUpdater::new(project_root, &config);
+            match stage_step(&step, &mut tx) {
+                Ok(changes) => {
+                    notify_changes(observer, &changes);
+                    report.changed_json.extend(changes);
+                }
+                Err(e) => {
+                    log::error!("{e}");
+                    notify_error(&mut report, observer, e.to_string());
+                }
+            }
+        }
+    }
+
+    // Safety net for the text-level edits: a pom.xml that parsed before must
+    // still parse, and replacements must only have changed values
+    if let Some(write) = tx.writes().iter().find(|w| w.path == pom_path) {
+        let verified = if xml::verify_pom(&write.original).is_ok() {
+            xml::verify_pom(&write.content)
+        } else {
+            Ok(())
+        }
+        .and_then(|()| match &replaced_pom {
+            Some((before, after)) => match xml::structural_difference(before, after) {
+                Some(diff) => Err(format!("replacements changed its structure ({diff})")),
+                None => Ok(()),
+            },
+            None => Ok(()),
+        });
+        if let Err(reason) = verified {
+            let err = MigrateError::PomVerification {
+                path: pom_path,
+                reason,
+            };
+            log::error!("{err}");
+            notify_error(&mut report, observer, err.to_string());
+            report.finish();
+            report::emit_report(&report, opts.report_format, opts.report_file)
+                .map_err(MigrateError::Report)?;
+            return Err(err);
+        }
+    }
+
+    if opts.strict && !report.file_errors.is_empty() {
+        let err = MigrateError::FileErrors(report.file_errors.len());
+        log::error!("{err}");
+        notify_error(&mut report, observer, err.to_string());
+        report.finish();
+        report::emit_report(&report, opts.report_format, opts.report_file)
+            .map_err(MigrateError::Report)?;
+        return Err(err);
+    }
+
+    // 17. Commit all staged changes at once
+    start_step(&mut report, observer, "commit");
+    if opts.interactive && !opts.dry_run && !tx.is_empty() {
+        let (reviewed, skipped) = interactive::review_transaction(
+            tx,
+            &mut std::io::stdin().lock(),
+            &mut std::io::stdout(),
+        )
+        .map_err(MigrateError::Interactive)?;
+        tx = reviewed;
+        report.skipped_files = skipped;
+    }
+    report.changed_files = tx
+        .writes()
         .iter()
-        .map(|r| (r.from.clone(), r.to.clone()))
+        .map(|w| w.path.display().to_string())
         .collect();
-    let rep_summary = file_ops::traverse_and_replace_summary(
-        project_root,
-        &replacements_vec,
-        opts.dry_run,
-        opts.backup,
-    );
-    replacements_summary.extend(rep_summary);
-
-    print_summary(
-        &changed_files,
-        &changed_properties,
-        &changed_json,
-        &replacements_summary,
-        &errors,
-        opts.dry_run,
-    );
-    Ok(())
+    // Both sides of a move, and removed files, are changes to commit too
+    let moved_paths: Vec<String> = tx
+        .renames()
+        .iter()
+        .flat_map(|r| [r.from.display().to_string(), r.to.display().to_string()])
+        .chain(tx.deletes().iter().map(|d| d.display().to_string()))
+        .collect();
+    report.diffs = tx
+        .writes()
+        .iter()
+        .map(|w| {
+            let path = w.path.display().to_string();
+            let diff = similar::TextDiff::from_lines(&w.original, &w.content)
+                .unified_diff()
+                .header(&path, &path)
+                .to_string();
+            report::FileDiff { path, diff }
+        })
+        .collect();
+    if let Some(out) = opts.plan_out.filter(|_| opts.dry_run) {
+        match plan::Plan::from_transaction(Path::new(project_root), &tx).save(Path::new(out)) {
+            Ok(()) => log::info!("Plan written to {out}"),
+            Err(e) => {
+                log::error!("{e}");
+                notify_error(&mut report, observer, e.to_string());
+            }
+        }
+    }
+    if !opts.dry_run && !tx.is_empty() {
+        let root = Path::new(project_root);
+        let backups_dir = backup::backups_dir(root, opts.backup_dir);
+        let snapshot = opts
+            .backup
+            .then(|| {
+                backup::Snapshot::create_for_run(root, &backups_dir, &report.run_id).map_err(
+                    |source| MigrateError::Backup {
+                        path: backups_dir.clone(),
+                        source,
+                    },
+                )
+            })
+            .transpose();
+        let bytes: u64 = tx.writes().iter().map(|w| w.content.len() as u64).sum();
+        let committed = snapshot.and_then(|snapshot| {
+            tx.commit(snapshot.as_ref())?;
+            Ok(snapshot)
+        });
+        match committed {
+            Ok(Some(snapshot)) => {
+                let run = snapshot.run_id().to_string();
+                match snapshot.finish(opts.backup_compression) {
+                    Ok(location) => log::info!("Backups saved to {}", location.display()),
+                    Err(e) => log::warn!("Failed to compress the backups of run {run}: {e}"),
+                }
+                if let Some(keep) = opts.keep_backups {
+                    match backup::prune_runs(&backups_dir, keep) {
+                        Ok(removed) => {
+                            for old in removed {
+                                log::info!("Removed old backup run {old}");
+                            }
+                        }
+                        Err(e) => log::warn!("Failed to remove old backups: {e}"),
+                    }
+                }
+                report.backup_run = Some(run);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                log::error!("{e}");
+                notify_error(&mut report, observer, e.to_string());
+                report.changed_files.clear();
+                report.renamed_files.clear();
+                report.created_files.clear();
+                report.deleted_files.clear();
+                report.finish();
+                report::emit_report(&report, opts.report_format, opts.report_file)
+                    .map_err(MigrateError::Report)?;
+                return Err(e);
+            }
+        }
+        report.stats.bytes_written = bytes;
+    }
+    for path in &report.changed_files {
+        observer.on_event(&MigrationEvent::FileChanged {
+            path: Path::new(path),
+        });
+    }
+    // Record the migrated state so a re-run (or a run resumed after a later
+    // step failed) can skip the files already done
+    if !opts.dry_run
+        && report.errors.is_empty()
+        && report.file_errors.is_empty()
+        && report.skipped_files.is_empty()
+        && report.skipped_steps.is_empty()
+    {
+        let lock = Lockfile::record(
+            Path::new(project_root),
+            &lock_hash,
+            file_ops::walk_files(Path::new(project_root), &all_files),
+        );
+        if let Err(e) = lock.save(Path::new(project_root)) {
+            log::warn!("{e}");
+        }
+    }
+
+    if !config.post_hooks.is_empty() && !opts.dry_run {
+        report.stats.start_step("post-hooks");
+        let (runs, err) = hooks::run_hooks(project_root, "post", &config.post_hooks);
+        report.hooks.extend(runs);
+        if let Some(err) = err {
+            log::error!("{err}");
+            notify_error(&mut report, observer, err.to_string());
+        }
+    }
+
+    if let (Some(before), Some(maven)) = (&tree_before, &maven) {
+        report.stats.start_step("dependency-tree");
+        if let Some(after) = capture_dependency_tree(maven, opts, &report.run_id) {
+            report.dependency_changes = maven::diff_dependency_trees(before, &after);
+        }
+    }
+
+    if opts.git_commit
+        && !opts.dry_run
+        && !(report.changed_files.is_empty() && moved_paths.is_empty())
+    {
+        let message = git::commit_message(&config.app_runtime_version, &report);
+        let files: Vec<String> = report
+            .changed_files
+            .iter()
+            .chain(&moved_paths)
+            .cloned()
+            .collect();
+        match git::commit_files(project_root, &files, &message) {
+            Ok(hash) => log::info!("Committed migration as {hash}"),
+            Err(e) => {
+                let msg = format!("Failed to commit the migration: {e}");
+                log::error!("{msg}");
+                notify_error(&mut report, observer, msg);
+            }
+        }
+    }
+
+    // 18. Flag custom Java code that breaks on the target JDK
+    if selected(StepKind::JavaCompat) {
+        start_step(&mut report, observer, "java-compatibility");
+        if let Some(java) =
+            java_compat::highest_java_version(&config.mule_artifact.java_specification_versions)
+        {
+            if java >= 11 {
+                log::info!("Scanning custom Java code for Java {java} incompatibilities...");
+                report
+                    .compatibility_warnings
+                    .extend(java_compat::scan_project(Path::new(project_root), java));
+            }
+        }
+    }
+
+    if let Some(maven) = maven.as_ref().filter(|_| opts.build_mule_project) {
+        let goals = if opts.maven_goals.is_empty() {
+            &config.maven.goals
+        } else {
+            opts.maven_goals
+        };
+        report.stats.start_step("build");
+        build_mule_project(&mut report, observer, opts, maven, goals);
+    }
+
+    if let Some(maven) = maven.as_ref().filter(|_| opts.run_tests) {
+        report.stats.start_step("tests");
+        run_tests(&mut report, observer, opts, maven, &config.maven.test_goals);
+    }
+
+    if let Some(deploy) = config.deploy.as_ref().filter(|_| opts.deploy) {
+        report.stats.start_step("deploy");
+        if opts.dry_run {
+            log::info!("Dry run, not redeploying");
+        } else if !report.errors.is_empty() {
+            log::warn!("Not redeploying, the run had errors");
+        } else {
+            let pom = file_ops::read_text(&pom_path).ok();
+            let vars =
+                template::project_variables(&config, pom.as_deref(), Path::new(project_root));
+            let application = template::render(&deploy.application, &vars);
+            let runtime_version = template::render(&deploy.runtime_version, &vars);
+            let proxy = config.version_lookup.proxy.as_deref();
+            match deploy::redeploy(deploy, &application, &runtime_version, proxy) {
+                Ok(status) => {
+                    if status.succeeded {
+                        log::info!("Redeployed {application}: {}", status.status);
+                    } else {
+                        let msg = format!("Redeploy of '{application}' ended {}", status.status);
+                        log::error!("{msg}");
+                        notify_error(&mut report, observer, msg);
+                    }
+                    report.deployment = Some(status);
+                }
+                Err(e) => {
+                    log::error!("{e}");
+                    notify_error(&mut report, observer, e.to_string());
+                }
+            }
+        }
+    }
+
+    report.finish();
+    report::emit_report(&report, opts.report_format, opts.report_file)
+        .map_err(MigrateError::Report)?;
+    Ok(report)
+}
+
+/// What a run with these options and config would use the network or an
+/// external program for, and so cannot do offline.
+fn offline_conflicts(opts: &MigrationOptions, config: &MigrationConfig) -> Vec<String> {
+    let mut needs = Vec::new();
+    if opts.update_maven_deps || opts.build_mule_project || opts.run_tests {
+        needs.push(
+            "Maven runs (--update-maven-deps, --build-mule-project, --run-tests)".to_string(),
+        );
+    }
+    if opts.latest_connectors {
+        needs.push("connector lookups (--latest-connectors)".to_string());
+    }
+    if opts.deploy {
+        needs.push("redeploying (--deploy)".to_string());
+    }
+    if !opts.dry_run && (opts.git_branch.is_some() || opts.git_commit) {
+        needs.push("git (--git-branch, --git-commit)".to_string());
+    }
+    let hooks = !config.pre_hooks.is_empty() || !config.post_hooks.is_empty();
+    if hooks && !opts.dry_run {
+        needs.push("the config's pre_hooks and post_hooks".to_string());
+    }
+    if !config.plugins.is_empty() && StepKind::Plugins.is_selected(opts.only, opts.skip) {
+        needs.push("the config's step plugins".to_string());
+    }
+    let pom_step = StepKind::Pom.is_selected(opts.only, opts.skip);
+    if pom_step
+        && config
+            .exchange
+            .api_specs
+            .iter()
+            .any(|s| s.version.is_none())
+    {
+        needs.push("Exchange lookups of exchange.api_specs without a version".to_string());
+    }
+    needs
+}
+
+/// Starts timing `step` and passes it to the observer.
+fn start_step(report: &mut MigrationReport, observer: &mut dyn MigrationObserver, step: &str) {
+    report.stats.start_step(step);
+    observer.on_event(&MigrationEvent::StepStarted { step });
+}
+
+/// Counts a scanned file in the report and passes it to the observer.
+fn notify_scanned(report: &mut MigrationReport, observer: &mut dyn MigrationObserver, path: &Path) {
+    report.stats.file_scanned(path);
+    observer.on_event(&MigrationEvent::FileScanned { path });
+}
+
+/// Records an error in the report and passes it to the observer.
+fn notify_error(
+    report: &mut MigrationReport,
+    observer: &mut dyn MigrationObserver,
+    message: String,
+) {
+    observer.on_event(&MigrationEvent::Error { message: &message });
+    report.errors.push(message);
+}
+
+/// Records a per-file error in the report and passes it to the observer.
+fn notify_file_error(
+    report: &mut MigrationReport,
+    observer: &mut dyn MigrationObserver,
+    message: String,
+) {
+    observer.on_event(&MigrationEvent::Error { message: &message });
+    report.file_errors.push(message);
+}
+
+/// Plans `step` on top of the changes staged so far and stages its own,
+/// returning their descriptions.
+fn stage_step(step: &dyn MigrationStep, tx: &mut Transaction) -> Result<Vec<String>, MigrateError> {
+    let plans = step.plan(&StepContext::new(tx))?;
+    let changes = step.summarize(&plans);
+    step.apply(plans, tx)?;
+    Ok(changes)
+}
+
+/// Like [`stage_step`] for a step reading many files: counts each file as
+/// scanned and reports one that cannot be read or fails the step's check as
+/// a file error, which the step skips.
+fn stage_files_step(
+    step: &dyn MigrationStep,
+    tx: &mut Transaction,
+    report: &mut MigrationReport,
+    observer: &mut dyn MigrationObserver,
+) -> Result<Vec<String>, MigrateError> {
+    let ctx = StepContext::new(tx);
+    for path in step.files() {
+        notify_scanned(report, observer, &path);
+        if let Err(e) = ctx
+            .read(&path)
+            .and_then(|content| step.check_file(&path, &content))
+        {
+            log::warn!("{e}");
+            notify_file_error(report, observer, e.to_string());
+        }
+    }
+    stage_step(step, tx)
+}
+
+/// Passes planned changes to the observer.
+fn notify_changes(observer: &mut dyn MigrationObserver, changes: &[String]) {
+    for description in changes {
+        observer.on_event(&MigrationEvent::PropertyUpdated { description });
+    }
 }
 
-/// Runs 'mvn versions:use-latest-releases' in the project root and removes pom.xml.versionsBackup if present.
-fn update_maven_dependencies(project_root: &str) {
-    log::info!("Running 'mvn versions:use-latest-releases' in {project_root}");
-    let status = Command::new("mvn")
-        .arg("versions:use-latest-releases")
-        .current_dir(project_root)
-        .status();
-    match status {
-        Ok(s) if s.success() => log::info!("Maven dependencies updated to latest releases."),
-        Ok(s) => log::error!("Maven exited with status: {s}"),
-        Err(e) => log::error!("Failed to run Maven: {e}"),
+/// Runs 'mvn versions:use-latest-releases', limited to the config's update
+/// patterns, in the project root and removes pom.xml.versionsBackup if present.
+fn update_maven_dependencies(
+    report: &mut MigrationReport,
+    observer: &mut dyn MigrationObserver,
+    opts: &MigrationOptions,
+    maven: &maven::Maven,
+    config: &maven::MavenConfig,
+) {
+    let args = config.update_args();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    if run_maven(report, observer, opts, maven, &args) {
+        log::info!("Maven dependencies updated to latest releases.");
     }
     // Cleanup pom.xml.versionsBackup if it exists
-    let backup_path = std::path::Path::new(project_root).join("pom.xml.versionsBackup");
+    let backup_path = Path::new(opts.project_root).join("pom.xml.versionsBackup");
     if backup_path.exists() {
         match std::fs::remove_file(&backup_path) {
             Ok(_) => log::info!("Removed Maven backup file: {}", backup_path.display()),
@@ -168,96 +1322,257 @@ fn update_maven_dependencies(project_root: &str) {
     }
 }
 
-/// Runs 'mvn clean install' in the project root.
-fn build_mule_project(project_root: &str) {
-    log::info!("Running 'mvn clean install' in {project_root}");
-    let status = Command::new("mvn")
-        .arg("clean")
-        .arg("install")
-        .current_dir(project_root)
-        .status();
-    match status {
-        Ok(s) if s.success() => log::info!("Mule project built successfully."),
-        Ok(s) => log::error!("Maven exited with status: {s}"),
-        Err(e) => log::error!("Failed to run Maven: {e}"),
+/// Resolves the dependency tree of the project; a failure is only a warning,
+/// as the tree is informational.
+fn capture_dependency_tree(
+    maven: &maven::Maven,
+    opts: &MigrationOptions,
+    run_id: &str,
+) -> Option<maven::DependencyTree> {
+    log::info!("Resolving the dependency tree...");
+    let output = std::env::temp_dir().join(format!("mule-migrate-tree-{run_id}.txt"));
+    match maven.dependency_tree(Path::new(opts.project_root), &output) {
+        Ok(tree) => Some(tree),
+        Err(e) => {
+            log::warn!("Could not resolve the dependency tree: {e}");
+            None
+        }
     }
 }
 
-/// Checks if the given directory is a Mule project (contains pom.xml and mule-artifact.json).
-fn is_mule_project(project_root: &str) -> bool {
-    let pom = Path::new(project_root).join("pom.xml");
-    let artifact = Path::new(project_root).join("mule-artifact.json");
-    pom.exists() && artifact.exists()
+/// Runs the build goals (`clean install` by default) in the project root.
+fn build_mule_project(
+    report: &mut MigrationReport,
+    observer: &mut dyn MigrationObserver,
+    opts: &MigrationOptions,
+    maven: &maven::Maven,
+    goals: &[String],
+) {
+    let goals: Vec<&str> = goals.iter().map(String::as_str).collect();
+    if run_maven(report, observer, opts, maven, &goals) {
+        log::info!("Mule project built successfully.");
+    }
 }
 
-/// Prints a colorized summary of the migration results.
-fn print_summary(
-    changed_files: &[String],
-    changed_properties: &[String],
-    changed_json: &[String],
-    replacements_summary: &[String],
-    errors: &[String],
-    dry_run: bool,
+/// Runs the test goals (`clean test` by default) in the project root and
+/// summarizes the test reports they leave behind.
+fn run_tests(
+    report: &mut MigrationReport,
+    observer: &mut dyn MigrationObserver,
+    opts: &MigrationOptions,
+    maven: &maven::Maven,
+    goals: &[String],
 ) {
-    println!(
-        "\n{}",
-        "================ MIGRATION SUMMARY ================"
-            .bold()
-            .blue()
+    let goals: Vec<&str> = goals.iter().map(String::as_str).collect();
+    run_maven(report, observer, opts, maven, &goals);
+    let summary = surefire::collect(Path::new(opts.project_root));
+    log::info!(
+        "Tests: {} run, {} failed, {} errors, {} skipped",
+        summary.tests,
+        summary.failures,
+        summary.errors,
+        summary.skipped
     );
-    if dry_run {
-        println!(
-            "{}",
-            "[DRY-RUN] No files were actually changed".bold().blue()
-        );
-    }
-    if !changed_files.is_empty() {
-        println!("{}", "Changed files:".green().bold());
-        for file in changed_files {
-            println!("  {}", file.green());
+    report.tests = Some(summary);
+}
+
+/// Runs Maven in the project root, recording the run in the report and a
+/// failure in its errors. Returns true if Maven succeeded.
+fn run_maven(
+    report: &mut MigrationReport,
+    observer: &mut dyn MigrationObserver,
+    opts: &MigrationOptions,
+    maven: &maven::Maven,
+    goals: &[&str],
+) -> bool {
+    log::info!(
+        "Running '{} {}' in {}",
+        maven.program().display(),
+        goals.join(" "),
+        opts.project_root
+    );
+    match maven.run(Path::new(opts.project_root), goals, opts.maven_output_lines) {
+        Ok(run) => {
+            let succeeded = run.succeeded();
+            if !succeeded {
+                let msg = run.failure_message();
+                log::error!("{msg}");
+                notify_error(report, observer, msg);
+            }
+            report.maven.push(run);
+            succeeded
         }
-    }
-    if !changed_properties.is_empty() {
-        println!("{}", "Updated properties:".green().bold());
-        for prop in changed_properties {
-            println!("  {}", prop.green());
+        Err(e) => {
+            let msg = format!("Failed to run Maven: {e}");
+            log::error!("{msg}");
+            notify_error(report, observer, msg);
+            false
         }
     }
-    if !changed_json.is_empty() {
-        println!("{}", "Updated JSON fields:".green().bold());
-        for field in changed_json {
-            println!("  {}", field.green());
+}
+
+/// Checks if the given directory is a Mule project (contains pom.xml and mule-artifact.json).
+pub fn is_mule_project(project_root: &Path) -> bool {
+    let pom = project_root.join("pom.xml");
+    let artifact = project_root.join("mule-artifact.json");
+    pom.exists() && artifact.exists()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    /// Options for a run of the built-in 4.4 to 4.9 profile on `root` with
+    /// a JSON report and nothing else enabled.
+    pub(crate) fn test_options(root: &str) -> MigrationOptions<'_> {
+        MigrationOptions {
+            config_path: None,
+            config_sha256: None,
+            config_vars: &[],
+            profile: Some("4.4-to-4.9-java17"),
+            project_root: root,
+            dry_run: false,
+            backup: false,
+            backup_dir: None,
+            backup_compression: backup::BackupCompression::None,
+            keep_backups: None,
+            update_maven_deps: false,
+            latest_connectors: false,
+            latest_lts: false,
+            latest: false,
+            build_mule_project: false,
+            run_tests: false,
+            deploy: false,
+            maven_bin: None,
+            maven_output_lines: 0,
+            maven_goals: &[],
+            maven_args: &[],
+            maven_timeout_secs: None,
+            include: &[],
+            exclude: &[],
+            interactive: false,
+            report_format: ReportFormat::Json,
+            report_file: None,
+            git_branch: None,
+            git_commit: false,
+            allow_dirty: false,
+            strict: false,
+            force: false,
+            ignore_lock: false,
+            plan_out: None,
+            only: &[],
+            skip: &[],
+            offline: false,
         }
     }
-    if !replacements_summary.is_empty() {
-        println!("{}", "String replacements:".yellow().bold());
-        for rep in replacements_summary {
-            println!("  {}", rep.yellow());
-        }
+
+    #[test]
+    fn test_run_migration_continues_past_unparsable_pom() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_str().unwrap();
+        fs::write(dir.path().join("pom.xml"), "<project><properties>").unwrap();
+        fs::write(
+            dir.path().join("mule-artifact.json"),
+            r#"{"minMuleVersion":"4.4.0","javaSpecificationVersions":["1.8"]}"#,
+        )
+        .unwrap();
+        let report_file = dir.path().join("report.json");
+        let opts = MigrationOptions {
+            report_file: report_file.to_str(),
+            ..test_options(root)
+        };
+        let report = run_migration(&opts).unwrap();
+        assert_eq!(report.errors.len(), 1);
+        assert!(report.errors[0].starts_with("Failed to parse"));
+        let artifact = fs::read_to_string(dir.path().join("mule-artifact.json")).unwrap();
+        assert!(artifact.contains("4.9.0"));
     }
-    if !errors.is_empty() {
-        println!("{}", "Warnings/Errors:".red().bold());
-        for err in errors {
-            println!("  {}", err.red());
+
+    #[test]
+    fn test_offline_run_fails_before_changing_anything() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_str().unwrap();
+        let artifact = r#"{"minMuleVersion":"4.4.0","javaSpecificationVersions":["1.8"]}"#;
+        fs::write(dir.path().join("pom.xml"), "<project/>").unwrap();
+        fs::write(dir.path().join("mule-artifact.json"), artifact).unwrap();
+        let opts = MigrationOptions {
+            latest_connectors: true,
+            build_mule_project: true,
+            git_commit: true,
+            offline: true,
+            ..test_options(root)
+        };
+        match run_migration(&opts) {
+            Err(MigrateError::Offline(needs)) => assert_eq!(needs.len(), 3),
+            other => panic!("expected an offline error, got {other:?}"),
         }
+        let unchanged = fs::read_to_string(dir.path().join("mule-artifact.json")).unwrap();
+        assert_eq!(unchanged, artifact);
     }
-    if changed_files.is_empty()
-        && changed_properties.is_empty()
-        && changed_json.is_empty()
-        && replacements_summary.is_empty()
-        && errors.is_empty()
-    {
-        println!(
-            "{}",
-            "No changes were needed. Project is up to date!"
-                .blue()
-                .bold()
-        );
+
+    #[test]
+    fn test_run_migration_runs_only_selected_steps() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_str().unwrap();
+        fs::write(dir.path().join("pom.xml"), "<project><properties>").unwrap();
+        fs::write(
+            dir.path().join("mule-artifact.json"),
+            r#"{"minMuleVersion":"4.4.0","javaSpecificationVersions":["1.8"]}"#,
+        )
+        .unwrap();
+        let report_file = dir.path().join("report.json");
+        let opts = MigrationOptions {
+            report_file: report_file.to_str(),
+            only: &[StepKind::Pom, StepKind::Artifact],
+            skip: &[StepKind::Pom],
+            ..test_options(root)
+        };
+        let report = run_migration(&opts).unwrap();
+        // The unparsable pom would be an error if its step ran
+        assert!(report.errors.is_empty());
+        assert_eq!(report.skipped_steps.len(), StepKind::ALL.len() - 1);
+        assert!(report.skipped_steps.contains(&"pom".to_string()));
+        let artifact = fs::read_to_string(dir.path().join("mule-artifact.json")).unwrap();
+        assert!(artifact.contains("4.9.0"));
+        assert!(!dir.path().join(lockfile::LOCKFILE_NAME).exists());
+    }
+
+    #[test]
+    fn test_run_migration_with_observer_reports_events_in_order() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_str().unwrap();
+        fs::write(dir.path().join("pom.xml"), "<project><properties>").unwrap();
+        fs::write(
+            dir.path().join("mule-artifact.json"),
+            r#"{"minMuleVersion":"4.4.0","javaSpecificationVersions":["1.8"]}"#,
+        )
+        .unwrap();
+        let report_file = dir.path().join("report.json");
+        let opts = MigrationOptions {
+            dry_run: true,
+            report_file: report_file.to_str(),
+            ..test_options(root)
+        };
+        let mut events = Vec::new();
+        run_migration_with_observer(&opts, |event: &MigrationEvent| {
+            events.push(match event {
+                MigrationEvent::StepStarted { step } => format!("step {step}"),
+                MigrationEvent::Error { .. } => "error".to_string(),
+                MigrationEvent::PropertyUpdated { description } => description.to_string(),
+                MigrationEvent::FileChanged { path } => {
+                    format!("changed {}", path.file_name().unwrap().to_string_lossy())
+                }
+                MigrationEvent::FileScanned { .. } => "scanned".to_string(),
+            })
+        })
+        .unwrap();
+        let position = |e: &str| events.iter().position(|x| x.starts_with(e)).unwrap();
+        assert!(position("step pom.xml") < position("error"));
+        assert!(position("error") < position("step mule-artifact.json"));
+        assert!(position("step mule-artifact.json") < position("minMuleVersion"));
+        assert!(position("minMuleVersion") < position("step commit"));
+        assert!(position("step commit") < position("changed mule-artifact.json"));
     }
-    println!(
-        "{}",
-        "=================================================="
-            .bold()
-            .blue()
-    );
 }
@@ -0,0 +1,137 @@
+use crate::config::MigrationConfig;
+use crate::error::MigrateError;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the lockfile written to the project root after a successful run.
+pub const LOCKFILE_NAME: &str = ".mule-migrate.lock";
+
+/// Content hashes of the files a successful run left behind, keyed by the
+/// config they were migrated with.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Lockfile {
+    /// Hash of the tool version, the config and the include/exclude globs.
+    pub config_hash: String,
+    /// Content hash of every scanned file, by path relative to the project root.
+    pub files: BTreeMap<String, String>,
+}
+
+/// 64-bit FNV-1a hash of `bytes` as hex. Stable across platforms and Rust
+/// versions, unlike `DefaultHasher`.
+pub fn content_hash(bytes: &[u8]) -> String {
+    let hash = bytes.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{hash:016x}")
+}
+
+/// Hashes everything that decides what a run does to a file.
+pub fn config_hash(config: &MigrationConfig, include: &[String], exclude: &[String]) -> String {
+    let key = format!(
+        "{} {config:?} {include:?} {exclude:?}",
+        env!("CARGO_PKG_VERSION")
+    );
+    content_hash(key.as_bytes())
+}
+
+/// Lockfile key of `relative`: `/`-separated on every platform.
+fn lock_key(relative: &Path) -> String {
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+impl Lockfile {
+    /// Reads the project's lockfile; a missing or unreadable lockfile is
+    /// treated as absent.
+    pub fn load(project_root: &Path) -> Option<Lockfile> {
+        let path = project_root.join(LOCKFILE_NAME);
+        let content = fs::read_to_string(&path).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(lock) => Some(lock),
+            Err(e) => {
+                log::warn!("Ignoring invalid {}: {e}", path.display());
+                None
+            }
+        }
+    }
+
+    /// Paths (relative to `project_root`) still identical to what the locked
+    /// run left behind. Empty if the lock was written with another config.
+    pub fn unchanged_files(&self, project_root: &Path, config_hash: &str) -> HashSet<PathBuf> {
+        if self.config_hash != config_hash {
+            return HashSet::new();
+        }
+        self.files
+            .iter()
+            .filter(|(key, hash)| {
+                fs::read(project_root.join(key)).is_ok_and(|bytes| content_hash(&bytes) == **hash)
+            })
+            .map(|(key, _)| PathBuf::from(key))
+            .collect()
+    }
+
+    /// Hashes the current content of `files`.
+    pub fn record(
+        project_root: &Path,
+        config_hash: &str,
+        files: impl IntoIterator<Item = PathBuf>,
+    ) -> Lockfile {
+        let files = files
+            .into_iter()
+            .filter_map(|path| {
+                let bytes = fs::read(&path).ok()?;
+                let relative = path.strip_prefix(project_root).unwrap_or(&path);
+                Some((lock_key(relative), content_hash(&bytes)))
+            })
+            .collect();
+        Lockfile {
+            config_hash: config_hash.to_string(),
+            files,
+        }
+    }
+
+    /// Writes the lockfile to the project root.
+    pub fn save(&self, project_root: &Path) -> Result<(), MigrateError> {
+        let path = project_root.join(LOCKFILE_NAME);
+        let content = serde_json::to_string_pretty(self).expect("Failed to serialize lockfile");
+        fs::write(&path, content).map_err(|source| MigrateError::Write { path, source })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_unchanged_files_detects_edits_and_config_changes() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src/main/mule")).unwrap();
+        fs::write(root.join("src/main/mule/a.xml"), "<a/>").unwrap();
+        fs::write(root.join("src/main/mule/b.xml"), "<b/>").unwrap();
+        Lockfile::record(
+            root,
+            "cfg",
+            [
+                root.join("src/main/mule/a.xml"),
+                root.join("src/main/mule/b.xml"),
+            ],
+        )
+        .save(root)
+        .unwrap();
+
+        fs::write(root.join("src/main/mule/b.xml"), "<b edited=\"true\"/>").unwrap();
+        let lock = Lockfile::load(root).unwrap();
+        assert_eq!(
+            lock.unchanged_files(root, "cfg"),
+            HashSet::from([PathBuf::from("src/main/mule/a.xml")])
+        );
+        assert!(lock.unchanged_files(root, "other").is_empty());
+    }
+}
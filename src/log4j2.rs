@@ -0,0 +1,232 @@
+use crate::xml::{apply_edits, escape_attr, indent_unit, insert_child, TextEdit};
+use roxmltree::{Document, Node};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// Location of the Log4j 2 configuration in a Mule project.
+pub const LOG4J2_PATH: &str = "src/main/resources/log4j2.xml";
+
+/// An edit of log4j2.xml. Elements are addressed by a `/`-separated path of
+/// element names from the root, where each step may filter on an attribute:
+/// `Configuration/Loggers/AsyncLogger[@name='org.mule.runtime']`.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(tag = "action", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Log4j2Edit {
+    /// Inserts an XML snippet as the last child of every matching element,
+    /// unless the element already has a child with the snippet's name.
+    Insert { parent: String, xml: String },
+    /// Sets (or adds) an attribute on every matching element.
+    SetAttribute {
+        element: String,
+        attribute: String,
+        value: String,
+    },
+}
+
+/// One step of an element path: a name and an optional `[@attr='value']` filter.
+struct Step<'a> {
+    name: &'a str,
+    attribute: Option<(&'a str, &'a str)>,
+}
+
+fn parse_path(path: &str) -> Vec<Step<'_>> {
+    path.trim_matches('/')
+        .split('/')
+        .map(|step| match step.split_once("[@") {
+            Some((name, filter)) => {
+                let filter = filter.trim_end_matches(']');
+                let (attr, value) = filter.split_once('=').unwrap_or((filter, ""));
+                Step {
+                    name,
+                    attribute: Some((attr, value.trim_matches(['\'', '"']))),
+                }
+            }
+            None => Step {
+                name: step,
+                attribute: None,
+            },
+        })
+        .collect()
+}
+
+fn step_matches(node: Node, step: &Step) -> bool {
+    node.is_element()
+        && node.tag_name().name() == step.name
+        && step
+            .attribute
+            .is_none_or(|(attr, value)| node.attribute(attr) == Some(value))
+}
+
+/// Finds the elements at `path`, starting with the root element.
+fn find_elements<'a, 'input>(doc: &'a Document<'input>, path: &str) -> Vec<Node<'a, 'input>> {
+    let steps = parse_path(path);
+    let Some((first, rest)) = steps.split_first() else {
+        return Vec::new();
+    };
+    let mut nodes: Vec<Node> = Some(doc.root_element())
+        .filter(|n| step_matches(*n, first))
+        .into_iter()
+        .collect();
+    for step in rest {
+        nodes = nodes
+            .iter()
+            .flat_map(|n| n.children().filter(|c| step_matches(*c, step)))
+            .collect();
+    }
+    nodes
+}
+
+/// Byte offset of the `>` closing the start tag of `node`.
+fn start_tag_end(content: &str, node: Node) -> usize {
+    let start = node.range().start;
+    let mut quote = None;
+    for (i, c) in content[start..].char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '>') => return start + i,
+            _ => {}
+        }
+    }
+    node.range().end
+}
+
+fn set_attribute(content: &str, element: Node, attribute: &str, value: &str) -> Option<TextEdit> {
+    let escaped = escape_attr(value);
+    if let Some(attr) = element.attributes().find(|a| a.name() == attribute) {
+        let range = attr.range_value();
+        return (content[range.clone()] != escaped).then_some(TextEdit {
+            range,
+            text: escaped,
+        });
+    }
+    let end = start_tag_end(content, element);
+    let end = if content[..end].ends_with('/') {
+        end - 1
+    } else {
+        end
+    };
+    let insert_at = content[..end].trim_end().len();
+    Some(TextEdit {
+        range: insert_at..insert_at,
+        text: format!(" {attribute}=\"{escaped}\""),
+    })
+}
+
+/// Applies the edits to log4j2.xml content, preserving the rest of the file.
+///
+/// Returns the new content and a description of each change.
+///
+/// # Errors
+/// Returns an error if the content or an inserted snippet is not well-formed.
+pub fn plan_log4j2_update(
+    content: &str,
+    edits: &[Log4j2Edit],
+) -> Result<(String, Vec<String>), roxmltree::Error> {
+    let doc = Document::parse(content)?;
+    let unit = indent_unit(content, &doc);
+    let mut text_edits = Vec::new();
+    let mut changes = Vec::new();
+    for edit in edits {
+        match edit {
+            Log4j2Edit::Insert { parent, xml } => {
+                let snippet = Document::parse(xml)?;
+                let name = snippet.root_element().tag_name().name();
+                for node in find_elements(&doc, parent) {
+                    if node
+                        .children()
+                        .any(|c| c.is_element() && c.tag_name().name() == name)
+                    {
+                        continue;
+                    }
+                    text_edits.push(insert_child(content, unit, node, xml));
+                    changes.push(format!("inserted <{name}> into {parent}"));
+                }
+            }
+            Log4j2Edit::SetAttribute {
+                element,
+                attribute,
+                value,
+            } => {
+                for node in find_elements(&doc, element) {
+                    if let Some(text_edit) = set_attribute(content, node, attribute, value) {
+                        let old = node.attribute(attribute.as_str()).unwrap_or("<missing>");
+                        changes.push(format!("{element} {attribute}: '{old}' -> '{value}'"));
+                        text_edits.push(text_edit);
+                    }
+                }
+            }
+        }
+    }
+    Ok((apply_edits(content, text_edits), changes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG4J2: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<Configuration>
+    <Appenders>
+        <RollingFile name="file" fileName="app.log">
+            <PatternLayout pattern="%-5p %d [%t] %c: %m%n"/>
+        </RollingFile>
+    </Appenders>
+    <Loggers>
+        <AsyncRoot level="INFO"/>
+    </Loggers>
+</Configuration>
+"#;
+
+    #[test]
+    fn test_insert_is_indented_and_idempotent() {
+        let edits = [
+            Log4j2Edit::Insert {
+                parent: "Configuration/Appenders/RollingFile[@name='file']".to_string(),
+                xml: "<JsonLayout compact=\"true\"/>".to_string(),
+            },
+            Log4j2Edit::Insert {
+                parent: "Configuration/Loggers/AsyncRoot".to_string(),
+                xml: "<AppenderRef ref=\"file\"/>".to_string(),
+            },
+        ];
+        let (updated, changes) = plan_log4j2_update(LOG4J2, &edits).unwrap();
+        assert_eq!(changes.len(), 2);
+        assert!(updated.contains(
+            "            <PatternLayout pattern=\"%-5p %d [%t] %c: %m%n\"/>\n            <JsonLayout compact=\"true\"/>\n        </RollingFile>"
+        ));
+        assert!(updated.contains(
+            "        <AsyncRoot level=\"INFO\">\n            <AppenderRef ref=\"file\"/>\n        </AsyncRoot>"
+        ));
+        let (again, changes) = plan_log4j2_update(&updated, &edits).unwrap();
+        assert!(changes.is_empty());
+        assert_eq!(again, updated);
+    }
+
+    #[test]
+    fn test_set_attribute_updates_or_adds() {
+        let edits = [
+            Log4j2Edit::SetAttribute {
+                element: "Configuration/Loggers/AsyncRoot".to_string(),
+                attribute: "level".to_string(),
+                value: "WARN".to_string(),
+            },
+            Log4j2Edit::SetAttribute {
+                element: "Configuration/Appenders/RollingFile".to_string(),
+                attribute: "immediateFlush".to_string(),
+                value: "false".to_string(),
+            },
+        ];
+        let (updated, changes) = plan_log4j2_update(LOG4J2, &edits).unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                "Configuration/Loggers/AsyncRoot level: 'INFO' -> 'WARN'",
+                "Configuration/Appenders/RollingFile immediateFlush: '<missing>' -> 'false'"
+            ]
+        );
+        assert!(updated.contains("<AsyncRoot level=\"WARN\"/>"));
+        assert!(updated
+            .contains("<RollingFile name=\"file\" fileName=\"app.log\" immediateFlush=\"false\">"));
+    }
+}
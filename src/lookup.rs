@@ -0,0 +1,562 @@
+use crate::config::MigrationConfig;
+use crate::http::{self, Auth};
+use crate::version::{self, Version, VersionRange};
+use crate::xml;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Maven repositories queried for connector releases, in order.
+pub const DEFAULT_REPOSITORIES: &[&str] = &[
+    "https://repository.mulesoft.org/nexus/content/repositories/releases",
+    "https://repo1.maven.org/maven2",
+];
+
+const DEFAULT_CACHE_TTL_HOURS: u64 = 24;
+
+/// `version_lookup` section of the migration config.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct VersionLookupConfig {
+    /// Maven repository base URLs to query, in order.
+    #[serde(default = "default_repositories")]
+    pub repositories: Vec<String>,
+    /// How long cached lookups are considered fresh.
+    #[serde(default = "default_cache_ttl_hours")]
+    pub cache_ttl_hours: u64,
+    /// HTTP proxy for the lookups, e.g. `http://proxy.example.com:3128`;
+    /// without one, `HTTPS_PROXY`, `HTTP_PROXY` or `ALL_PROXY` is used (and
+    /// `NO_PROXY` honored).
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// Credentials of the repositories that need them, e.g. a Nexus or
+    /// Artifactory mirror.
+    #[serde(default)]
+    pub credentials: Vec<RepositoryCredentials>,
+    /// Allow looked-up releases with a higher major version than the one
+    /// declared; if false, dependencies only move within their major version.
+    #[serde(default = "default_true")]
+    pub allow_major_updates: bool,
+    /// Maven version ranges looked-up releases must lie in, keyed by
+    /// `groupId:artifactId`, e.g. `org.mule.connectors:mule-http-connector:
+    /// "[1.7,2.0)"`; takes precedence over `allow_major_updates`.
+    #[serde(default)]
+    pub ranges: BTreeMap<String, String>,
+}
+
+impl Default for VersionLookupConfig {
+    fn default() -> Self {
+        VersionLookupConfig {
+            repositories: default_repositories(),
+            cache_ttl_hours: default_cache_ttl_hours(),
+            proxy: None,
+            credentials: Vec::new(),
+            allow_major_updates: true,
+            ranges: BTreeMap::new(),
+        }
+    }
+}
+
+impl VersionLookupConfig {
+    /// Credentials for `repository`: those of the longest matching
+    /// `repository` prefix.
+    pub fn auth_for(&self, repository: &str) -> Option<Auth> {
+        let repository = repository.trim_end_matches('/');
+        self.credentials
+            .iter()
+            .filter(|c| {
+                let base = c.repository.trim_end_matches('/');
+                repository == base || repository.starts_with(&format!("{base}/"))
+            })
+            .max_by_key(|c| c.repository.trim_end_matches('/').len())
+            .and_then(RepositoryCredentials::auth)
+    }
+
+    /// Range a release of `group_id:artifact_id` must lie in to replace
+    /// `current`: its configured range, else the major version of `current`
+    /// unless major updates are allowed.
+    pub fn range_for(
+        &self,
+        group_id: &str,
+        artifact_id: &str,
+        current: Option<&str>,
+    ) -> Option<VersionRange> {
+        if let Some(range) = self.ranges.get(&format!("{group_id}:{artifact_id}")) {
+            return VersionRange::parse(range);
+        }
+        if self.allow_major_updates {
+            return None;
+        }
+        Some(VersionRange::same_major(&Version::parse(current?)?))
+    }
+}
+
+/// Basic or bearer credentials of a Maven repository. Use `${env:NAME}`
+/// placeholders to keep secrets out of the config.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RepositoryCredentials {
+    /// Base URL of the repository; also covers the URLs below it.
+    pub repository: String,
+    /// User name for basic authentication.
+    #[serde(default)]
+    pub username: Option<String>,
+    /// Password for basic authentication.
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Bearer token, e.g. an Artifactory access token; takes precedence
+    /// over `username` and `password`.
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+impl RepositoryCredentials {
+    fn auth(&self) -> Option<Auth> {
+        if let Some(token) = &self.token {
+            return Some(Auth::Bearer(token.clone()));
+        }
+        Some(Auth::Basic {
+            username: self.username.clone()?,
+            password: self.password.clone().unwrap_or_default(),
+        })
+    }
+}
+
+fn default_repositories() -> Vec<String> {
+    DEFAULT_REPOSITORIES.iter().map(|r| r.to_string()).collect()
+}
+
+fn default_cache_ttl_hours() -> u64 {
+    DEFAULT_CACHE_TTL_HOURS
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Extracts the highest release (non-qualified) version from a
+/// `maven-metadata.xml` document.
+pub fn latest_release_from_metadata(xml: &str) -> Option<String> {
+    latest_release_in_range(xml, None)
+}
+
+/// Like [`latest_release_from_metadata`], only considering versions in
+/// `range` if given.
+pub fn latest_release_in_range(xml: &str, range: Option<&VersionRange>) -> Option<String> {
+    let doc = roxmltree::Document::parse(xml).ok()?;
+    doc.descendants()
+        .filter(|n| {
+            n.has_tag_name("version") && n.parent().is_some_and(|p| p.has_tag_name("versions"))
+        })
+        .filter_map(|n| n.text())
+        .filter_map(|v| Version::parse(v).map(|parsed| (parsed, v.trim().to_string())))
+        .filter(|(parsed, _)| !parsed.to_string().contains('-'))
+        .filter(|(parsed, _)| range.is_none_or(|r| r.contains(parsed)))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, v)| v)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    version: String,
+    /// Unix timestamp of the lookup.
+    fetched_at: i64,
+}
+
+/// On-disk cache of looked-up versions, keyed by `groupId:artifactId`.
+#[derive(Debug, Default)]
+pub struct VersionCache {
+    path: Option<PathBuf>,
+    entries: BTreeMap<String, CacheEntry>,
+}
+
+/// Returns the per-user cache directory of the tool.
+pub fn cache_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("LOCALAPPDATA").map(PathBuf::from))
+        .or_else(|| std::env::var_os("HOME").map(|h| Path::new(&h).join(".cache")))?;
+    Some(base.join("mule-lazy-migrate"))
+}
+
+impl VersionCache {
+    /// Loads the cache from `path`; a missing or unreadable file yields an empty cache.
+    pub fn load(path: PathBuf) -> Self {
+        let entries = fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        VersionCache {
+            path: Some(path),
+            entries,
+        }
+    }
+
+    /// Loads the cache from the default location in the user's cache directory.
+    pub fn load_default() -> Self {
+        match cache_dir() {
+            Some(dir) => Self::load(dir.join("versions.json")),
+            None => Self::default(),
+        }
+    }
+
+    /// Returns the cached version and whether it is still fresh.
+    fn get(&self, key: &str, ttl_hours: u64) -> Option<(&str, bool)> {
+        let entry = self.entries.get(key)?;
+        let age = chrono::Utc::now().timestamp() - entry.fetched_at;
+        Some((&entry.version, age < (ttl_hours * 3600) as i64))
+    }
+
+    fn insert(&mut self, key: String, version: String) {
+        let fetched_at = chrono::Utc::now().timestamp();
+        self.entries.insert(
+            key,
+            CacheEntry {
+                version,
+                fetched_at,
+            },
+        );
+    }
+
+    /// Writes the cache back to disk.
+    pub fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).ok();
+        }
+        let data = serde_json::to_string_pretty(&self.entries).expect("Failed to serialize cache");
+        if let Err(e) = fs::write(path, data) {
+            log::warn!("Failed to write version cache {}: {e}", path.display());
+        }
+    }
+}
+
+/// Looks up the latest release of `group_id:artifact_id`.
+///
+/// Fresh cache entries are used as-is. Otherwise each repository is queried
+/// in order; if none answers, a stale cache entry is used as a fallback.
+pub fn lookup_latest(
+    group_id: &str,
+    artifact_id: &str,
+    config: &VersionLookupConfig,
+    cache: &mut VersionCache,
+) -> Option<String> {
+    lookup_latest_authorized(group_id, artifact_id, config, cache, None, None)
+}
+
+/// Like [`lookup_latest`], authenticating to the repositories with a bearer
+/// `token` if given, instead of with their configured credentials, and only
+/// considering releases in `range` if given.
+pub fn lookup_latest_authorized(
+    group_id: &str,
+    artifact_id: &str,
+    config: &VersionLookupConfig,
+    cache: &mut VersionCache,
+    token: Option<&str>,
+    range: Option<&VersionRange>,
+) -> Option<String> {
+    let key = match range {
+        Some(range) => format!("{group_id}:{artifact_id}@{range}"),
+        None => format!("{group_id}:{artifact_id}"),
+    };
+    let stale = match cache.get(&key, config.cache_ttl_hours) {
+        Some((version, true)) => {
+            log::debug!("Using cached version {version} for {key}");
+            return Some(version.to_string());
+        }
+        Some((version, false)) => Some(version.to_string()),
+        None => None,
+    };
+    for repo in &config.repositories {
+        let url = format!(
+            "{}/{}/{}/maven-metadata.xml",
+            repo.trim_end_matches('/'),
+            group_id.replace('.', "/"),
+            artifact_id
+        );
+        let auth = match token {
+            Some(token) => Some(Auth::Bearer(token.to_string())),
+            None => config.auth_for(repo),
+        };
+        match http::get_string_with(&url, auth.as_ref(), config.proxy.as_deref(), None) {
+            Ok(xml) => {
+                if let Some(version) = latest_release_in_range(&xml, range) {
+                    log::info!("Latest release of {key}: {version}");
+                    cache.insert(key, version.clone());
+                    return Some(version);
+                }
+            }
+            Err(e) => log::debug!("Lookup of {key} in {repo} failed: {e}"),
+        }
+    }
+    if let Some(version) = &stale {
+        log::warn!("Could not reach any repository for {key}, using cached version {version}");
+    } else {
+        log::warn!("Could not determine the latest release of {key}");
+    }
+    stale
+}
+
+/// Raises every Mule connector declared in pom.xml content to its latest
+/// release, within the range [`VersionLookupConfig::range_for`] gives.
+/// Connectors pinned in the config's `dependencies` are skipped.
+///
+/// Returns the new content and a description of each upgraded connector.
+pub fn upgrade_to_latest_connectors(
+    content: &str,
+    config: &MigrationConfig,
+) -> Result<(String, Vec<String>), roxmltree::Error> {
+    let mut cache = VersionCache::load_default();
+    let mut latest = Vec::new();
+    for artifact in xml::declared_artifacts(content)? {
+        if !artifact.is_mule_plugin()
+            || config
+                .dependencies
+                .iter()
+                .any(|d| d.group_id == artifact.group_id && d.artifact_id == artifact.artifact_id)
+        {
+            continue;
+        }
+        let range = config.version_lookup.range_for(
+            &artifact.group_id,
+            &artifact.artifact_id,
+            artifact.version.as_deref(),
+        );
+        if let Some(version) = lookup_latest_authorized(
+            &artifact.group_id,
+            &artifact.artifact_id,
+            &config.version_lookup,
+            &mut cache,
+            None,
+            range.as_ref(),
+        ) {
+            latest.push((artifact.group_id, artifact.artifact_id, version));
+        }
+    }
+    cache.save();
+    xml::upgrade_pom_connector_versions(content, &latest)
+}
+
+/// Artifacts whose latest releases `--latest` migrates to, by config field.
+pub const LATEST_TARGETS: &[(&str, &str, &str)] = &[
+    ("app_runtime_version", "org.mule.runtime", "mule-core"),
+    (
+        "mule_maven_plugin_version",
+        "org.mule.tools.maven",
+        "mule-maven-plugin",
+    ),
+    (
+        "munit_version",
+        "com.mulesoft.munit.tools",
+        "munit-maven-plugin",
+    ),
+];
+
+fn target_field<'a>(config: &'a mut MigrationConfig, field: &str) -> &'a mut String {
+    match field {
+        "app_runtime_version" => &mut config.app_runtime_version,
+        "mule_maven_plugin_version" => &mut config.mule_maven_plugin_version,
+        _ => &mut config.munit_version,
+    }
+}
+
+/// Raises the runtime (unless `keep_runtime`), mule-maven-plugin and MUnit
+/// versions of `config` to their latest releases, within the range
+/// [`VersionLookupConfig::range_for`] gives. A version that cannot be looked
+/// up keeps its configured value.
+///
+/// Returns a description of each raised version.
+pub fn apply_latest_versions(
+    config: &mut MigrationConfig,
+    cache: &mut VersionCache,
+    keep_runtime: bool,
+) -> Vec<String> {
+    let mut changes = Vec::new();
+    for (field, group_id, artifact_id) in LATEST_TARGETS {
+        if keep_runtime && *field == "app_runtime_version" {
+            continue;
+        }
+        let current = target_field(config, field).clone();
+        let range = config
+            .version_lookup
+            .range_for(group_id, artifact_id, Some(&current));
+        let latest = lookup_latest_authorized(
+            group_id,
+            artifact_id,
+            &config.version_lookup,
+            cache,
+            None,
+            range.as_ref(),
+        );
+        match latest {
+            Some(latest)
+                if version::is_semver(&latest)
+                    && version::compare(&current, &latest) == Some(Ordering::Less) =>
+            {
+                changes.push(format!("{field}: '{current}' -> '{latest}'"));
+                *target_field(config, field) = latest;
+            }
+            Some(_) => {}
+            None => log::info!("Keeping {field} {current} from the config"),
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_latest_release_from_metadata() {
+        let xml = r#"<metadata>
+  <groupId>org.mule.connectors</groupId>
+  <artifactId>mule-http-connector</artifactId>
+  <versioning>
+    <latest>1.11.0-rc1</latest>
+    <versions>
+      <version>1.9.0</version>
+      <version>1.10.3</version>
+      <version>1.11.0-rc1</version>
+      <version>1.2.0</version>
+    </versions>
+  </versioning>
+</metadata>"#;
+        assert_eq!(
+            latest_release_from_metadata(xml),
+            Some("1.10.3".to_string())
+        );
+        let range = VersionRange::parse("[1.0,1.10)").unwrap();
+        assert_eq!(
+            latest_release_in_range(xml, Some(&range)),
+            Some("1.9.0".to_string())
+        );
+    }
+
+    #[test]
+    fn test_range_for_prefers_configured_range() {
+        let config: VersionLookupConfig = serde_yaml::from_str(
+            r#"
+allow_major_updates: false
+ranges:
+  org.mule.connectors:mule-http-connector: "[1.7,2.0)"
+"#,
+        )
+        .unwrap();
+        let range = |artifact, current| {
+            config
+                .range_for("org.mule.connectors", artifact, current)
+                .map(|r| r.to_string())
+        };
+        assert_eq!(
+            range("mule-http-connector", Some("1.5.0")).as_deref(),
+            Some("[1.7,2.0)")
+        );
+        assert_eq!(
+            range("mule-db-connector", Some("1.14.2")).as_deref(),
+            Some("[1,2)")
+        );
+        assert_eq!(range("mule-db-connector", Some("${db.version}")), None);
+        assert_eq!(
+            VersionLookupConfig::default().range_for("g", "a", Some("1.0.0")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_lookup_latest_uses_fresh_cache_offline() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("versions.json");
+        let mut cache = VersionCache::load(path.clone());
+        cache.insert(
+            "org.mule.connectors:mule-http-connector".to_string(),
+            "1.10.3".to_string(),
+        );
+        cache.save();
+
+        let mut cache = VersionCache::load(path);
+        let config = VersionLookupConfig {
+            repositories: vec![],
+            cache_ttl_hours: 24,
+            ..Default::default()
+        };
+        let version = lookup_latest(
+            "org.mule.connectors",
+            "mule-http-connector",
+            &config,
+            &mut cache,
+        );
+        assert_eq!(version, Some("1.10.3".to_string()));
+    }
+
+    #[test]
+    fn test_apply_latest_versions_only_raises() {
+        let dir = tempdir().unwrap();
+        let mut cache = VersionCache::load(dir.path().join("versions.json"));
+        cache.insert(
+            "org.mule.runtime:mule-core".to_string(),
+            "4.9.0".to_string(),
+        );
+        cache.insert(
+            "org.mule.tools.maven:mule-maven-plugin".to_string(),
+            "4.3.0".to_string(),
+        );
+        let mut config = MigrationConfig::from_str_with_format(
+            r#"{
+                "app_runtime_version": "4.9.4",
+                "mule_maven_plugin_version": "4.1.1",
+                "munit_version": "3.1.0",
+                "mule_artifact": {
+                    "min_mule_version": "4.9.0",
+                    "java_specification_versions": ["17"]
+                },
+                "replacements": [],
+                "version_lookup": { "repositories": [] }
+            }"#,
+            crate::config::ConfigFormat::Json,
+        )
+        .unwrap();
+        let changes = apply_latest_versions(&mut config, &mut cache, false);
+        assert_eq!(
+            changes,
+            vec!["mule_maven_plugin_version: '4.1.1' -> '4.3.0'"]
+        );
+        assert_eq!(config.app_runtime_version, "4.9.4");
+        assert_eq!(config.mule_maven_plugin_version, "4.3.0");
+        assert_eq!(config.munit_version, "3.1.0");
+    }
+
+    #[test]
+    fn test_auth_for_matches_repository_prefix() {
+        let config: VersionLookupConfig = serde_yaml::from_str(
+            r#"
+credentials:
+  - repository: https://nexus.example.com/repository/
+    username: ci
+    password: secret
+  - repository: https://nexus.example.com/repository/mulesoft
+    token: abc
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.auth_for("https://nexus.example.com/repository/maven-public"),
+            Some(Auth::Basic {
+                username: "ci".to_string(),
+                password: "secret".to_string()
+            })
+        );
+        assert_eq!(
+            config.auth_for("https://nexus.example.com/repository/mulesoft/"),
+            Some(Auth::Bearer("abc".to_string()))
+        );
+        assert_eq!(config.auth_for("https://nexus.example.com/repo"), None);
+        assert_eq!(config.auth_for("https://repo1.maven.org/maven2"), None);
+    }
+}
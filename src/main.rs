@@ -1,54 +1,1108 @@
-use clap::Parser;
-use mule_lazy_migrate::{run_migration, MigrationOptions};
+use clap::{ArgAction, Args, CommandFactory, Parser, Subcommand};
+use colored::*;
+use mule_lazy_migrate::backup::BackupCompression;
+use mule_lazy_migrate::compat;
+use mule_lazy_migrate::json_log::{self, JsonLogObserver, LogFormat};
+use mule_lazy_migrate::plan::{apply_plan, Plan};
+use mule_lazy_migrate::progress::ProgressObserver;
+use mule_lazy_migrate::report::{self, EXIT_CHANGES, EXIT_FAILURE};
+use mule_lazy_migrate::steps::StepKind;
+use mule_lazy_migrate::{
+    backup, batch, config, docs, find_mule_projects, http, init, inspect, load_config, plugins,
+    profiles, run_lock, run_migration, run_migration_with_observer, self_update, support, watch,
+    MigrateError, MigrationOptions, MigrationReport, ReportFormat,
+};
+use std::io::IsTerminal;
+use std::path::Path;
 
 #[derive(Parser)]
 #[command(name = "mule-lazy-migrate")]
 #[command(about = "Migrate Mule 4 projects to a new runtime using a JSON config. The summary at the end is colorized for clarity.", long_about = None)]
 #[command(version = env!("CARGO_PKG_VERSION"))]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Cli {
-    /// Path to the JSON config file
+    #[command(subcommand)]
+    command: Option<Commands>,
+
+    /// Without a subcommand, the arguments of `migrate` are accepted directly
+    #[command(flatten)]
+    migrate: MigrateArgs,
+
+    /// Show verbose logs: -v for debug, -vv for trace
+    #[arg(short, long, global = true, action = ArgAction::Count, conflicts_with = "quiet")]
+    verbose: u8,
+
+    /// Print only the summary (and errors), no per-file logs or progress spinner
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Do not use colors in the summary and logs (also with NO_COLOR set, or
+    /// when the output is not a terminal)
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Never use the network or run external programs (Maven, git, hooks,
+    /// plugins); fail up front if the run would need them
+    #[arg(long, global = true)]
+    offline: bool,
+
+    /// Do not show a progress spinner while scanning the project
+    #[arg(long, global = true)]
+    no_progress: bool,
+
+    /// Format of the logs on stderr; json writes one object per log line and
+    /// per migration event (step, file, change with old and new value)
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+}
+
+/// Where the migration config comes from.
+#[derive(Args)]
+struct ConfigArgs {
+    /// Path or http(s) URL of the migration config file (JSON, YAML or TOML; default: built-in defaults for the latest runtime)
     #[arg(short, long)]
-    config: String,
+    config: Option<String>,
+
+    /// Expected SHA-256 of the config file; the run fails if it does not match
+    #[arg(long, value_name = "HEX", requires = "config")]
+    config_sha256: Option<String>,
+
+    /// Value of a ${var.KEY} placeholder in the config (repeatable)
+    #[arg(long = "set", value_name = "KEY=VALUE", value_parser = parse_var)]
+    vars: Vec<(String, String)>,
+
+    /// Use a built-in migration profile instead of a config file
+    #[arg(long, conflicts_with = "config")]
+    profile: Option<String>,
+
+    /// List the built-in migration profiles and exit
+    #[arg(long)]
+    list_profiles: bool,
+}
+
+#[derive(Args)]
+struct MigrateArgs {
+    #[command(flatten)]
+    source: ConfigArgs,
 
     /// Perform a dry run without making changes
     #[arg(long)]
     dry_run: bool,
 
+    /// Dry run that exits with status 2 if the project still needs to be migrated
+    #[arg(long, conflicts_with_all = ["interactive", "git_branch", "git_commit"])]
+    check: bool,
+
+    /// Dry-run the migration again, printing the summary and diffs, whenever
+    /// the config file changes (stop with Ctrl-C)
+    #[arg(
+        long,
+        requires = "config",
+        conflicts_with_all = ["check", "recursive", "interactive", "git_branch", "git_commit"]
+    )]
+    watch: bool,
+
     /// Backup files before modifying (default: false)
     #[arg(long, default_value_t = false)]
     backup: bool,
 
+    /// Directory for the --backup snapshots, relative to the project root (default: .mule-migrate/backups)
+    #[arg(long, value_name = "DIR")]
+    backup_dir: Option<String>,
+
+    /// Store each --backup snapshot as a plain directory, a zip or a tar.gz archive
+    #[arg(long, value_enum, default_value_t = BackupCompression::None)]
+    backup_compression: BackupCompression,
+
+    /// Keep only the N most recent --backup snapshots
+    #[arg(long, value_name = "N")]
+    keep_backups: Option<usize>,
+
     /// Path to the Mule project root (default: current directory)
     #[arg(short, long, default_value = ".")]
     project: String,
 
+    /// Treat --project as a workspace and migrate every Mule project found under it
+    #[arg(short, long)]
+    recursive: bool,
+
     /// Also update all Maven dependencies to their latest release versions
     #[arg(short = 'u', long)]
     update_maven_deps: bool,
 
+    /// Raise Mule connectors in pom.xml to their latest releases (queries Maven repositories)
+    #[arg(long)]
+    latest_connectors: bool,
+
+    /// Migrate to the newest supported LTS runtime instead of app_runtime_version
+    #[arg(long)]
+    latest_lts: bool,
+
+    /// Migrate to the latest runtime, mule-maven-plugin and MUnit releases (queries Maven repositories)
+    #[arg(long)]
+    latest: bool,
+
     /// Build the Mule project with 'mvn clean install' after migration
     #[arg(short = 'b', long)]
     build_mule_project: bool,
 
-    /// Show verbose (debug) logs
+    /// Run the project's tests (MUnit) after migration and summarize the results
+    #[arg(long)]
+    run_tests: bool,
+
+    /// Redeploy the app to the environment in the config's 'deploy' section after a successful run
+    #[arg(long)]
+    deploy: bool,
+
+    /// Maven executable for -u/-b (default: the project's mvnw, else mvn on the PATH)
+    #[arg(long, value_name = "PATH")]
+    maven_bin: Option<String>,
+
+    /// Last lines of Maven output to keep in the report
+    #[arg(long, value_name = "N", default_value_t = 20)]
+    maven_output_lines: usize,
+
+    /// Maven goals for -b, replacing the config's maven.goals (repeatable)
+    #[arg(long = "maven-goal", value_name = "GOAL")]
+    maven_goals: Vec<String>,
+
+    /// Extra Maven argument, e.g. -DskipTests or -Pci (repeatable)
+    #[arg(long = "maven-arg", value_name = "ARG", allow_hyphen_values = true)]
+    maven_args: Vec<String>,
+
+    /// Kill a Maven run after this many seconds
+    #[arg(long, value_name = "SECS")]
+    maven_timeout: Option<u64>,
+
+    /// Only consider source files matching this glob (repeatable)
+    #[arg(long = "include", value_name = "GLOB")]
+    include: Vec<String>,
+
+    /// Never touch source files matching this glob (repeatable)
+    #[arg(long = "exclude", value_name = "GLOB")]
+    exclude: Vec<String>,
+
+    /// Run only these steps (comma-separated, e.g. pom,artifact)
+    #[arg(long, value_enum, value_name = "STEPS", value_delimiter = ',')]
+    only: Vec<StepKind>,
+
+    /// Skip these steps (comma-separated, e.g. replacements,java-compat)
+    #[arg(long, value_enum, value_name = "STEPS", value_delimiter = ',')]
+    skip: Vec<StepKind>,
+
+    /// Review each change and approve it (y/n/a/q) before it is written
     #[arg(short, long)]
-    verbose: bool,
+    interactive: bool,
+
+    /// Format of the migration report
+    #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+    report_format: ReportFormat,
+
+    /// Write the migration report to this file
+    #[arg(long)]
+    report_file: Option<String>,
+
+    /// Create and switch to this git branch before migrating
+    #[arg(long, value_name = "NAME")]
+    git_branch: Option<String>,
+
+    /// Commit the migrated files with a generated message listing the changes
+    #[arg(long)]
+    git_commit: bool,
+
+    /// Run git operations even if the working tree has uncommitted changes
+    #[arg(long)]
+    allow_dirty: bool,
+
+    /// Fail without writing anything if any source file cannot be processed
+    #[arg(long)]
+    strict: bool,
+
+    /// Process every file, even those .mule-migrate.lock records as already migrated
+    #[arg(long)]
+    ignore_lock: bool,
+
+    /// Migrate even if the config's target versions are incompatible with each other
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Args)]
+struct CheckArgs {
+    #[command(flatten)]
+    source: ConfigArgs,
+
+    /// Path to the Mule project root (default: current directory)
+    #[arg(short, long, default_value = ".")]
+    project: String,
+
+    /// Also check Mule connectors against their latest releases (queries Maven repositories)
+    #[arg(long)]
+    latest_connectors: bool,
+
+    /// Check against the newest supported LTS runtime instead of app_runtime_version
+    #[arg(long)]
+    latest_lts: bool,
+
+    /// Check against the latest runtime, mule-maven-plugin and MUnit releases (queries Maven repositories)
+    #[arg(long)]
+    latest: bool,
+
+    /// Only consider source files matching this glob (repeatable)
+    #[arg(long = "include", value_name = "GLOB")]
+    include: Vec<String>,
+
+    /// Never touch source files matching this glob (repeatable)
+    #[arg(long = "exclude", value_name = "GLOB")]
+    exclude: Vec<String>,
+
+    /// Run only these steps (comma-separated, e.g. pom,artifact)
+    #[arg(long, value_enum, value_name = "STEPS", value_delimiter = ',')]
+    only: Vec<StepKind>,
+
+    /// Skip these steps (comma-separated, e.g. replacements,java-compat)
+    #[arg(long, value_enum, value_name = "STEPS", value_delimiter = ',')]
+    skip: Vec<StepKind>,
+
+    /// Format of the migration report
+    #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+    report_format: ReportFormat,
+
+    /// Write the migration report to this file
+    #[arg(long)]
+    report_file: Option<String>,
+
+    /// Migrate even if the config's target versions are incompatible with each other
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Args)]
+struct PlanArgs {
+    #[command(flatten)]
+    check: CheckArgs,
+
+    /// Write the plan (every intended edit) to this file
+    #[arg(long, value_name = "FILE")]
+    out: String,
+}
+
+#[derive(Args)]
+struct ApplyArgs {
+    /// Plan file written by `plan --out`
+    plan: String,
+
+    /// Path to the Mule project root (default: current directory)
+    #[arg(short, long, default_value = ".")]
+    project: String,
+
+    /// Save the files about to change to a snapshot of the run
+    #[arg(long)]
+    backup: bool,
+
+    /// Directory for the --backup snapshots, relative to the project root (default: .mule-migrate/backups)
+    #[arg(long, value_name = "DIR")]
+    backup_dir: Option<String>,
+}
+
+#[derive(Args)]
+struct MigrateAllArgs {
+    #[command(flatten)]
+    source: ConfigArgs,
+
+    /// JSON manifest listing the project roots: {"projects": ["apps/orders", ...]}
+    #[arg(long, value_name = "FILE")]
+    projects_file: String,
+
+    /// Perform a dry run without making changes
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Backup files before modifying (default: false)
+    #[arg(long, default_value_t = false)]
+    backup: bool,
+
+    /// Directory for the --backup snapshots, relative to the project root (default: .mule-migrate/backups)
+    #[arg(long, value_name = "DIR")]
+    backup_dir: Option<String>,
+
+    /// Store each --backup snapshot as a plain directory, a zip or a tar.gz archive
+    #[arg(long, value_enum, default_value_t = BackupCompression::None)]
+    backup_compression: BackupCompression,
+
+    /// Keep only the N most recent --backup snapshots
+    #[arg(long, value_name = "N")]
+    keep_backups: Option<usize>,
+
+    /// Raise Mule connectors in pom.xml to their latest releases (queries Maven repositories)
+    #[arg(long)]
+    latest_connectors: bool,
+
+    /// Migrate to the newest supported LTS runtime instead of app_runtime_version
+    #[arg(long)]
+    latest_lts: bool,
+
+    /// Migrate to the latest runtime, mule-maven-plugin and MUnit releases (queries Maven repositories)
+    #[arg(long)]
+    latest: bool,
+
+    /// Only consider source files matching this glob (repeatable)
+    #[arg(long = "include", value_name = "GLOB")]
+    include: Vec<String>,
+
+    /// Never touch source files matching this glob (repeatable)
+    #[arg(long = "exclude", value_name = "GLOB")]
+    exclude: Vec<String>,
+
+    /// Run only these steps (comma-separated, e.g. pom,artifact)
+    #[arg(long, value_enum, value_name = "STEPS", value_delimiter = ',')]
+    only: Vec<StepKind>,
+
+    /// Skip these steps (comma-separated, e.g. replacements,java-compat)
+    #[arg(long, value_enum, value_name = "STEPS", value_delimiter = ',')]
+    skip: Vec<StepKind>,
+
+    /// Format of the consolidated report (text or json)
+    #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+    report_format: ReportFormat,
+
+    /// Write the consolidated report to this file
+    #[arg(long)]
+    report_file: Option<String>,
+
+    /// Create and switch to this git branch in each project before migrating
+    #[arg(long, value_name = "NAME")]
+    git_branch: Option<String>,
+
+    /// Commit the migrated files of each project with a generated message
+    #[arg(long)]
+    git_commit: bool,
+
+    /// Run git operations even if a working tree has uncommitted changes
+    #[arg(long)]
+    allow_dirty: bool,
+
+    /// Skip (fail) a project without writing anything if any of its files cannot be processed
+    #[arg(long)]
+    strict: bool,
+
+    /// Process every file, even those .mule-migrate.lock records as already migrated
+    #[arg(long)]
+    ignore_lock: bool,
+
+    /// Migrate even if the config's target versions are incompatible with each other
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Migrate the project (the default when no subcommand is given)
+    Migrate(MigrateArgs),
+    /// Validate a migration config or profile without touching any project
+    Validate(ConfigArgs),
+    /// Report the pending changes; exits with status 2 if the project is not migrated yet
+    Check(CheckArgs),
+    /// Dry-run the migration and record every intended edit in a plan file
+    Plan(PlanArgs),
+    /// Execute exactly the edits of a plan file, failing if a file changed since
+    Apply(ApplyArgs),
+    /// Migrate every project listed in a manifest and print a consolidated report
+    MigrateAll(MigrateAllArgs),
+    /// Generate a starter migration config from the project's current versions
+    InitConfig {
+        /// Path to the Mule project root (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        project: String,
+
+        /// Write the config to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Print the runtime, Java, MUnit and connector versions a project is on, without any config
+    Inspect {
+        /// Path to the Mule project root (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        project: String,
+
+        /// Output format (text or json)
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+    },
+    /// Restore the files saved by a --backup run from .mule-migrate/backups
+    Restore {
+        /// Path to the Mule project root (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        project: String,
+
+        /// Directory of the backup snapshots, relative to the project root (default: .mule-migrate/backups)
+        #[arg(long, value_name = "DIR")]
+        backup_dir: Option<String>,
+
+        /// Id of the run to restore (default: the latest run)
+        #[arg(long, value_name = "ID", conflicts_with = "list")]
+        run: Option<String>,
+
+        /// List the backed-up runs instead of restoring
+        #[arg(long)]
+        list: bool,
+
+        /// Show what would be restored without making changes
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// List the mule-migrate-step-* plugin executables found on the PATH
+    Plugins,
+    /// Print reference documentation
+    Docs {
+        /// Topic to document
+        #[arg(value_enum)]
+        topic: DocsTopic,
+    },
+    /// Print the JSON Schema of the migration config, for editors
+    Schema,
+    /// Print a shell completion script to stdout
+    Completions {
+        /// Shell to generate the completions for
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    /// Replace this binary with the latest release from GitHub
+    SelfUpdate {
+        /// Only report whether a newer release is available
+        #[arg(long)]
+        check: bool,
+
+        /// Install the release even if it cannot be verified against its
+        /// published .sha256 file
+        #[arg(long)]
+        skip_checksum: bool,
+    },
+    /// Restore all files from the .bak backups created by a previous run
+    Rollback {
+        /// Path to the Mule project root (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        project: String,
+
+        /// Show what would be restored without making changes
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+/// Topics of the `docs` subcommand.
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum DocsTopic {
+    /// Every config key with its type, default and description, and an example
+    Config,
 }
 
 fn main() {
     let cli = Cli::parse();
-    let log_level = if cli.verbose { "debug" } else { "info" };
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level)).init();
+    let log_level = match (cli.quiet, cli.verbose) {
+        (true, _) => "error",
+        (false, 0) => "info",
+        (false, 1) => "debug",
+        (false, _) => "trace",
+    };
+    // Colors only on terminals; https://no-color.org asks to honor any non-empty NO_COLOR
+    let no_color = cli.no_color || std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    if no_color || !std::io::stdout().is_terminal() {
+        colored::control::set_override(false);
+    }
+    let mut logger =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level));
+    logger.write_style(if no_color {
+        env_logger::WriteStyle::Never
+    } else {
+        env_logger::WriteStyle::Auto
+    });
+    if cli.log_format == LogFormat::Json {
+        logger.format(json_log::format_record);
+    }
+    logger.init();
+
+    // The spinner would garble debug logs, and is useless when stderr is not a terminal
+    let feedback = if cli.log_format == LogFormat::Json {
+        Feedback::JsonEvents
+    } else if !cli.no_progress && !cli.quiet && cli.verbose == 0 && std::io::stderr().is_terminal()
+    {
+        Feedback::Progress
+    } else {
+        Feedback::None
+    };
+
+    if cli.offline {
+        http::set_offline(true);
+    }
+    // Passive new-version notice, only for people at a terminal
+    if !cli.offline
+        && !cli.quiet
+        && cli.log_format == LogFormat::Text
+        && std::io::stderr().is_terminal()
+        && !matches!(
+            cli.command,
+            Some(Commands::SelfUpdate { .. } | Commands::Completions { .. } | Commands::Schema)
+        )
+    {
+        if let Some(notice) = self_update::update_notice() {
+            eprintln!("{}", notice.yellow());
+        }
+    }
+
+    match &cli.command {
+        None => migrate(&cli.migrate, feedback, cli.offline),
+        Some(Commands::Migrate(args)) => migrate(args, feedback, cli.offline),
+        Some(Commands::Validate(source)) => validate(source),
+        Some(Commands::Check(args)) => check(args, None, feedback, cli.offline),
+        Some(Commands::Plan(args)) => check(&args.check, Some(&args.out), feedback, cli.offline),
+        Some(Commands::Apply(args)) => apply(args),
+        Some(Commands::MigrateAll(args)) => migrate_all(args, cli.offline),
+        Some(Commands::InitConfig { project, output }) => init_config(project, output.as_deref()),
+        Some(Commands::Inspect { project, format }) => inspect(project, *format),
+        Some(Commands::Restore {
+            project,
+            backup_dir,
+            run,
+            list,
+            dry_run,
+        }) => restore(
+            project,
+            backup_dir.as_deref(),
+            run.as_deref(),
+            *list,
+            *dry_run,
+        ),
+        Some(Commands::Plugins) => list_plugins(),
+        Some(Commands::Docs {
+            topic: DocsTopic::Config,
+        }) => print!("{}", docs::config_reference()),
+        Some(Commands::Schema) => println!(
+            "{}",
+            serde_json::to_string_pretty(&docs::config_json_schema())
+                .expect("Failed to serialize the schema")
+        ),
+        Some(Commands::Completions { shell }) => clap_complete::generate(
+            *shell,
+            &mut Cli::command(),
+            "mule-lazy-migrate",
+            &mut std::io::stdout(),
+        ),
+        Some(Commands::SelfUpdate {
+            check,
+            skip_checksum,
+        }) => update_self(*check, *skip_checksum),
+        Some(Commands::Rollback { project, dry_run }) => rollback(project, *dry_run),
+    }
+}
+
+/// Parses a `--set KEY=VALUE` config variable.
+fn parse_var(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected KEY=VALUE, got '{s}'")),
+    }
+}
+
+/// Exits unless the report format can render a consolidated batch report.
+fn require_batch_format(format: ReportFormat) {
+    if !matches!(format, ReportFormat::Text | ReportFormat::Json) {
+        eprintln!("Batch migrations support only the text and json report formats");
+        std::process::exit(EXIT_FAILURE);
+    }
+}
+
+/// How a running migration reports on stderr besides the logs.
+#[derive(Clone, Copy)]
+enum Feedback {
+    None,
+    /// Progress spinner
+    Progress,
+    /// JSON line per migration event
+    JsonEvents,
+}
+
+/// Runs the migration with the given feedback.
+fn run_with_feedback(
+    opts: &MigrationOptions,
+    feedback: Feedback,
+) -> Result<MigrationReport, MigrateError> {
+    match feedback {
+        Feedback::None => run_migration(opts),
+        Feedback::Progress => run_migration_with_observer(opts, ProgressObserver::new()),
+        Feedback::JsonEvents => run_migration_with_observer(opts, JsonLogObserver::new()),
+    }
+}
+
+/// Prints the built-in profiles if requested; returns true if it did.
+fn list_profiles(source: &ConfigArgs) -> bool {
+    if !source.list_profiles {
+        return false;
+    }
+    println!("{}", "Built-in profiles:".blue().bold());
+    for (name, description) in profiles::list() {
+        println!("  {:<20} {description}", name.green());
+    }
+    true
+}
+
+fn migrate(args: &MigrateArgs, feedback: Feedback, offline: bool) {
+    if list_profiles(&args.source) {
+        return;
+    }
     let opts = MigrationOptions {
-        config_path: &cli.config,
-        project_root: &cli.project,
-        dry_run: cli.dry_run,
-        backup: cli.backup,
-        update_maven_deps: cli.update_maven_deps,
-        build_mule_project: cli.build_mule_project,
-    };
-    if let Err(e) = run_migration(&opts) {
-        eprintln!("Migration failed: {e}");
-        std::process::exit(1);
+        config_path: args.source.config.as_deref(),
+        config_sha256: args.source.config_sha256.as_deref(),
+        config_vars: &args.source.vars,
+        profile: args.source.profile.as_deref(),
+        project_root: &args.project,
+        dry_run: args.dry_run || args.check || args.watch,
+        backup: args.backup,
+        backup_dir: args.backup_dir.as_deref(),
+        backup_compression: args.backup_compression,
+        keep_backups: args.keep_backups,
+        update_maven_deps: args.update_maven_deps,
+        latest_connectors: args.latest_connectors,
+        latest_lts: args.latest_lts,
+        latest: args.latest,
+        build_mule_project: args.build_mule_project,
+        run_tests: args.run_tests,
+        deploy: args.deploy,
+        maven_bin: args.maven_bin.as_deref(),
+        maven_output_lines: args.maven_output_lines,
+        maven_goals: &args.maven_goals,
+        maven_args: &args.maven_args,
+        maven_timeout_secs: args.maven_timeout,
+        include: &args.include,
+        exclude: &args.exclude,
+        interactive: args.interactive,
+        report_format: args.report_format,
+        report_file: args.report_file.as_deref(),
+        git_branch: args.git_branch.as_deref(),
+        git_commit: args.git_commit,
+        allow_dirty: args.allow_dirty,
+        strict: args.strict,
+        force: args.force,
+        ignore_lock: args.ignore_lock,
+        plan_out: None,
+        only: &args.only,
+        skip: &args.skip,
+        offline,
+    };
+    if args.recursive {
+        require_batch_format(args.report_format);
+        let projects = find_mule_projects(&args.project);
+        if projects.is_empty() {
+            eprintln!("No Mule projects found under '{}'", args.project);
+            std::process::exit(EXIT_FAILURE);
+        }
+        finish_batch(
+            batch::run_batch(&projects, &opts),
+            args.report_format,
+            args.report_file.as_deref(),
+        );
+    }
+    if args.watch {
+        let config = args.source.config.as_deref().unwrap_or_default();
+        watch_config(config, &opts, feedback);
+    }
+    match run_with_feedback(&opts, feedback) {
+        Ok(report) => {
+            if args.check && report.exit_code() == EXIT_CHANGES {
+                eprintln!(
+                    "{} file(s) still need to be migrated",
+                    report.changed_files.len()
+                );
+            }
+            std::process::exit(report.exit_code());
+        }
+        Err(e) => {
+            eprintln!("Migration failed: {e}");
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+}
+
+/// Dry-runs the migration each time the config file changes, until interrupted.
+fn watch_config(config: &str, opts: &MigrationOptions, feedback: Feedback) -> ! {
+    if config::is_url(config) {
+        eprintln!("--watch needs a local config file, not a URL");
+        std::process::exit(EXIT_FAILURE);
+    }
+    loop {
+        println!(
+            "{}",
+            format!(
+                "[{}] Dry run with {config}",
+                chrono::Local::now().format("%H:%M:%S")
+            )
+            .blue()
+            .bold()
+        );
+        match run_with_feedback(opts, feedback) {
+            Ok(report) => print!("{}", report.diffs_to_text()),
+            Err(e) => eprintln!("{}", format!("Migration failed: {e}").red()),
+        }
+        println!("Watching {config} for changes (Ctrl-C to stop)");
+        watch::wait_for_change(Path::new(config), watch::POLL_INTERVAL);
+    }
+}
+
+fn validate(source: &ConfigArgs) {
+    if list_profiles(source) {
+        return;
+    }
+    match load_config(
+        source.config.as_deref(),
+        source.profile.as_deref(),
+        source.config_sha256.as_deref(),
+        &source.vars,
+    ) {
+        Ok(config) => {
+            let problems = compat::check_config(&config);
+            if !problems.is_empty() {
+                eprintln!("{}", MigrateError::Incompatible(problems));
+                std::process::exit(EXIT_FAILURE);
+            }
+            let today = chrono::Local::now().date_naive();
+            if let Some(warning) = support::runtime_warning(&config.app_runtime_version, today) {
+                eprintln!("{}", warning.yellow());
+            }
+            println!(
+                "{} (runtime {}, {} replacement rules)",
+                "Config is valid".green().bold(),
+                config.app_runtime_version,
+                config.replacements.len()
+            )
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+}
+
+fn check(args: &CheckArgs, plan_out: Option<&str>, feedback: Feedback, offline: bool) {
+    if list_profiles(&args.source) {
+        return;
+    }
+    let opts = MigrationOptions {
+        config_path: args.source.config.as_deref(),
+        config_sha256: args.source.config_sha256.as_deref(),
+        config_vars: &args.source.vars,
+        profile: args.source.profile.as_deref(),
+        project_root: &args.project,
+        dry_run: true,
+        backup: false,
+        backup_dir: None,
+        backup_compression: BackupCompression::None,
+        keep_backups: None,
+        update_maven_deps: false,
+        latest_connectors: args.latest_connectors,
+        latest_lts: args.latest_lts,
+        latest: args.latest,
+        build_mule_project: false,
+        run_tests: false,
+        deploy: false,
+        maven_bin: None,
+        maven_output_lines: 0,
+        maven_goals: &[],
+        maven_args: &[],
+        maven_timeout_secs: None,
+        include: &args.include,
+        exclude: &args.exclude,
+        interactive: false,
+        report_format: args.report_format,
+        report_file: args.report_file.as_deref(),
+        git_branch: None,
+        git_commit: false,
+        allow_dirty: false,
+        strict: false,
+        force: args.force,
+        ignore_lock: false,
+        plan_out,
+        only: &args.only,
+        skip: &args.skip,
+        offline,
+    };
+    match run_with_feedback(&opts, feedback) {
+        Ok(report) if plan_out.is_some() => {
+            if report.exit_code() == EXIT_FAILURE {
+                std::process::exit(EXIT_FAILURE);
+            }
+            eprintln!(
+                "Plan for {} file(s) written to {}",
+                report.changed_files.len(),
+                plan_out.unwrap_or_default()
+            );
+        }
+        Ok(report) => {
+            if report.exit_code() == EXIT_CHANGES {
+                eprintln!(
+                    "{} file(s) still need to be migrated",
+                    report.changed_files.len()
+                );
+            }
+            std::process::exit(report.exit_code());
+        }
+        Err(e) => {
+            eprintln!("Check failed: {e}");
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+}
+
+fn apply(args: &ApplyArgs) {
+    let root = Path::new(&args.project);
+    let applied = Plan::load(Path::new(&args.plan)).and_then(|plan| {
+        let _lock = run_lock::RunLock::acquire(root)?;
+        let snapshot = args
+            .backup
+            .then(|| {
+                let backups = backup::backups_dir(root, args.backup_dir.as_deref());
+                backup::Snapshot::create(root, &backups).map_err(|source| MigrateError::Backup {
+                    path: backups,
+                    source,
+                })
+            })
+            .transpose()?;
+        let changed = apply_plan(&plan, root, snapshot.as_ref())?;
+        if let Some(snapshot) = snapshot {
+            println!(
+                "{} run {} (undo with `restore --run {}`)",
+                "Backups:".bold(),
+                snapshot.run_id(),
+                snapshot.run_id()
+            );
+        }
+        Ok(changed)
+    });
+    match applied {
+        Ok(changed) if changed.is_empty() => {
+            println!("{}", "The plan has no changes.".blue().bold())
+        }
+        Ok(changed) => {
+            println!("{}", "Changed files:".green().bold());
+            for file in changed {
+                println!("  {}", file.display().to_string().green());
+            }
+        }
+        Err(e) => {
+            eprintln!("Apply failed: {e}");
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+}
+
+fn migrate_all(args: &MigrateAllArgs, offline: bool) {
+    if list_profiles(&args.source) {
+        return;
+    }
+    require_batch_format(args.report_format);
+    let projects = match batch::Manifest::from_file(&args.projects_file) {
+        Ok(projects) => projects,
+        Err(e) => {
+            eprintln!("Invalid projects file: {e}");
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+    let opts = MigrationOptions {
+        config_path: args.source.config.as_deref(),
+        config_sha256: args.source.config_sha256.as_deref(),
+        config_vars: &args.source.vars,
+        profile: args.source.profile.as_deref(),
+        project_root: ".",
+        dry_run: args.dry_run,
+        backup: args.backup,
+        backup_dir: args.backup_dir.as_deref(),
+        backup_compression: args.backup_compression,
+        keep_backups: args.keep_backups,
+        update_maven_deps: false,
+        latest_connectors: args.latest_connectors,
+        latest_lts: args.latest_lts,
+        latest: args.latest,
+        build_mule_project: false,
+        run_tests: false,
+        deploy: false,
+        maven_bin: None,
+        maven_output_lines: 0,
+        maven_goals: &[],
+        maven_args: &[],
+        maven_timeout_secs: None,
+        include: &args.include,
+        exclude: &args.exclude,
+        interactive: false,
+        report_format: args.report_format,
+        report_file: None,
+        git_branch: args.git_branch.as_deref(),
+        git_commit: args.git_commit,
+        allow_dirty: args.allow_dirty,
+        strict: args.strict,
+        force: args.force,
+        ignore_lock: args.ignore_lock,
+        plan_out: None,
+        only: &args.only,
+        skip: &args.skip,
+        offline,
+    };
+    finish_batch(
+        batch::run_batch(&projects, &opts),
+        args.report_format,
+        args.report_file.as_deref(),
+    );
+}
+
+/// Prints (or writes) the consolidated batch report and exits with its code.
+fn finish_batch(report: batch::BatchReport, format: ReportFormat, report_file: Option<&str>) -> ! {
+    let render = || match format {
+        ReportFormat::Json => report.to_json(),
+        _ => report.to_text(),
+    };
+    match report_file {
+        Some(path) => {
+            print!("{}", report.to_text());
+            let rendered = report::without_colors(render);
+            if let Err(e) = std::fs::write(path, rendered) {
+                eprintln!("Failed to write {path}: {e}");
+                std::process::exit(EXIT_FAILURE);
+            }
+        }
+        None => println!("{}", render()),
+    }
+    std::process::exit(report.exit_code());
+}
+
+fn init_config(project: &str, output: Option<&str>) {
+    let config = match init::starter_config(project) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Failed to inspect project '{project}': {e}");
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+    let Some(output) = output else {
+        println!("{config}");
+        return;
+    };
+    if std::path::Path::new(output).exists() {
+        eprintln!("{output} already exists, not overwriting it");
+        std::process::exit(EXIT_FAILURE);
+    }
+    if let Err(e) = std::fs::write(output, config + "\n") {
+        eprintln!("Failed to write {output}: {e}");
+        std::process::exit(EXIT_FAILURE);
+    }
+    println!(
+        "{} {output} (replace the TODO values before migrating)",
+        "Wrote".green().bold()
+    );
+}
+
+fn inspect(project: &str, format: ReportFormat) {
+    let inventory = match inspect::inspect_project(project) {
+        Ok(inventory) => inventory,
+        Err(e) => {
+            eprintln!("Failed to inspect project '{project}': {e}");
+            std::process::exit(EXIT_FAILURE);
+        }
+    };
+    match format {
+        ReportFormat::Text => print!("{}", inventory.to_text()),
+        ReportFormat::Json => println!("{}", inventory.to_json()),
+        _ => {
+            eprintln!("inspect supports only the text and json formats");
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+}
+
+fn list_plugins() {
+    let plugins = plugins::discover();
+    if plugins.is_empty() {
+        println!(
+            "No {}* executables found on the PATH",
+            plugins::PLUGIN_PREFIX
+        );
+    }
+    for (name, path) in plugins {
+        println!("{name}\t{}", path.display());
+    }
+}
+
+fn update_self(check: bool, skip_checksum: bool) {
+    let current = self_update::CURRENT_VERSION;
+    match self_update::self_update(check, skip_checksum) {
+        Ok(self_update::UpdateOutcome::UpToDate) => {
+            println!("mule-lazy-migrate {current} is the latest release");
+        }
+        Ok(self_update::UpdateOutcome::Available(latest)) => {
+            println!(
+                "mule-lazy-migrate {latest} is available (you have {current}); run `mule-lazy-migrate self-update`"
+            );
+            std::process::exit(EXIT_CHANGES);
+        }
+        Ok(self_update::UpdateOutcome::Updated(latest)) => {
+            println!(
+                "{}",
+                format!("Updated mule-lazy-migrate {current} -> {latest}")
+                    .green()
+                    .bold()
+            );
+        }
+        Err(e) => {
+            eprintln!("Self-update failed: {e}");
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+}
+
+fn restore(project: &str, backup_dir: Option<&str>, run: Option<&str>, list: bool, dry_run: bool) {
+    let backups = backup::backups_dir(Path::new(project), backup_dir);
+    let runs = backup::list_runs(&backups);
+    if list {
+        for run in &runs {
+            println!("{run}");
+        }
+        return;
+    }
+    let Some(run) = run.or(runs.last().map(String::as_str)) else {
+        println!("{}", "No backups found, nothing to restore.".blue().bold());
+        return;
+    };
+    match backup::restore(Path::new(project), &backups, run, dry_run) {
+        Ok(restored) => {
+            println!(
+                "{}",
+                format!("Restored files from run {run}:").green().bold()
+            );
+            for file in restored {
+                println!("  {}", file.green());
+            }
+        }
+        Err(e) => {
+            eprintln!("Restore failed: {e}");
+            std::process::exit(EXIT_FAILURE);
+        }
+    }
+}
+
+fn rollback(project: &str, dry_run: bool) {
+    match backup::rollback(Path::new(project), dry_run) {
+        Ok(restored) if restored.is_empty() => {
+            println!(
+                "{}",
+                "No backups found, nothing to roll back.".blue().bold()
+            )
+        }
+        Ok(restored) => {
+            println!("{}", "Restored files:".green().bold());
+            for file in restored {
+                println!("  {}", file.green());
+            }
+        }
+        Err(e) => {
+            eprintln!("Rollback failed: {e}");
+            std::process::exit(EXIT_FAILURE);
+        }
     }
 }
@@ -0,0 +1,162 @@
+use crate::config::MigrationConfig;
+use crate::java_compat;
+use crate::json_ops;
+use crate::version;
+use crate::xml;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::fs;
+use std::path::Path;
+
+/// Current and target value of one version the migration sets.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VersionChange {
+    /// Property or field name, e.g. `app.runtime` or `minMuleVersion`.
+    pub name: String,
+    /// Value in the project, `None` if it is not declared.
+    pub current: Option<String>,
+    pub target: String,
+    /// True if the target is older than the current value.
+    pub downgrade: bool,
+}
+
+impl VersionChange {
+    fn new(name: &str, current: Option<String>, target: &str) -> Self {
+        let downgrade =
+            current.as_deref().and_then(|c| version::compare(target, c)) == Some(Ordering::Less);
+        VersionChange {
+            name: name.to_string(),
+            current,
+            target: target.to_string(),
+            downgrade,
+        }
+    }
+}
+
+/// Compares the versions the project declares today with the ones `config`
+/// migrates to. A missing or unparsable mule-artifact.json yields rows
+/// without a current value.
+pub fn version_matrix(project_root: &Path, config: &MigrationConfig) -> Vec<VersionChange> {
+    let pom = fs::read_to_string(project_root.join("pom.xml"))
+        .ok()
+        .and_then(|content| xml::read_pom_versions(&content).ok())
+        .unwrap_or_default();
+    let (min_mule_version, java_versions) =
+        fs::read_to_string(project_root.join("mule-artifact.json"))
+            .ok()
+            .and_then(|content| json_ops::read_mule_artifact_versions(&content).ok())
+            .unwrap_or_default();
+
+    let target_java = &config.mule_artifact.java_specification_versions;
+    let java = VersionChange {
+        name: "javaSpecificationVersions".to_string(),
+        current: (!java_versions.is_empty()).then(|| java_versions.join(", ")),
+        target: target_java.join(", "),
+        downgrade: java_compat::highest_java_version(target_java)
+            < java_compat::highest_java_version(&java_versions),
+    };
+    // Only pom properties the project declares are updated
+    let pom_rows = [
+        (
+            "mule.version",
+            pom.mule_version,
+            &config.app_runtime_version,
+        ),
+        ("app.runtime", pom.app_runtime, &config.app_runtime_version),
+        ("munit.version", pom.munit_version, &config.munit_version),
+        (
+            "mule.maven.plugin.version",
+            pom.mule_maven_plugin_version,
+            &config.mule_maven_plugin_version,
+        ),
+    ];
+    let mut rows: Vec<VersionChange> = pom_rows
+        .into_iter()
+        .filter(|(_, current, _)| current.is_some())
+        .map(|(name, current, target)| VersionChange::new(name, current, target))
+        .collect();
+    rows.push(VersionChange::new(
+        "minMuleVersion",
+        min_mule_version,
+        &config.mule_artifact.min_mule_version,
+    ));
+    rows.push(java);
+    rows
+}
+
+/// Renders the matrix as aligned `name  current -> target` lines, marking
+/// downgrades.
+pub fn render_matrix(rows: &[VersionChange]) -> Vec<String> {
+    let width = rows.iter().map(|r| r.name.len()).max().unwrap_or(0);
+    let current_width = rows
+        .iter()
+        .map(|r| r.current.as_deref().unwrap_or("-").len())
+        .max()
+        .unwrap_or(0);
+    rows.iter()
+        .map(|row| {
+            let line = format!(
+                "{:width$}  {:current_width$} -> {}",
+                row.name,
+                row.current.as_deref().unwrap_or("-"),
+                row.target
+            );
+            if row.downgrade {
+                format!("{line}  (DOWNGRADE)")
+            } else {
+                line
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigFormat;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_version_matrix_flags_downgrades() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("pom.xml"),
+            "<project><properties><app.runtime>4.4.0</app.runtime>\
+             <munit.version>3.5.0</munit.version></properties></project>",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("mule-artifact.json"),
+            r#"{"minMuleVersion": "4.4.0", "javaSpecificationVersions": ["11"]}"#,
+        )
+        .unwrap();
+        let config = MigrationConfig::from_str_with_format(
+            r#"{
+                "app_runtime_version": "4.9.4",
+                "mule_maven_plugin_version": "4.3.1",
+                "munit_version": "3.4.0",
+                "mule_artifact": {"min_mule_version": "4.9.0", "java_specification_versions": ["17"]},
+                "replacements": []
+            }"#,
+            ConfigFormat::Json,
+        )
+        .unwrap();
+
+        let rows = version_matrix(dir.path(), &config);
+        let downgrades: Vec<_> = rows
+            .iter()
+            .filter(|r| r.downgrade)
+            .map(|r| &r.name)
+            .collect();
+        assert_eq!(downgrades, ["munit.version"]);
+        assert_eq!(
+            render_matrix(&rows),
+            vec![
+                "app.runtime                4.4.0 -> 4.9.4",
+                "munit.version              3.5.0 -> 3.4.0  (DOWNGRADE)",
+                "minMuleVersion             4.4.0 -> 4.9.0",
+                "javaSpecificationVersions  11    -> 17",
+            ]
+        );
+    }
+}
@@ -0,0 +1,529 @@
+use crate::error::MigrateError;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Name of the Maven wrapper script in the project root.
+#[cfg(windows)]
+const WRAPPER: &str = "mvnw.cmd";
+#[cfg(not(windows))]
+const WRAPPER: &str = "mvnw";
+
+/// Names Maven is installed under on the PATH.
+#[cfg(windows)]
+const MAVEN_NAMES: &[&str] = &["mvn.cmd", "mvn.bat", "mvn.exe"];
+#[cfg(not(windows))]
+const MAVEN_NAMES: &[&str] = &["mvn"];
+
+/// Number of `[ERROR]` lines kept from a Maven run.
+const MAX_ERROR_LINES: usize = 10;
+
+/// How often a Maven run with a timeout is polled for completion.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// `maven` section of the migration config.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct MavenConfig {
+    /// Goals run to build the project after migration.
+    #[serde(default = "default_goals")]
+    pub goals: Vec<String>,
+    /// Goals run to test the project after migration.
+    #[serde(default = "default_test_goals")]
+    pub test_goals: Vec<String>,
+    /// Extra arguments for every Maven run, e.g. `-DskipTests`,
+    /// `-s settings.xml` or `-Pci`.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Kill a Maven run after this many seconds.
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// Dependencies `--update-maven-deps` may update, as
+    /// `groupId:artifactId:type:classifier:version` patterns with `*`
+    /// wildcards, e.g. `org.mule.connectors:*`; empty means all.
+    #[serde(default)]
+    pub update_includes: Vec<String>,
+    /// Dependencies `--update-maven-deps` must not touch, e.g. internal
+    /// libraries as `com.acme.*:*`.
+    #[serde(default)]
+    pub update_excludes: Vec<String>,
+    /// Let `--update-maven-deps` move dependencies to a new major version;
+    /// if false, they only get the latest release of their major version.
+    #[serde(default = "default_true")]
+    pub allow_major_updates: bool,
+}
+
+impl MavenConfig {
+    /// Goal and arguments of the `--update-maven-deps` run.
+    pub fn update_args(&self) -> Vec<String> {
+        let mut args = vec!["versions:use-latest-releases".to_string()];
+        if !self.update_includes.is_empty() {
+            args.push(format!("-Dincludes={}", self.update_includes.join(",")));
+        }
+        if !self.update_excludes.is_empty() {
+            args.push(format!("-Dexcludes={}", self.update_excludes.join(",")));
+        }
+        if !self.allow_major_updates {
+            args.push("-DallowMajorUpdates=false".to_string());
+        }
+        args
+    }
+}
+
+impl Default for MavenConfig {
+    fn default() -> Self {
+        MavenConfig {
+            goals: default_goals(),
+            test_goals: default_test_goals(),
+            args: Vec::new(),
+            timeout_secs: None,
+            update_includes: Vec::new(),
+            update_excludes: Vec::new(),
+            allow_major_updates: true,
+        }
+    }
+}
+
+fn default_goals() -> Vec<String> {
+    vec!["clean".to_string(), "install".to_string()]
+}
+
+fn default_test_goals() -> Vec<String> {
+    vec!["clean".to_string(), "test".to_string()]
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Outcome of one Maven invocation, with its output captured.
+#[derive(Debug, Clone, Serialize)]
+pub struct MavenRun {
+    /// Arguments passed to Maven, e.g. `clean install`.
+    pub goals: String,
+    /// Exit code, or `None` if Maven was killed by a signal.
+    pub exit_code: Option<i32>,
+    /// True if Maven was killed for exceeding the timeout.
+    pub timed_out: bool,
+    /// First `[ERROR]` lines of the output, without the prefix.
+    pub errors: Vec<String>,
+    /// Last lines of the combined stdout and stderr.
+    pub output_tail: Vec<String>,
+}
+
+impl MavenRun {
+    pub fn succeeded(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+
+    /// One-line description of a failed run for the report's errors.
+    pub fn failure_message(&self) -> String {
+        let status = match self.exit_code {
+            _ if self.timed_out => "timed out".to_string(),
+            Some(code) => format!("exit {code}"),
+            None => "killed".to_string(),
+        };
+        match self.errors.first() {
+            Some(error) => format!("Maven '{}' failed ({status}): {error}", self.goals),
+            None => format!("Maven '{}' failed ({status})", self.goals),
+        }
+    }
+}
+
+/// Extracts the messages of the first `[ERROR]` lines of Maven output.
+fn parse_errors(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.trim_start().strip_prefix("[ERROR]"))
+        .map(str::trim)
+        .filter(|message| !message.is_empty())
+        .take(MAX_ERROR_LINES)
+        .map(str::to_string)
+        .collect()
+}
+
+/// Versions of the resolved dependencies of a project, keyed by
+/// `groupId:artifactId`, plus `:classifier` if there is one.
+pub type DependencyTree = BTreeMap<String, String>;
+
+/// Parses the text output of `mvn dependency:tree` (for every module of a
+/// multi-module build). The modules themselves are left out.
+pub fn parse_dependency_tree(text: &str) -> DependencyTree {
+    let mut tree = DependencyTree::new();
+    for line in text.lines() {
+        let Some(start) = line.find("+- ").or_else(|| line.find("\\- ")) else {
+            continue;
+        };
+        let coordinates = line[start + 3..].split_whitespace().next().unwrap_or("");
+        // groupId:artifactId:type[:classifier]:version:scope
+        let parts: Vec<&str> = coordinates.split(':').collect();
+        let (key, version) = match parts.as_slice() {
+            [group, artifact, _, version, _] => (format!("{group}:{artifact}"), version),
+            [group, artifact, _, classifier, version, _] => {
+                (format!("{group}:{artifact}:{classifier}"), version)
+            }
+            _ => continue,
+        };
+        tree.entry(key).or_insert_with(|| version.to_string());
+    }
+    tree
+}
+
+/// Describes how the resolved dependencies changed between two trees.
+pub fn diff_dependency_trees(before: &DependencyTree, after: &DependencyTree) -> Vec<String> {
+    let mut changes = Vec::new();
+    for (key, old) in before {
+        match after.get(key) {
+            Some(new) if new != old => changes.push(format!("{key}: '{old}' -> '{new}'")),
+            Some(_) => {}
+            None => changes.push(format!("{key}: removed '{old}'")),
+        }
+    }
+    for (key, new) in after {
+        if !before.contains_key(key) {
+            changes.push(format!("{key}: added '{new}'"));
+        }
+    }
+    changes.sort();
+    changes
+}
+
+/// The Maven executable used for a project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Maven {
+    program: PathBuf,
+    /// Run the program through `sh`, for wrappers checked out without the
+    /// executable bit.
+    via_shell: bool,
+    /// Arguments added to every run.
+    args: Vec<String>,
+    timeout: Option<Duration>,
+}
+
+/// Reads a child's output stream to the end on another thread, so a full
+/// pipe never blocks the child.
+fn read_in_background<R: Read + Send + 'static>(stream: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut stream) = stream {
+            let _ = stream.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+/// Waits for `child`, killing it once `timeout` has passed. Returns `None`
+/// if it was killed.
+fn wait_with_timeout(
+    child: &mut Child,
+    timeout: Option<Duration>,
+) -> io::Result<Option<ExitStatus>> {
+    let Some(timeout) = timeout else {
+        return child.wait().map(Some);
+    };
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            child.kill()?;
+            child.wait()?;
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn find_on_path(names: &[&str]) -> Option<PathBuf> {
+    let path = env::var_os("PATH")?;
+    env::split_paths(&path)
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    true
+}
+
+impl Maven {
+    /// Picks the Maven to run in `project_root`: `maven_bin` if given, else
+    /// the project's Maven wrapper, else `mvn` from the PATH.
+    ///
+    /// # Errors
+    /// Returns an error if none of them is available.
+    pub fn locate(project_root: &Path, maven_bin: Option<&str>) -> Result<Maven, MigrateError> {
+        if let Some(bin) = maven_bin {
+            return Ok(Maven {
+                program: PathBuf::from(bin),
+                via_shell: false,
+                args: Vec::new(),
+                timeout: None,
+            });
+        }
+        let wrapper = project_root.join(WRAPPER);
+        if wrapper.is_file() {
+            // Relative programs are not reliably resolved against current_dir
+            let wrapper = wrapper.canonicalize().unwrap_or(wrapper);
+            return Ok(Maven {
+                via_shell: !is_executable(&wrapper),
+                program: wrapper,
+                args: Vec::new(),
+                timeout: None,
+            });
+        }
+        find_on_path(MAVEN_NAMES)
+            .map(|program| Maven {
+                program,
+                via_shell: false,
+                args: Vec::new(),
+                timeout: None,
+            })
+            .ok_or_else(|| MigrateError::MavenNotFound(project_root.to_path_buf()))
+    }
+
+    /// A command running Maven with `args` in `project_root`.
+    pub fn command<I, S>(&self, project_root: &Path, args: I) -> Command
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let mut command = if self.via_shell {
+            let mut sh = Command::new("sh");
+            sh.arg(&self.program);
+            sh
+        } else {
+            Command::new(&self.program)
+        };
+        command.args(args).current_dir(project_root);
+        command
+    }
+
+    /// Adds `args` to every run and kills runs taking longer than `timeout`.
+    pub fn with_settings(mut self, args: Vec<String>, timeout: Option<Duration>) -> Maven {
+        self.args = args;
+        self.timeout = timeout;
+        self
+    }
+
+    /// Runs Maven with `args` (after the configured extra arguments) in
+    /// `project_root`, capturing its output and keeping the last `tail` lines
+    /// of it.
+    pub fn run(&self, project_root: &Path, args: &[&str], tail: usize) -> io::Result<MavenRun> {
+        let mut child = self
+            .command(
+                project_root,
+                self.args
+                    .iter()
+                    .map(String::as_str)
+                    .chain(args.iter().copied()),
+            )
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let stdout = read_in_background(child.stdout.take());
+        let stderr = read_in_background(child.stderr.take());
+        let status = wait_with_timeout(&mut child, self.timeout)?;
+        let mut combined = String::from_utf8_lossy(&stdout.join().unwrap_or_default()).into_owned();
+        combined.push_str(&String::from_utf8_lossy(&stderr.join().unwrap_or_default()));
+        for line in combined.lines() {
+            log::debug!("[mvn] {line}");
+        }
+        let lines: Vec<&str> = combined.lines().collect();
+        Ok(MavenRun {
+            goals: args.join(" "),
+            exit_code: status.and_then(|s| s.code()),
+            timed_out: status.is_none(),
+            errors: parse_errors(&combined),
+            output_tail: lines[lines.len().saturating_sub(tail)..]
+                .iter()
+                .map(|l| l.to_string())
+                .collect(),
+        })
+    }
+
+    /// The program shown in logs.
+    pub fn program(&self) -> &Path {
+        &self.program
+    }
+
+    /// Runs `dependency:tree` in `project_root`, writing the tree to the
+    /// scratch file `output`, and returns the resolved dependencies.
+    ///
+    /// # Errors
+    /// Returns a description of the failure if Maven could not be run, failed
+    /// or left no tree behind.
+    pub fn dependency_tree(
+        &self,
+        project_root: &Path,
+        output: &Path,
+    ) -> Result<DependencyTree, String> {
+        // Modules append to the file, so a leftover would be read too
+        fs::remove_file(output).ok();
+        let output_arg = format!("-DoutputFile={}", output.display());
+        let run = self
+            .run(
+                project_root,
+                &["dependency:tree", &output_arg, "-DappendOutput=true"],
+                0,
+            )
+            .map_err(|e| e.to_string())?;
+        if !run.succeeded() {
+            return Err(run.failure_message());
+        }
+        let text = fs::read_to_string(output).map_err(|e| e.to_string())?;
+        fs::remove_file(output).ok();
+        Ok(parse_dependency_tree(&text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_locate_prefers_override_then_wrapper() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join(WRAPPER), "#!/bin/sh\n").unwrap();
+        let maven = Maven::locate(dir.path(), None).unwrap();
+        assert_eq!(
+            maven.program(),
+            dir.path().join(WRAPPER).canonicalize().unwrap()
+        );
+        assert_eq!(maven.via_shell, cfg!(unix));
+
+        let maven = Maven::locate(dir.path(), Some("/opt/maven/bin/mvn")).unwrap();
+        assert_eq!(maven.program(), Path::new("/opt/maven/bin/mvn"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let output = "[INFO] Building orders-api\n\
+                      [ERROR] Failed to execute goal org.mule.tools.maven:mule-maven-plugin:4.3.1:compile\n\
+                      [ERROR] \n\
+                      [ERROR] -> [Help 1]\n";
+        assert_eq!(
+            parse_errors(output),
+            vec![
+                "Failed to execute goal org.mule.tools.maven:mule-maven-plugin:4.3.1:compile",
+                "-> [Help 1]"
+            ]
+        );
+        let run = MavenRun {
+            goals: "clean install".to_string(),
+            exit_code: Some(1),
+            timed_out: false,
+            errors: parse_errors(output),
+            output_tail: Vec::new(),
+        };
+        assert_eq!(
+            run.failure_message(),
+            "Maven 'clean install' failed (exit 1): Failed to execute goal org.mule.tools.maven:mule-maven-plugin:4.3.1:compile"
+        );
+    }
+
+    #[test]
+    fn test_update_args_pass_patterns() {
+        let config = MavenConfig {
+            update_includes: vec!["org.mule.connectors:*".to_string()],
+            update_excludes: vec!["com.acme.*:*".to_string(), "*:*:*:*:*-SNAPSHOT".to_string()],
+            allow_major_updates: false,
+            ..MavenConfig::default()
+        };
+        assert_eq!(
+            config.update_args(),
+            [
+                "versions:use-latest-releases",
+                "-Dincludes=org.mule.connectors:*",
+                "-Dexcludes=com.acme.*:*,*:*:*:*:*-SNAPSHOT",
+                "-DallowMajorUpdates=false"
+            ]
+        );
+        assert_eq!(
+            MavenConfig::default().update_args(),
+            ["versions:use-latest-releases"]
+        );
+    }
+
+    #[test]
+    fn test_dependency_tree_diff() {
+        let before = parse_dependency_tree(
+            "com.example:orders-api:mule-application:1.0.0\n\
+             +- org.mule.connectors:mule-http-connector:jar:mule-plugin:1.5.0:provided\n\
+             |  \\- org.mule.runtime:mule-api:jar:1.4.0:provided\n\
+             \\- com.fasterxml.jackson.core:jackson-databind:jar:2.13.0:compile (optional)\n",
+        );
+        assert_eq!(
+            before.get("org.mule.connectors:mule-http-connector:mule-plugin"),
+            Some(&"1.5.0".to_string())
+        );
+        assert_eq!(before.len(), 3);
+        let after = parse_dependency_tree(
+            "com.example:orders-api:mule-application:1.1.0\n\
+             +- org.mule.connectors:mule-http-connector:jar:mule-plugin:1.10.2:provided\n\
+             \\- com.fasterxml.jackson.core:jackson-databind:jar:2.13.0:compile\n\
+             \\- org.slf4j:slf4j-api:jar:2.0.9:compile\n",
+        );
+        assert_eq!(
+            diff_dependency_trees(&before, &after),
+            vec![
+                "org.mule.connectors:mule-http-connector:mule-plugin: '1.5.0' -> '1.10.2'",
+                "org.mule.runtime:mule-api: removed '1.4.0'",
+                "org.slf4j:slf4j-api: added '2.0.9'",
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_passes_extra_args_and_times_out() {
+        let dir = tempdir().unwrap();
+        let script = dir.path().join("mvn");
+        fs::write(
+            &script,
+            "#!/bin/sh\necho \"args: $*\"\nif [ \"$1\" = -Dslow ]; then exec sleep 5; fi\n",
+        )
+        .unwrap();
+        let maven = Maven::locate(dir.path(), Some("sh")).unwrap();
+
+        let run = maven
+            .clone()
+            .with_settings(
+                vec![script.display().to_string(), "-DskipTests".to_string()],
+                None,
+            )
+            .run(dir.path(), &["clean", "package"], 5)
+            .unwrap();
+        assert!(run.succeeded());
+        assert_eq!(run.output_tail, vec!["args: -DskipTests clean package"]);
+
+        let run = maven
+            .with_settings(
+                vec![script.display().to_string(), "-Dslow".to_string()],
+                Some(Duration::from_millis(200)),
+            )
+            .run(dir.path(), &["install"], 5)
+            .unwrap();
+        assert!(run.timed_out);
+        assert_eq!(run.failure_message(), "Maven 'install' failed (timed out)");
+    }
+}
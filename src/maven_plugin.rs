@@ -0,0 +1,560 @@
+use crate::xml::{
+    apply_edits, artifact_declarations, child_element, child_elements, child_text,
+    edit_declared_versions, escape_text, indent_unit, insert_before, insert_child, pom_property,
+    set_element_text,
+};
+use roxmltree::{Document, Node};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+pub const MULE_MAVEN_PLUGIN_GROUP: &str = "org.mule.tools.maven";
+pub const MULE_MAVEN_PLUGIN: &str = "mule-maven-plugin";
+
+/// Property holding the plugin version in most Mule projects.
+const VERSION_PROPERTY: &str = "mule.maven.plugin.version";
+
+/// Packagings built by the mule-maven-plugin.
+const MULE_PACKAGINGS: [&str; 3] = ["mule-application", "mule-domain", "mule-policy"];
+
+/// Top-level pom sections that conventionally follow `<build>`.
+const POM_SECTIONS_AFTER_BUILD: [&str; 2] = ["reporting", "profiles"];
+
+pub fn is_mule_maven_plugin(group_id: &str, artifact_id: &str) -> bool {
+    group_id == MULE_MAVEN_PLUGIN_GROUP && artifact_id == MULE_MAVEN_PLUGIN
+}
+
+/// Aligns the mule-maven-plugin declaration of pom.xml with `version`: a
+/// hard-coded `<version>` is set directly (property references are left to
+/// the `<properties>` update). If `add_missing` is set and a Mule project
+/// does not declare the plugin at all, a plugin block is added to
+/// `<build><plugins>`, creating those if needed.
+///
+/// Returns the new content and a description of each change.
+pub fn plan_mule_maven_plugin_update(
+    content: &str,
+    version: &str,
+    add_missing: bool,
+) -> Result<(String, Vec<String>), roxmltree::Error> {
+    let (content, mut changes) =
+        edit_declared_versions(content, "plugin", |group_id, artifact_id, _| {
+            is_mule_maven_plugin(group_id, artifact_id).then(|| version.to_string())
+        })?;
+    if !add_missing {
+        return Ok((content, changes));
+    }
+
+    let doc = Document::parse(&content)?;
+    let project = doc.root_element();
+    let declared = artifact_declarations(project).into_iter().any(|d| {
+        d.tag_name().name() == "plugin"
+            && child_text(d, "groupId") == Some(MULE_MAVEN_PLUGIN_GROUP)
+            && child_text(d, "artifactId") == Some(MULE_MAVEN_PLUGIN)
+    });
+    let packaging = child_text(project, "packaging").unwrap_or("jar");
+    if declared || !MULE_PACKAGINGS.contains(&packaging) {
+        return Ok((content, changes));
+    }
+
+    let unit = indent_unit(&content, &doc);
+    let version_value = if matches!(pom_property(&content, VERSION_PROPERTY), Ok(Some(_))) {
+        format!("${{{VERSION_PROPERTY}}}")
+    } else {
+        version.to_string()
+    };
+    let plugin = format!(
+        "<plugin>\n{unit}<groupId>{MULE_MAVEN_PLUGIN_GROUP}</groupId>\n\
+         {unit}<artifactId>{MULE_MAVEN_PLUGIN}</artifactId>\n\
+         {unit}<version>{version_value}</version>\n\
+         {unit}<extensions>true</extensions>\n</plugin>"
+    );
+    // Nests a snippet one level deeper inside a new parent element
+    let wrap = |name: &str, inner: &str| {
+        let body = inner.replace('\n', &format!("\n{unit}"));
+        format!("<{name}>\n{unit}{body}\n</{name}>")
+    };
+    let build = child_element(project, "build");
+    let edit = match (build, build.and_then(|b| child_element(b, "plugins"))) {
+        (_, Some(plugins)) => insert_child(&content, unit, plugins, &plugin),
+        (Some(build), None) => insert_child(&content, unit, build, &wrap("plugins", &plugin)),
+        (None, _) => {
+            let snippet = wrap("build", &wrap("plugins", &plugin));
+            let next = project.children().find(|n| {
+                n.is_element() && POM_SECTIONS_AFTER_BUILD.contains(&n.tag_name().name())
+            });
+            match next {
+                Some(next) => insert_before(&content, next, &snippet),
+                None => insert_child(&content, unit, project, &snippet),
+            }
+        }
+    };
+    changes.push(format!(
+        "plugin {MULE_MAVEN_PLUGIN_GROUP}:{MULE_MAVEN_PLUGIN}: added '{version_value}'"
+    ));
+    Ok((apply_edits(&content, vec![edit]), changes))
+}
+
+/// `maven_plugin` section of the migration config.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct MavenPluginConfig {
+    /// Edits of the mule-maven-plugin `<configuration>`, in order.
+    #[serde(default)]
+    pub configuration: Vec<ConfigurationEdit>,
+    /// Runtime settings of the CloudHub, CloudHub 2.0 and Runtime Fabric
+    /// deployment blocks in the plugin `<configuration>`.
+    #[serde(default)]
+    pub deployment: DeploymentConfig,
+}
+
+/// Deployment blocks of the plugin `<configuration>` whose runtime settings
+/// `maven_plugin.deployment` updates.
+pub const DEPLOYMENT_BLOCKS: [&str; 3] = [
+    "cloudHubDeployment",
+    "cloudhub2Deployment",
+    "runtimeFabricDeployment",
+];
+
+/// `maven_plugin.deployment` section of the migration config. Values may
+/// reference template variables, e.g. `{{app_runtime_version}}`.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DeploymentConfig {
+    /// `<muleVersion>` of the deployment blocks, e.g. `{{app_runtime_version}}`.
+    #[serde(default)]
+    pub mule_version: Option<String>,
+    /// `<javaVersion>` of the deployment blocks, e.g. `"17"`.
+    #[serde(default)]
+    pub java_version: Option<String>,
+}
+
+impl DeploymentConfig {
+    /// The element names and values to set, in document order.
+    pub fn elements(&self) -> Vec<(&'static str, &str)> {
+        [
+            ("muleVersion", &self.mule_version),
+            ("javaVersion", &self.java_version),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| Some((name, value.as_deref()?)))
+        .collect()
+    }
+}
+
+/// An edit of the mule-maven-plugin `<configuration>`. Elements are
+/// addressed by a `/`-separated path of element names below
+/// `<configuration>`, e.g. `sharedLibraries`; missing elements on the path
+/// are created.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(tag = "action", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ConfigurationEdit {
+    /// Sets the text of the element at `path`, e.g. `classifier`.
+    Set { path: String, value: String },
+    /// Inserts an XML snippet as the last child of the element at `parent`
+    /// (empty for `<configuration>` itself), unless an identical element is
+    /// already there.
+    Insert {
+        #[serde(default)]
+        parent: String,
+        xml: String,
+    },
+}
+
+fn path_steps(path: &str) -> Vec<&str> {
+    path.split('/').filter(|s| !s.is_empty()).collect()
+}
+
+/// Element names and texts of `node` and its descendants, ignoring
+/// formatting, to tell whether two elements are the same.
+fn signature(node: Node) -> Vec<(String, String)> {
+    node.descendants()
+        .filter(|n| n.is_element())
+        .map(|n| {
+            let text: String = n
+                .children()
+                .filter(|c| c.is_text())
+                .filter_map(|c| c.text())
+                .collect();
+            (n.tag_name().name().to_string(), text.trim().to_string())
+        })
+        .collect()
+}
+
+/// Wraps `inner` in new elements named by `steps`, outermost first.
+fn nest(steps: &[&str], inner: String, unit: &str) -> String {
+    steps.iter().rev().fold(inner, |inner, name| {
+        let body = inner.replace('\n', &format!("\n{unit}"));
+        format!("<{name}>\n{unit}{body}\n</{name}>")
+    })
+}
+
+/// The plugin declaration whose `<configuration>` is edited: the first one
+/// with a configuration, else the first one.
+fn configured_plugin<'a, 'input>(doc: &'a Document<'input>) -> Option<Node<'a, 'input>> {
+    let plugins: Vec<Node> = artifact_declarations(doc.root_element())
+        .into_iter()
+        .filter(|d| {
+            d.tag_name().name() == "plugin"
+                && child_text(*d, "groupId") == Some(MULE_MAVEN_PLUGIN_GROUP)
+                && child_text(*d, "artifactId") == Some(MULE_MAVEN_PLUGIN)
+        })
+        .collect();
+    plugins
+        .iter()
+        .find(|p| child_element(**p, "configuration").is_some())
+        .or(plugins.first())
+        .copied()
+}
+
+/// Applies one edit, returning the new content and its description, if the
+/// edit changed anything.
+fn apply_configuration_edit(
+    content: &str,
+    edit: &ConfigurationEdit,
+) -> Result<Option<(String, String)>, roxmltree::Error> {
+    let doc = Document::parse(content)?;
+    let unit = indent_unit(content, &doc);
+    let Some(plugin) = configured_plugin(&doc) else {
+        return Ok(None);
+    };
+    let (target, leaf) = match edit {
+        ConfigurationEdit::Set { path, value } => {
+            let steps = path_steps(path);
+            let leaf = format!(
+                "<{0}>{1}</{0}>",
+                steps.last().unwrap_or(&""),
+                escape_text(value)
+            );
+            (steps, leaf)
+        }
+        ConfigurationEdit::Insert { parent, xml } => (path_steps(parent), xml.trim().to_string()),
+    };
+    // Walk down from <plugin> as far as the elements exist
+    let mut path = vec!["configuration"];
+    path.extend(match edit {
+        ConfigurationEdit::Set { .. } => &target[..target.len().saturating_sub(1)],
+        ConfigurationEdit::Insert { .. } => &target[..],
+    });
+    let mut node = plugin;
+    let mut depth = 0;
+    while let Some(child) = path.get(depth).and_then(|name| child_element(node, name)) {
+        node = child;
+        depth += 1;
+    }
+    let label = if target.is_empty() {
+        "configuration".to_string()
+    } else {
+        target.join("/")
+    };
+
+    if depth == path.len() {
+        match edit {
+            ConfigurationEdit::Set { value, .. } => {
+                let name = target.last().copied().unwrap_or_default();
+                let existing: Vec<Node> = child_elements(node, name).collect();
+                if let Some(element) = existing.first() {
+                    let old = element.text().unwrap_or("").trim();
+                    if old == value || element.children().any(|c| c.is_element()) {
+                        return Ok(None);
+                    }
+                    let text_edit = set_element_text(content, *element, value);
+                    let change =
+                        format!("{MULE_MAVEN_PLUGIN} configuration {label}: '{old}' -> '{value}'");
+                    return Ok(Some((apply_edits(content, vec![text_edit]), change)));
+                }
+            }
+            ConfigurationEdit::Insert { .. } => {
+                let snippet = Document::parse(&leaf)?;
+                let wanted = signature(snippet.root_element());
+                if node
+                    .children()
+                    .filter(|c| c.is_element())
+                    .any(|c| signature(c) == wanted)
+                {
+                    return Ok(None);
+                }
+            }
+        }
+    }
+    let text_edit = insert_child(content, unit, node, &nest(&path[depth..], leaf, unit));
+    let change = match edit {
+        ConfigurationEdit::Set { value, .. } => {
+            format!("{MULE_MAVEN_PLUGIN} configuration {label}: added '{value}'")
+        }
+        ConfigurationEdit::Insert { xml, .. } => {
+            let name = Document::parse(xml)?
+                .root_element()
+                .tag_name()
+                .name()
+                .to_string();
+            format!("{MULE_MAVEN_PLUGIN} configuration {label}: inserted <{name}>")
+        }
+    };
+    Ok(Some((apply_edits(content, vec![text_edit]), change)))
+}
+
+/// Applies the configured edits to the `<configuration>` of the
+/// mule-maven-plugin declared in pom.xml content, creating the elements the
+/// edits need. Projects that do not declare the plugin are left unchanged.
+///
+/// Returns the new content and a description of each change.
+pub fn plan_configuration_update(
+    content: &str,
+    edits: &[ConfigurationEdit],
+) -> Result<(String, Vec<String>), roxmltree::Error> {
+    let mut content = content.to_string();
+    let mut changes = Vec::new();
+    // Each edit sees the elements the previous ones created
+    for edit in edits {
+        if let Some((updated, change)) = apply_configuration_edit(&content, edit)? {
+            content = updated;
+            changes.push(change);
+        }
+    }
+    Ok((content, changes))
+}
+
+/// Sets the `elements` (name and value, e.g. `muleVersion` and `4.9.4`) of
+/// every [`DEPLOYMENT_BLOCKS`] block in the mule-maven-plugin declarations of
+/// pom.xml content, including those in profiles, and adds the ones a block
+/// lacks. Values that reference a property (`${...}`) are left to the
+/// `<properties>` update.
+///
+/// Returns the new content and a description of each change.
+pub fn plan_deployment_update(
+    content: &str,
+    elements: &[(&str, &str)],
+) -> Result<(String, Vec<String>), roxmltree::Error> {
+    if elements.is_empty() {
+        return Ok((content.to_string(), Vec::new()));
+    }
+    let doc = Document::parse(content)?;
+    let unit = indent_unit(content, &doc);
+    let blocks = doc.descendants().filter(|n| {
+        n.is_element()
+            && DEPLOYMENT_BLOCKS.contains(&n.tag_name().name())
+            && n.parent_element()
+                .filter(|c| c.tag_name().name() == "configuration")
+                .and_then(|c| c.parent_element())
+                .is_some_and(|p| {
+                    p.tag_name().name() == "plugin"
+                        && child_text(p, "groupId") == Some(MULE_MAVEN_PLUGIN_GROUP)
+                        && child_text(p, "artifactId") == Some(MULE_MAVEN_PLUGIN)
+                })
+    });
+    let mut edits = Vec::new();
+    let mut changes = Vec::new();
+    for block in blocks {
+        let profile = block
+            .ancestors()
+            .find(|a| a.tag_name().name() == "profile")
+            .and_then(|p| child_text(p, "id"));
+        let label = match profile {
+            Some(id) => format!("{} (profile {id})", block.tag_name().name()),
+            None => block.tag_name().name().to_string(),
+        };
+        let mut missing = Vec::new();
+        for (name, value) in elements {
+            let Some(element) = child_element(block, name) else {
+                missing.push(format!("<{name}>{}</{name}>", escape_text(value)));
+                changes.push(format!(
+                    "{MULE_MAVEN_PLUGIN} {label} {name}: added '{value}'"
+                ));
+                continue;
+            };
+            let old = element.text().unwrap_or("").trim();
+            if old == *value {
+                continue;
+            }
+            if old.contains("${") {
+                log::debug!("Leaving {label} {name} '{old}', it references a property");
+                continue;
+            }
+            edits.push(set_element_text(content, element, value));
+            changes.push(format!(
+                "{MULE_MAVEN_PLUGIN} {label} {name}: '{old}' -> '{value}'"
+            ));
+        }
+        if !missing.is_empty() {
+            edits.push(insert_child(content, unit, block, &missing.join("\n")));
+        }
+    }
+    Ok((apply_edits(content, edits), changes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inline_plugin_version_is_updated() {
+        let pom = r#"<project>
+  <build>
+    <plugins>
+      <plugin>
+        <groupId>org.mule.tools.maven</groupId>
+        <artifactId>mule-maven-plugin</artifactId>
+        <version>3.8.0</version>
+      </plugin>
+    </plugins>
+  </build>
+</project>"#;
+        let (content, changes) = plan_mule_maven_plugin_update(pom, "4.3.1", true).unwrap();
+        assert_eq!(content, pom.replace("3.8.0", "4.3.1"));
+        assert_eq!(
+            changes,
+            ["plugin org.mule.tools.maven:mule-maven-plugin: '3.8.0' -> '4.3.1'"]
+        );
+    }
+
+    #[test]
+    fn test_configuration_edits_create_and_update_elements() {
+        let pom = r#"<project>
+  <build>
+    <plugins>
+      <plugin>
+        <groupId>org.mule.tools.maven</groupId>
+        <artifactId>mule-maven-plugin</artifactId>
+        <configuration>
+          <classifier>mule-application</classifier>
+        </configuration>
+      </plugin>
+    </plugins>
+  </build>
+</project>"#;
+        let edits: Vec<ConfigurationEdit> = serde_yaml::from_str(
+            r#"
+- action: set
+  path: classifier
+  value: mule-application-light-package
+- action: insert
+  parent: sharedLibraries
+  xml: |
+    <sharedLibrary>
+      <groupId>com.oracle</groupId>
+      <artifactId>ojdbc8</artifactId>
+    </sharedLibrary>
+"#,
+        )
+        .unwrap();
+        let (content, changes) = plan_configuration_update(pom, &edits).unwrap();
+        assert_eq!(
+            content,
+            r#"<project>
+  <build>
+    <plugins>
+      <plugin>
+        <groupId>org.mule.tools.maven</groupId>
+        <artifactId>mule-maven-plugin</artifactId>
+        <configuration>
+          <classifier>mule-application-light-package</classifier>
+          <sharedLibraries>
+            <sharedLibrary>
+              <groupId>com.oracle</groupId>
+              <artifactId>ojdbc8</artifactId>
+            </sharedLibrary>
+          </sharedLibraries>
+        </configuration>
+      </plugin>
+    </plugins>
+  </build>
+</project>"#
+        );
+        assert_eq!(
+            changes,
+            [
+                "mule-maven-plugin configuration classifier: 'mule-application' -> 'mule-application-light-package'",
+                "mule-maven-plugin configuration sharedLibraries: inserted <sharedLibrary>"
+            ]
+        );
+        let (_, changes) = plan_configuration_update(&content, &edits).unwrap();
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn test_missing_plugin_is_added() {
+        let pom =
+            "<project>\n  <packaging>mule-application</packaging>\n  <profiles/>\n</project>\n";
+        let (content, changes) = plan_mule_maven_plugin_update(pom, "4.3.1", true).unwrap();
+        assert_eq!(
+            content,
+            "<project>\n  <packaging>mule-application</packaging>\n  <build>\n    <plugins>\n      <plugin>\n        <groupId>org.mule.tools.maven</groupId>\n        <artifactId>mule-maven-plugin</artifactId>\n        <version>4.3.1</version>\n        <extensions>true</extensions>\n      </plugin>\n    </plugins>\n  </build>\n  <profiles/>\n</project>\n"
+        );
+        assert_eq!(
+            changes,
+            ["plugin org.mule.tools.maven:mule-maven-plugin: added '4.3.1'"]
+        );
+        // Idempotent, and never added without add_missing or to other packagings
+        assert!(plan_mule_maven_plugin_update(&content, "4.3.1", true)
+            .unwrap()
+            .1
+            .is_empty());
+        assert!(plan_mule_maven_plugin_update(pom, "4.3.1", false)
+            .unwrap()
+            .1
+            .is_empty());
+        let jar = pom.replace("mule-application", "jar");
+        assert!(plan_mule_maven_plugin_update(&jar, "4.3.1", true)
+            .unwrap()
+            .1
+            .is_empty());
+    }
+
+    #[test]
+    fn test_deployment_blocks_are_updated() {
+        let pom = r#"<project>
+  <build>
+    <plugins>
+      <plugin>
+        <groupId>org.mule.tools.maven</groupId>
+        <artifactId>mule-maven-plugin</artifactId>
+        <configuration>
+          <cloudHubDeployment>
+            <muleVersion>4.4.0</muleVersion>
+            <javaVersion>${java.version}</javaVersion>
+          </cloudHubDeployment>
+        </configuration>
+      </plugin>
+    </plugins>
+  </build>
+  <profiles>
+    <profile>
+      <id>rtf</id>
+      <build>
+        <plugins>
+          <plugin>
+            <groupId>org.mule.tools.maven</groupId>
+            <artifactId>mule-maven-plugin</artifactId>
+            <configuration>
+              <runtimeFabricDeployment>
+                <target>prod</target>
+              </runtimeFabricDeployment>
+            </configuration>
+          </plugin>
+        </plugins>
+      </build>
+    </profile>
+  </profiles>
+</project>"#;
+        let elements = [("muleVersion", "4.9.4"), ("javaVersion", "17")];
+        let (content, changes) = plan_deployment_update(pom, &elements).unwrap();
+        assert_eq!(
+            content,
+            pom.replace("4.4.0", "4.9.4").replace(
+                "<target>prod</target>\n",
+                "<target>prod</target>\n                <muleVersion>4.9.4</muleVersion>\n                <javaVersion>17</javaVersion>\n"
+            )
+        );
+        assert_eq!(
+            changes,
+            [
+                "mule-maven-plugin cloudHubDeployment muleVersion: '4.4.0' -> '4.9.4'",
+                "mule-maven-plugin runtimeFabricDeployment (profile rtf) muleVersion: added '4.9.4'",
+                "mule-maven-plugin runtimeFabricDeployment (profile rtf) javaVersion: added '17'",
+            ]
+        );
+        assert!(plan_deployment_update(&content, &elements)
+            .unwrap()
+            .1
+            .is_empty());
+    }
+}
@@ -0,0 +1,393 @@
+use crate::config::MigrationConfig;
+use crate::error::MigrateError;
+use crate::file_ops::{self, FileFilter};
+use crate::steps::{FilePlan, MigrationStep, StepContext};
+use crate::xml::{apply_edits, escape_attr, TextEdit};
+use crate::{catalog, munit};
+use roxmltree::Document;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Directory holding the Mule configuration files of a project.
+pub const MULE_CONFIG_DIR: &str = "src/main/mule";
+
+/// Structural edit of an attribute on Mule config elements, matched by
+/// namespace URI so it works whatever prefix a file binds the namespace to.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct MuleXmlRule {
+    /// Namespace URI of the element, e.g. `http://www.mulesoft.org/schema/mule/http`.
+    pub namespace: String,
+    /// Local name of the element, or `*` for every element in the namespace.
+    pub element: String,
+    /// Local name of the attribute, e.g. `path` or `schemaLocation`.
+    pub attribute: String,
+    /// Only replace this substring of the attribute value (e.g. one URL in a
+    /// `schemaLocation` list) instead of the whole value.
+    #[serde(default)]
+    pub from: Option<String>,
+    /// New attribute value, or the replacement for `from`.
+    pub value: String,
+}
+
+/// Returns true if `path` (relative to the project root) is a Mule config file.
+pub fn is_mule_config(relative: &Path) -> bool {
+    relative.starts_with(MULE_CONFIG_DIR) && relative.extension().is_some_and(|e| e == "xml")
+}
+
+const MULE_CORE_NS: &str = "http://www.mulesoft.org/schema/mule/core";
+const XSI_NS: &str = "http://www.w3.org/2001/XMLSchema-instance";
+
+/// Checks the schemas a Mule config declares: `xsi:schemaLocation` must pair
+/// each namespace with an XSD URL, and every namespace used by an element
+/// must have one. Content that is not a Mule config has no problems.
+///
+/// Returns a description of each problem.
+pub fn schema_problems(content: &str) -> Result<Vec<String>, roxmltree::Error> {
+    let doc = Document::parse(content)?;
+    let root = doc.root_element();
+    if root.tag_name().namespace() != Some(MULE_CORE_NS) {
+        return Ok(Vec::new());
+    }
+    let mut problems = Vec::new();
+    let tokens: Vec<&str> = root
+        .attribute((XSI_NS, "schemaLocation"))
+        .unwrap_or("")
+        .split_whitespace()
+        .collect();
+    if !tokens.len().is_multiple_of(2) {
+        problems.push("xsi:schemaLocation has an odd number of entries".to_string());
+    }
+    let locations: Vec<(&str, &str)> = tokens.chunks_exact(2).map(|p| (p[0], p[1])).collect();
+    for (namespace, location) in &locations {
+        if !location.ends_with(".xsd") {
+            problems.push(format!(
+                "schema location '{location}' of {namespace} is not an XSD"
+            ));
+        }
+    }
+    let mut undeclared: Vec<&str> = doc
+        .descendants()
+        .filter_map(|n| n.tag_name().namespace())
+        .filter(|ns| !locations.iter().any(|(declared, _)| declared == ns))
+        .collect();
+    undeclared.sort();
+    undeclared.dedup();
+    problems.extend(
+        undeclared
+            .into_iter()
+            .map(|ns| format!("no schema location for namespace {ns}")),
+    );
+    Ok(problems)
+}
+
+/// Checks XML content that replacements produced from `before`: it must
+/// still be well-formed and, with `schemas`, not have new
+/// [schema problems](schema_problems). Content that did not parse before is
+/// not checked.
+///
+/// Returns a description of the first new problem.
+pub fn check_replaced_xml(before: &str, after: &str, schemas: bool) -> Option<String> {
+    let old_problems = schema_problems(before).ok()?;
+    let new_problems = match schema_problems(after) {
+        Ok(problems) => problems,
+        Err(e) => return Some(format!("not well-formed XML: {e}")),
+    };
+    if !schemas {
+        return None;
+    }
+    new_problems.into_iter().find(|p| !old_problems.contains(p))
+}
+
+/// Common prefix of the namespaces of the runtime and MuleSoft connectors.
+pub const MULE_SCHEMA_BASE: &str = "http://www.mulesoft.org/schema/mule/";
+
+/// [`MULE_SCHEMA_BASE`] without the scheme, which schema URLs may vary.
+const MULE_SCHEMA_PATH: &str = "www.mulesoft.org/schema/mule/";
+
+/// Namespaces (below [`MULE_SCHEMA_BASE`]) of schemas shipped with the
+/// runtime and MUnit rather than with a connector.
+pub const RUNTIME_SCHEMAS: &[&str] = &["core", "ee/core", "batch", "tls", "munit", "munit-tools"];
+
+/// Points the `xsi:schemaLocation` entries of the `known` namespaces (names
+/// below [`MULE_SCHEMA_BASE`]) to the `current` schema, which Mule 4
+/// resolves against the runtime and connector versions the app depends on,
+/// instead of a pinned version such as `3.9`.
+///
+/// Returns the new content and a description of each rewritten location.
+pub fn plan_schema_location_update(
+    content: &str,
+    known: &[&str],
+) -> Result<(String, Vec<String>), roxmltree::Error> {
+    let doc = Document::parse(content)?;
+    let Some(attr) = doc
+        .root_element()
+        .attributes()
+        .find(|a| a.namespace() == Some(XSI_NS) && a.name() == "schemaLocation")
+    else {
+        return Ok((content.to_string(), Vec::new()));
+    };
+    let range = attr.range_value();
+    let mut raw = content[range.clone()].to_string();
+    let mut changes = Vec::new();
+    let tokens: Vec<&str> = attr.value().split_whitespace().collect();
+    for pair in tokens.chunks_exact(2) {
+        let (namespace, location) = (pair[0], pair[1]);
+        let Some(name) = namespace.strip_prefix(MULE_SCHEMA_BASE) else {
+            continue;
+        };
+        if !known.contains(&name) {
+            continue;
+        }
+        // <scheme>://www.mulesoft.org/schema/mule/<name>/<version>/<file>.xsd
+        let Some((prefix, rest)) = location.split_once("://") else {
+            continue;
+        };
+        let Some((version, file)) = rest
+            .strip_prefix(&format!("{MULE_SCHEMA_PATH}{name}/"))
+            .and_then(|r| r.split_once('/'))
+        else {
+            continue;
+        };
+        if version == "current" || file.contains('/') || !file.ends_with(".xsd") {
+            continue;
+        }
+        let new = format!("{prefix}://{MULE_SCHEMA_PATH}{name}/current/{file}");
+        raw = raw.replace(location, &new);
+        changes.push(format!(
+            "schema location of {namespace}: '{location}' -> '{new}'"
+        ));
+    }
+    if changes.is_empty() {
+        return Ok((content.to_string(), changes));
+    }
+    Ok((
+        apply_edits(content, vec![TextEdit { range, text: raw }]),
+        changes,
+    ))
+}
+
+/// Step pointing the schema locations of the Mule configs and MUnit suites
+/// of a project to the current schemas.
+#[derive(Debug, Clone)]
+pub struct SchemaLocationUpdater {
+    project_root: PathBuf,
+    filter: FileFilter,
+    known: Vec<String>,
+}
+
+impl SchemaLocationUpdater {
+    /// Updates the locations of the runtime schemas and of the schemas of
+    /// the built-in and configured connector catalogs, in the files
+    /// `filter` selects.
+    pub fn new<P: AsRef<Path>>(
+        project_root: P,
+        config: &MigrationConfig,
+        filter: FileFilter,
+    ) -> Self {
+        let mut known: Vec<String> = RUNTIME_SCHEMAS.iter().map(|s| s.to_string()).collect();
+        known.extend(
+            catalog::builtin_catalog()
+                .iter()
+                .chain(&config.connector_catalog)
+                .filter_map(|req| req.schema.clone()),
+        );
+        SchemaLocationUpdater {
+            project_root: project_root.as_ref().to_path_buf(),
+            filter,
+            known,
+        }
+    }
+
+    fn plan_file(&self, path: &Path, content: &str) -> Result<(String, Vec<String>), MigrateError> {
+        let known: Vec<&str> = self.known.iter().map(String::as_str).collect();
+        plan_schema_location_update(content, &known).map_err(|source| MigrateError::Xml {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+}
+
+impl MigrationStep for SchemaLocationUpdater {
+    fn name(&self) -> &str {
+        "schema-locations"
+    }
+
+    fn files(&self) -> Vec<PathBuf> {
+        file_ops::walk_files(&self.project_root, &self.filter)
+            .filter(|p| {
+                let relative = p.strip_prefix(&self.project_root).unwrap_or(p);
+                is_mule_config(relative) || munit::is_munit_test(relative)
+            })
+            .collect()
+    }
+
+    fn check_file(&self, path: &Path, content: &str) -> Result<(), MigrateError> {
+        self.plan_file(path, content).map(|_| ())
+    }
+
+    fn plan(&self, ctx: &StepContext) -> Result<Vec<FilePlan>, MigrateError> {
+        let mut plans = Vec::new();
+        for (path, original) in ctx.read_each(self.files()) {
+            match self.plan_file(&path, &original) {
+                Ok((content, changes)) if !changes.is_empty() => {
+                    plans.push(FilePlan::for_file(path, original, content, changes));
+                }
+                Ok(_) => {}
+                Err(e) => log::debug!("{e}"),
+            }
+        }
+        Ok(plans)
+    }
+}
+
+/// Applies the rules to Mule config content. Existing attributes are edited
+/// in place; missing ones are not added.
+///
+/// Returns the new content and a description of each changed attribute.
+pub fn plan_mule_xml_update(
+    content: &str,
+    rules: &[MuleXmlRule],
+) -> Result<(String, Vec<String>), roxmltree::Error> {
+    let doc = Document::parse(content)?;
+    let mut edits = Vec::new();
+    let mut changes = Vec::new();
+    for node in doc.descendants().filter(|n| n.is_element()) {
+        let tag = node.tag_name();
+        for rule in rules {
+            if tag.namespace() != Some(rule.namespace.as_str())
+                || (rule.element != "*" && tag.name() != rule.element)
+            {
+                continue;
+            }
+            let Some(attr) = node.attributes().find(|a| a.name() == rule.attribute) else {
+                continue;
+            };
+            let range = attr.range_value();
+            let raw = &content[range.clone()];
+            let new_raw = match &rule.from {
+                Some(from) => raw.replace(&escape_attr(from), &escape_attr(&rule.value)),
+                None => escape_attr(&rule.value),
+            };
+            if new_raw == raw {
+                continue;
+            }
+            let qname = &content[node.range().start + 1..]
+                .split(|c: char| c.is_whitespace() || c == '/' || c == '>')
+                .next()
+                .unwrap_or(tag.name());
+            changes.push(format!(
+                "<{qname}> {}: '{raw}' -> '{new_raw}'",
+                rule.attribute
+            ));
+            edits.push(TextEdit {
+                range,
+                text: new_raw,
+            });
+        }
+    }
+    Ok((apply_edits(content, edits), changes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HTTP_NS: &str = "http://www.mulesoft.org/schema/mule/http";
+
+    fn rule(element: &str, attribute: &str, from: Option<&str>, value: &str) -> MuleXmlRule {
+        MuleXmlRule {
+            namespace: HTTP_NS.to_string(),
+            element: element.to_string(),
+            attribute: attribute.to_string(),
+            from: from.map(str::to_string),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_rules_match_namespace_not_prefix() {
+        let xml = r#"<mule xmlns="http://www.mulesoft.org/schema/mule/core"
+      xmlns:h="http://www.mulesoft.org/schema/mule/http">
+  <!-- listener -->
+  <flow name="api">
+    <h:listener config-ref="HTTP_Listener" path="/api/*"/>
+  </flow>
+  <flow name="other"><listener path="/api/*"/></flow>
+</mule>"#;
+        let (updated, changes) =
+            plan_mule_xml_update(xml, &[rule("listener", "path", None, "/v2/*")]).unwrap();
+        assert_eq!(changes, vec!["<h:listener> path: '/api/*' -> '/v2/*'"]);
+        assert_eq!(updated, xml.replacen("/api/*", "/v2/*", 1));
+    }
+
+    #[test]
+    fn test_rule_with_from_replaces_substring() {
+        let xml = r#"<mule xmlns="http://www.mulesoft.org/schema/mule/core" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"
+      xsi:schemaLocation="http://www.mulesoft.org/schema/mule/core http://www.mulesoft.org/schema/mule/core/current/mule.xsd
+        http://www.mulesoft.org/schema/mule/http http://www.mulesoft.org/schema/mule/http/1.0/mule-http.xsd"/>"#;
+        let mut core = rule(
+            "mule",
+            "schemaLocation",
+            Some("http/1.0/mule-http.xsd"),
+            "http/current/mule-http.xsd",
+        );
+        core.namespace = "http://www.mulesoft.org/schema/mule/core".to_string();
+        let (updated, changes) = plan_mule_xml_update(xml, &[core]).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(
+            updated,
+            xml.replace("http/1.0/mule-http.xsd", "http/current/mule-http.xsd")
+        );
+    }
+
+    #[test]
+    fn test_check_replaced_xml() {
+        let before = r#"<mule xmlns="http://www.mulesoft.org/schema/mule/core"
+      xmlns:http="http://www.mulesoft.org/schema/mule/http"
+      xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"
+      xsi:schemaLocation="http://www.mulesoft.org/schema/mule/core http://www.mulesoft.org/schema/mule/core/current/mule.xsd
+http://www.mulesoft.org/schema/mule/http http://www.mulesoft.org/schema/mule/http/current/mule-http.xsd">
+  <http:listener-config name="api"/>
+</mule>"#;
+        let version_bump = before.replace("name=\"api\"", "name=\"orders-api\"");
+        assert_eq!(check_replaced_xml(before, &version_bump, true), None);
+
+        let unclosed = before.replace(
+            "<http:listener-config name=\"api\"/>",
+            "<http:listener-config>",
+        );
+        assert!(check_replaced_xml(before, &unclosed, false)
+            .unwrap()
+            .starts_with("not well-formed XML"));
+
+        let mangled = before.replace("mule-http.xsd", "mule-http");
+        assert_eq!(check_replaced_xml(before, &mangled, false), None);
+        assert_eq!(
+            check_replaced_xml(before, &mangled, true),
+            Some("schema location 'http://www.mulesoft.org/schema/mule/http/current/mule-http' of http://www.mulesoft.org/schema/mule/http is not an XSD".to_string())
+        );
+    }
+
+    #[test]
+    fn test_schema_locations_point_to_current() {
+        let content = r#"<mule xmlns="http://www.mulesoft.org/schema/mule/core"
+      xmlns:http="http://www.mulesoft.org/schema/mule/http"
+      xmlns:acme="http://www.mulesoft.org/schema/mule/acme"
+      xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"
+      xsi:schemaLocation="http://www.mulesoft.org/schema/mule/core http://www.mulesoft.org/schema/mule/core/3.9/mule.xsd
+http://www.mulesoft.org/schema/mule/http https://www.mulesoft.org/schema/mule/http/current/mule-http.xsd
+http://www.mulesoft.org/schema/mule/acme http://www.mulesoft.org/schema/mule/acme/1.2/mule-acme.xsd">
+</mule>"#;
+        let (out, changes) = plan_schema_location_update(content, &["core", "http"]).unwrap();
+        assert_eq!(
+            changes,
+            vec!["schema location of http://www.mulesoft.org/schema/mule/core: 'http://www.mulesoft.org/schema/mule/core/3.9/mule.xsd' -> 'http://www.mulesoft.org/schema/mule/core/current/mule.xsd'"]
+        );
+        assert!(out
+            .contains("core/current/mule.xsd\nhttp://www.mulesoft.org/schema/mule/http https://"));
+        // Namespaces not in the catalog are left alone
+        assert!(out.contains("acme/1.2/mule-acme.xsd"));
+    }
+}
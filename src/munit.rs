@@ -0,0 +1,133 @@
+use crate::mule_xml::MuleXmlRule;
+use crate::xml::{
+    apply_edits, artifact_declarations, child_element, child_text, edit_declared_versions,
+    set_element_text,
+};
+use roxmltree::Document;
+use std::path::Path;
+
+/// Directory holding the MUnit test suites of a project.
+pub const MUNIT_TEST_DIR: &str = "src/test/munit";
+
+/// Namespace URI of the MUnit runner elements (`munit:config`, `munit:test`).
+pub const MUNIT_NAMESPACE: &str = "http://www.mulesoft.org/schema/mule/munit";
+
+/// Returns true if `path` (relative to the project root) is an MUnit suite.
+pub fn is_munit_test(relative: &Path) -> bool {
+    relative.starts_with(MUNIT_TEST_DIR) && relative.extension().is_some_and(|e| e == "xml")
+}
+
+/// MUnit artifacts are published under `com.mulesoft.munit` (runner, tools)
+/// and `com.mulesoft.munit.tools` (the Maven plugin).
+fn is_munit_artifact(group_id: &str) -> bool {
+    group_id == "com.mulesoft.munit" || group_id == "com.mulesoft.munit.tools"
+}
+
+/// Aligns the MUnit declarations of pom.xml with the target versions:
+/// hard-coded `munit-runner`/`munit-tools`/`munit-maven-plugin` versions are
+/// set to `munit_version`, and a hard-coded `<runtimeVersion>` in the plugin
+/// configuration is set to `runtime_version`. Property references are left
+/// to the `<properties>` update.
+///
+/// Returns the new content and a description of each change.
+pub fn plan_munit_pom_update(
+    content: &str,
+    munit_version: &str,
+    runtime_version: &str,
+) -> Result<(String, Vec<String>), roxmltree::Error> {
+    let (content, mut changes) = edit_declared_versions(content, "munit", |group_id, _, _| {
+        is_munit_artifact(group_id).then(|| munit_version.to_string())
+    })?;
+
+    let doc = Document::parse(&content)?;
+    let mut edits = Vec::new();
+    for plugin in artifact_declarations(doc.root_element()) {
+        if plugin.tag_name().name() != "plugin"
+            || child_text(plugin, "artifactId") != Some("munit-maven-plugin")
+        {
+            continue;
+        }
+        let Some(runtime) =
+            child_element(plugin, "configuration").and_then(|c| child_element(c, "runtimeVersion"))
+        else {
+            continue;
+        };
+        let old = runtime.text().unwrap_or("").trim();
+        if old.starts_with("${") || old == runtime_version {
+            continue;
+        }
+        edits.push(set_element_text(&content, runtime, runtime_version));
+        changes.push(format!(
+            "munit-maven-plugin runtimeVersion: '{old}' -> '{runtime_version}'"
+        ));
+    }
+    Ok((apply_edits(&content, edits), changes))
+}
+
+/// Attribute rules applied to the MUnit suites: `munit:config`
+/// `minMuleVersion` follows the target `minMuleVersion` of the application.
+pub fn test_rules(min_mule_version: &str) -> Vec<MuleXmlRule> {
+    vec![MuleXmlRule {
+        namespace: MUNIT_NAMESPACE.to_string(),
+        element: "config".to_string(),
+        attribute: "minMuleVersion".to_string(),
+        from: None,
+        value: min_mule_version.to_string(),
+    }]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mule_xml::plan_mule_xml_update;
+
+    #[test]
+    fn test_plan_munit_pom_update() {
+        let pom = r#"<project>
+  <dependencies>
+    <dependency>
+      <groupId>com.mulesoft.munit</groupId>
+      <artifactId>munit-runner</artifactId>
+      <version>2.3.0</version>
+      <classifier>mule-plugin</classifier>
+    </dependency>
+    <dependency>
+      <groupId>com.mulesoft.munit</groupId>
+      <artifactId>munit-tools</artifactId>
+      <version>${munit.version}</version>
+      <classifier>mule-plugin</classifier>
+    </dependency>
+  </dependencies>
+  <build><plugins><plugin>
+    <groupId>com.mulesoft.munit.tools</groupId>
+    <artifactId>munit-maven-plugin</artifactId>
+    <version>2.3.0</version>
+    <configuration>
+      <runtimeVersion>4.4.0</runtimeVersion>
+    </configuration>
+  </plugin></plugins></build>
+</project>"#;
+        let (updated, changes) = plan_munit_pom_update(pom, "3.4.0", "4.9.4").unwrap();
+        assert_eq!(changes.len(), 3);
+        assert!(updated.contains("<version>3.4.0</version>\n      <classifier>"));
+        assert!(updated.contains("<version>${munit.version}</version>"));
+        assert!(updated.contains("<version>3.4.0</version>\n    <configuration>"));
+        assert!(updated.contains("<runtimeVersion>4.9.4</runtimeVersion>"));
+
+        let (_, changes) = plan_munit_pom_update(&updated, "3.4.0", "4.9.4").unwrap();
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn test_munit_config_min_mule_version() {
+        let suite = r#"<mule xmlns:munit="http://www.mulesoft.org/schema/mule/munit">
+  <munit:config name="orders-test-suite.xml" minMuleVersion="4.4.0"/>
+</mule>"#;
+        let (updated, changes) = plan_mule_xml_update(suite, &test_rules("4.9.0")).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert!(updated.contains(r#"minMuleVersion="4.9.0""#));
+        assert!(is_munit_test(Path::new(
+            "src/test/munit/orders-test-suite.xml"
+        )));
+    }
+}
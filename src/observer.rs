@@ -0,0 +1,31 @@
+use std::path::Path;
+
+/// Something that happened during a migration, reported as it happens.
+#[derive(Debug, Clone, Copy)]
+pub enum MigrationEvent<'a> {
+    /// A migration step started, e.g. `pom.xml` or `replacements`.
+    StepStarted { step: &'a str },
+    /// A file was read and checked for changes.
+    FileScanned { path: &'a Path },
+    /// A change was planned: a property, JSON field, XML attribute or replacement.
+    PropertyUpdated { description: &'a str },
+    /// A file was written (or, in a dry run, would be written).
+    FileChanged { path: &'a Path },
+    /// An error was recorded in the report.
+    Error { message: &'a str },
+}
+
+/// Receives the events of a migration run, e.g. to drive a progress UI.
+///
+/// Events are delivered synchronously on the migrating thread; an observer
+/// that feeds another thread (a GUI event loop) can forward them through a
+/// channel. Any `FnMut(&MigrationEvent)` closure is an observer.
+pub trait MigrationObserver {
+    fn on_event(&mut self, event: &MigrationEvent);
+}
+
+impl<F: FnMut(&MigrationEvent)> MigrationObserver for F {
+    fn on_event(&mut self, event: &MigrationEvent) {
+        self(event)
+    }
+}
@@ -0,0 +1,180 @@
+use crate::file_ops::read_file;
+use crate::xml::{child_element, child_text, pom_property};
+use roxmltree::Document;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Parents followed at most, guarding against cyclic `relativePath`s.
+const MAX_DEPTH: usize = 10;
+
+/// What to do about version properties pom.xml inherits from a parent pom
+/// instead of declaring them itself.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum InheritedProperties {
+    /// Report that the property must be changed in the parent pom.
+    #[default]
+    Warn,
+    /// Declare the property in pom.xml, overriding the inherited value.
+    Override,
+    /// Do not look at parent poms.
+    Ignore,
+}
+
+/// A property pom.xml inherits from a parent pom.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InheritedProperty {
+    pub name: String,
+    pub value: String,
+    /// The parent pom declaring it.
+    pub source: PathBuf,
+}
+
+/// The `<parent>` coordinates of a pom.
+struct ParentRef {
+    group_id: String,
+    artifact_id: String,
+    version: String,
+    /// `None` if `<relativePath/>` is empty, i.e. the parent is only in a
+    /// repository.
+    relative_path: Option<String>,
+}
+
+fn parent_ref(content: &str) -> Result<Option<ParentRef>, roxmltree::Error> {
+    let doc = Document::parse(content)?;
+    let Some(parent) = child_element(doc.root_element(), "parent") else {
+        return Ok(None);
+    };
+    let relative_path = match child_element(parent, "relativePath") {
+        Some(node) => Some(node.text().unwrap_or("").trim().to_string()).filter(|p| !p.is_empty()),
+        None => Some("../pom.xml".to_string()),
+    };
+    Ok(Some(ParentRef {
+        group_id: child_text(parent, "groupId").unwrap_or("").to_string(),
+        artifact_id: child_text(parent, "artifactId").unwrap_or("").to_string(),
+        version: child_text(parent, "version").unwrap_or("").to_string(),
+        relative_path,
+    }))
+}
+
+/// Directory of the local Maven repository.
+fn local_repository() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+    Some(Path::new(&home).join(".m2").join("repository"))
+}
+
+/// Finds the parent pom the way Maven does: at `relativePath` if the pom
+/// there has the parent's artifactId, else in the local repository.
+fn locate_parent(pom_dir: &Path, parent: &ParentRef) -> Option<(PathBuf, String)> {
+    if let Some(relative) = &parent.relative_path {
+        let mut path = pom_dir.join(relative);
+        if path.is_dir() {
+            path = path.join("pom.xml");
+        }
+        if let Ok(content) = read_file(&path) {
+            let matches = Document::parse(&content).is_ok_and(|doc| {
+                child_text(doc.root_element(), "artifactId") == Some(&parent.artifact_id)
+            });
+            if matches {
+                return Some((path, content));
+            }
+        }
+    }
+    let path = local_repository()?
+        .join(parent.group_id.replace('.', "/"))
+        .join(&parent.artifact_id)
+        .join(&parent.version)
+        .join(format!("{}-{}.pom", parent.artifact_id, parent.version));
+    let content = read_file(&path).ok()?;
+    Some((path, content))
+}
+
+/// Looks up the properties among `names` that the pom at `pom_path` with
+/// `content` does not declare, in its chain of parent poms. Parents that
+/// cannot be found or parsed end the search.
+pub fn inherited_properties(
+    pom_path: &Path,
+    content: &str,
+    names: &[&str],
+) -> Vec<InheritedProperty> {
+    let mut missing: Vec<&str> = names
+        .iter()
+        .copied()
+        .filter(|name| matches!(pom_property(content, name), Ok(None)))
+        .collect();
+    let mut found = Vec::new();
+    let mut dir = pom_path.parent().unwrap_or(Path::new(".")).to_path_buf();
+    let mut current = content.to_string();
+    for _ in 0..MAX_DEPTH {
+        if missing.is_empty() {
+            break;
+        }
+        let Ok(Some(parent)) = parent_ref(&current) else {
+            break;
+        };
+        let Some((path, parent_content)) = locate_parent(&dir, &parent) else {
+            log::debug!(
+                "Parent pom {}:{}:{} not found",
+                parent.group_id,
+                parent.artifact_id,
+                parent.version
+            );
+            break;
+        };
+        missing.retain(|name| match pom_property(&parent_content, name) {
+            Ok(Some(value)) => {
+                found.push(InheritedProperty {
+                    name: name.to_string(),
+                    value,
+                    source: path.clone(),
+                });
+                false
+            }
+            _ => true,
+        });
+        dir = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+        current = parent_content;
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_inherited_properties_follow_relative_path() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("pom.xml"),
+            r#"<project>
+  <parent><groupId>com.acme</groupId><artifactId>corp-parent</artifactId><version>1</version></parent>
+  <artifactId>platform-parent</artifactId>
+  <properties><app.runtime>4.4.0</app.runtime><munit.version>2.3.0</munit.version></properties>
+</project>"#,
+        )
+        .unwrap();
+        fs::create_dir(dir.path().join("app")).unwrap();
+        let app = r#"<project>
+  <parent><groupId>com.acme</groupId><artifactId>platform-parent</artifactId><version>1</version></parent>
+  <artifactId>app</artifactId>
+  <properties><munit.version>3.4.0</munit.version></properties>
+</project>"#;
+        let inherited = inherited_properties(
+            &dir.path().join("app/pom.xml"),
+            app,
+            &["app.runtime", "munit.version", "mule.version"],
+        );
+        assert_eq!(
+            inherited,
+            vec![InheritedProperty {
+                name: "app.runtime".to_string(),
+                value: "4.4.0".to_string(),
+                source: dir.path().join("app/../pom.xml"),
+            }]
+        );
+    }
+}
@@ -0,0 +1,332 @@
+use crate::backup::Snapshot;
+use crate::error::MigrateError;
+use crate::file_ops::read_file;
+use crate::lockfile::content_hash;
+use crate::transaction::Transaction;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Version of the plan file format.
+const PLAN_FORMAT: u32 = 1;
+
+/// Every edit a dry run would make, recorded for review and a later
+/// [`apply_plan`].
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Plan {
+    pub format: u32,
+    /// RFC 3339 timestamp of when the plan was made.
+    pub created_at: String,
+    pub files: Vec<PlannedFile>,
+    /// File moves, made after the edits.
+    #[serde(default)]
+    pub renames: Vec<PlannedRename>,
+    /// Files to remove.
+    #[serde(default)]
+    pub deletes: Vec<PlannedDelete>,
+}
+
+/// The edits of one file, against its content at planning time.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlannedFile {
+    /// Path relative to the project root, `/`-separated.
+    pub path: String,
+    /// Content hash of the file when the plan was made.
+    pub hash: String,
+    pub edits: Vec<PlannedEdit>,
+    /// The file is new; its content is the single edit.
+    #[serde(default)]
+    pub created: bool,
+}
+
+/// A move of the file at `from` to `to`, both relative to the project root
+/// and `/`-separated.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlannedRename {
+    pub from: String,
+    pub to: String,
+    /// Content hash of `from` when the plan was made.
+    pub hash: String,
+}
+
+/// Removal of the file at `path`, relative to the project root and
+/// `/`-separated.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlannedDelete {
+    pub path: String,
+    /// Content hash of the file when the plan was made.
+    pub hash: String,
+}
+
+/// Replacement of the byte span `start..end` (holding `old`) with `new`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlannedEdit {
+    pub start: usize,
+    pub end: usize,
+    pub old: String,
+    pub new: String,
+}
+
+/// Content hash of the file at `path`, if it can be read.
+fn file_hash(path: &Path) -> Option<String> {
+    fs::read(path).ok().map(|content| content_hash(&content))
+}
+
+/// Byte offset of the start of each line, plus the total length.
+fn line_offsets(lines: &[&str]) -> Vec<usize> {
+    let mut offsets = vec![0];
+    for line in lines {
+        offsets.push(offsets.last().unwrap() + line.len());
+    }
+    offsets
+}
+
+/// Line-granular edits turning `original` into `content`.
+fn diff_edits(original: &str, content: &str) -> Vec<PlannedEdit> {
+    let diff = similar::TextDiff::from_lines(original, content);
+    let old = line_offsets(diff.old_slices());
+    let new = line_offsets(diff.new_slices());
+    diff.ops()
+        .iter()
+        .filter(|op| op.tag() != similar::DiffTag::Equal)
+        .map(|op| {
+            let (start, end) = (old[op.old_range().start], old[op.old_range().end]);
+            let (new_start, new_end) = (new[op.new_range().start], new[op.new_range().end]);
+            PlannedEdit {
+                start,
+                end,
+                old: original[start..end].to_string(),
+                new: content[new_start..new_end].to_string(),
+            }
+        })
+        .collect()
+}
+
+impl Plan {
+    /// Records the staged writes, moves and removals of a run on the project at
+    /// `project_root`.
+    pub fn from_transaction(project_root: &Path, tx: &Transaction) -> Plan {
+        let relative = |path: &Path| {
+            path.strip_prefix(project_root)
+                .unwrap_or(path)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/")
+        };
+        let files = tx
+            .writes()
+            .iter()
+            .map(|write| PlannedFile {
+                path: relative(&write.path),
+                hash: content_hash(write.original.as_bytes()),
+                edits: diff_edits(&write.original, &write.content),
+                created: write.created,
+            })
+            .collect();
+        let renames = tx
+            .renames()
+            .iter()
+            .map(|rename| PlannedRename {
+                from: relative(&rename.from),
+                to: relative(&rename.to),
+                hash: file_hash(&rename.from).unwrap_or_default(),
+            })
+            .collect();
+        let deletes = tx
+            .deletes()
+            .iter()
+            .map(|path| PlannedDelete {
+                path: relative(path),
+                hash: file_hash(path).unwrap_or_default(),
+            })
+            .collect();
+        Plan {
+            format: PLAN_FORMAT,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            files,
+            renames,
+            deletes,
+        }
+    }
+
+    /// Writes the plan as pretty-printed JSON.
+    pub fn save(&self, path: &Path) -> Result<(), MigrateError> {
+        let content = serde_json::to_string_pretty(self).expect("Failed to serialize plan");
+        fs::write(path, content).map_err(|source| MigrateError::Write {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// Reads a plan written by [`Plan::save`].
+    pub fn load(path: &Path) -> Result<Plan, MigrateError> {
+        let invalid = |reason: String| MigrateError::Plan {
+            path: path.to_path_buf(),
+            reason,
+        };
+        let plan: Plan =
+            serde_json::from_str(&read_file(path)?).map_err(|e| invalid(e.to_string()))?;
+        if plan.format != PLAN_FORMAT {
+            return Err(invalid(format!("unsupported plan format {}", plan.format)));
+        }
+        Ok(plan)
+    }
+}
+
+/// Applies `edits` to `original`, checking that each span still holds the
+/// planned old text.
+fn apply_planned_edits(original: &str, edits: &[PlannedEdit]) -> Option<String> {
+    let mut content = original.to_string();
+    for edit in edits.iter().rev() {
+        if content.get(edit.start..edit.end) != Some(edit.old.as_str()) {
+            return None;
+        }
+        content.replace_range(edit.start..edit.end, &edit.new);
+    }
+    Some(content)
+}
+
+/// Executes exactly the edits of `plan` on the project at `project_root`,
+/// independent of any config.
+///
+/// Every edited, moved and removed file is checked against its planned
+/// hash, and every move target and new file against the files on disk,
+/// before anything is written, so a project modified since the plan was
+/// made is left untouched.
+/// Returns the changed files.
+///
+/// # Errors
+/// Returns an error if a file changed since planning or a write fails; in
+/// the latter case all written files are rolled back.
+pub fn apply_plan(
+    plan: &Plan,
+    project_root: &Path,
+    snapshot: Option<&Snapshot>,
+) -> Result<Vec<PathBuf>, MigrateError> {
+    let mut tx = Transaction::new();
+    for file in &plan.files {
+        let path = project_root.join(&file.path);
+        if file.created {
+            let content = (!path.exists())
+                .then(|| apply_planned_edits("", &file.edits))
+                .flatten()
+                .ok_or_else(|| MigrateError::PlanOutdated { path: path.clone() })?;
+            tx.stage_new(&path, content);
+            continue;
+        }
+        let original = read_file(&path)?;
+        let content = (content_hash(original.as_bytes()) == file.hash)
+            .then(|| apply_planned_edits(&original, &file.edits))
+            .flatten()
+            .ok_or_else(|| MigrateError::PlanOutdated { path: path.clone() })?;
+        tx.stage(&path, original, content);
+    }
+    for rename in &plan.renames {
+        let (from, to) = (
+            project_root.join(&rename.from),
+            project_root.join(&rename.to),
+        );
+        if file_hash(&from).as_ref() != Some(&rename.hash) || to.exists() {
+            return Err(MigrateError::PlanOutdated { path: from });
+        }
+        tx.stage_rename(from, to);
+    }
+    for delete in &plan.deletes {
+        let path = project_root.join(&delete.path);
+        if file_hash(&path).as_ref() != Some(&delete.hash) {
+            return Err(MigrateError::PlanOutdated { path });
+        }
+        tx.stage_delete(path);
+    }
+    tx.commit(snapshot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_plan_round_trip_and_outdated_files() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let flow = root.join("flow.xml");
+        let original = "<mule>\n  <flow name=\"a\"/>\n  <flow name=\"b\"/>\n</mule>\n";
+        let migrated = "<mule>\n  <flow name=\"a2\"/>\n  <flow name=\"b\"/>\n</mule>\n";
+        fs::write(&flow, original).unwrap();
+        let mut tx = Transaction::new();
+        tx.stage(&flow, original.to_string(), migrated.to_string());
+
+        let plan_path = root.join("plan.json");
+        tx.stage_rename(&flow, root.join("flows/flow.xml"));
+        tx.stage_new(
+            root.join("config.yaml"),
+            "http:\n  port: 8081\n".to_string(),
+        );
+        fs::write(root.join("exchange.json"), "{}").unwrap();
+        tx.stage_delete(root.join("exchange.json"));
+        Plan::from_transaction(root, &tx).save(&plan_path).unwrap();
+        let plan = Plan::load(&plan_path).unwrap();
+        assert_eq!(plan.files[0].path, "flow.xml");
+        assert_eq!(plan.files[0].edits[0].old, "  <flow name=\"a\"/>\n");
+        assert_eq!(plan.renames[0].to, "flows/flow.xml");
+        assert!(plan.files[1].created);
+        assert_eq!(plan.deletes[0].path, "exchange.json");
+
+        fs::write(&flow, original.replace("\"b\"", "\"c\"")).unwrap();
+        assert!(matches!(
+            apply_plan(&plan, root, None),
+            Err(MigrateError::PlanOutdated { .. })
+        ));
+
+        fs::write(&flow, original).unwrap();
+        apply_plan(&plan, root, None).unwrap();
+        assert_eq!(
+            fs::read_to_string(root.join("flows/flow.xml")).unwrap(),
+            migrated
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("config.yaml")).unwrap(),
+            "http:\n  port: 8081\n"
+        );
+        assert!(!root.join("exchange.json").exists());
+    }
+
+    #[test]
+    fn test_apply_plan_refuses_edited_rename_source() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let properties = root.join("mule-app.properties");
+        fs::write(&properties, "http.port=8081\n").unwrap();
+        let mut tx = Transaction::new();
+        tx.stage_rename(&properties, root.join("app.properties"));
+        let plan = Plan::from_transaction(root, &tx);
+
+        fs::write(&properties, "http.port=8082\n").unwrap();
+        assert!(matches!(
+            apply_plan(&plan, root, None),
+            Err(MigrateError::PlanOutdated { .. })
+        ));
+        assert!(properties.is_file());
+        assert!(!root.join("app.properties").exists());
+    }
+
+    #[test]
+    fn test_apply_plan_refuses_edited_removed_file() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let exchange = root.join("exchange.json");
+        fs::write(&exchange, "{}").unwrap();
+        let mut tx = Transaction::new();
+        tx.stage_delete(&exchange);
+        let plan = Plan::from_transaction(root, &tx);
+
+        fs::write(&exchange, r#"{"version": "1.0.1"}"#).unwrap();
+        assert!(matches!(
+            apply_plan(&plan, root, None),
+            Err(MigrateError::PlanOutdated { .. })
+        ));
+        assert!(exchange.is_file());
+    }
+}
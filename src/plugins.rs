@@ -0,0 +1,298 @@
+use crate::config::MigrationConfig;
+use crate::error::MigrateError;
+use crate::steps::{FilePlan, MigrationStep, StepContext};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Prefix of the executables discovered as step plugins.
+pub const PLUGIN_PREFIX: &str = "mule-migrate-step-";
+
+/// Step plugins on the PATH, by name (the executable name without
+/// [`PLUGIN_PREFIX`] and extension). The first one found wins.
+pub fn discover() -> BTreeMap<String, PathBuf> {
+    let mut plugins = BTreeMap::new();
+    let Some(path) = env::var_os("PATH") else {
+        return plugins;
+    };
+    for dir in env::split_paths(&path) {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| s.strip_prefix(PLUGIN_PREFIX));
+            if let Some(name) = name.filter(|_| path.is_file()) {
+                plugins.entry(name.to_string()).or_insert(path);
+            }
+        }
+    }
+    plugins
+}
+
+/// Versions the migration targets, as sent to plugins.
+#[derive(Debug, Serialize)]
+struct TargetVersions<'a> {
+    app_runtime_version: &'a str,
+    mule_maven_plugin_version: &'a str,
+    munit_version: &'a str,
+    min_mule_version: &'a str,
+    java_specification_versions: &'a [String],
+}
+
+/// JSON a plugin receives on stdin.
+#[derive(Debug, Serialize)]
+struct PluginRequest<'a> {
+    project_root: &'a Path,
+    dry_run: bool,
+    target: TargetVersions<'a>,
+    /// Content of the files earlier steps changed, by path relative to the
+    /// project root; other files are read from disk.
+    staged: BTreeMap<String, &'a str>,
+}
+
+/// A whole-file edit returned by a plugin.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PluginEdit {
+    /// Path relative to the project root.
+    path: String,
+    content: String,
+    /// What the edit does, for the summary.
+    description: String,
+}
+
+/// JSON a plugin prints on stdout.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PluginResponse {
+    #[serde(default)]
+    edits: Vec<PluginEdit>,
+}
+
+/// A migration step implemented by an external executable, so steps can be
+/// written in any language.
+///
+/// The executable runs in the project root, reads a JSON request with the
+/// target versions and the content staged by earlier steps from stdin, and
+/// prints `{"edits": [{"path", "content", "description"}]}` to stdout.
+#[derive(Debug, Clone)]
+pub struct ExternalStep<'a> {
+    name: String,
+    program: PathBuf,
+    project_root: PathBuf,
+    config: &'a MigrationConfig,
+    dry_run: bool,
+}
+
+impl<'a> ExternalStep<'a> {
+    /// The plugin `name`: an executable path, or the name of a
+    /// `mule-migrate-step-<name>` executable on the PATH.
+    ///
+    /// # Errors
+    /// Returns an error if no such plugin is installed.
+    pub fn resolve(
+        name: &str,
+        project_root: &Path,
+        config: &'a MigrationConfig,
+        dry_run: bool,
+    ) -> Result<Self, MigrateError> {
+        let program = if name.contains(['/', '\\']) {
+            project_root.join(name)
+        } else {
+            discover()
+                .remove(name)
+                .ok_or_else(|| MigrateError::Plugin {
+                    name: name.to_string(),
+                    reason: format!("no {PLUGIN_PREFIX}{name} executable on the PATH"),
+                })?
+        };
+        Ok(ExternalStep {
+            name: name.to_string(),
+            program,
+            project_root: project_root.to_path_buf(),
+            config,
+            dry_run,
+        })
+    }
+
+    fn error(&self, reason: impl Into<String>) -> MigrateError {
+        MigrateError::Plugin {
+            name: self.name.clone(),
+            reason: reason.into(),
+        }
+    }
+
+    fn request(&self, ctx: &StepContext) -> String {
+        let root = self
+            .project_root
+            .canonicalize()
+            .unwrap_or_else(|_| self.project_root.clone());
+        let request = PluginRequest {
+            project_root: &root,
+            dry_run: self.dry_run,
+            target: TargetVersions {
+                app_runtime_version: &self.config.app_runtime_version,
+                mule_maven_plugin_version: &self.config.mule_maven_plugin_version,
+                munit_version: &self.config.munit_version,
+                min_mule_version: &self.config.mule_artifact.min_mule_version,
+                java_specification_versions: &self.config.mule_artifact.java_specification_versions,
+            },
+            staged: ctx
+                .staged_files()
+                .map(|(path, content)| {
+                    let relative = path.strip_prefix(&self.project_root).unwrap_or(path);
+                    (relative.to_string_lossy().replace('\\', "/"), content)
+                })
+                .collect(),
+        };
+        serde_json::to_string(&request).expect("Failed to serialize plugin request")
+    }
+}
+
+/// Checks that a plugin's edit stays inside the project.
+fn is_project_path(relative: &Path) -> bool {
+    relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+impl MigrationStep for ExternalStep<'_> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn plan(&self, ctx: &StepContext) -> Result<Vec<FilePlan>, MigrateError> {
+        log::info!("Running plugin {} ({})", self.name, self.program.display());
+        let mut child = Command::new(&self.program)
+            .current_dir(&self.project_root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| self.error(e.to_string()))?;
+        let request = self.request(ctx);
+        let stdin = child.stdin.take();
+        // The request is written while the output is drained, so a plugin
+        // writing before it has read all of it cannot block on a full pipe
+        let output = std::thread::scope(|scope| {
+            if let Some(mut stdin) = stdin {
+                scope.spawn(move || {
+                    // A plugin that ignores its input may exit before reading it
+                    let _ = stdin.write_all(request.as_bytes());
+                });
+            }
+            child.wait_with_output()
+        })
+        .map_err(|e| self.error(e.to_string()))?;
+        for line in String::from_utf8_lossy(&output.stderr).lines() {
+            log::debug!("[{}] {line}", self.name);
+        }
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let detail = stderr.lines().last().unwrap_or_default();
+            return Err(self.error(match output.status.code() {
+                Some(code) => format!("exited with status {code}: {detail}"),
+                None => "terminated by a signal".to_string(),
+            }));
+        }
+        let response: PluginResponse = serde_json::from_slice(&output.stdout)
+            .map_err(|e| self.error(format!("invalid response: {e}")))?;
+        let mut plans = Vec::new();
+        for edit in response.edits {
+            if !is_project_path(Path::new(&edit.path)) {
+                return Err(self.error(format!("edit of '{}' is outside the project", edit.path)));
+            }
+            let path = self.project_root.join(&edit.path);
+            // Plugins may create files
+            let original = if path.exists() {
+                ctx.read(&path)?
+            } else {
+                String::new()
+            };
+            if original == edit.content {
+                continue;
+            }
+            plans.push(FilePlan {
+                changes: vec![format!("{}: {}", path.display(), edit.description)],
+                path,
+                original,
+                content: edit.content,
+            });
+        }
+        Ok(plans)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::profiles;
+    use crate::transaction::Transaction;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::tempdir;
+
+    fn write_plugin(path: &Path, script: &str) {
+        fs::write(path, format!("#!/bin/sh\n{script}\n")).unwrap();
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn test_external_step_returns_edits() {
+        let dir = tempdir().unwrap();
+        let plugin = dir.path().join("plugin.sh");
+        write_plugin(
+            &plugin,
+            r#"grep -q '"app_runtime_version":"4.9.4"' || exit 3
+cat <<'EOF'
+{"edits": [{"path": "Jenkinsfile", "content": "mule 4.9.4", "description": "set runtime"}]}
+EOF"#,
+        );
+        let config = profiles::default_config().unwrap();
+        let step = ExternalStep::resolve("./plugin.sh", dir.path(), &config, false).unwrap();
+        let tx = Transaction::new();
+        let plans = step.plan(&StepContext::new(&tx)).unwrap();
+        assert_eq!(plans.len(), 1);
+        assert_eq!(plans[0].path, dir.path().join("Jenkinsfile"));
+        assert_eq!(plans[0].content, "mule 4.9.4");
+
+        write_plugin(
+            &plugin,
+            r#"echo '{"edits": [{"path": "../x", "content": "", "description": ""}]}'"#,
+        );
+        let err = step.plan(&StepContext::new(&tx)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Plugin './plugin.sh' failed: edit of '../x' is outside the project"
+        );
+    }
+
+    #[test]
+    fn test_external_step_with_large_request_and_output() {
+        let dir = tempdir().unwrap();
+        let plugin = dir.path().join("plugin.sh");
+        // Fills the stderr pipe before reading the request
+        write_plugin(
+            &plugin,
+            r#"head -c 131072 /dev/zero | tr '\0' x >&2
+[ "$(wc -c)" -gt 131072 ] || exit 3
+echo '{"edits": []}'"#,
+        );
+        let mut tx = Transaction::new();
+        tx.stage(
+            dir.path().join("large.txt"),
+            String::new(),
+            "x".repeat(128 * 1024),
+        );
+        let config = profiles::default_config().unwrap();
+        let step = ExternalStep::resolve("./plugin.sh", dir.path(), &config, false).unwrap();
+        assert!(step.plan(&StepContext::new(&tx)).unwrap().is_empty());
+    }
+}
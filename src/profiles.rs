@@ -0,0 +1,90 @@
+use crate::config::{ConfigFormat, MigrationConfig};
+
+/// Built-in migration profiles: (name, description, JSON config).
+const PROFILES: &[(&str, &str, &str)] = &[
+    (
+        "4.4-to-4.6-java8",
+        "Runtime 4.6.0 keeping Java 8 compatibility (also declares Java 11 and 17)",
+        include_str!("../profiles/4.4-to-4.6-java8.json"),
+    ),
+    (
+        "4.4-to-4.6-java17",
+        "Runtime 4.6.0 LTS on Java 17, with connector catalog upgrades and DataWeave error fixes",
+        include_str!("../profiles/4.4-to-4.6-java17.json"),
+    ),
+    (
+        "4.4-to-4.9-java17",
+        "Runtime 4.9.4 on Java 17, with connector catalog upgrades and DataWeave error fixes",
+        include_str!("../profiles/4.4-to-4.9-java17.json"),
+    ),
+];
+
+/// Config used when neither a config file nor a profile is given: the latest
+/// supported runtime, plugin and MUnit versions, without replacements.
+const DEFAULT_CONFIG: &str = include_str!("../profiles/default.json");
+
+/// Returns the names and descriptions of the built-in profiles.
+pub fn list() -> impl Iterator<Item = (&'static str, &'static str)> {
+    PROFILES
+        .iter()
+        .map(|(name, description, _)| (*name, *description))
+}
+
+/// Returns the JSON source of the built-in profile `name`.
+pub fn source(name: &str) -> Option<&'static str> {
+    PROFILES
+        .iter()
+        .find(|(n, _, _)| *n == name)
+        .map(|(_, _, data)| *data)
+}
+
+/// Loads the built-in profile `name`.
+///
+/// # Errors
+/// Returns an error listing the available profiles if `name` is unknown.
+pub fn load(name: &str) -> Result<MigrationConfig, Box<dyn std::error::Error>> {
+    let Some(data) = source(name) else {
+        let available: Vec<&str> = list().map(|(n, _)| n).collect();
+        return Err(format!(
+            "Unknown profile '{name}' (available: {})",
+            available.join(", ")
+        )
+        .into());
+    };
+    MigrationConfig::from_str_with_format(data, ConfigFormat::Json)
+        .map_err(|e| format!("Invalid built-in profile {name}: {e}").into())
+}
+
+/// Loads the zero-config defaults.
+pub fn default_config() -> Result<MigrationConfig, Box<dyn std::error::Error>> {
+    MigrationConfig::from_str_with_format(DEFAULT_CONFIG, ConfigFormat::Json)
+        .map_err(|e| format!("Invalid built-in defaults: {e}").into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_profiles_load() {
+        for (name, _) in list() {
+            let config = load(name).unwrap();
+            assert!(name.contains(&config.app_runtime_version[..3]));
+        }
+    }
+
+    #[test]
+    fn test_default_config_targets_latest_profile() {
+        let defaults = default_config().unwrap();
+        let latest = load("4.4-to-4.9-java17").unwrap();
+        assert_eq!(defaults.app_runtime_version, latest.app_runtime_version);
+        assert_eq!(defaults.munit_version, latest.munit_version);
+        assert!(defaults.replacements.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_profile_lists_available() {
+        let err = load("3.9-to-4.9").err().unwrap();
+        assert!(err.to_string().contains("4.4-to-4.9-java17"));
+    }
+}
@@ -0,0 +1,72 @@
+use crate::observer::{MigrationEvent, MigrationObserver};
+use indicatif::{HumanBytes, ProgressBar, ProgressStyle};
+use std::time::Duration;
+
+/// Spinner on stderr showing the current step and the number and size of
+/// the files scanned so far.
+///
+/// The spinner is cleared when the changes are about to be committed, so it
+/// never mixes with interactive prompts or the final summary.
+pub struct ProgressObserver {
+    bar: ProgressBar,
+    step: String,
+    bytes: u64,
+}
+
+impl ProgressObserver {
+    pub fn new() -> Self {
+        let bar = ProgressBar::new_spinner();
+        bar.set_style(
+            ProgressStyle::with_template("{spinner} {msg}: {pos} file(s) scanned ({elapsed})")
+                .expect("valid progress template"),
+        );
+        bar.enable_steady_tick(Duration::from_millis(120));
+        ProgressObserver {
+            bar,
+            step: String::new(),
+            bytes: 0,
+        }
+    }
+
+    fn update_message(&self) {
+        self.bar
+            .set_message(format!("{}, {}", self.step, HumanBytes(self.bytes)));
+    }
+}
+
+impl Default for ProgressObserver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MigrationObserver for ProgressObserver {
+    fn on_event(&mut self, event: &MigrationEvent) {
+        if self.bar.is_finished() {
+            return;
+        }
+        match event {
+            MigrationEvent::StepStarted { step: "commit" } => self.bar.finish_and_clear(),
+            MigrationEvent::StepStarted { step } => {
+                self.step = step.to_string();
+                self.update_message();
+            }
+            MigrationEvent::FileScanned { path } => {
+                self.bytes += std::fs::metadata(path).map_or(0, |m| m.len());
+                self.bar.inc(1);
+                self.update_message();
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Drop for ProgressObserver {
+    fn drop(&mut self) {
+        // Leave the spinner in place if the run stopped early: clearing it
+        // now could erase lines of the summary printed after it.
+        if !self.bar.is_finished() {
+            self.bar.abandon();
+        }
+    }
+}
@@ -0,0 +1,323 @@
+use crate::error::MigrateError;
+use crate::file_ops::{self, FileFilter};
+use crate::steps::{FilePlan, MigrationStep, StepContext};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Files searched for the keys of `properties_updates`.
+pub const PROPERTIES_FILES: &str = "src/main/resources/**/*.properties";
+
+/// A `key=value` entry: the key and the byte range of its value, which may
+/// span continuation lines.
+struct Entry {
+    key: String,
+    value: std::ops::Range<usize>,
+}
+
+/// Returns true if `line` ends with an odd number of backslashes, i.e.
+/// continues on the next line.
+fn continues(line: &str) -> bool {
+    line.chars().rev().take_while(|c| *c == '\\').count() % 2 == 1
+}
+
+/// Parses the logical line starting at byte `start` of `content`; `end` is
+/// where the logical line (with its continuations) ends, before the newline.
+fn parse_entry(content: &str, start: usize, end: usize) -> Option<Entry> {
+    let line = &content[start..end];
+    let indent = line.len() - line.trim_start_matches([' ', '\t', '\x0c']).len();
+    let rest = &line[indent..];
+    if rest.is_empty() || rest.starts_with('#') || rest.starts_with('!') {
+        return None;
+    }
+    let mut key = String::new();
+    let mut chars = rest.char_indices();
+    let mut key_end = rest.len();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some((_, 'n')) => key.push('\n'),
+                Some((_, 't')) => key.push('\t'),
+                Some((_, '\n')) => {}
+                Some((_, escaped)) => key.push(escaped),
+                None => {}
+            },
+            '=' | ':' | ' ' | '\t' | '\x0c' => {
+                key_end = i;
+                break;
+            }
+            _ => key.push(c),
+        }
+    }
+    // Separator: whitespace, at most one `=` or `:`, whitespace
+    let after = &rest[key_end..];
+    let mut value_start =
+        key_end + (after.len() - after.trim_start_matches([' ', '\t', '\x0c']).len());
+    if rest[value_start..].starts_with(['=', ':']) {
+        value_start += 1;
+        let after = &rest[value_start..];
+        value_start += after.len() - after.trim_start_matches([' ', '\t', '\x0c']).len();
+    }
+    Some(Entry {
+        key,
+        value: start + indent + value_start..end,
+    })
+}
+
+/// Decodes a raw value: escapes, and continuation lines with their leading
+/// whitespace removed.
+fn unescape_value(raw: &str) -> String {
+    let mut value = String::new();
+    let mut chars = raw.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => value.push('\n'),
+            Some('t') => value.push('\t'),
+            Some('r') => value.push('\r'),
+            Some('\n') => {
+                while chars
+                    .next_if(|c| matches!(c, ' ' | '\t' | '\x0c'))
+                    .is_some()
+                {}
+            }
+            Some(escaped) => value.push(escaped),
+            None => {}
+        }
+    }
+    value
+}
+
+/// Encodes a value for a `.properties` file.
+fn escape_value(value: &str) -> String {
+    let mut escaped = String::new();
+    for (i, c) in value.chars().enumerate() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            ' ' if i == 0 => escaped.push_str("\\ "),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Sets the values of existing keys in `.properties` content, leaving
+/// comments, ordering, separators and every other line as they are. A key
+/// that occurs more than once is updated everywhere.
+///
+/// Returns the new content, a description of each change and the keys that
+/// were not found.
+pub fn plan_properties_update<'a>(
+    content: &str,
+    updates: &[(&'a str, &str)],
+) -> (String, Vec<String>, Vec<&'a str>) {
+    let mut found = vec![false; updates.len()];
+    let mut changes = Vec::new();
+    let mut out = String::with_capacity(content.len());
+    let mut last = 0;
+    let mut start = 0;
+    while start < content.len() {
+        // Extend the logical line over its continuation lines
+        let mut end = start;
+        loop {
+            let line_end = content[end..].find('\n').map_or(content.len(), |i| end + i);
+            let line = content[end..line_end].trim_end_matches('\r');
+            let comment = end == start && {
+                let trimmed = line.trim_start();
+                trimmed.starts_with('#') || trimmed.starts_with('!')
+            };
+            if comment || !continues(line) || line_end == content.len() {
+                end = line_end;
+                break;
+            }
+            end = line_end + 1;
+        }
+        let value_end = if content[..end].ends_with('\r') {
+            end - 1
+        } else {
+            end
+        };
+        if let Some(entry) = parse_entry(content, start, value_end) {
+            if let Some(i) = updates.iter().position(|(key, _)| *key == entry.key) {
+                found[i] = true;
+                let raw = &content[entry.value.clone()];
+                let old = unescape_value(raw);
+                let new = updates[i].1;
+                if old != new {
+                    out.push_str(&content[last..entry.value.start]);
+                    out.push_str(&escape_value(new));
+                    last = entry.value.end;
+                    changes.push(format!("{}: '{old}' -> '{new}'", entry.key));
+                }
+            }
+        }
+        start = end + 1;
+    }
+    out.push_str(&content[last..]);
+    let missing = updates
+        .iter()
+        .zip(found)
+        .filter(|(_, found)| !found)
+        .map(|((key, _), _)| *key)
+        .collect();
+    (out, changes, missing)
+}
+
+/// Step setting the `properties_updates` of a project in its properties
+/// files.
+#[derive(Debug, Clone)]
+pub struct PropertiesUpdater<'a> {
+    project_root: PathBuf,
+    updates: &'a BTreeMap<String, String>,
+    filter: FileFilter,
+    properties_files: FileFilter,
+}
+
+impl<'a> PropertiesUpdater<'a> {
+    /// Sets `updates` in the properties files among the files `filter`
+    /// selects.
+    ///
+    /// # Errors
+    /// Returns an error if [`PROPERTIES_FILES`] is not a valid glob.
+    pub fn new<P: AsRef<Path>>(
+        project_root: P,
+        updates: &'a BTreeMap<String, String>,
+        filter: FileFilter,
+    ) -> Result<Self, MigrateError> {
+        Ok(PropertiesUpdater {
+            project_root: project_root.as_ref().to_path_buf(),
+            updates,
+            filter,
+            properties_files: FileFilter::new(&[PROPERTIES_FILES.to_string()], &[])?,
+        })
+    }
+}
+
+impl MigrationStep for PropertiesUpdater<'_> {
+    fn name(&self) -> &str {
+        "properties"
+    }
+
+    fn files(&self) -> Vec<PathBuf> {
+        file_ops::walk_files(&self.project_root, &self.filter)
+            .filter(|p| {
+                let relative = p.strip_prefix(&self.project_root).unwrap_or(p);
+                self.properties_files.is_selected(relative)
+            })
+            .collect()
+    }
+
+    fn plan(&self, ctx: &StepContext) -> Result<Vec<FilePlan>, MigrateError> {
+        let updates: Vec<(&str, &str)> = self
+            .updates
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect();
+        let mut found_keys = HashSet::new();
+        let mut plans = Vec::new();
+        for (path, original) in ctx.read_each(self.files()) {
+            let (content, changes, missing) = plan_properties_update(&original, &updates);
+            found_keys.extend(
+                updates
+                    .iter()
+                    .map(|(k, _)| *k)
+                    .filter(|k| !missing.contains(k)),
+            );
+            if !changes.is_empty() {
+                plans.push(FilePlan::for_file(path, original, content, changes));
+            }
+        }
+        for (key, _) in &updates {
+            if !found_keys.contains(key) {
+                log::warn!("Property '{key}' not found in any file matching {PROPERTIES_FILES}");
+            }
+        }
+        Ok(plans)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::Transaction;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_plan_properties_update_keeps_layout() {
+        let content = "# Anypoint credentials\n\
+anypoint.platform.client_id = old-id\n\
+anypoint.platform.client_secret:secret\n\
+! legacy = 1\n\
+http.path=/api/v1/\\\n    orders\n\
+db.url=jdbc:mysql://localhost/db\n";
+        let (out, changes, missing) = plan_properties_update(
+            content,
+            &[
+                ("anypoint.platform.client_id", "new-id"),
+                ("http.path", "/api/v2/orders"),
+                ("db.url", "jdbc:mysql://localhost/db"),
+                ("legacy", "2"),
+            ],
+        );
+        assert_eq!(
+            out,
+            "# Anypoint credentials\n\
+anypoint.platform.client_id = new-id\n\
+anypoint.platform.client_secret:secret\n\
+! legacy = 1\n\
+http.path=/api/v2/orders\n\
+db.url=jdbc:mysql://localhost/db\n"
+        );
+        assert_eq!(
+            changes,
+            vec![
+                "anypoint.platform.client_id: 'old-id' -> 'new-id'",
+                "http.path: '/api/v1/orders' -> '/api/v2/orders'",
+            ]
+        );
+        assert_eq!(missing, vec!["legacy"]);
+    }
+
+    #[test]
+    fn test_escaped_keys_and_values() {
+        let content = "path\\ with\\ spaces=C:\\\\temp\n";
+        let (out, changes, _) =
+            plan_properties_update(content, &[("path with spaces", " D:\\data")]);
+        assert_eq!(out, "path\\ with\\ spaces=\\ D:\\\\data\n");
+        assert_eq!(changes, vec!["path with spaces: 'C:\\temp' -> ' D:\\data'"]);
+    }
+
+    #[test]
+    fn test_properties_updater_reads_staged_content() {
+        let dir = tempdir().unwrap();
+        let resources = dir.path().join("src/main/resources");
+        fs::create_dir_all(&resources).unwrap();
+        let path = resources.join("app.properties");
+        fs::write(&path, "http.port=8081\n").unwrap();
+        fs::write(dir.path().join("other.properties"), "http.port=8081\n").unwrap();
+        let mut tx = Transaction::new();
+        tx.stage(
+            &path,
+            "http.port=8081\n".to_string(),
+            "http.port=8082\n".to_string(),
+        );
+        let updates = BTreeMap::from([("http.port".to_string(), "9090".to_string())]);
+
+        let step = PropertiesUpdater::new(dir.path(), &updates, FileFilter::default()).unwrap();
+        assert_eq!(step.files(), std::slice::from_ref(&path));
+        let plans = step.plan(&StepContext::new(&tx)).unwrap();
+        assert_eq!(plans.len(), 1);
+        assert_eq!(plans[0].original, "http.port=8082\n");
+        assert_eq!(plans[0].content, "http.port=9090\n");
+        assert_eq!(
+            plans[0].changes,
+            [format!("{}: http.port: '8082' -> '9090'", path.display())]
+        );
+    }
+}
@@ -0,0 +1,638 @@
+use crate::deploy::DeploymentStatus;
+use crate::file_ops::RuleImpact;
+use crate::hooks::HookRun;
+use crate::matrix::{self, VersionChange};
+use crate::maven::MavenRun;
+use crate::surefire::TestSummary;
+use colored::*;
+use indicatif::HumanBytes;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Output format of the migration report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ReportFormat {
+    /// Colorized human-readable summary
+    #[default]
+    Text,
+    /// Machine-readable JSON document
+    Json,
+    /// Standalone HTML page with per-file diffs
+    Html,
+    /// SARIF 2.1.0 log for code-scanning tools
+    Sarif,
+}
+
+/// Exit code when the project is already up to date.
+pub const EXIT_UP_TO_DATE: i32 = 0;
+/// Exit code when the migration failed or reported errors.
+pub const EXIT_FAILURE: i32 = 1;
+/// Exit code when files were changed (or, in a dry run, need to be changed).
+pub const EXIT_CHANGES: i32 = 2;
+
+/// Unified diff of one changed file.
+#[derive(Debug, Clone)]
+pub struct FileDiff {
+    pub path: String,
+    pub diff: String,
+}
+
+/// Wall-clock time of one migration step.
+#[derive(Debug, Clone, Serialize)]
+pub struct StepTime {
+    pub step: String,
+    pub millis: u64,
+}
+
+/// Totals of a run, to track the scale of migrations across many projects.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct RunStats {
+    /// Distinct files read and checked for changes.
+    pub files_scanned: usize,
+    pub files_changed: usize,
+    /// Bytes of the changed files written to disk (0 in a dry run).
+    pub bytes_written: u64,
+    /// Matches of all replacement rules; see `rule_impact` for each rule.
+    pub replacements: usize,
+    /// Steps in run order.
+    pub steps: Vec<StepTime>,
+    pub total_millis: u64,
+    #[serde(skip)]
+    scanned: BTreeSet<PathBuf>,
+    #[serde(skip)]
+    started: Option<Instant>,
+    #[serde(skip)]
+    current: Option<(String, Instant)>,
+}
+
+impl RunStats {
+    /// Counts `path` as scanned, once however many steps read it.
+    pub fn file_scanned(&mut self, path: &Path) {
+        if self.scanned.insert(path.to_path_buf()) {
+            self.files_scanned += 1;
+        }
+    }
+
+    /// Starts timing `step`, ending the step timed so far.
+    pub fn start_step(&mut self, step: &str) {
+        self.end_step();
+        self.current = Some((step.to_string(), Instant::now()));
+    }
+
+    /// Ends the step being timed, if any.
+    pub fn end_step(&mut self) {
+        if let Some((step, started)) = self.current.take() {
+            self.steps.push(StepTime {
+                step,
+                millis: started.elapsed().as_millis() as u64,
+            });
+        }
+    }
+}
+
+/// Everything a migration run changed or failed to change.
+#[derive(Debug, Default, Serialize)]
+pub struct MigrationReport {
+    pub dry_run: bool,
+    /// Unique id of the run (UUID v4), also found in the backups directory
+    /// name and the git commit message.
+    pub run_id: String,
+    /// Version of mule-lazy-migrate that made the run.
+    pub tool_version: String,
+    /// Project root the paths in the report are relative to.
+    pub project_root: String,
+    /// RFC 3339 timestamp of when the run started.
+    pub started_at: String,
+    /// RFC 3339 timestamp of when the run finished.
+    pub finished_at: String,
+    /// Current and target value of each version the config sets.
+    pub versions: Vec<VersionChange>,
+    pub changed_files: Vec<String>,
+    /// Files moved by the `file_renames` rules, as `from -> to`.
+    pub renamed_files: Vec<String>,
+    /// Files created by the `files_to_create` rules.
+    pub created_files: Vec<String>,
+    /// Files removed by the `files_to_delete` rules.
+    pub deleted_files: Vec<String>,
+    pub changed_properties: Vec<String>,
+    pub changed_json: Vec<String>,
+    /// Attributes edited by the Mule XML rules.
+    pub changed_xml: Vec<String>,
+    /// Renames applied to DataWeave scripts.
+    pub changed_dataweave: Vec<String>,
+    /// Edits made by external step plugins.
+    pub plugin_changes: Vec<String>,
+    pub replacements: Vec<String>,
+    /// Replacements and YAML updates in CI definitions.
+    pub ci_changes: Vec<String>,
+    /// Base image and `ENV` updates in Dockerfiles.
+    pub dockerfile_changes: Vec<String>,
+    /// Matches per replacement rule, including rules that matched nothing.
+    pub rule_impact: Vec<RuleImpact>,
+    /// Findings that need manual attention, e.g. Java APIs removed in the target JDK.
+    pub compatibility_warnings: Vec<String>,
+    /// Pre/post hook commands with their captured output.
+    pub hooks: Vec<HookRun>,
+    /// Maven invocations with their exit status and captured output.
+    pub maven: Vec<MavenRun>,
+    /// Resolved dependencies that changed between the dependency trees taken
+    /// before and after a run with `--update-maven-deps`.
+    pub dependency_changes: Vec<String>,
+    /// Results of the post-migration test run, if tests were run.
+    pub tests: Option<TestSummary>,
+    /// Outcome of the post-migration redeployment, if the app was redeployed.
+    pub deployment: Option<DeploymentStatus>,
+    /// Run id of the snapshot holding the backups of the changed files.
+    pub backup_run: Option<String>,
+    /// Files whose pending changes were all declined in interactive mode.
+    pub skipped_files: Vec<String>,
+    /// Steps left out with `--only` or `--skip`.
+    pub skipped_steps: Vec<String>,
+    /// Source files that could not be processed (unreadable, binary, ...).
+    pub file_errors: Vec<String>,
+    pub errors: Vec<String>,
+    pub stats: RunStats,
+    /// Per-file diffs of the changes, rendered in HTML reports.
+    #[serde(skip)]
+    pub diffs: Vec<FileDiff>,
+}
+
+impl MigrationReport {
+    pub fn new(dry_run: bool) -> Self {
+        MigrationReport {
+            dry_run,
+            run_id: uuid::Uuid::new_v4().to_string(),
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            started_at: chrono::Utc::now().to_rfc3339(),
+            stats: RunStats {
+                started: Some(Instant::now()),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Stamps the report with the finish time and completes the totals.
+    pub fn finish(&mut self) {
+        self.finished_at = chrono::Utc::now().to_rfc3339();
+        self.stats.end_step();
+        self.stats.files_changed = self.changed_files.len();
+        self.stats.replacements = self.rule_impact.iter().map(|i| i.matches).sum();
+        if let Some(started) = self.stats.started {
+            self.stats.total_millis = started.elapsed().as_millis() as u64;
+        }
+    }
+
+    /// Returns true if the run neither changed anything nor reported errors.
+    pub fn is_empty(&self) -> bool {
+        self.changed_files.is_empty()
+            && self.renamed_files.is_empty()
+            && self.created_files.is_empty()
+            && self.deleted_files.is_empty()
+            && self.changed_properties.is_empty()
+            && self.changed_json.is_empty()
+            && self.changed_xml.is_empty()
+            && self.changed_dataweave.is_empty()
+            && self.plugin_changes.is_empty()
+            && self.replacements.is_empty()
+            && self.ci_changes.is_empty()
+            && self.dockerfile_changes.is_empty()
+            && self.dependency_changes.is_empty()
+            && self.compatibility_warnings.is_empty()
+            && self.file_errors.is_empty()
+            && self.errors.is_empty()
+    }
+
+    /// Process exit code summarizing the run: errors win over changes.
+    pub fn exit_code(&self) -> i32 {
+        if !self.errors.is_empty() {
+            EXIT_FAILURE
+        } else if !self.changed_files.is_empty()
+            || !self.renamed_files.is_empty()
+            || !self.deleted_files.is_empty()
+        {
+            EXIT_CHANGES
+        } else {
+            EXIT_UP_TO_DATE
+        }
+    }
+
+    /// Serializes the report as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Failed to serialize report")
+    }
+
+    /// Renders the report as a standalone HTML page.
+    pub fn to_html(&self) -> String {
+        crate::html::render_report(self)
+    }
+
+    /// Serializes the report as a pretty-printed SARIF 2.1.0 log.
+    pub fn to_sarif(&self) -> String {
+        serde_json::to_string_pretty(&crate::sarif::to_sarif(self))
+            .expect("Failed to serialize SARIF log")
+    }
+
+    /// Renders the unified diff of every changed file, colorized.
+    pub fn diffs_to_text(&self) -> String {
+        let mut out = String::new();
+        for file in &self.diffs {
+            for line in file.diff.lines() {
+                let line = match line.chars().next() {
+                    _ if line.starts_with("+++") || line.starts_with("---") => line.bold(),
+                    Some('+') => line.green(),
+                    Some('-') => line.red(),
+                    Some('@') => line.cyan(),
+                    _ => line.normal(),
+                };
+                out.push_str(&format!("{line}\n"));
+            }
+        }
+        out
+    }
+
+    /// Renders the colorized text summary of the migration results.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        let mut line = |s: String| {
+            out.push_str(&s);
+            out.push('\n');
+        };
+        line(format!(
+            "\n{}",
+            "================ MIGRATION SUMMARY ================"
+                .bold()
+                .blue()
+        ));
+        line(format!(
+            "Run {} (mule-lazy-migrate {})",
+            self.run_id, self.tool_version
+        ));
+        if self.finished_at.is_empty() {
+            line(format!("Started {}", self.started_at));
+        } else {
+            line(format!(
+                "Started {}, finished {}",
+                self.started_at, self.finished_at
+            ));
+        }
+        if self.dry_run {
+            line(format!(
+                "{}",
+                "[DRY-RUN] No files were actually changed".bold().blue()
+            ));
+        }
+        if !self.versions.is_empty() {
+            line(format!("{}", "Versions (current -> target):".bold()));
+            for (row, entry) in self
+                .versions
+                .iter()
+                .zip(matrix::render_matrix(&self.versions))
+            {
+                if row.downgrade {
+                    line(format!("  {}", entry.red()));
+                } else {
+                    line(format!("  {entry}"));
+                }
+            }
+        }
+        let sections = [
+            ("Changed files:", &self.changed_files, Color::Green),
+            ("Moved files:", &self.renamed_files, Color::Green),
+            ("Created files:", &self.created_files, Color::Green),
+            ("Deleted files:", &self.deleted_files, Color::Green),
+            (
+                "Updated properties:",
+                &self.changed_properties,
+                Color::Green,
+            ),
+            ("Updated JSON fields:", &self.changed_json, Color::Green),
+            ("Updated Mule XML:", &self.changed_xml, Color::Green),
+            ("Updated DataWeave:", &self.changed_dataweave, Color::Green),
+            ("Plugin changes:", &self.plugin_changes, Color::Green),
+            ("String replacements:", &self.replacements, Color::Yellow),
+            ("CI files:", &self.ci_changes, Color::Yellow),
+            ("Dockerfiles:", &self.dockerfile_changes, Color::Green),
+            (
+                "Resolved dependencies:",
+                &self.dependency_changes,
+                Color::Green,
+            ),
+            ("Skipped files:", &self.skipped_files, Color::Yellow),
+            ("Skipped steps:", &self.skipped_steps, Color::Yellow),
+            (
+                "Compatibility warnings:",
+                &self.compatibility_warnings,
+                Color::Yellow,
+            ),
+            ("File errors:", &self.file_errors, Color::Red),
+            ("Warnings/Errors:", &self.errors, Color::Red),
+        ];
+        for (title, entries, color) in sections {
+            if entries.is_empty() {
+                continue;
+            }
+            line(format!("{}", title.color(color).bold()));
+            for entry in entries {
+                line(format!("  {}", entry.color(color)));
+            }
+        }
+        if !self.hooks.is_empty() {
+            line(format!("{}", "Hooks:".bold()));
+            for run in &self.hooks {
+                let status = match run.exit_code {
+                    Some(code) => format!("exit {code}"),
+                    None => "killed".to_string(),
+                };
+                let entry = format!("[{}] {} ({status})", run.phase, run.command);
+                let color = if run.succeeded() {
+                    Color::Green
+                } else {
+                    Color::Red
+                };
+                line(format!("  {}", entry.color(color)));
+                for output in run.stdout.lines().chain(run.stderr.lines()) {
+                    line(format!("    {}", output.dimmed()));
+                }
+            }
+        }
+        if !self.maven.is_empty() {
+            line(format!("{}", "Maven:".bold()));
+            for run in &self.maven {
+                if run.succeeded() {
+                    line(format!("  {}", format!("{} (ok)", run.goals).green()));
+                    continue;
+                }
+                line(format!("  {}", run.failure_message().red()));
+                for error in run.errors.iter().skip(1) {
+                    line(format!("    {}", error.red()));
+                }
+                for output in &run.output_tail {
+                    line(format!("    {}", output.dimmed()));
+                }
+            }
+        }
+        if let Some(tests) = &self.tests {
+            let entry = format!(
+                "{} passed, {} failed, {} errors, {} skipped",
+                tests.passed(),
+                tests.failures,
+                tests.errors,
+                tests.skipped
+            );
+            let entry = if tests.failed.is_empty() {
+                entry.green()
+            } else {
+                entry.red()
+            };
+            line(format!("{} {entry}", "Tests:".bold()));
+            for test in &tests.failed {
+                let name = format!("{} / {}", test.suite, test.name);
+                match &test.message {
+                    Some(message) => line(format!("  {}: {message}", name.red())),
+                    None => line(format!("  {}", name.red())),
+                }
+            }
+        }
+        if let Some(deployment) = &self.deployment {
+            let status = match &deployment.application_status {
+                Some(application) => format!("{} ({application})", deployment.status),
+                None => deployment.status.clone(),
+            };
+            let entry = format!(
+                "{} on runtime {} in environment {}: {status}",
+                deployment.application, deployment.runtime_version, deployment.environment_id
+            );
+            let entry = if deployment.succeeded {
+                entry.green()
+            } else {
+                entry.red()
+            };
+            line(format!("{} {entry}", "Deployment:".bold()));
+        }
+        if let Some(run) = &self.backup_run {
+            line(format!(
+                "{} run {run} (undo with `restore --run {run}`)",
+                "Backups:".bold()
+            ));
+        }
+        if self.dry_run && !self.rule_impact.is_empty() {
+            line(format!("{}", "Replacement rule impact:".bold()));
+            for impact in &self.rule_impact {
+                let entry = format!(
+                    "'{}' -> '{}': {} match(es) in {} file(s)",
+                    impact.from, impact.to, impact.matches, impact.files
+                );
+                if impact.matches == 0 {
+                    line(format!("  {}", (entry + " - check 'from'").yellow()));
+                } else {
+                    line(format!("  {entry}"));
+                }
+            }
+        }
+        let stats = &self.stats;
+        line(format!("{}", "Statistics:".bold()));
+        line(format!(
+            "  {} file(s) scanned, {} changed, {} written",
+            stats.files_scanned,
+            stats.files_changed,
+            HumanBytes(stats.bytes_written)
+        ));
+        if stats.replacements > 0 {
+            let per_rule: Vec<String> = self
+                .rule_impact
+                .iter()
+                .filter(|i| i.matches > 0)
+                .map(|i| format!("'{}' -> '{}': {}", i.from, i.to, i.matches))
+                .collect();
+            line(format!(
+                "  {} replacement(s): {}",
+                stats.replacements,
+                per_rule.join(", ")
+            ));
+        }
+        if !stats.steps.is_empty() {
+            let times: Vec<String> = stats
+                .steps
+                .iter()
+                .map(|t| format!("{} {}ms", t.step, t.millis))
+                .collect();
+            line(format!(
+                "  {} (total {}ms)",
+                times.join(", ").dimmed(),
+                stats.total_millis
+            ));
+        }
+        if self.is_empty() {
+            line(format!(
+                "{}",
+                "No changes were needed. Project is up to date!"
+                    .blue()
+                    .bold()
+            ));
+        }
+        line(format!(
+            "{}",
+            "=================================================="
+                .bold()
+                .blue()
+        ));
+        out
+    }
+}
+
+/// Renders with colors turned off, e.g. for a file, then restores the
+/// color setting of the terminal output.
+pub fn without_colors<T>(render: impl FnOnce() -> T) -> T {
+    let colorize = colored::control::SHOULD_COLORIZE.should_colorize();
+    colored::control::set_override(false);
+    let rendered = render();
+    colored::control::set_override(colorize);
+    rendered
+}
+
+/// Emits the report in the requested format.
+///
+/// Without `report_file` the report goes to stdout. With it, the colorized
+/// summary is still printed and the report is written to the file (text
+/// reports without ANSI colors).
+pub fn emit_report(
+    report: &MigrationReport,
+    format: ReportFormat,
+    report_file: Option<&str>,
+) -> std::io::Result<()> {
+    match (format, report_file) {
+        (ReportFormat::Text, None) => print!("{}", report.to_text()),
+        (ReportFormat::Json, None) => println!("{}", report.to_json()),
+        (ReportFormat::Html, None) => print!("{}", report.to_html()),
+        (ReportFormat::Sarif, None) => println!("{}", report.to_sarif()),
+        (ReportFormat::Text, Some(path)) => {
+            print!("{}", report.to_text());
+            fs::write(path, without_colors(|| report.to_text()))?;
+        }
+        (ReportFormat::Json, Some(path)) => {
+            print!("{}", report.to_text());
+            fs::write(path, report.to_json())?;
+        }
+        (ReportFormat::Html, Some(path)) => {
+            print!("{}", report.to_text());
+            fs::write(path, report.to_html())?;
+        }
+        (ReportFormat::Sarif, Some(path)) => {
+            print!("{}", report.to_text());
+            fs::write(path, report.to_sarif())?;
+        }
+    }
+    if let Some(path) = report_file {
+        log::info!("Report written to {path}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_report_to_json() {
+        let mut report = MigrationReport::new(true);
+        report.changed_files.push("pom.xml".to_string());
+        report.errors.push("boom".to_string());
+        report.finish();
+        let value: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(value["dry_run"], true);
+        assert_eq!(value["changed_files"][0], "pom.xml");
+        assert_eq!(value["errors"][0], "boom");
+        assert!(!value["started_at"].as_str().unwrap().is_empty());
+        assert!(!value["finished_at"].as_str().unwrap().is_empty());
+        assert_eq!(value["run_id"].as_str().unwrap().len(), 36);
+        assert_eq!(value["tool_version"], env!("CARGO_PKG_VERSION"));
+        assert_ne!(MigrationReport::new(true).run_id, report.run_id);
+    }
+
+    #[test]
+    fn test_exit_code() {
+        let mut report = MigrationReport::new(true);
+        assert_eq!(report.exit_code(), EXIT_UP_TO_DATE);
+        report.changed_files.push("pom.xml".to_string());
+        assert_eq!(report.exit_code(), EXIT_CHANGES);
+        report
+            .errors
+            .push("No mule-artifact.json found".to_string());
+        assert_eq!(report.exit_code(), EXIT_FAILURE);
+    }
+
+    #[test]
+    fn test_rule_impact_in_dry_run_text() {
+        colored::control::set_override(false);
+        let mut report = MigrationReport::new(true);
+        report.rule_impact = vec![
+            RuleImpact {
+                from: "orders-api".to_string(),
+                to: "orders-sapi".to_string(),
+                matches: 3,
+                files: 2,
+            },
+            RuleImpact {
+                from: "muleMesage".to_string(),
+                to: "errorMessage".to_string(),
+                matches: 0,
+                files: 0,
+            },
+        ];
+        let text = report.to_text();
+        colored::control::unset_override();
+        assert!(text.contains("'orders-api' -> 'orders-sapi': 3 match(es) in 2 file(s)"));
+        assert!(text
+            .contains("'muleMesage' -> 'errorMessage': 0 match(es) in 0 file(s) - check 'from'"));
+
+        report.dry_run = false;
+        assert!(!report.to_text().contains("Replacement rule impact"));
+    }
+
+    #[test]
+    fn test_stats_in_text() {
+        colored::control::set_override(false);
+        let mut report = MigrationReport::new(false);
+        report.stats.file_scanned(Path::new("pom.xml"));
+        report
+            .stats
+            .file_scanned(Path::new("src/main/mule/api.xml"));
+        report.stats.file_scanned(Path::new("pom.xml"));
+        report.stats.start_step("pom.xml");
+        report.stats.start_step("replacements");
+        report.changed_files.push("pom.xml".to_string());
+        report.stats.bytes_written = 2048;
+        report.rule_impact = vec![RuleImpact {
+            from: "orders-api".to_string(),
+            to: "orders-sapi".to_string(),
+            matches: 3,
+            files: 1,
+        }];
+        report.finish();
+        let text = report.to_text();
+        colored::control::unset_override();
+        assert_eq!(report.stats.files_scanned, 2);
+        assert_eq!(report.stats.replacements, 3);
+        let steps: Vec<&str> = report.stats.steps.iter().map(|t| t.step.as_str()).collect();
+        assert_eq!(steps, ["pom.xml", "replacements"]);
+        assert!(text.contains("2 file(s) scanned, 1 changed, 2.00 KiB written"));
+        assert!(text.contains("3 replacement(s): 'orders-api' -> 'orders-sapi': 3"));
+    }
+
+    #[test]
+    fn test_emit_report_json_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("report.json");
+        let mut report = MigrationReport::new(false);
+        report.replacements.push("flow.xml: 'a' -> 'b'".to_string());
+        emit_report(&report, ReportFormat::Json, path.to_str()).unwrap();
+        let value: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(value["replacements"][0], "flow.xml: 'a' -> 'b'");
+    }
+}
@@ -0,0 +1,218 @@
+use crate::error::MigrateError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Lock file (relative to the project root) held while a run writes to the
+/// project.
+pub const RUN_LOCK_PATH: &str = ".mule-migrate/run.lock";
+
+/// Locks older than this are considered left behind by a crashed run.
+const STALE_AFTER: chrono::Duration = chrono::Duration::hours(24);
+
+/// Unreadable locks older than this were left empty or partly written by a
+/// crash or a full disk, rather than being written right now.
+const UNREADABLE_STALE_AFTER: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Who holds the lock.
+#[derive(Debug, Serialize, Deserialize)]
+struct LockInfo {
+    pid: u32,
+    /// RFC 3339 timestamp of when the lock was taken.
+    started_at: String,
+}
+
+impl LockInfo {
+    fn is_stale(&self) -> bool {
+        let expired = chrono::DateTime::parse_from_rfc3339(&self.started_at).map_or(true, |t| {
+            chrono::Utc::now() - t.with_timezone(&chrono::Utc) > STALE_AFTER
+        });
+        expired || !process_alive(self.pid)
+    }
+}
+
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // Signal 0 only checks the process exists; EPERM means it does but
+    // belongs to another user
+    // SAFETY: kill with signal 0 sends nothing
+    let checked = unsafe { libc::kill(pid, 0) };
+    checked == 0 || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(windows)]
+fn process_alive(pid: u32) -> bool {
+    use windows_sys::Win32::Foundation::{CloseHandle, ERROR_ACCESS_DENIED, STILL_ACTIVE};
+    use windows_sys::Win32::System::Threading::{
+        GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    // SAFETY: the handle is checked before use and closed after it
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            // The process exists, but we may not query it
+            return io::Error::last_os_error().raw_os_error() == Some(ERROR_ACCESS_DENIED as i32);
+        }
+        let mut code = 0;
+        let queried = GetExitCodeProcess(handle, &mut code) != 0;
+        CloseHandle(handle);
+        !queried || code == STILL_ACTIVE as u32
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn process_alive(_pid: u32) -> bool {
+    // Without a check, only the age tells a lock is stale
+    true
+}
+
+/// Exclusive lock on a project, released when dropped.
+#[derive(Debug)]
+pub struct RunLock {
+    path: PathBuf,
+}
+
+impl RunLock {
+    /// Takes the lock of `project_root`, replacing a stale lock of a run that
+    /// crashed or was killed.
+    ///
+    /// # Errors
+    /// Returns [`MigrateError::Locked`] if another run holds the lock.
+    pub fn acquire(project_root: &Path) -> Result<RunLock, MigrateError> {
+        let path = project_root.join(RUN_LOCK_PATH);
+        let write_err = |source: io::Error| MigrateError::Write {
+            path: path.clone(),
+            source,
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(write_err)?;
+            // Keep the lock out of git, so it does not make the tree dirty
+            let ignore = dir.join(".gitignore");
+            if !ignore.exists() {
+                fs::write(&ignore, "*\n").map_err(write_err)?;
+            }
+        }
+        for _ in 0..2 {
+            match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(mut file) => {
+                    let info = LockInfo {
+                        pid: std::process::id(),
+                        started_at: chrono::Utc::now().to_rfc3339(),
+                    };
+                    let json = serde_json::to_string(&info).expect("Failed to serialize lock");
+                    file.write_all(json.as_bytes()).map_err(write_err)?;
+                    return Ok(RunLock { path });
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    let info = fs::read_to_string(&path)
+                        .ok()
+                        .and_then(|data| serde_json::from_str::<LockInfo>(&data).ok());
+                    match info {
+                        Some(info) if info.is_stale() => {
+                            log::warn!(
+                                "Removing stale lock {} of process {} from {}",
+                                path.display(),
+                                info.pid,
+                                info.started_at
+                            );
+                            let _ = fs::remove_file(&path);
+                        }
+                        Some(info) => {
+                            let holder = format!("process {} since {}", info.pid, info.started_at);
+                            return Err(MigrateError::Locked { path, holder });
+                        }
+                        None if fs::metadata(&path)
+                            .and_then(|m| m.modified())
+                            .ok()
+                            .and_then(|t| t.elapsed().ok())
+                            .is_some_and(|age| age > UNREADABLE_STALE_AFTER) =>
+                        {
+                            log::warn!("Removing unreadable lock {}", path.display());
+                            let _ = fs::remove_file(&path);
+                        }
+                        // A recent unreadable lock is being written right now
+                        None => {
+                            let holder = "another run".to_string();
+                            return Err(MigrateError::Locked { path, holder });
+                        }
+                    }
+                }
+                Err(e) => return Err(write_err(e)),
+            }
+        }
+        Err(MigrateError::Locked {
+            path,
+            holder: "another run".to_string(),
+        })
+    }
+}
+
+impl Drop for RunLock {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.path) {
+            log::warn!("Failed to remove {}: {e}", self.path.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_acquire_is_exclusive_and_replaces_stale_locks() {
+        let dir = tempdir().unwrap();
+        let lock = RunLock::acquire(dir.path()).unwrap();
+        assert!(matches!(
+            RunLock::acquire(dir.path()),
+            Err(MigrateError::Locked { .. })
+        ));
+        drop(lock);
+        assert!(!dir.path().join(RUN_LOCK_PATH).exists());
+
+        fs::write(
+            dir.path().join(RUN_LOCK_PATH),
+            r#"{"pid": 1, "started_at": "2020-01-01T00:00:00+00:00"}"#,
+        )
+        .unwrap();
+        let _lock = RunLock::acquire(dir.path()).unwrap();
+    }
+
+    #[test]
+    fn test_acquire_replaces_old_unreadable_lock() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(RUN_LOCK_PATH);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let file = fs::File::create(&path).unwrap();
+        assert!(matches!(
+            RunLock::acquire(dir.path()),
+            Err(MigrateError::Locked { .. })
+        ));
+
+        let old = std::time::SystemTime::now() - UNREADABLE_STALE_AFTER * 2;
+        file.set_modified(old).unwrap();
+        let _lock = RunLock::acquire(dir.path()).unwrap();
+    }
+
+    #[test]
+    fn test_process_alive() {
+        assert!(process_alive(std::process::id()));
+        let mut child = std::process::Command::new(std::env::current_exe().unwrap())
+            .arg("--list")
+            .stdout(std::process::Stdio::null())
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        assert!(!process_alive(pid));
+    }
+}
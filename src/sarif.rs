@@ -0,0 +1,167 @@
+use crate::report::MigrationReport;
+use serde_json::{json, Value};
+use std::path::Path;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Rules reported by the tool: (id, short description).
+const RULES: &[(&str, &str)] = &[
+    (
+        "pending-change",
+        "The migration changes this part of the file",
+    ),
+    (
+        "java-compatibility",
+        "Custom Java code uses an API unavailable on the target JDK",
+    ),
+    ("file-error", "A source file could not be processed"),
+    ("migration-error", "The migration reported an error"),
+];
+
+/// Path of `path` relative to the project root, with `/` separators, as
+/// SARIF artifact locations expect.
+fn relative_uri(project_root: &str, path: &str) -> String {
+    let relative = Path::new(path)
+        .strip_prefix(project_root)
+        .unwrap_or(Path::new(path));
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn result(rule_id: &str, level: &str, message: &str, location: Option<(String, usize)>) -> Value {
+    let mut result = json!({
+        "ruleId": rule_id,
+        "level": level,
+        "message": { "text": message },
+    });
+    if let Some((uri, line)) = location {
+        result["locations"] = json!([{
+            "physicalLocation": {
+                "artifactLocation": { "uri": uri, "uriBaseId": "%SRCROOT%" },
+                "region": { "startLine": line.max(1) }
+            }
+        }]);
+    }
+    result
+}
+
+/// Splits a unified diff into hunks: (first line in the original file, hunk text).
+fn hunks(diff: &str) -> Vec<(usize, String)> {
+    let mut hunks: Vec<(usize, String)> = Vec::new();
+    for line in diff.lines() {
+        if let Some(header) = line.strip_prefix("@@ -") {
+            let start = header
+                .split([',', ' '])
+                .next()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(1);
+            hunks.push((start, String::new()));
+        } else if let Some((_, text)) = hunks.last_mut() {
+            text.push_str(line);
+            text.push('\n');
+        }
+    }
+    hunks
+}
+
+/// Converts the report to a SARIF 2.1.0 log: one result per changed hunk,
+/// per Java compatibility finding and per error.
+///
+/// Pending changes are warnings in a dry run (the project still needs to be
+/// migrated) and notes otherwise.
+pub fn to_sarif(report: &MigrationReport) -> Value {
+    let root = report.project_root.as_str();
+    let mut results = Vec::new();
+
+    let change_level = if report.dry_run { "warning" } else { "note" };
+    for file in &report.diffs {
+        for (line, text) in hunks(&file.diff) {
+            results.push(result(
+                "pending-change",
+                change_level,
+                &format!("Migration change:\n{text}"),
+                Some((relative_uri(root, &file.path), line)),
+            ));
+        }
+    }
+    for warning in &report.compatibility_warnings {
+        // Findings are formatted as `path:line: explanation`
+        let location = warning.split_once(": ").and_then(|(location, message)| {
+            let (path, line) = location.rsplit_once(':')?;
+            Some((path, line.parse().ok()?, message))
+        });
+        match location {
+            Some((path, line, message)) => results.push(result(
+                "java-compatibility",
+                "warning",
+                message,
+                Some((relative_uri(root, path), line)),
+            )),
+            None => results.push(result("java-compatibility", "warning", warning, None)),
+        }
+    }
+    for error in &report.file_errors {
+        results.push(result("file-error", "error", error, None));
+    }
+    for error in &report.errors {
+        results.push(result("migration-error", "error", error, None));
+    }
+
+    let rules: Vec<Value> = RULES
+        .iter()
+        .map(|(id, description)| json!({ "id": id, "shortDescription": { "text": description } }))
+        .collect();
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules
+                }
+            },
+            "results": results
+        }]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::FileDiff;
+
+    #[test]
+    fn test_to_sarif_locates_hunks_and_java_findings() {
+        let mut report = MigrationReport::new(true);
+        report.project_root = "/work/app".to_string();
+        report.diffs.push(FileDiff {
+            path: "/work/app/src/main/mule/flow.xml".to_string(),
+            diff: "--- a\n+++ b\n@@ -12,3 +12,3 @@\n a\n-b\n+c\n".to_string(),
+        });
+        report
+            .compatibility_warnings
+            .push("/work/app/src/main/java/A.java:7: javax.xml.bind was removed".to_string());
+        report.errors.push("boom".to_string());
+
+        let sarif = to_sarif(&report);
+        let results = sarif["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), 3);
+        let location = &results[0]["locations"][0]["physicalLocation"];
+        assert_eq!(results[0]["level"], "warning");
+        assert_eq!(
+            location["artifactLocation"]["uri"],
+            "src/main/mule/flow.xml"
+        );
+        assert_eq!(location["region"]["startLine"], 12);
+        let location = &results[1]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "src/main/java/A.java");
+        assert_eq!(location["region"]["startLine"], 7);
+        assert_eq!(results[1]["message"]["text"], "javax.xml.bind was removed");
+        assert!(results[2].get("locations").is_none());
+    }
+}
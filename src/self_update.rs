@@ -0,0 +1,407 @@
+use crate::http;
+use crate::lookup;
+use crate::version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::fs;
+use std::io::{self, Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Version of the running binary.
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// GitHub API endpoint of the latest release.
+pub const RELEASES_URL: &str =
+    "https://api.github.com/repos/kchernokozinsky/mule-lazy-migrate/releases/latest";
+
+/// crates.io API endpoint of the crate, asked when GitHub cannot be reached.
+pub const CRATES_IO_URL: &str = "https://crates.io/api/v1/crates/mule-lazy-migrate";
+
+/// Environment variable that turns off the new-version notice when set.
+pub const NO_UPDATE_CHECK_ENV: &str = "MULE_LAZY_MIGRATE_NO_UPDATE_CHECK";
+
+/// How often the new-version notice asks for the latest release.
+const NOTICE_INTERVAL_HOURS: i64 = 24;
+
+/// The notice must not hold up a migration on a slow network.
+const NOTICE_TIMEOUT: Duration = Duration::from_secs(2);
+
+const BINARY_NAME: &str = if cfg!(windows) {
+    "mule-lazy-migrate.exe"
+} else {
+    "mule-lazy-migrate"
+};
+
+/// A downloadable file of a release.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+/// The latest published release. Releases only known from crates.io have no assets.
+#[derive(Debug, Clone)]
+pub struct Release {
+    pub version: String,
+    pub assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    #[serde(default)]
+    assets: Vec<ReleaseAsset>,
+}
+
+/// Result of [`self_update`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateOutcome {
+    /// The running binary is the latest release.
+    UpToDate,
+    /// A newer release exists; only reported with `check_only`.
+    Available(String),
+    /// The binary was replaced with this release.
+    Updated(String),
+}
+
+fn parse_github_release(json: &str) -> Option<Release> {
+    let release: GithubRelease = serde_json::from_str(json).ok()?;
+    Some(Release {
+        version: release.tag_name.trim_start_matches('v').to_string(),
+        assets: release.assets,
+    })
+}
+
+fn parse_crates_io(json: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(json).ok()?;
+    let version = value["crate"]["max_stable_version"].as_str()?;
+    Some(version.to_string())
+}
+
+/// Looks up the latest release on GitHub, falling back to crates.io.
+pub fn latest_release() -> Result<Release, String> {
+    latest_release_within(None)
+}
+
+fn latest_release_within(timeout: Option<Duration>) -> Result<Release, String> {
+    let get = |url| http::get_string_with(url, None, None, timeout);
+    let github = match get(RELEASES_URL) {
+        Ok(json) => match parse_github_release(&json) {
+            Some(release) => return Ok(release),
+            None => "unexpected response".to_string(),
+        },
+        Err(e) => e.to_string(),
+    };
+    log::debug!("Release lookup on GitHub failed: {github}");
+    match get(CRATES_IO_URL) {
+        Ok(json) => parse_crates_io(&json)
+            .map(|version| Release {
+                version,
+                assets: Vec::new(),
+            })
+            .ok_or_else(|| "crates.io returned an unexpected response".to_string()),
+        Err(e) => Err(format!(
+            "could not reach GitHub ({github}) or crates.io ({e})"
+        )),
+    }
+}
+
+/// Returns true if `latest` is a newer version than the running binary.
+pub fn is_newer(latest: &str) -> bool {
+    version::compare(CURRENT_VERSION, latest) == Some(Ordering::Less)
+}
+
+/// Rust target triple the release archives are named after, e.g.
+/// `mule-lazy-migrate-x86_64-unknown-linux-gnu.tar.gz`.
+fn target_triple() -> Option<&'static str> {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("linux", "x86_64") => Some("x86_64-unknown-linux-gnu"),
+        ("linux", "aarch64") => Some("aarch64-unknown-linux-gnu"),
+        ("macos", "x86_64") => Some("x86_64-apple-darwin"),
+        ("macos", "aarch64") => Some("aarch64-apple-darwin"),
+        ("windows", "x86_64") => Some("x86_64-pc-windows-msvc"),
+        _ => None,
+    }
+}
+
+/// Picks the `.tar.gz` or `.zip` archive built for `triple`.
+fn select_asset<'a>(assets: &'a [ReleaseAsset], triple: &str) -> Option<&'a ReleaseAsset> {
+    assets.iter().find(|asset| {
+        asset.name.contains(triple)
+            && (asset.name.ends_with(".tar.gz") || asset.name.ends_with(".zip"))
+    })
+}
+
+/// Checks `data` against the `<asset>.sha256` file published next to the
+/// archive; a release without one is refused. With `skip` the check is not
+/// made.
+fn verify_checksum(
+    data: &[u8],
+    asset: &ReleaseAsset,
+    assets: &[ReleaseAsset],
+    skip: bool,
+) -> Result<(), String> {
+    if skip {
+        log::warn!(
+            "Skipping the verification of {} (--skip-checksum)",
+            asset.name
+        );
+        return Ok(());
+    }
+    let checksum_name = format!("{}.sha256", asset.name);
+    let Some(checksum) = assets.iter().find(|a| a.name == checksum_name) else {
+        return Err(format!(
+            "release has no {checksum_name} to verify {} against; pass --skip-checksum to install it anyway",
+            asset.name
+        ));
+    };
+    let expected = http::get_string(&checksum.browser_download_url)
+        .map_err(|e| format!("failed to download {checksum_name}: {e}"))?;
+    let expected = expected
+        .split_whitespace()
+        .next()
+        .unwrap_or("")
+        .to_lowercase();
+    let actual: String = Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    if actual != expected {
+        return Err(format!(
+            "checksum mismatch for {}: expected {expected}, got {actual}",
+            asset.name
+        ));
+    }
+    Ok(())
+}
+
+/// Extracts the executable from a release archive.
+fn extract_binary(archive_name: &str, data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut binary = Vec::new();
+    if archive_name.ends_with(".zip") {
+        let mut archive = zip::ZipArchive::new(Cursor::new(data)).map_err(io::Error::other)?;
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i).map_err(io::Error::other)?;
+            if entry.is_file() && file_name_is_binary(Path::new(entry.name())) {
+                entry.read_to_end(&mut binary)?;
+                return Ok(binary);
+            }
+        }
+    } else {
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(data));
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if entry.header().entry_type().is_file() && file_name_is_binary(&entry.path()?) {
+                entry.read_to_end(&mut binary)?;
+                return Ok(binary);
+            }
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{archive_name} does not contain {BINARY_NAME}"),
+    ))
+}
+
+fn file_name_is_binary(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == BINARY_NAME)
+}
+
+/// Returns the upgrade command if `exe` was installed by a package manager,
+/// which would lose track of a binary replaced behind its back.
+fn package_manager_command(exe: &Path) -> Option<&'static str> {
+    let path = exe.to_string_lossy();
+    (path.contains("/Cellar/") || path.contains("/homebrew/") || path.contains("/linuxbrew/"))
+        .then_some("brew upgrade mule-lazy-migrate")
+}
+
+/// Writes `binary` next to `exe` and moves it into place. A running
+/// executable cannot be overwritten on Windows, but it can be renamed.
+fn replace_executable(exe: &Path, binary: &[u8]) -> io::Result<()> {
+    let sibling = |suffix: &str| -> PathBuf {
+        let mut name = exe.file_name().unwrap_or_default().to_os_string();
+        name.push(suffix);
+        exe.with_file_name(name)
+    };
+    let staged = sibling(".new");
+    fs::write(&staged, binary)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))?;
+    }
+    // Windows cannot replace a running executable, but can rename it
+    let old = cfg!(windows).then(|| sibling(".old"));
+    if let Some(old) = &old {
+        fs::remove_file(old).ok();
+        fs::rename(exe, old)?;
+    }
+    fs::rename(&staged, exe).inspect_err(|_| {
+        fs::remove_file(&staged).ok();
+        // Put the installed binary back
+        if let Some(old) = &old {
+            fs::rename(old, exe).ok();
+        }
+    })
+}
+
+/// Replaces the running binary with the latest release, or with
+/// `check_only` just reports whether there is one. With `skip_checksum` the
+/// download is installed without verifying it against its `.sha256` file.
+pub fn self_update(check_only: bool, skip_checksum: bool) -> Result<UpdateOutcome, String> {
+    let release = latest_release()?;
+    if !is_newer(&release.version) {
+        return Ok(UpdateOutcome::UpToDate);
+    }
+    if check_only {
+        return Ok(UpdateOutcome::Available(release.version));
+    }
+    let exe = std::env::current_exe()
+        .and_then(fs::canonicalize)
+        .map_err(|e| format!("cannot locate the running executable: {e}"))?;
+    if let Some(command) = package_manager_command(&exe) {
+        return Err(format!(
+            "{} is managed by Homebrew; run `{command}` instead",
+            exe.display()
+        ));
+    }
+    let triple = target_triple().ok_or_else(|| {
+        "no prebuilt binaries for this platform; run `cargo install mule-lazy-migrate` instead"
+            .to_string()
+    })?;
+    let asset = select_asset(&release.assets, triple).ok_or_else(|| {
+        format!(
+            "release {} has no binary for {triple}; run `cargo install mule-lazy-migrate` instead",
+            release.version
+        )
+    })?;
+    log::info!("Downloading {}", asset.browser_download_url);
+    let data = http::get_bytes(&asset.browser_download_url)
+        .map_err(|e| format!("failed to download {}: {e}", asset.name))?;
+    verify_checksum(&data, asset, &release.assets, skip_checksum)?;
+    let binary = extract_binary(&asset.name, &data).map_err(|e| e.to_string())?;
+    replace_executable(&exe, &binary)
+        .map_err(|e| format!("failed to replace {}: {e}", exe.display()))?;
+    Ok(UpdateOutcome::Updated(release.version))
+}
+
+/// Latest release seen by the new-version notice.
+#[derive(Debug, Serialize, Deserialize)]
+struct NoticeCache {
+    version: String,
+    /// Unix timestamp of the lookup.
+    checked_at: i64,
+}
+
+/// Returns a notice if a newer release is out. The latest release is looked
+/// up at most once a day, with a short timeout; failed lookups are not retried
+/// before the next day either.
+pub fn update_notice() -> Option<String> {
+    if std::env::var_os(NO_UPDATE_CHECK_ENV).is_some() {
+        return None;
+    }
+    let path = lookup::cache_dir()?.join("latest-release.json");
+    let now = chrono::Utc::now().timestamp();
+    let cached: Option<NoticeCache> = fs::read_to_string(&path)
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok());
+    let latest = match cached {
+        Some(cache) if now - cache.checked_at < NOTICE_INTERVAL_HOURS * 3600 => cache.version,
+        cached => {
+            let version = match latest_release_within(Some(NOTICE_TIMEOUT)) {
+                Ok(release) => release.version,
+                Err(e) => {
+                    log::debug!("New-version check failed: {e}");
+                    cached.map(|c| c.version).unwrap_or_default()
+                }
+            };
+            let cache = NoticeCache {
+                version: version.clone(),
+                checked_at: now,
+            };
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).ok();
+            }
+            let data = serde_json::to_string(&cache).expect("Failed to serialize cache");
+            fs::write(&path, data).ok();
+            version
+        }
+    };
+    is_newer(&latest).then(|| {
+        format!(
+            "mule-lazy-migrate {latest} is available (you have {CURRENT_VERSION}); run `mule-lazy-migrate self-update`"
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_release_parsing_and_asset_selection() {
+        let json = r#"{
+  "tag_name": "v0.2.0",
+  "assets": [
+    {"name": "mule-lazy-migrate-x86_64-unknown-linux-gnu.tar.gz", "browser_download_url": "https://example.com/linux.tar.gz"},
+    {"name": "mule-lazy-migrate-x86_64-unknown-linux-gnu.tar.gz.sha256", "browser_download_url": "https://example.com/linux.sha256"},
+    {"name": "mule-lazy-migrate-x86_64-pc-windows-msvc.zip", "browser_download_url": "https://example.com/windows.zip"}
+  ]
+}"#;
+        let release = parse_github_release(json).unwrap();
+        assert_eq!(release.version, "0.2.0");
+        assert!(is_newer(&release.version));
+        assert!(!is_newer(CURRENT_VERSION));
+        let asset = select_asset(&release.assets, "x86_64-unknown-linux-gnu").unwrap();
+        assert_eq!(
+            asset.browser_download_url,
+            "https://example.com/linux.tar.gz"
+        );
+        assert!(select_asset(&release.assets, "aarch64-apple-darwin").is_none());
+
+        let crates = r#"{"crate": {"name": "mule-lazy-migrate", "max_stable_version": "0.2.0"}}"#;
+        assert_eq!(parse_crates_io(crates), Some("0.2.0".to_string()));
+    }
+
+    #[test]
+    fn test_verify_checksum_refuses_release_without_checksum() {
+        let asset = ReleaseAsset {
+            name: "mule-lazy-migrate-x86_64-unknown-linux-gnu.tar.gz".to_string(),
+            browser_download_url: "https://example.com/linux.tar.gz".to_string(),
+        };
+        let assets = [asset.clone()];
+        let err = verify_checksum(b"data", &asset, &assets, false).unwrap_err();
+        assert_eq!(
+            err,
+            "release has no mule-lazy-migrate-x86_64-unknown-linux-gnu.tar.gz.sha256 to verify mule-lazy-migrate-x86_64-unknown-linux-gnu.tar.gz against; pass --skip-checksum to install it anyway"
+        );
+        assert!(verify_checksum(b"data", &asset, &assets, true).is_ok());
+    }
+
+    #[test]
+    fn test_extract_binary_from_tar_gz() {
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            Vec::new(),
+            flate2::Compression::default(),
+        ));
+        for (path, content) in [
+            ("mule-lazy-migrate-0.2.0/README.md", &b"readme"[..]),
+            (
+                &format!("mule-lazy-migrate-0.2.0/{BINARY_NAME}"),
+                &b"\x7fELF"[..],
+            ),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o755);
+            header.set_cksum();
+            builder.append_data(&mut header, path, content).unwrap();
+        }
+        let data = builder.into_inner().unwrap().finish().unwrap();
+        let binary = extract_binary("mule-lazy-migrate.tar.gz", &data).unwrap();
+        assert_eq!(binary, b"\x7fELF");
+        assert!(extract_binary("mule-lazy-migrate.tar.gz", b"").is_err());
+    }
+}
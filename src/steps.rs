@@ -0,0 +1,943 @@
+//! Individual migration steps for library users who want to run only some
+//! of them, e.g. just the pom.xml update, instead of [`crate::run_migration`].
+//!
+//! Every step is configured builder-style, then either `plan()`ned, which
+//! reads the project but changes nothing, or `apply()`d, which writes the
+//! planned changes all-or-nothing.
+//!
+//! The steps also implement [`MigrationStep`], so they can be combined with
+//! custom steps in a [`Pipeline`] where each step sees the changes of the
+//! steps before it.
+//!
+//! ```no_run
+//! use mule_lazy_migrate::profiles;
+//! use mule_lazy_migrate::steps::PomUpdater;
+//!
+//! let config = profiles::default_config()?;
+//! let plan = PomUpdater::new("path/to/app", &config).plan()?;
+//! for change in &plan.changes {
+//!     println!("{change}");
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::backup::Snapshot;
+use crate::config::{MigrationConfig, ReplacementRule};
+use crate::error::MigrateError;
+use crate::file_ops::{self, FileFilter};
+use crate::parent_pom::{self, InheritedProperties, InheritedProperty};
+use crate::transaction::Transaction;
+use crate::version::VersionBump;
+use crate::{exchange, json_ops, lookup, template, xml};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// The built-in steps of [`crate::run_migration`], in the order they run,
+/// for selecting a subset of them with `--only` and `--skip`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum StepKind {
+    /// Versions in pom.xml
+    Pom,
+    /// Fields of mule-artifact.json
+    Artifact,
+    /// Mule XML rules and MUnit suites
+    MuleXml,
+    /// Schema locations of Mule configs and MUnit suites
+    SchemaLocations,
+    /// YAML property files
+    YamlProperties,
+    /// `.properties` files
+    Properties,
+    /// log4j2.xml edits
+    Log4j2,
+    /// DataWeave renames
+    Dataweave,
+    /// String replacements in source files
+    Replacements,
+    /// Replacements and YAML updates in CI definitions
+    Ci,
+    /// Base images and `ENV` values in Dockerfiles
+    Dockerfile,
+    /// External step plugins
+    Plugins,
+    /// File renames and moves
+    FileRenames,
+    /// Files to delete and create
+    Files,
+    /// Version bump of the project in pom.xml
+    ProjectVersion,
+    /// Asset version bump in exchange.json
+    ExchangeJson,
+    /// Scan of custom Java code for JDK incompatibilities
+    JavaCompat,
+}
+
+impl StepKind {
+    pub const ALL: [StepKind; 17] = [
+        StepKind::Pom,
+        StepKind::Artifact,
+        StepKind::MuleXml,
+        StepKind::SchemaLocations,
+        StepKind::YamlProperties,
+        StepKind::Properties,
+        StepKind::Log4j2,
+        StepKind::Dataweave,
+        StepKind::Replacements,
+        StepKind::Ci,
+        StepKind::Dockerfile,
+        StepKind::Plugins,
+        StepKind::FileRenames,
+        StepKind::Files,
+        StepKind::ProjectVersion,
+        StepKind::ExchangeJson,
+        StepKind::JavaCompat,
+    ];
+
+    /// Name of the step on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            StepKind::Pom => "pom",
+            StepKind::Artifact => "artifact",
+            StepKind::MuleXml => "mule-xml",
+            StepKind::SchemaLocations => "schema-locations",
+            StepKind::YamlProperties => "yaml-properties",
+            StepKind::Properties => "properties",
+            StepKind::Log4j2 => "log4j2",
+            StepKind::Dataweave => "dataweave",
+            StepKind::Replacements => "replacements",
+            StepKind::Ci => "ci",
+            StepKind::Dockerfile => "dockerfile",
+            StepKind::Plugins => "plugins",
+            StepKind::FileRenames => "file-renames",
+            StepKind::Files => "files",
+            StepKind::ProjectVersion => "project-version",
+            StepKind::ExchangeJson => "exchange-json",
+            StepKind::JavaCompat => "java-compat",
+        }
+    }
+
+    /// Checks whether the step runs: it is in `only` (or `only` is empty)
+    /// and not in `skip`.
+    pub fn is_selected(self, only: &[StepKind], skip: &[StepKind]) -> bool {
+        (only.is_empty() || only.contains(&self)) && !skip.contains(&self)
+    }
+}
+
+/// Changes a step would make to one file.
+#[derive(Debug, Clone)]
+pub struct FilePlan {
+    pub path: PathBuf,
+    pub original: String,
+    pub content: String,
+    /// One description per change, e.g. `app.runtime: '4.4.0' -> '4.9.4'`.
+    pub changes: Vec<String>,
+}
+
+impl FilePlan {
+    /// A plan for one of many files a step changes; each change is
+    /// prefixed with the path.
+    pub fn for_file(
+        path: PathBuf,
+        original: String,
+        content: String,
+        changes: Vec<String>,
+    ) -> Self {
+        let changes = changes
+            .into_iter()
+            .map(|c| format!("{}: {c}", path.display()))
+            .collect();
+        FilePlan {
+            path,
+            original,
+            content,
+            changes,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// Writes the non-empty plans, rolling back every file already written if
+/// one write fails. Files are saved to `backup` first if given.
+///
+/// # Errors
+/// Returns an error if a backup or write fails.
+pub fn apply_plans(
+    plans: impl IntoIterator<Item = FilePlan>,
+    backup: Option<&Snapshot>,
+) -> Result<Vec<PathBuf>, MigrateError> {
+    let mut tx = Transaction::new();
+    for plan in plans.into_iter().filter(|p| !p.is_empty()) {
+        tx.stage(&plan.path, plan.original, plan.content);
+    }
+    tx.commit(backup)
+}
+
+/// What a step sees of the project: the content earlier steps staged but
+/// did not write yet, else the files on disk.
+#[derive(Clone, Copy)]
+pub struct StepContext<'a> {
+    staged: &'a Transaction,
+}
+
+impl<'a> StepContext<'a> {
+    pub fn new(staged: &'a Transaction) -> Self {
+        StepContext { staged }
+    }
+
+    /// Reads `path` as the earlier steps left it.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or is not text.
+    pub fn read(&self, path: &Path) -> Result<String, MigrateError> {
+        match self.staged.staged_content(path) {
+            Some(content) => Ok(content.to_string()),
+            None => file_ops::read_file(path),
+        }
+    }
+
+    /// Reads each of `paths` as the earlier steps left it, skipping the
+    /// files that cannot be read.
+    pub fn read_each(&self, paths: Vec<PathBuf>) -> impl Iterator<Item = (PathBuf, String)> + 'a {
+        let ctx = *self;
+        paths
+            .into_iter()
+            .filter_map(move |path| match ctx.read(&path) {
+                Ok(content) => Some((path, content)),
+                Err(e) => {
+                    log::debug!("{e}");
+                    None
+                }
+            })
+    }
+
+    /// Template variables of the project, from its pom.xml as the earlier
+    /// steps left it.
+    pub fn project_variables(
+        &self,
+        config: &MigrationConfig,
+        project_root: &Path,
+    ) -> BTreeMap<&'static str, String> {
+        let pom = self.read(&project_root.join("pom.xml")).ok();
+        template::project_variables(config, pom.as_deref(), project_root)
+    }
+
+    /// Whether earlier steps changed anything.
+    pub fn has_changes(&self) -> bool {
+        !self.staged.is_empty()
+    }
+
+    /// The files earlier steps changed, with their new content.
+    pub fn staged_files(&self) -> impl Iterator<Item = (&Path, &str)> {
+        self.staged
+            .writes()
+            .iter()
+            .map(|w| (w.path.as_path(), w.content.as_str()))
+    }
+}
+
+/// A migration step that can run on its own or as part of a [`Pipeline`].
+pub trait MigrationStep {
+    /// Short name for logs and summaries, e.g. `pom.xml`.
+    fn name(&self) -> &str;
+
+    /// Files the step reads, for callers that count or report them one by
+    /// one; a step reading many files skips those that cannot be read.
+    /// Empty if the step does not list them.
+    fn files(&self) -> Vec<PathBuf> {
+        Vec::new()
+    }
+
+    /// Checks `content` of one of [`files`](Self::files); the step skips
+    /// the files that fail.
+    fn check_file(&self, _path: &Path, _content: &str) -> Result<(), MigrateError> {
+        Ok(())
+    }
+
+    /// Computes the changes of the step without writing anything, one plan
+    /// per file that would change.
+    ///
+    /// # Errors
+    /// Returns an error if a file the step needs cannot be processed.
+    fn plan(&self, ctx: &StepContext) -> Result<Vec<FilePlan>, MigrateError>;
+
+    /// Adds the planned changes to `tx`, which writes them together with the
+    /// changes of the other steps. Steps with effects beyond file changes
+    /// perform them here.
+    ///
+    /// # Errors
+    /// Returns an error if the step cannot be applied.
+    fn apply(&self, plans: Vec<FilePlan>, tx: &mut Transaction) -> Result<(), MigrateError> {
+        for plan in plans {
+            tx.stage(&plan.path, plan.original, plan.content);
+        }
+        Ok(())
+    }
+
+    /// Describes the planned changes, one line each.
+    fn summarize(&self, plans: &[FilePlan]) -> Vec<String> {
+        plans.iter().flat_map(|p| p.changes.clone()).collect()
+    }
+}
+
+/// Changes one step of a pipeline made.
+#[derive(Debug, Clone)]
+pub struct StepSummary {
+    pub name: String,
+    pub changes: Vec<String>,
+}
+
+/// Runs migration steps in order and writes their changes all-or-nothing.
+#[derive(Default)]
+pub struct Pipeline<'a> {
+    steps: Vec<Box<dyn MigrationStep + 'a>>,
+}
+
+impl<'a> Pipeline<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a step; steps run in the order they are added.
+    pub fn step(mut self, step: impl MigrationStep + 'a) -> Self {
+        self.steps.push(Box::new(step));
+        self
+    }
+
+    /// Plans and applies every step, each seeing the changes of the steps
+    /// before it, then writes all changes unless `dry_run` is set.
+    ///
+    /// # Errors
+    /// Returns the first error of a step, or of writing the changes; no file
+    /// is left half-migrated in either case.
+    pub fn run(
+        &self,
+        dry_run: bool,
+        backup: Option<&Snapshot>,
+    ) -> Result<Vec<StepSummary>, MigrateError> {
+        let mut tx = Transaction::new();
+        let mut summaries = Vec::new();
+        for step in &self.steps {
+            log::info!("Running step {}", step.name());
+            let plans = step.plan(&StepContext::new(&tx))?;
+            summaries.push(StepSummary {
+                name: step.name().to_string(),
+                changes: step.summarize(&plans),
+            });
+            step.apply(plans, &mut tx)?;
+        }
+        if !dry_run {
+            tx.commit(backup)?;
+        }
+        Ok(summaries)
+    }
+}
+
+fn changed(plan: FilePlan) -> Vec<FilePlan> {
+    if plan.is_empty() {
+        Vec::new()
+    } else {
+        vec![plan]
+    }
+}
+
+/// Sets the runtime, plugin, MUnit and dependency versions in pom.xml.
+#[derive(Debug, Clone)]
+pub struct PomUpdater<'a> {
+    path: PathBuf,
+    config: &'a MigrationConfig,
+    latest_connectors: bool,
+}
+
+impl<'a> PomUpdater<'a> {
+    pub fn new<P: AsRef<Path>>(project_root: P, config: &'a MigrationConfig) -> Self {
+        PomUpdater {
+            path: project_root.as_ref().join("pom.xml"),
+            config,
+            latest_connectors: false,
+        }
+    }
+
+    /// Also raises connectors to their latest releases in the Maven
+    /// repositories of the config (network access).
+    pub fn latest_connectors(mut self, enabled: bool) -> Self {
+        self.latest_connectors = enabled;
+        self
+    }
+
+    /// Computes the new pom.xml without writing it.
+    ///
+    /// # Errors
+    /// Returns an error if pom.xml cannot be read or parsed.
+    pub fn plan(&self) -> Result<FilePlan, MigrateError> {
+        self.plan_from(file_ops::read_file(&self.path)?)
+    }
+
+    fn plan_from(&self, original: String) -> Result<FilePlan, MigrateError> {
+        let xml_err = |source| MigrateError::Xml {
+            path: self.path.clone(),
+            source,
+        };
+        let (mut content, mut changes) =
+            xml::plan_pom_xml_update(&original, self.config).map_err(xml_err)?;
+        if self.config.inherited_properties == InheritedProperties::Override {
+            let overrides = self.inherited_overrides(&original);
+            let properties: Vec<(&str, &str)> = overrides
+                .iter()
+                .map(|(p, target)| (p.name.as_str(), *target))
+                .collect();
+            content = xml::insert_pom_properties(&content, &properties).map_err(xml_err)?;
+            changes.extend(overrides.iter().map(|(p, target)| {
+                format!(
+                    "{}: '{}' (inherited from {}) -> '{target}' (local override)",
+                    p.name,
+                    p.value,
+                    p.source.display()
+                )
+            }));
+        }
+        if self.config.add_missing_properties {
+            let missing = self.missing_properties(&original);
+            content = xml::insert_pom_properties(&content, &missing).map_err(xml_err)?;
+            changes.extend(
+                missing
+                    .iter()
+                    .map(|(name, value)| format!("{name}: added '{value}'")),
+            );
+        }
+        if !self.config.exchange.api_specs.is_empty() {
+            let versions = exchange::resolve_api_spec_versions(
+                &content,
+                &self.config.exchange,
+                &self.config.version_lookup,
+            )
+            .map_err(xml_err)?;
+            let (updated, spec_changes) =
+                exchange::update_api_spec_versions(&content, &versions).map_err(xml_err)?;
+            content = updated;
+            changes.extend(spec_changes);
+        }
+        if self.latest_connectors {
+            log::info!("Looking up latest connector releases...");
+            let (upgraded, upgrades) =
+                lookup::upgrade_to_latest_connectors(&content, self.config).map_err(xml_err)?;
+            content = upgraded;
+            changes.extend(upgrades);
+        }
+        Ok(FilePlan {
+            path: self.path.clone(),
+            original,
+            content,
+            changes,
+        })
+    }
+
+    /// Version properties pom.xml inherits from a parent pom with a value
+    /// other than the target, paired with the target.
+    pub fn inherited_overrides(&self, content: &str) -> Vec<(InheritedProperty, &'a str)> {
+        let targets = xml::pom_property_updates(
+            &self.config.app_runtime_version,
+            &self.config.mule_maven_plugin_version,
+            &self.config.munit_version,
+        );
+        let names: Vec<&str> = targets.iter().map(|(name, _)| *name).collect();
+        parent_pom::inherited_properties(&self.path, content, &names)
+            .into_iter()
+            .filter_map(|p| {
+                let (_, target) = targets.iter().find(|(name, _)| *name == p.name)?;
+                (p.value != *target).then_some((p, *target))
+            })
+            .collect()
+    }
+
+    /// Version properties pom.xml neither declares nor inherits (unless
+    /// parent poms are ignored), with their target values. `mule.version`
+    /// is left out: Mule 4 apps declare the runtime as `app.runtime`.
+    fn missing_properties(&self, content: &str) -> Vec<(&'static str, &'a str)> {
+        let targets = xml::pom_property_updates(
+            &self.config.app_runtime_version,
+            &self.config.mule_maven_plugin_version,
+            &self.config.munit_version,
+        );
+        let mut missing: Vec<_> = targets
+            .into_iter()
+            .filter(|(name, _)| *name != "mule.version")
+            .filter(|(name, _)| matches!(xml::pom_property(content, name), Ok(None)))
+            .collect();
+        if self.config.inherited_properties != InheritedProperties::Ignore {
+            let names: Vec<&str> = missing.iter().map(|(name, _)| *name).collect();
+            let inherited = parent_pom::inherited_properties(&self.path, content, &names);
+            missing.retain(|(name, _)| !inherited.iter().any(|p| p.name == *name));
+        }
+        missing
+    }
+
+    /// Plans and writes the update, returning its changes.
+    ///
+    /// # Errors
+    /// Returns an error if pom.xml cannot be read, parsed or written.
+    pub fn apply(&self) -> Result<Vec<String>, MigrateError> {
+        let plan = self.plan()?;
+        let changes = plan.changes.clone();
+        apply_plans([plan], None)?;
+        Ok(changes)
+    }
+}
+
+/// Sets `minMuleVersion`, `javaSpecificationVersions` and the extra fields
+/// of mule-artifact.json.
+#[derive(Debug, Clone)]
+pub struct ArtifactJsonUpdater<'a> {
+    path: PathBuf,
+    config: &'a MigrationConfig,
+}
+
+impl<'a> ArtifactJsonUpdater<'a> {
+    pub fn new<P: AsRef<Path>>(project_root: P, config: &'a MigrationConfig) -> Self {
+        ArtifactJsonUpdater {
+            path: project_root.as_ref().join("mule-artifact.json"),
+            config,
+        }
+    }
+
+    /// Computes the new mule-artifact.json without writing it.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or parsed, or an extra
+    /// field cannot be set.
+    pub fn plan(&self) -> Result<FilePlan, MigrateError> {
+        self.plan_from(file_ops::read_file(&self.path)?)
+    }
+
+    fn plan_from(&self, original: String) -> Result<FilePlan, MigrateError> {
+        let artifact = &self.config.mule_artifact;
+        let (content, mut changes) = json_ops::plan_mule_artifact_json_update(
+            &original,
+            &artifact.min_mule_version,
+            &artifact.java_specification_versions,
+        )
+        .map_err(|source| MigrateError::Json {
+            path: self.path.clone(),
+            source,
+        })?;
+        let (content, extra) =
+            json_ops::plan_mule_artifact_extra_fields(&content, &artifact.extra_fields).map_err(
+                |reason| MigrateError::JsonField {
+                    path: self.path.clone(),
+                    reason,
+                },
+            )?;
+        changes.extend(extra);
+        Ok(FilePlan {
+            path: self.path.clone(),
+            original,
+            content,
+            changes,
+        })
+    }
+
+    /// Plans and writes the update, returning its changes.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read, parsed or written.
+    pub fn apply(&self) -> Result<Vec<String>, MigrateError> {
+        let plan = self.plan()?;
+        let changes = plan.changes.clone();
+        apply_plans([plan], None)?;
+        Ok(changes)
+    }
+}
+
+/// Changes the asset version and API version in exchange.json, see
+/// [`json_ops::plan_exchange_json_update`]. As a [`MigrationStep`] it only
+/// increments them if earlier steps changed the project.
+#[derive(Debug, Clone)]
+pub struct ExchangeJsonUpdater<'a> {
+    path: PathBuf,
+    config: &'a MigrationConfig,
+}
+
+impl<'a> ExchangeJsonUpdater<'a> {
+    pub fn new<P: AsRef<Path>>(project_root: P, config: &'a MigrationConfig) -> Self {
+        ExchangeJsonUpdater {
+            path: project_root.as_ref().join("exchange.json"),
+            config,
+        }
+    }
+
+    fn plan_from(
+        &self,
+        original: String,
+        allow_increments: bool,
+    ) -> Result<FilePlan, MigrateError> {
+        let (content, changes) = json_ops::plan_exchange_json_update(
+            &original,
+            &self.config.exchange_json,
+            allow_increments,
+        )
+        .map_err(|reason| MigrateError::JsonField {
+            path: self.path.clone(),
+            reason,
+        })?;
+        Ok(FilePlan {
+            path: self.path.clone(),
+            original,
+            content,
+            changes: changes
+                .into_iter()
+                .map(|c| format!("exchange.json {c}"))
+                .collect(),
+        })
+    }
+}
+
+/// Changes the project's own version in pom.xml, see
+/// [`xml::plan_project_version_update`]. As a [`MigrationStep`] it only
+/// increments it if earlier steps changed the project.
+#[derive(Debug, Clone)]
+pub struct ProjectVersionUpdater<'a> {
+    path: PathBuf,
+    bump: &'a VersionBump,
+}
+
+impl<'a> ProjectVersionUpdater<'a> {
+    pub fn new<P: AsRef<Path>>(project_root: P, bump: &'a VersionBump) -> Self {
+        ProjectVersionUpdater {
+            path: project_root.as_ref().join("pom.xml"),
+            bump,
+        }
+    }
+
+    fn plan_from(
+        &self,
+        original: String,
+        allow_increments: bool,
+    ) -> Result<FilePlan, MigrateError> {
+        let (content, changes) =
+            xml::plan_project_version_update(&original, self.bump, allow_increments).map_err(
+                |reason| MigrateError::ProjectVersion {
+                    path: self.path.clone(),
+                    reason,
+                },
+            )?;
+        Ok(FilePlan {
+            path: self.path.clone(),
+            original,
+            content,
+            changes,
+        })
+    }
+}
+
+/// Applies string replacement rules to the source files of a project.
+#[derive(Debug, Clone)]
+pub struct SourceReplacer<'a> {
+    project_root: PathBuf,
+    rules: &'a [ReplacementRule],
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl<'a> SourceReplacer<'a> {
+    pub fn new<P: AsRef<Path>>(project_root: P, rules: &'a [ReplacementRule]) -> Self {
+        SourceReplacer {
+            project_root: project_root.as_ref().to_path_buf(),
+            rules,
+            include: Vec::new(),
+            exclude: Vec::new(),
+        }
+    }
+
+    /// Only considers files matching `glob` (relative to the project root).
+    pub fn include(mut self, glob: impl Into<String>) -> Self {
+        self.include.push(glob.into());
+        self
+    }
+
+    /// Never touches files matching `glob` (relative to the project root).
+    pub fn exclude(mut self, glob: impl Into<String>) -> Self {
+        self.exclude.push(glob.into());
+        self
+    }
+
+    /// Computes the replacements without writing them, one plan per file
+    /// that would change.
+    ///
+    /// # Errors
+    /// Returns an error if a glob is invalid or a file cannot be processed.
+    pub fn plan(&self) -> Result<Vec<FilePlan>, MigrateError> {
+        self.plan_with(|p| file_ops::read_text(p))
+    }
+
+    fn plan_with<F>(&self, read: F) -> Result<Vec<FilePlan>, MigrateError>
+    where
+        F: FnMut(&Path) -> std::io::Result<String>,
+    {
+        let filter = FileFilter::new(&self.include, &self.exclude)?;
+        let plan = file_ops::plan_replacements(&self.project_root, self.rules, &filter, read);
+        if let Some(e) = plan.errors.into_iter().next() {
+            return Err(e);
+        }
+        Ok(plan
+            .planned
+            .into_iter()
+            .map(|p| FilePlan {
+                path: p.path,
+                original: p.original,
+                content: p.content,
+                changes: p.summary,
+            })
+            .collect())
+    }
+
+    /// Plans and writes the replacements, returning their descriptions.
+    ///
+    /// # Errors
+    /// Returns an error if a file cannot be processed or written.
+    pub fn apply(&self) -> Result<Vec<String>, MigrateError> {
+        let plans = self.plan()?;
+        let changes = plans.iter().flat_map(|p| p.changes.clone()).collect();
+        apply_plans(plans, None)?;
+        Ok(changes)
+    }
+}
+
+impl MigrationStep for PomUpdater<'_> {
+    fn name(&self) -> &str {
+        "pom.xml"
+    }
+
+    fn plan(&self, ctx: &StepContext) -> Result<Vec<FilePlan>, MigrateError> {
+        self.plan_from(ctx.read(&self.path)?).map(changed)
+    }
+}
+
+impl MigrationStep for ArtifactJsonUpdater<'_> {
+    fn name(&self) -> &str {
+        "mule-artifact.json"
+    }
+
+    fn plan(&self, ctx: &StepContext) -> Result<Vec<FilePlan>, MigrateError> {
+        self.plan_from(ctx.read(&self.path)?).map(changed)
+    }
+}
+
+impl MigrationStep for ExchangeJsonUpdater<'_> {
+    fn name(&self) -> &str {
+        "exchange.json"
+    }
+
+    fn plan(&self, ctx: &StepContext) -> Result<Vec<FilePlan>, MigrateError> {
+        self.plan_from(ctx.read(&self.path)?, ctx.has_changes())
+            .map(changed)
+    }
+}
+
+impl MigrationStep for ProjectVersionUpdater<'_> {
+    fn name(&self) -> &str {
+        "project-version"
+    }
+
+    fn plan(&self, ctx: &StepContext) -> Result<Vec<FilePlan>, MigrateError> {
+        self.plan_from(ctx.read(&self.path)?, ctx.has_changes())
+            .map(changed)
+    }
+}
+
+impl MigrationStep for SourceReplacer<'_> {
+    fn name(&self) -> &str {
+        "replacements"
+    }
+
+    fn plan(&self, ctx: &StepContext) -> Result<Vec<FilePlan>, MigrateError> {
+        self.plan_with(|p| match ctx.staged.staged_content(p) {
+            Some(content) => Ok(content.to_string()),
+            None => file_ops::read_text(p),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiles;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_steps_plan_and_apply_individually() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("pom.xml"),
+            "<project><properties><app.runtime>4.4.0</app.runtime></properties></project>",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("mule-artifact.json"),
+            r#"{"minMuleVersion": "4.4.0"}"#,
+        )
+        .unwrap();
+        let config = profiles::default_config().unwrap();
+
+        let pom = PomUpdater::new(dir.path(), &config).plan().unwrap();
+        assert_eq!(pom.changes, ["app.runtime: '4.4.0' -> '4.9.4'"]);
+        assert!(fs::read_to_string(dir.path().join("pom.xml"))
+            .unwrap()
+            .contains("4.4.0"));
+
+        let changes = ArtifactJsonUpdater::new(dir.path(), &config)
+            .apply()
+            .unwrap();
+        assert!(!changes.is_empty());
+        assert!(fs::read_to_string(dir.path().join("mule-artifact.json"))
+            .unwrap()
+            .contains("4.9.0"));
+
+        let rules = [ReplacementRule {
+            from: "4.4.0".to_string(),
+            to: "4.9.4".to_string(),
+            regex: false,
+            paths: Vec::new(),
+            whole_word: false,
+            case_insensitive: false,
+            max_replacements: None,
+            attribute: None,
+            skip_dataweave: false,
+        }];
+        let plans = SourceReplacer::new(dir.path(), &rules)
+            .include("*.json")
+            .plan()
+            .unwrap();
+        assert!(plans.is_empty());
+    }
+
+    /// Appends a line to the Jenkinsfile, as a step from another crate would.
+    struct JenkinsfileStep(PathBuf);
+
+    impl MigrationStep for JenkinsfileStep {
+        fn name(&self) -> &str {
+            "Jenkinsfile"
+        }
+
+        fn plan(&self, ctx: &StepContext) -> Result<Vec<FilePlan>, MigrateError> {
+            let original = ctx.read(&self.0)?;
+            Ok(vec![FilePlan {
+                path: self.0.clone(),
+                content: format!("{original}// migrated\n"),
+                original,
+                changes: vec!["Jenkinsfile: marked as migrated".to_string()],
+            }])
+        }
+    }
+
+    #[test]
+    fn test_pom_updater_adds_missing_properties() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("pom.xml"),
+            "<project>\n  <properties>\n    <app.runtime>4.4.0</app.runtime>\n  </properties>\n</project>\n",
+        )
+        .unwrap();
+        let mut config = profiles::default_config().unwrap();
+        config.add_missing_properties = true;
+
+        let pom = PomUpdater::new(dir.path(), &config).plan().unwrap();
+        assert_eq!(
+            pom.changes,
+            [
+                "app.runtime: '4.4.0' -> '4.9.4'",
+                "munit.version: added '3.4.0'",
+                "mule.maven.plugin.version: added '4.3.1'"
+            ]
+        );
+        assert_eq!(
+            pom.content,
+            "<project>\n  <properties>\n    <app.runtime>4.9.4</app.runtime>\n    <munit.version>3.4.0</munit.version>\n    <mule.maven.plugin.version>4.3.1</mule.maven.plugin.version>\n  </properties>\n</project>\n"
+        );
+    }
+
+    #[test]
+    fn test_pipeline_runs_custom_steps_on_staged_content() {
+        let dir = tempdir().unwrap();
+        // .groovy, so the replacements consider it too
+        let jenkinsfile = dir.path().join("Jenkinsfile.groovy");
+        fs::write(&jenkinsfile, "pipeline {}\n").unwrap();
+        let rules = [ReplacementRule {
+            from: "migrated".to_string(),
+            to: "migrated to 4.9".to_string(),
+            regex: false,
+            paths: Vec::new(),
+            whole_word: false,
+            case_insensitive: false,
+            max_replacements: None,
+            attribute: None,
+            skip_dataweave: false,
+        }];
+        let pipeline = Pipeline::new()
+            .step(JenkinsfileStep(jenkinsfile.clone()))
+            .step(SourceReplacer::new(dir.path(), &rules));
+
+        let summaries = pipeline.run(true, None).unwrap();
+        assert_eq!(summaries[0].changes, ["Jenkinsfile: marked as migrated"]);
+        // The replacements see the content the first step staged
+        assert_eq!(summaries[1].changes.len(), 1);
+        assert_eq!(fs::read_to_string(&jenkinsfile).unwrap(), "pipeline {}\n");
+
+        pipeline.run(false, None).unwrap();
+        assert_eq!(
+            fs::read_to_string(&jenkinsfile).unwrap(),
+            "pipeline {}\n// migrated to 4.9\n"
+        );
+    }
+
+    #[test]
+    fn test_exchange_json_updater_bumps_only_after_changes() {
+        use crate::version::BumpKind;
+        let dir = tempdir().unwrap();
+        let jenkinsfile = dir.path().join("Jenkinsfile");
+        fs::write(&jenkinsfile, "pipeline {}\n").unwrap();
+        fs::write(
+            dir.path().join("exchange.json"),
+            "{\n  \"version\": \"1.2.3\"\n}\n",
+        )
+        .unwrap();
+        let mut config = profiles::default_config().unwrap();
+        config.exchange_json.version = Some(VersionBump::Bump(BumpKind::Patch));
+
+        let alone = Pipeline::new().step(ExchangeJsonUpdater::new(dir.path(), &config));
+        assert!(alone.run(true, None).unwrap()[0].changes.is_empty());
+
+        let pipeline = Pipeline::new()
+            .step(JenkinsfileStep(jenkinsfile))
+            .step(ExchangeJsonUpdater::new(dir.path(), &config));
+        let summaries = pipeline.run(true, None).unwrap();
+        assert_eq!(
+            summaries[1].changes,
+            ["exchange.json /version: \"1.2.3\" -> \"1.2.4\""]
+        );
+    }
+
+    #[test]
+    fn test_project_version_updater_reads_staged_pom() {
+        use crate::version::BumpKind;
+        let dir = tempdir().unwrap();
+        let pom = dir.path().join("pom.xml");
+        fs::write(&pom, "<project><artifactId>orders</artifactId></project>").unwrap();
+        let bump = VersionBump::Bump(BumpKind::Minor);
+        let step = ProjectVersionUpdater::new(dir.path(), &bump);
+        let mut tx = Transaction::new();
+        // Nothing changed, nothing to bump
+        assert!(step.plan(&StepContext::new(&tx)).unwrap().is_empty());
+
+        let staged = "<project><artifactId>orders</artifactId><version>1.2.3</version></project>";
+        tx.stage(&pom, String::new(), staged.to_string());
+        let plans = step.plan(&StepContext::new(&tx)).unwrap();
+        assert!(plans[0].content.contains("<version>1.3.0</version>"));
+
+        tx.stage(&pom, String::new(), "<project/>".to_string());
+        let err = step.plan(&StepContext::new(&tx)).unwrap_err();
+        assert!(matches!(err, MigrateError::ProjectVersion { .. }), "{err}");
+    }
+}
@@ -0,0 +1,163 @@
+use crate::version::Version;
+use chrono::NaiveDate;
+
+/// Days before the end of standard support from which a target runtime is
+/// reported as nearing it.
+const NEARING_DAYS: i64 = 90;
+
+/// Support window of a Mule 4 runtime minor line.
+#[derive(Debug, Clone, Copy)]
+pub struct RuntimeSupport {
+    /// Minor line, e.g. `4.6`.
+    pub line: &'static str,
+    /// Long-term support release.
+    pub lts: bool,
+    /// Version `--latest-lts` migrates to on this line.
+    pub release: &'static str,
+    pub standard_end: &'static str,
+    pub extended_end: &'static str,
+}
+
+/// Support windows of the Mule 4 runtime lines, from MuleSoft's product
+/// versioning and support policy. Lines newer than the last one are not
+/// checked.
+pub const RUNTIME_SUPPORT: &[RuntimeSupport] = &[
+    RuntimeSupport {
+        line: "4.1",
+        lts: false,
+        release: "4.1.6",
+        standard_end: "2020-11-02",
+        extended_end: "2022-11-02",
+    },
+    RuntimeSupport {
+        line: "4.2",
+        lts: false,
+        release: "4.2.2",
+        standard_end: "2021-05-02",
+        extended_end: "2023-05-02",
+    },
+    RuntimeSupport {
+        line: "4.3",
+        lts: false,
+        release: "4.3.0",
+        standard_end: "2023-03-07",
+        extended_end: "2024-03-07",
+    },
+    RuntimeSupport {
+        line: "4.4",
+        lts: false,
+        release: "4.4.0",
+        standard_end: "2024-10-08",
+        extended_end: "2025-10-08",
+    },
+    RuntimeSupport {
+        line: "4.5",
+        lts: false,
+        release: "4.5.0",
+        standard_end: "2024-02-08",
+        extended_end: "2024-06-08",
+    },
+    RuntimeSupport {
+        line: "4.6",
+        lts: true,
+        release: "4.6.0",
+        standard_end: "2025-08-08",
+        extended_end: "2026-02-08",
+    },
+    RuntimeSupport {
+        line: "4.7",
+        lts: false,
+        release: "4.7.0",
+        standard_end: "2024-10-08",
+        extended_end: "2025-02-08",
+    },
+    RuntimeSupport {
+        line: "4.8",
+        lts: false,
+        release: "4.8.0",
+        standard_end: "2025-02-08",
+        extended_end: "2025-06-08",
+    },
+    RuntimeSupport {
+        line: "4.9",
+        lts: true,
+        release: "4.9.4",
+        standard_end: "2026-08-08",
+        extended_end: "2027-02-08",
+    },
+];
+
+fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").expect("valid date in the support table")
+}
+
+/// Support window of the line of `version`, if it is in the table.
+pub fn runtime_support(version: &str) -> Option<&'static RuntimeSupport> {
+    let version = Version::parse(version)?;
+    let line = format!("{}.{}", version.major(), version.minor());
+    RUNTIME_SUPPORT.iter().find(|s| s.line == line)
+}
+
+/// Warns if runtime `version` is out of support on `today`, only in
+/// extended support, or nearing the end of standard support.
+pub fn runtime_warning(version: &str, today: NaiveDate) -> Option<String> {
+    let Some(support) = runtime_support(version) else {
+        log::debug!("Mule runtime {version} is not in the support table");
+        return None;
+    };
+    let (standard_end, extended_end) = (date(support.standard_end), date(support.extended_end));
+    if today >= extended_end {
+        Some(format!(
+            "Mule runtime {version} is end of life: support for {} ended on {extended_end}",
+            support.line
+        ))
+    } else if today >= standard_end {
+        Some(format!(
+            "Mule runtime {version} is only in extended support: standard support for {} ended on {standard_end}, extended support ends on {extended_end}",
+            support.line
+        ))
+    } else {
+        let days = (standard_end - today).num_days();
+        (days <= NEARING_DAYS).then(|| {
+            format!(
+                "Standard support for Mule runtime {} ends on {standard_end}, in {days} days",
+                support.line
+            )
+        })
+    }
+}
+
+/// The newest LTS release still supported (in standard or extended support)
+/// on `today`.
+pub fn latest_lts(today: NaiveDate) -> Option<&'static RuntimeSupport> {
+    RUNTIME_SUPPORT
+        .iter()
+        .filter(|s| s.lts && today < date(s.extended_end))
+        .max_by_key(|s| Version::parse(s.release))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runtime_warning_and_latest_lts() {
+        let today = date("2025-07-01");
+        assert_eq!(
+            runtime_warning("4.4.0", today).as_deref(),
+            Some("Mule runtime 4.4.0 is only in extended support: standard support for 4.4 ended on 2024-10-08, extended support ends on 2025-10-08")
+        );
+        assert_eq!(
+            runtime_warning("4.3.0-20230911", today).as_deref(),
+            Some("Mule runtime 4.3.0-20230911 is end of life: support for 4.3 ended on 2024-03-07")
+        );
+        assert_eq!(
+            runtime_warning("4.6.2", today).as_deref(),
+            Some("Standard support for Mule runtime 4.6 ends on 2025-08-08, in 38 days")
+        );
+        assert_eq!(runtime_warning("4.9.4", today), None);
+        assert_eq!(runtime_warning("4.42.0", today), None);
+        assert_eq!(latest_lts(today).map(|s| s.release), Some("4.9.4"));
+        assert!(latest_lts(date("2030-01-01")).is_none());
+    }
+}
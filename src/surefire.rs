@@ -0,0 +1,166 @@
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+/// Directories (relative to the project root) holding JUnit-style XML
+/// reports: Surefire's and the MUnit Maven plugin's.
+const REPORT_DIRS: &[&str] = &["target/surefire-reports", "target/munit-reports"];
+
+/// A test that failed or errored.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FailedTest {
+    /// Suite (class or MUnit test file) the test belongs to.
+    pub suite: String,
+    pub name: String,
+    /// First line of the failure message, if any.
+    pub message: Option<String>,
+}
+
+/// Totals of the test reports of a project.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TestSummary {
+    pub tests: usize,
+    pub failures: usize,
+    pub errors: usize,
+    pub skipped: usize,
+    pub failed: Vec<FailedTest>,
+}
+
+impl TestSummary {
+    /// Number of tests that passed.
+    pub fn passed(&self) -> usize {
+        self.tests
+            .saturating_sub(self.failures + self.errors + self.skipped)
+    }
+
+    fn add(&mut self, other: TestSummary) {
+        self.tests += other.tests;
+        self.failures += other.failures;
+        self.errors += other.errors;
+        self.skipped += other.skipped;
+        self.failed.extend(other.failed);
+    }
+}
+
+/// Parses one JUnit-style XML report (`<testsuite>` or `<testsuites>`),
+/// counting its test cases.
+pub fn parse_report(xml: &str) -> Result<TestSummary, String> {
+    let doc = roxmltree::Document::parse(xml).map_err(|e| e.to_string())?;
+    let mut summary = TestSummary::default();
+    for case in doc.descendants().filter(|n| n.has_tag_name("testcase")) {
+        summary.tests += 1;
+        let outcome = case.children().find(|n| {
+            n.has_tag_name("failure") || n.has_tag_name("error") || n.has_tag_name("skipped")
+        });
+        let Some(outcome) = outcome else { continue };
+        match outcome.tag_name().name() {
+            "skipped" => {
+                summary.skipped += 1;
+                continue;
+            }
+            "failure" => summary.failures += 1,
+            _ => summary.errors += 1,
+        }
+        let suite = case
+            .attribute("classname")
+            .or_else(|| {
+                case.ancestors()
+                    .find(|n| n.has_tag_name("testsuite"))
+                    .and_then(|n| n.attribute("name"))
+            })
+            .unwrap_or_default();
+        let message = outcome
+            .attribute("message")
+            .or_else(|| outcome.text())
+            .and_then(|m| m.trim().lines().next())
+            .filter(|m| !m.is_empty());
+        summary.failed.push(FailedTest {
+            suite: suite.to_string(),
+            name: case.attribute("name").unwrap_or_default().to_string(),
+            message: message.map(str::to_string),
+        });
+    }
+    Ok(summary)
+}
+
+/// Sums up every XML test report the last build of the project left in
+/// `target`. Unparsable reports are skipped with a warning.
+pub fn collect(project_root: &Path) -> TestSummary {
+    let mut summary = TestSummary::default();
+    for dir in REPORT_DIRS {
+        let Ok(entries) = fs::read_dir(project_root.join(dir)) else {
+            continue;
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "xml"))
+            .collect();
+        paths.sort();
+        for path in paths {
+            match fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|xml| parse_report(&xml))
+            {
+                Ok(report) => summary.add(report),
+                Err(e) => log::warn!("Skipping test report {}: {e}", path.display()),
+            }
+        }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_collect_sums_reports_and_lists_failures() {
+        let dir = tempdir().unwrap();
+        let reports = dir.path().join("target/munit-reports");
+        fs::create_dir_all(&reports).unwrap();
+        fs::write(
+            reports.join("TEST-orders-test-suite.xml"),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuite name="orders-test-suite.xml" tests="4" failures="1" errors="1" skipped="1">
+  <testcase name="create-order-test" classname="orders-test-suite.xml"/>
+  <testcase name="get-order-test" classname="orders-test-suite.xml">
+    <failure message="Expected 200 but was 500&#10;at flow get-order">stack</failure>
+  </testcase>
+  <testcase name="delete-order-test" classname="orders-test-suite.xml">
+    <error>java.lang.NullPointerException</error>
+  </testcase>
+  <testcase name="legacy-test" classname="orders-test-suite.xml"><skipped/></testcase>
+</testsuite>"#,
+        )
+        .unwrap();
+        fs::write(reports.join("broken.xml"), "<testsuite").unwrap();
+
+        let summary = collect(dir.path());
+        assert_eq!(
+            (
+                summary.tests,
+                summary.failures,
+                summary.errors,
+                summary.skipped
+            ),
+            (4, 1, 1, 1)
+        );
+        assert_eq!(summary.passed(), 1);
+        assert_eq!(
+            summary.failed,
+            vec![
+                FailedTest {
+                    suite: "orders-test-suite.xml".to_string(),
+                    name: "get-order-test".to_string(),
+                    message: Some("Expected 200 but was 500".to_string()),
+                },
+                FailedTest {
+                    suite: "orders-test-suite.xml".to_string(),
+                    name: "delete-order-test".to_string(),
+                    message: Some("java.lang.NullPointerException".to_string()),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use crate::config::{MigrationConfig, ReplacementRule};
+use crate::xml;
+use regex::{Captures, Regex};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// `{{name}}` references in replacement `to` values, with optional spaces
+/// inside the braces.
+const VARIABLE: &str = r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}";
+
+/// Variables a replacement `to` value may reference.
+pub const VARIABLES: &[&str] = &[
+    "app_runtime_version",
+    "mule_maven_plugin_version",
+    "munit_version",
+    "min_mule_version",
+    "group_id",
+    "artifact_id",
+    "version",
+    "project_name",
+];
+
+fn variable_pattern() -> Regex {
+    Regex::new(VARIABLE).expect("Invalid variable pattern")
+}
+
+/// Returns the variables `template` references that are not in [`VARIABLES`].
+pub fn unknown_variables(template: &str) -> Vec<String> {
+    variable_pattern()
+        .captures_iter(template)
+        .map(|caps| caps[1].to_string())
+        .filter(|name| !VARIABLES.contains(&name.as_str()))
+        .collect()
+}
+
+/// Resolves the template variables of a project: the target versions from
+/// the config, and the coordinates and name from its pom.xml content. The
+/// groupId and version may be inherited from the `<parent>`; the project
+/// name falls back to the artifactId, then to the project directory name.
+pub fn project_variables(
+    config: &MigrationConfig,
+    pom: Option<&str>,
+    project_root: &Path,
+) -> BTreeMap<&'static str, String> {
+    let mut vars = BTreeMap::from([
+        ("app_runtime_version", config.app_runtime_version.clone()),
+        (
+            "mule_maven_plugin_version",
+            config.mule_maven_plugin_version.clone(),
+        ),
+        ("munit_version", config.munit_version.clone()),
+        (
+            "min_mule_version",
+            config.mule_artifact.min_mule_version.clone(),
+        ),
+    ]);
+    let doc = pom.and_then(|pom| roxmltree::Document::parse(pom).ok());
+    if let Some(project) = doc.as_ref().map(|d| d.root_element()) {
+        let parent = xml::child_element(project, "parent");
+        let inherited = |name| {
+            xml::child_text(project, name).or_else(|| parent.and_then(|p| xml::child_text(p, name)))
+        };
+        for (var, element) in [("group_id", "groupId"), ("version", "version")] {
+            if let Some(value) = inherited(element) {
+                vars.insert(var, value.to_string());
+            }
+        }
+        for var in ["artifact_id", "project_name"] {
+            if let Some(value) = xml::child_text(project, "artifactId") {
+                vars.insert(var, value.to_string());
+            }
+        }
+        if let Some(name) = xml::child_text(project, "name").filter(|n| !n.is_empty()) {
+            vars.insert("project_name", name.to_string());
+        }
+    }
+    if !vars.contains_key("project_name") {
+        let dir = project_root
+            .canonicalize()
+            .ok()
+            .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()));
+        if let Some(dir) = dir {
+            vars.insert("project_name", dir);
+        }
+    }
+    vars
+}
+
+/// Replaces the `{{name}}` references in `template` with the values of
+/// `vars`. References to variables without a value are left as written.
+pub fn render(template: &str, vars: &BTreeMap<&'static str, String>) -> String {
+    variable_pattern()
+        .replace_all(template, |caps: &Captures| {
+            vars.get(&caps[1])
+                .cloned()
+                .unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned()
+}
+
+/// Returns the replacement rules with the variables in their `to` values
+/// resolved. Values inserted into regex rules are escaped, so a `$` in them
+/// is not taken for a capture group reference.
+pub fn render_rules(
+    rules: &[ReplacementRule],
+    vars: &BTreeMap<&'static str, String>,
+) -> Vec<ReplacementRule> {
+    let escaped: BTreeMap<&'static str, String> = vars
+        .iter()
+        .map(|(name, value)| (*name, value.replace('$', "$$")))
+        .collect();
+    rules
+        .iter()
+        .map(|rule| {
+            let values = if rule.regex { &escaped } else { vars };
+            let to = render(&rule.to, values);
+            if to != rule.to {
+                log::debug!("Replacement '{}': '{}' -> '{to}'", rule.from, rule.to);
+            }
+            ReplacementRule { to, ..rule.clone() }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigFormat;
+
+    #[test]
+    fn test_render_rules_with_project_variables() {
+        let config = MigrationConfig::from_str_with_format(
+            r#"
+app_runtime_version: 4.9.4
+mule_maven_plugin_version: 4.3.1
+munit_version: 3.4.0
+mule_artifact:
+  min_mule_version: 4.9.0
+  java_specification_versions: ["17"]
+replacements:
+  - from: "orders-api-1.0"
+    to: "{{ artifact_id }}-{{version}} on {{app_runtime_version}}"
+  - from: "runtime-(\\w+)"
+    to: "$1-{{project_name}}"
+    regex: true
+"#,
+            ConfigFormat::Yaml,
+        )
+        .unwrap();
+        let pom = r#"<project>
+  <parent><groupId>com.acme</groupId><artifactId>parent</artifactId><version>2.0.0</version></parent>
+  <artifactId>orders-api</artifactId>
+  <name>Orders $API</name>
+</project>"#;
+        let vars = project_variables(&config, Some(pom), Path::new("."));
+        assert_eq!(vars["group_id"], "com.acme");
+        let rules = render_rules(&config.replacements, &vars);
+        assert_eq!(rules[0].to, "orders-api-2.0.0 on 4.9.4");
+        assert_eq!(rules[1].to, "$1-Orders $$API");
+
+        assert_eq!(render("{{unknown}}", &vars), "{{unknown}}");
+        assert_eq!(
+            unknown_variables("{{artifact_id}}-{{artifactId}}"),
+            vec!["artifactId"]
+        );
+    }
+}
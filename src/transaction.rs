@@ -0,0 +1,374 @@
+use crate::backup::Snapshot;
+use crate::error::MigrateError;
+use crate::file_ops::{backup_file, write_text};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A file modification staged in memory.
+#[derive(Debug)]
+pub struct PendingWrite {
+    pub path: PathBuf,
+    /// File content at planning time, used to roll back on failure.
+    pub original: String,
+    pub content: String,
+    /// The file does not exist yet; rolling back removes it.
+    pub created: bool,
+}
+
+/// A file move staged in memory.
+#[derive(Debug, Clone)]
+pub struct PendingRename {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// Collects every file modification of a migration run and writes them to
+/// disk all at once.
+///
+/// If any write fails, files that were already written are restored to
+/// their original content, so the project is never left half-migrated.
+#[derive(Debug, Default)]
+pub struct Transaction {
+    writes: Vec<PendingWrite>,
+    renames: Vec<PendingRename>,
+    deletes: Vec<PathBuf>,
+}
+
+impl Transaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stages new content for `path`. Staging a file twice keeps the first
+    /// original and the latest content.
+    pub fn stage<P: AsRef<Path>>(&mut self, path: P, original: String, content: String) {
+        let path = path.as_ref();
+        if let Some(existing) = self.writes.iter_mut().find(|w| w.path == path) {
+            existing.content = content;
+            return;
+        }
+        self.writes.push(PendingWrite {
+            path: path.to_path_buf(),
+            original,
+            content,
+            created: false,
+        });
+    }
+
+    /// Stages a file to create at `path`, along with its parent directories.
+    pub fn stage_new<P: AsRef<Path>>(&mut self, path: P, content: String) {
+        let path = path.as_ref();
+        if let Some(existing) = self.writes.iter_mut().find(|w| w.path == path) {
+            existing.content = content;
+            return;
+        }
+        self.writes.push(PendingWrite {
+            path: path.to_path_buf(),
+            original: String::new(),
+            content,
+            created: true,
+        });
+    }
+
+    /// Returns the staged content for `path`, if any.
+    pub fn staged_content<P: AsRef<Path>>(&self, path: P) -> Option<&str> {
+        let path = path.as_ref();
+        self.writes
+            .iter()
+            .find(|w| w.path == path)
+            .map(|w| w.content.as_str())
+    }
+
+    /// Stages a move of `from` to `to`, made after all writes, so staged
+    /// content of `from` ends up at `to`.
+    pub fn stage_rename<P: AsRef<Path>, Q: AsRef<Path>>(&mut self, from: P, to: Q) {
+        self.renames.push(PendingRename {
+            from: from.as_ref().to_path_buf(),
+            to: to.as_ref().to_path_buf(),
+        });
+    }
+
+    /// Stages the removal of `path`, made after all writes and moves. Content
+    /// staged for the file is dropped.
+    pub fn stage_delete<P: AsRef<Path>>(&mut self, path: P) {
+        let path = path.as_ref();
+        self.writes.retain(|w| w.path != path);
+        if !self.deletes.iter().any(|d| d == path) {
+            self.deletes.push(path.to_path_buf());
+        }
+    }
+
+    pub fn writes(&self) -> &[PendingWrite] {
+        &self.writes
+    }
+
+    pub fn renames(&self) -> &[PendingRename] {
+        &self.renames
+    }
+
+    pub fn deletes(&self) -> &[PathBuf] {
+        &self.deletes
+    }
+
+    pub fn into_writes(self) -> Vec<PendingWrite> {
+        self.writes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.writes.is_empty() && self.renames.is_empty() && self.deletes.is_empty()
+    }
+
+    /// Writes all staged modifications, then makes the staged moves and
+    /// removals, saving the current files to `snapshot` first if given.
+    ///
+    /// Returns the written files, the new paths of the moved ones and the
+    /// removed ones.
+    ///
+    /// # Errors
+    /// Returns an error if a backup, write, move or removal fails; in that
+    /// case all files written, moved or removed so far are rolled back.
+    pub fn commit(self, snapshot: Option<&Snapshot>) -> Result<Vec<PathBuf>, MigrateError> {
+        if let Some(snapshot) = snapshot {
+            for path in self
+                .writes
+                .iter()
+                .filter(|w| !w.created)
+                .map(|w| &w.path)
+                .chain(self.renames.iter().map(|r| &r.from))
+                .chain(&self.deletes)
+            {
+                backup_file(snapshot, path)?;
+            }
+            let renames: Vec<(&Path, &Path)> = self
+                .renames
+                .iter()
+                .map(|r| (r.from.as_path(), r.to.as_path()))
+                .collect();
+            let created: Vec<&Path> = self
+                .writes
+                .iter()
+                .filter(|w| w.created)
+                .map(|w| w.path.as_path())
+                .collect();
+            snapshot
+                .record(&renames, &created)
+                .map_err(|source| MigrateError::Backup {
+                    path: snapshot.dir().to_path_buf(),
+                    source,
+                })?;
+        }
+
+        let mut written: Vec<&PendingWrite> = Vec::new();
+        for write in &self.writes {
+            let result = match write.path.parent().filter(|_| write.created) {
+                Some(parent) => fs::create_dir_all(parent),
+                None => Ok(()),
+            }
+            .and_then(|()| write_text(&write.path, &write.content));
+            if let Err(source) = result {
+                log::error!("Failed to write {}: {source}", write.path.display());
+                roll_back(&written, &[], &[]);
+                return Err(MigrateError::RolledBack {
+                    path: write.path.clone(),
+                    source,
+                });
+            }
+            written.push(write);
+        }
+        let mut moved: Vec<&PendingRename> = Vec::new();
+        for rename in &self.renames {
+            let result = match rename.to.parent() {
+                Some(parent) => fs::create_dir_all(parent),
+                None => Ok(()),
+            }
+            .and_then(|()| fs::rename(&rename.from, &rename.to));
+            if let Err(source) = result {
+                log::error!(
+                    "Failed to move {} to {}: {source}",
+                    rename.from.display(),
+                    rename.to.display()
+                );
+                roll_back(&written, &moved, &[]);
+                return Err(MigrateError::RolledBack {
+                    path: rename.from.clone(),
+                    source,
+                });
+            }
+            moved.push(rename);
+        }
+        let mut removed: Vec<(&Path, Vec<u8>)> = Vec::new();
+        for path in &self.deletes {
+            let result = fs::read(path).and_then(|bytes| {
+                fs::remove_file(path)?;
+                Ok(bytes)
+            });
+            match result {
+                Ok(bytes) => removed.push((path, bytes)),
+                Err(source) => {
+                    log::error!("Failed to remove {}: {source}", path.display());
+                    roll_back(&written, &moved, &removed);
+                    return Err(MigrateError::RolledBack {
+                        path: path.clone(),
+                        source,
+                    });
+                }
+            }
+        }
+        let mut changed: Vec<PathBuf> = self.writes.into_iter().map(|w| w.path).collect();
+        changed.extend(self.renames.into_iter().map(|r| r.to));
+        changed.extend(self.deletes);
+        Ok(changed)
+    }
+}
+
+/// Puts back `removed` files, moves `moved` files back, then restores the
+/// original content of `written` and removes the files it created.
+fn roll_back(written: &[&PendingWrite], moved: &[&PendingRename], removed: &[(&Path, Vec<u8>)]) {
+    for (path, bytes) in removed.iter().rev() {
+        if let Err(e) = fs::write(path, bytes) {
+            log::error!("Failed to put back {}: {e}", path.display());
+        } else {
+            log::info!("Put back {}", path.display());
+        }
+    }
+    for rename in moved.iter().rev() {
+        if let Err(e) = fs::rename(&rename.to, &rename.from) {
+            log::error!("Failed to move back {}: {e}", rename.to.display());
+        } else {
+            log::info!("Moved back {}", rename.from.display());
+        }
+    }
+    for done in written.iter().rev() {
+        let result = if done.created {
+            fs::remove_file(&done.path)
+        } else {
+            write_text(&done.path, &done.original)
+        };
+        if let Err(e) = result {
+            log::error!("Failed to roll back {}: {e}", done.path.display());
+        } else {
+            log::info!("Rolled back {}", done.path.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::BackupCompression;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_commit_writes_all_files() {
+        let dir = tempdir().unwrap();
+        let a = dir.path().join("a.xml");
+        let b = dir.path().join("b.xml");
+        fs::write(&a, "a1").unwrap();
+        fs::write(&b, "b1").unwrap();
+        let mut tx = Transaction::new();
+        tx.stage(&a, "a1".to_string(), "a2".to_string());
+        tx.stage(&b, "b1".to_string(), "b2".to_string());
+        let snapshot = Snapshot::create(dir.path(), &dir.path().join("backups")).unwrap();
+        let written = tx.commit(Some(&snapshot)).unwrap();
+        assert_eq!(written.len(), 2);
+        assert_eq!(fs::read_to_string(&a).unwrap(), "a2");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "b2");
+        assert_eq!(
+            fs::read_to_string(snapshot.dir().join("a.xml")).unwrap(),
+            "a1"
+        );
+    }
+
+    #[test]
+    fn test_commit_rolls_back_on_failure() {
+        let dir = tempdir().unwrap();
+        let a = dir.path().join("a.xml");
+        fs::write(&a, "a1").unwrap();
+        let mut tx = Transaction::new();
+        tx.stage(&a, "a1".to_string(), "a2".to_string());
+        // Writing to a path inside a missing directory fails
+        tx.stage(
+            dir.path().join("missing/b.xml"),
+            "b1".to_string(),
+            "b2".to_string(),
+        );
+        assert!(tx.commit(None).is_err());
+        assert_eq!(fs::read_to_string(&a).unwrap(), "a1");
+    }
+
+    #[test]
+    fn test_commit_moves_files_after_writing() {
+        let dir = tempdir().unwrap();
+        let a = dir.path().join("a.properties");
+        let moved = dir.path().join("config/a.properties");
+        fs::write(&a, "a1").unwrap();
+        let mut tx = Transaction::new();
+        tx.stage(&a, "a1".to_string(), "a2".to_string());
+        tx.stage_rename(&a, &moved);
+        let snapshot = Snapshot::create(dir.path(), &dir.path().join("backups")).unwrap();
+        let changed = tx.commit(Some(&snapshot)).unwrap();
+        assert_eq!(changed, vec![a.clone(), moved.clone()]);
+        assert!(!a.exists());
+        assert_eq!(fs::read_to_string(&moved).unwrap(), "a2");
+        assert_eq!(
+            fs::read_to_string(snapshot.dir().join("a.properties")).unwrap(),
+            "a1"
+        );
+
+        // Restoring the run moves the file back
+        let run = snapshot.run_id().to_string();
+        snapshot.finish(BackupCompression::None).unwrap();
+        crate::backup::restore(dir.path(), &dir.path().join("backups"), &run, false).unwrap();
+        assert_eq!(fs::read_to_string(&a).unwrap(), "a1");
+        assert!(!moved.exists());
+
+        // A failed move puts back the files moved before it
+        let b = dir.path().join("b.xml");
+        fs::write(&b, "b").unwrap();
+        let mut tx = Transaction::new();
+        tx.stage_rename(&b, dir.path().join("flows/b.xml"));
+        tx.stage_rename(dir.path().join("missing.xml"), dir.path().join("c.xml"));
+        assert!(tx.commit(None).is_err());
+        assert_eq!(fs::read_to_string(&b).unwrap(), "b");
+    }
+
+    #[test]
+    fn test_commit_creates_and_removes_files() {
+        let dir = tempdir().unwrap();
+        let old = dir.path().join("exchange.json");
+        let new = dir.path().join("src/main/resources/app.yaml");
+        fs::write(&old, "{}").unwrap();
+        let mut tx = Transaction::new();
+        tx.stage(&old, "{}".to_string(), "{ }".to_string());
+        tx.stage_delete(&old);
+        tx.stage_new(&new, "http:\n  port: 8081\n".to_string());
+        assert_eq!(tx.writes().len(), 1);
+        let snapshot = Snapshot::create(dir.path(), &dir.path().join("backups")).unwrap();
+        tx.commit(Some(&snapshot)).unwrap();
+        assert!(!old.exists());
+        assert_eq!(fs::read_to_string(&new).unwrap(), "http:\n  port: 8081\n");
+        assert_eq!(
+            fs::read_to_string(snapshot.dir().join("exchange.json")).unwrap(),
+            "{}"
+        );
+
+        // Restoring the run removes the created file and puts back the removed one
+        let run = snapshot.run_id().to_string();
+        snapshot.finish(BackupCompression::None).unwrap();
+        crate::backup::restore(dir.path(), &dir.path().join("backups"), &run, false).unwrap();
+        assert_eq!(fs::read_to_string(&old).unwrap(), "{}");
+        assert!(!new.exists());
+        fs::write(&new, "http:\n  port: 8081\n").unwrap();
+
+        // A failed removal removes the created files and puts back the removed ones
+        let created = dir.path().join("new/log4j2.xml");
+        let mut tx = Transaction::new();
+        tx.stage_new(&created, "<Configuration/>".to_string());
+        tx.stage_delete(&new);
+        tx.stage_delete(dir.path().join("missing.json"));
+        assert!(tx.commit(None).is_err());
+        assert!(!created.exists());
+        assert!(new.exists());
+    }
+}
@@ -0,0 +1,340 @@
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A lenient Maven-style version: dot-separated numeric components with an
+/// optional qualifier (`1.10.3`, `4.9.0`, `3.0.0-SNAPSHOT`, `4.4.0-20240619`).
+///
+/// Missing components compare as zero, and a release sorts after the same
+/// version with a qualifier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    parts: Vec<u64>,
+    qualifier: Option<String>,
+}
+
+impl Version {
+    /// Parses a version, returning `None` if it does not start with a number.
+    pub fn parse(s: &str) -> Option<Version> {
+        let s = s.trim();
+        let (numbers, qualifier) = match s.find(['-', '+']) {
+            Some(i) => (&s[..i], Some(s[i + 1..].to_string())),
+            None => (s, None),
+        };
+        let parts = numbers
+            .split('.')
+            .map(|p| p.parse::<u64>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        if parts.is_empty() {
+            return None;
+        }
+        Some(Version { parts, qualifier })
+    }
+
+    /// Major version (first component).
+    pub fn major(&self) -> u64 {
+        self.parts[0]
+    }
+
+    /// Minor version (second component, zero if absent).
+    pub fn minor(&self) -> u64 {
+        self.parts.get(1).copied().unwrap_or(0)
+    }
+
+    /// Increments the component of `kind`, zeroing the ones after it. The
+    /// qualifier is kept, so `1.0.0-SNAPSHOT` becomes `1.0.1-SNAPSHOT`.
+    pub fn bump(&self, kind: BumpKind) -> Version {
+        let index = match kind {
+            BumpKind::Major => 0,
+            BumpKind::Minor => 1,
+            BumpKind::Patch => 2,
+        };
+        let mut parts = self.parts.clone();
+        if parts.len() <= index {
+            parts.resize(index + 1, 0);
+        }
+        parts[index] += 1;
+        for part in &mut parts[index + 1..] {
+            *part = 0;
+        }
+        Version {
+            parts,
+            qualifier: self.qualifier.clone(),
+        }
+    }
+}
+
+/// Component of a version to increment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BumpKind {
+    Major,
+    Minor,
+    Patch,
+}
+
+/// How to change a version: increment a component (`major`, `minor`,
+/// `patch`) or set an explicit value.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum VersionBump {
+    Bump(BumpKind),
+    Set(String),
+}
+
+impl VersionBump {
+    /// Returns the new version for `current`, or `None` if a bump is asked
+    /// for and `current` is not a version. A leading `v` (as in the `v1` of
+    /// an API version) is kept.
+    pub fn apply(&self, current: &str) -> Option<String> {
+        match self {
+            VersionBump::Set(version) => Some(version.clone()),
+            VersionBump::Bump(kind) => {
+                let (prefix, number) = match current.strip_prefix('v') {
+                    Some(rest) => ("v", rest),
+                    None => ("", current),
+                };
+                Some(format!("{prefix}{}", Version::parse(number)?.bump(*kind)))
+            }
+        }
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.parts.len().max(other.parts.len());
+        for i in 0..len {
+            let a = self.parts.get(i).copied().unwrap_or(0);
+            let b = other.parts.get(i).copied().unwrap_or(0);
+            match a.cmp(&b) {
+                Ordering::Equal => continue,
+                ord => return ord,
+            }
+        }
+        match (&self.qualifier, &other.qualifier) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => a.cmp(b),
+        }
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let numbers: Vec<String> = self.parts.iter().map(|p| p.to_string()).collect();
+        write!(f, "{}", numbers.join("."))?;
+        if let Some(q) = &self.qualifier {
+            write!(f, "-{q}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A Maven version range such as `[1.5,2.0)`, `[1.0,)` or `(,3.0]`, or a
+/// union of them like `[1.0,2.0),[3.0,4.0)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionRange {
+    intervals: Vec<Interval>,
+}
+
+/// Bounds of one interval; `true` marks an inclusive bound.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Interval {
+    lower: Option<(Version, bool)>,
+    upper: Option<(Version, bool)>,
+}
+
+impl VersionRange {
+    /// Parses a range in Maven syntax, returning `None` if it is malformed.
+    /// A bare version is not a range.
+    pub fn parse(s: &str) -> Option<VersionRange> {
+        let mut intervals = Vec::new();
+        let mut rest = s.trim();
+        while !rest.is_empty() {
+            let lower_inclusive = match rest.chars().next()? {
+                '[' => true,
+                '(' => false,
+                _ => return None,
+            };
+            let end = rest.find([']', ')'])?;
+            let upper_inclusive = rest[end..].starts_with(']');
+            let bound = |b: &str| -> Option<Option<Version>> {
+                match b.trim() {
+                    "" => Some(None),
+                    b => Version::parse(b).map(Some),
+                }
+            };
+            let body = &rest[1..end];
+            let interval = match body.split_once(',') {
+                Some((lower, upper)) => Interval {
+                    lower: bound(lower)?.map(|v| (v, lower_inclusive)),
+                    upper: bound(upper)?.map(|v| (v, upper_inclusive)),
+                },
+                // `[1.2]` is exactly that version
+                None if lower_inclusive && upper_inclusive => {
+                    let version = bound(body)??;
+                    Interval {
+                        lower: Some((version.clone(), true)),
+                        upper: Some((version, true)),
+                    }
+                }
+                None => return None,
+            };
+            intervals.push(interval);
+            rest = rest[end + 1..].trim_start();
+            if let Some(next) = rest.strip_prefix(',') {
+                rest = next.trim_start();
+                if rest.is_empty() {
+                    return None;
+                }
+            } else if !rest.is_empty() {
+                return None;
+            }
+        }
+        if intervals.is_empty() {
+            return None;
+        }
+        Some(VersionRange { intervals })
+    }
+
+    /// The versions with the same major version as `version`, e.g.
+    /// `[1,2)` for `1.4.2`.
+    pub fn same_major(version: &Version) -> VersionRange {
+        let major = version.major();
+        VersionRange {
+            intervals: vec![Interval {
+                lower: Some((Version::parse(&major.to_string()).expect("a number"), true)),
+                upper: Some((
+                    Version::parse(&(major + 1).to_string()).expect("a number"),
+                    false,
+                )),
+            }],
+        }
+    }
+
+    /// Returns true if `version` lies in the range.
+    pub fn contains(&self, version: &Version) -> bool {
+        self.intervals.iter().any(|interval| {
+            // `cmp`, not `==`: `1.2` and `1.2.0` are the same version
+            let above = interval.lower.as_ref().is_none_or(|(lower, inclusive)| {
+                version.cmp(lower) == Ordering::Greater
+                    || (*inclusive && version.cmp(lower) == Ordering::Equal)
+            });
+            let below = interval.upper.as_ref().is_none_or(|(upper, inclusive)| {
+                version.cmp(upper) == Ordering::Less
+                    || (*inclusive && version.cmp(upper) == Ordering::Equal)
+            });
+            above && below
+        })
+    }
+}
+
+impl fmt::Display for VersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, interval) in self.intervals.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            match &interval.lower {
+                Some((v, inclusive)) => write!(f, "{}{v}", if *inclusive { '[' } else { '(' })?,
+                None => write!(f, "(")?,
+            }
+            write!(f, ",")?;
+            match &interval.upper {
+                Some((v, inclusive)) => write!(f, "{v}{}", if *inclusive { ']' } else { ')' })?,
+                None => write!(f, ")")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns true if `s` is a `MAJOR.MINOR.PATCH` version, optionally with a
+/// `-qualifier`.
+pub fn is_semver(s: &str) -> bool {
+    let s = s.trim();
+    let numbers = s.split(['-', '+']).next().unwrap_or("");
+    let parts: Vec<&str> = numbers.split('.').collect();
+    parts.len() == 3
+        && parts
+            .iter()
+            .all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Compares two version strings, returning `None` if either cannot be parsed.
+pub fn compare(a: &str, b: &str) -> Option<Ordering> {
+    Some(Version::parse(a)?.cmp(&Version::parse(b)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare("1.10.3", "1.9.0"), Some(Ordering::Greater));
+        assert_eq!(compare("4.9", "4.9.0"), Some(Ordering::Equal));
+        assert_eq!(compare("3.0.0-SNAPSHOT", "3.0.0"), Some(Ordering::Less));
+        assert_eq!(compare("${mule.version}", "4.9.0"), None);
+        assert!(is_semver("4.9.0"));
+        assert!(is_semver("3.0.0-SNAPSHOT"));
+        assert!(!is_semver("4.9"));
+        assert!(!is_semver("latest"));
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        let v = Version::parse("4.4.0-20240619").unwrap();
+        assert_eq!(v.major(), 4);
+        assert_eq!(v.minor(), 4);
+        assert_eq!(v.to_string(), "4.4.0-20240619");
+    }
+
+    #[test]
+    fn test_version_range() {
+        let v = |s| Version::parse(s).unwrap();
+        let range = VersionRange::parse("[1.5,2.0)").unwrap();
+        assert!(range.contains(&v("1.5")));
+        assert!(range.contains(&v("1.10.3")));
+        assert!(!range.contains(&v("2.0.0")));
+        assert!(!range.contains(&v("1.4.9")));
+        let union = VersionRange::parse("(,1.0],[3.0,)").unwrap();
+        assert!(union.contains(&v("0.9")) && union.contains(&v("3.1")));
+        assert!(!union.contains(&v("2.0")));
+        assert!(VersionRange::parse("[1.2]").unwrap().contains(&v("1.2.0")));
+        assert_eq!(VersionRange::same_major(&v("1.4.2")).to_string(), "[1,2)");
+        assert_eq!(VersionRange::parse("1.0"), None);
+        assert_eq!(VersionRange::parse("[1.0,2.0"), None);
+        assert_eq!(VersionRange::parse("[1.0,2.0),"), None);
+    }
+
+    #[test]
+    fn test_version_bump() {
+        let minor = VersionBump::Bump(BumpKind::Minor);
+        assert_eq!(minor.apply("1.2.3").as_deref(), Some("1.3.0"));
+        assert_eq!(minor.apply("v1").as_deref(), Some("v1.1"));
+        assert_eq!(
+            VersionBump::Bump(BumpKind::Patch)
+                .apply("1.0.0-SNAPSHOT")
+                .as_deref(),
+            Some("1.0.1-SNAPSHOT")
+        );
+        assert_eq!(
+            VersionBump::Bump(BumpKind::Major).apply("v2").as_deref(),
+            Some("v3")
+        );
+        assert_eq!(minor.apply("${revision}"), None);
+        let bump: VersionBump = serde_json::from_str("\"patch\"").unwrap();
+        assert_eq!(bump, VersionBump::Bump(BumpKind::Patch));
+        let bump: VersionBump = serde_json::from_str("\"2.0.0\"").unwrap();
+        assert_eq!(bump, VersionBump::Set("2.0.0".to_string()));
+    }
+}
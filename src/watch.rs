@@ -0,0 +1,58 @@
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// How often `--watch` polls the config file.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Size and modification time of a file; `None` while it does not exist,
+/// e.g. in the middle of an editor's save-by-rename.
+fn stamp(path: &Path) -> Option<(u64, SystemTime)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.len(), metadata.modified().ok()?))
+}
+
+/// Blocks until `path` changes, then until it has been left alone for one
+/// poll interval, so a save made of several writes triggers a single run.
+pub fn wait_for_change(path: &Path, interval: Duration) {
+    let before = stamp(path);
+    let mut last = loop {
+        thread::sleep(interval);
+        let now = stamp(path);
+        if now != before {
+            break now;
+        }
+    };
+    loop {
+        thread::sleep(interval);
+        let now = stamp(path);
+        if now == last && now.is_some() {
+            return;
+        }
+        last = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_wait_for_change_returns_after_an_edit() {
+        let dir = tempdir().unwrap();
+        let config = dir.path().join("migration.yaml");
+        fs::write(&config, "app_runtime_version: 4.9.4\n").unwrap();
+        let writer = {
+            let config = config.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(50));
+                fs::write(&config, "app_runtime_version: 4.9.5-rc\n").unwrap();
+            })
+        };
+        wait_for_change(&config, Duration::from_millis(10));
+        writer.join().unwrap();
+        assert!(fs::read_to_string(&config).unwrap().contains("4.9.5-rc"));
+    }
+}
@@ -1,147 +1,1039 @@
+use crate::backup::Snapshot;
+use crate::catalog;
+use crate::config::{DependencyVersion, MigrationConfig, RepositoryMapping};
+use crate::error::MigrateError;
+use crate::file_ops::{backup_file, read_file, write_file};
+use crate::java_compat;
+use crate::maven_plugin;
+use crate::munit;
+use crate::template;
+use crate::version::{self, VersionBump};
 use log;
-use regex::Regex;
-use std::fs;
+use roxmltree::{Document, Node};
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::ops::Range;
+use std::path::Path;
+
+/// A single replacement of a byte range in the original document.
+///
+/// Edits are computed against the parsed document and spliced into the
+/// original text, so everything outside the edited spans (comments,
+/// indentation, attribute order) is preserved byte for byte.
+#[derive(Debug, Clone)]
+pub(crate) struct TextEdit {
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+/// Applies non-overlapping edits to `content`, back to front.
+pub(crate) fn apply_edits(content: &str, mut edits: Vec<TextEdit>) -> String {
+    edits.sort_by_key(|e| std::cmp::Reverse(e.range.start));
+    let mut out = content.to_string();
+    for edit in edits {
+        out.replace_range(edit.range, &edit.text);
+    }
+    out
+}
+
+/// Escapes text for use as XML character data.
+pub(crate) fn escape_text(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Escapes text for use inside a quoted XML attribute value.
+pub(crate) fn escape_attr(value: &str) -> String {
+    escape_text(value)
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Rewrites the values of every attribute named `name` (the qualified name
+/// as written, e.g. `doc:name`) through `rewrite`, which returns the new value
+/// and its number of changes. Text, CDATA and comments are never touched.
+pub(crate) fn rewrite_attribute_values<F>(
+    content: &str,
+    name: &str,
+    mut rewrite: F,
+) -> Result<(String, usize), roxmltree::Error>
+where
+    F: FnMut(&str) -> (String, usize),
+{
+    let doc = Document::parse(content)?;
+    let mut edits = Vec::new();
+    let mut count = 0;
+    for attr in doc.descendants().flat_map(|n| n.attributes()) {
+        if &content[attr.range_qname()] != name {
+            continue;
+        }
+        let (value, changes) = rewrite(attr.value());
+        if changes > 0 {
+            count += changes;
+            edits.push(TextEdit {
+                range: attr.range_value(),
+                text: escape_attr(&value),
+            });
+        }
+    }
+    Ok((apply_edits(content, edits), count))
+}
+
+/// Namespace of the Transform Message (`ee:`) elements holding DataWeave.
+const EE_NS: &str = "http://www.mulesoft.org/schema/mule/ee/core";
+
+/// Byte ranges of the embedded DataWeave scripts of a Mule config: text in
+/// `ee:` elements (`<ee:set-payload>`, `<ee:set-variable>`, ...) and CDATA
+/// sections.
+pub(crate) fn dataweave_ranges(content: &str) -> Result<Vec<Range<usize>>, roxmltree::Error> {
+    let doc = Document::parse(content)?;
+    Ok(doc
+        .descendants()
+        .filter(|n| n.is_text())
+        .filter(|n| {
+            n.parent()
+                .is_some_and(|p| p.tag_name().namespace() == Some(EE_NS))
+                || content[n.range()].contains("<![CDATA[")
+        })
+        .map(|n| n.range())
+        .collect())
+}
+
+/// Returns the first child element with the given local name.
+pub(crate) fn child_element<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &str,
+) -> Option<Node<'a, 'input>> {
+    node.children()
+        .find(|n| n.is_element() && n.tag_name().name() == name)
+}
+
+/// Returns all child elements with the given local name.
+pub(crate) fn child_elements<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    name: &'a str,
+) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children()
+        .filter(move |n| n.is_element() && n.tag_name().name() == name)
+}
+
+/// Returns the trimmed text of a child element, if present.
+pub(crate) fn child_text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    child_element(node, name)
+        .and_then(|n| n.text())
+        .map(|t| t.trim())
+}
+
+/// Builds an edit that sets the text content of a simple (text-only) element.
+pub(crate) fn set_element_text(src: &str, element: Node, new_value: &str) -> TextEdit {
+    let escaped = escape_text(new_value);
+    if let Some(text) = element.first_child().filter(|n| n.is_text()) {
+        return TextEdit {
+            range: text.range(),
+            text: escaped,
+        };
+    }
+    let range = element.range();
+    let raw = &src[range.clone()];
+    if raw.ends_with("/>") {
+        let qname_end = raw[1..]
+            .find(|c: char| c.is_whitespace() || c == '/')
+            .map(|i| i + 1)
+            .unwrap_or(raw.len() - 2);
+        let qname = &raw[1..qname_end];
+        TextEdit {
+            range,
+            text: format!("<{qname}>{escaped}</{qname}>"),
+        }
+    } else {
+        let close = range.start + raw.rfind("</").unwrap_or(raw.len());
+        TextEdit {
+            range: close..close,
+            text: escaped,
+        }
+    }
+}
+
+/// Whitespace before `pos` on its line, if the line has nothing else there.
+fn line_indent(content: &str, pos: usize) -> Option<&str> {
+    let line_start = content[..pos].rfind('\n').map_or(0, |i| i + 1);
+    let prefix = &content[line_start..pos];
+    prefix.trim().is_empty().then_some(prefix)
+}
+
+/// Indentation unit of the document, guessed from the root's first child.
+pub(crate) fn indent_unit<'a>(content: &'a str, doc: &Document) -> &'a str {
+    doc.root_element()
+        .children()
+        .find(|n| n.is_element())
+        .and_then(|n| line_indent(content, n.range().start))
+        .filter(|i| !i.is_empty())
+        .unwrap_or("  ")
+}
+
+/// Re-indents a (possibly multi-line) snippet to `indent`.
+fn reindent(snippet: &str, indent: &str) -> String {
+    let lines: Vec<&str> = snippet.trim().lines().collect();
+    let common = lines[1..]
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let mut out = format!("{indent}{}", lines[0].trim());
+    for line in &lines[1..] {
+        out.push('\n');
+        if !line.trim().is_empty() {
+            out.push_str(indent);
+            out.push_str(&line[common.min(line.len() - line.trim_start().len())..]);
+        }
+    }
+    out
+}
+
+/// Builds an edit that inserts `snippet` as the last child of `parent`,
+/// indented like its other children (or one `unit` deeper than `parent`).
+pub(crate) fn insert_child(content: &str, unit: &str, parent: Node, snippet: &str) -> TextEdit {
+    let parent_indent = line_indent(content, parent.range().start).unwrap_or("");
+    let child_indent = parent
+        .children()
+        .find(|n| n.is_element())
+        .and_then(|n| line_indent(content, n.range().start))
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{parent_indent}{unit}"));
+    let body = reindent(snippet, &child_indent);
+    let range = parent.range();
+    let raw = &content[range.clone()];
+    if raw.ends_with("/>") {
+        let tag_end = range.end - 2;
+        let trimmed = content[..tag_end].trim_end().len();
+        let qname = &content[range.start + 1..]
+            .split(|c: char| c.is_whitespace() || c == '/' || c == '>')
+            .next()
+            .unwrap_or_default();
+        return TextEdit {
+            range: trimmed..range.end,
+            text: format!(">\n{body}\n{parent_indent}</{qname}>"),
+        };
+    }
+    let close = range.start + raw.rfind("</").unwrap_or(raw.len());
+    match line_indent(content, close) {
+        Some(indent) => {
+            let line_start = close - indent.len();
+            TextEdit {
+                range: line_start..line_start,
+                text: format!("{body}\n"),
+            }
+        }
+        None => TextEdit {
+            range: close..close,
+            text: format!("\n{body}\n{parent_indent}"),
+        },
+    }
+}
+
+/// Builds an edit that inserts `snippet` right before `sibling`, on its own
+/// line with the sibling's indentation if the sibling starts a line.
+pub(crate) fn insert_before(content: &str, sibling: Node, snippet: &str) -> TextEdit {
+    let start = sibling.range().start;
+    match line_indent(content, start) {
+        Some(indent) => {
+            let line_start = start - indent.len();
+            TextEdit {
+                range: line_start..line_start,
+                text: format!("{}\n", reindent(snippet, indent)),
+            }
+        }
+        None => TextEdit {
+            range: start..start,
+            text: reindent(snippet, ""),
+        },
+    }
+}
+
+/// Collects the `<properties>` blocks that apply to the project: the
+/// top-level one plus one per `<profiles><profile>`, labelled by profile id.
+fn property_scopes<'a, 'input>(
+    project: Node<'a, 'input>,
+) -> Vec<(Option<String>, Node<'a, 'input>)> {
+    let mut scopes = Vec::new();
+    if let Some(props) = child_element(project, "properties") {
+        scopes.push((None, props));
+    }
+    if let Some(profiles) = child_element(project, "profiles") {
+        for profile in child_elements(profiles, "profile") {
+            let id = child_text(profile, "id").unwrap_or("<unnamed>").to_string();
+            if let Some(props) = child_element(profile, "properties") {
+                scopes.push((Some(id), props));
+            }
+        }
+    }
+    scopes
+}
+
+/// Updates property values in pom.xml content.
+///
+/// Only elements that are direct children of the project's `<properties>` (or
+/// a profile's `<properties>`) are touched; commented-out values and
+/// same-named elements elsewhere in the pom are left alone.
+///
+/// Returns the new content and a description of each updated property.
+pub fn update_pom_properties(
+    content: &str,
+    updates: &[(&str, &str)],
+) -> Result<(String, Vec<String>), roxmltree::Error> {
+    let doc = Document::parse(content)?;
+    let project = doc.root_element();
+    let mut edits = Vec::new();
+    let mut updated_props = Vec::new();
+
+    for (scope, props) in property_scopes(project) {
+        for (property_name, new_value) in updates {
+            for element in child_elements(props, property_name) {
+                let old_value = element.text().unwrap_or("").trim();
+                if old_value == *new_value {
+                    continue;
+                }
+                edits.push(set_element_text(content, element, new_value));
+                let label = match &scope {
+                    Some(id) => format!("{property_name} (profile '{id}')"),
+                    None => property_name.to_string(),
+                };
+                updated_props.push(format!("{label}: '{old_value}' -> '{new_value}'"));
+            }
+        }
+    }
+    Ok((apply_edits(content, edits), updated_props))
+}
+
+/// Declares properties in the project-level `<properties>` of pom.xml
+/// content, creating the block at the end of the project if it is missing.
+/// Properties are not checked for existing declarations.
+pub fn insert_pom_properties(
+    content: &str,
+    properties: &[(&str, &str)],
+) -> Result<String, roxmltree::Error> {
+    if properties.is_empty() {
+        return Ok(content.to_string());
+    }
+    let doc = Document::parse(content)?;
+    let unit = indent_unit(content, &doc);
+    let elements: Vec<String> = properties
+        .iter()
+        .map(|(name, value)| format!("<{name}>{}</{name}>", escape_text(value)))
+        .collect();
+    let project = doc.root_element();
+    let edit = match child_element(project, "properties") {
+        Some(props) => insert_child(content, unit, props, &elements.join("\n")),
+        None => {
+            let body = elements.join(&format!("\n{unit}"));
+            let snippet = format!("<properties>\n{unit}{body}\n</properties>");
+            // Maven's conventional order puts <properties> before these
+            let next = project.children().find(|n| {
+                n.is_element() && POM_SECTIONS_AFTER_PROPERTIES.contains(&n.tag_name().name())
+            });
+            match next {
+                Some(next) => insert_before(content, next, &snippet),
+                None => insert_child(content, unit, project, &snippet),
+            }
+        }
+    };
+    Ok(apply_edits(content, vec![edit]))
+}
+
+/// Top-level pom sections that conventionally follow `<properties>`.
+const POM_SECTIONS_AFTER_PROPERTIES: [&str; 7] = [
+    "dependencyManagement",
+    "dependencies",
+    "repositories",
+    "pluginRepositories",
+    "build",
+    "reporting",
+    "profiles",
+];
+
+/// Reads a project-level `<properties>` value from pom.xml content.
+pub fn pom_property(content: &str, name: &str) -> Result<Option<String>, roxmltree::Error> {
+    let doc = Document::parse(content)?;
+    Ok(child_element(doc.root_element(), "properties")
+        .and_then(|props| child_text(props, name))
+        .map(|v| v.trim().to_string()))
+}
+
+/// The Mule-related versions a pom.xml currently declares.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PomVersions {
+    pub mule_version: Option<String>,
+    pub app_runtime: Option<String>,
+    pub munit_version: Option<String>,
+    /// The `mule.maven.plugin.version` property, else the version of the
+    /// declared mule-maven-plugin.
+    pub mule_maven_plugin_version: Option<String>,
+}
+
+/// Reads the Mule-related versions from pom.xml content without changing it.
+pub fn read_pom_versions(content: &str) -> Result<PomVersions, roxmltree::Error> {
+    let plugin = match pom_property(content, "mule.maven.plugin.version")? {
+        Some(v) => Some(v),
+        None => declared_artifacts(content)?
+            .into_iter()
+            .find(|a| a.is_plugin && a.artifact_id == "mule-maven-plugin")
+            .and_then(|a| a.version),
+    };
+    Ok(PomVersions {
+        mule_version: pom_property(content, "mule.version")?,
+        app_runtime: pom_property(content, "app.runtime")?,
+        munit_version: pom_property(content, "munit.version")?,
+        mule_maven_plugin_version: plugin,
+    })
+}
+
+/// Collects the `<dependency>` and `<plugin>` declarations of the project:
+/// `<dependencies>`, `<dependencyManagement>`, `<build><plugins>` and
+/// `<build><pluginManagement>`.
+pub(crate) fn artifact_declarations<'a, 'input>(
+    project: Node<'a, 'input>,
+) -> Vec<Node<'a, 'input>> {
+    let mut declarations = Vec::new();
+    let mut collect = |parent: Option<Node<'a, 'input>>, list: &str, item: &'static str| {
+        if let Some(container) = parent.and_then(|p| child_element(p, list)) {
+            declarations.extend(child_elements(container, item));
+        }
+    };
+    collect(Some(project), "dependencies", "dependency");
+    collect(
+        child_element(project, "dependencyManagement"),
+        "dependencies",
+        "dependency",
+    );
+    let build = child_element(project, "build");
+    collect(build, "plugins", "plugin");
+    collect(
+        build.and_then(|b| child_element(b, "pluginManagement")),
+        "plugins",
+        "plugin",
+    );
+    declarations
+}
+
+/// A `<dependency>` or `<plugin>` declared in pom.xml.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeclaredArtifact {
+    pub is_plugin: bool,
+    pub group_id: String,
+    pub artifact_id: String,
+    pub version: Option<String>,
+    pub classifier: Option<String>,
+}
+
+impl DeclaredArtifact {
+    /// Mule connectors and modules are packaged with the `mule-plugin` classifier.
+    pub fn is_mule_plugin(&self) -> bool {
+        self.classifier.as_deref() == Some("mule-plugin")
+    }
+}
+
+/// Lists the dependencies and plugins declared in pom.xml content.
+pub fn declared_artifacts(content: &str) -> Result<Vec<DeclaredArtifact>, roxmltree::Error> {
+    let doc = Document::parse(content)?;
+    Ok(artifact_declarations(doc.root_element())
+        .into_iter()
+        .filter_map(|declaration| {
+            let is_plugin = declaration.tag_name().name() == "plugin";
+            Some(DeclaredArtifact {
+                is_plugin,
+                group_id: child_text(declaration, "groupId")
+                    .unwrap_or(if is_plugin {
+                        "org.apache.maven.plugins"
+                    } else {
+                        ""
+                    })
+                    .to_string(),
+                artifact_id: child_text(declaration, "artifactId")?.to_string(),
+                version: child_text(declaration, "version").map(str::to_string),
+                classifier: child_text(declaration, "classifier").map(str::to_string),
+            })
+        })
+        .collect())
+}
+
+/// Rewrites the `<version>` of dependency/plugin declarations.
+///
+/// `new_version` receives `(groupId, artifactId, current version)` and
+/// returns the version to set, if any. Versions that reference a property
+/// (`${...}`) are left untouched, since those are handled through the
+/// `<properties>` update instead.
+pub(crate) fn edit_declared_versions<F>(
+    content: &str,
+    label: &str,
+    mut new_version: F,
+) -> Result<(String, Vec<String>), roxmltree::Error>
+where
+    F: FnMut(&str, &str, &str) -> Option<String>,
+{
+    let doc = Document::parse(content)?;
+    let mut edits = Vec::new();
+    let mut updated = Vec::new();
+
+    for declaration in artifact_declarations(doc.root_element()) {
+        let is_plugin = declaration.tag_name().name() == "plugin";
+        // Maven defaults the groupId of plugins to org.apache.maven.plugins
+        let group_id = child_text(declaration, "groupId").unwrap_or(if is_plugin {
+            "org.apache.maven.plugins"
+        } else {
+            ""
+        });
+        let Some(artifact_id) = child_text(declaration, "artifactId") else {
+            continue;
+        };
+        let Some(version) = child_element(declaration, "version") else {
+            continue;
+        };
+        let old_version = version.text().unwrap_or("").trim();
+        let Some(target) = new_version(group_id, artifact_id, old_version) else {
+            continue;
+        };
+        if old_version.starts_with("${") {
+            log::info!(
+                "  {group_id}:{artifact_id} version is set via property '{old_version}', skipping"
+            );
+            continue;
+        }
+        if old_version == target {
+            continue;
+        }
+        edits.push(set_element_text(content, version, &target));
+        let kind = if is_plugin { "plugin" } else { label };
+        updated.push(format!(
+            "{kind} {group_id}:{artifact_id}: '{old_version}' -> '{target}'"
+        ));
+    }
+    Ok((apply_edits(content, edits), updated))
+}
+
+/// Updates the `<version>` of dependencies and plugins matching the given
+/// groupId/artifactId coordinates.
+///
+/// Returns the new content and a description of each updated declaration.
+pub fn update_pom_dependency_versions(
+    content: &str,
+    dependencies: &[DependencyVersion],
+) -> Result<(String, Vec<String>), roxmltree::Error> {
+    edit_declared_versions(content, "dependency", |group_id, artifact_id, _| {
+        dependencies
+            .iter()
+            .find(|d| d.group_id == group_id && d.artifact_id == artifact_id)
+            .map(|d| d.version.clone())
+    })
+}
+
+/// Raises connector versions that are below the given minimums; versions
+/// already at or above the minimum are kept.
+///
+/// `minimums` are `(groupId, artifactId, minimum version)` triples, as
+/// returned by [`crate::catalog::minimum_versions`].
+pub fn upgrade_pom_connector_versions(
+    content: &str,
+    minimums: &[(String, String, String)],
+) -> Result<(String, Vec<String>), roxmltree::Error> {
+    edit_declared_versions(content, "connector", |group_id, artifact_id, old| {
+        let (_, _, min) = minimums
+            .iter()
+            .find(|(g, a, _)| g == group_id && a == artifact_id)?;
+        match version::compare(old, min) {
+            Some(Ordering::Less) => Some(min.clone()),
+            _ => None,
+        }
+    })
+}
+
+pub(crate) fn pom_property_updates<'a>(
+    runtime_version: &'a str,
+    plugin_version: &'a str,
+    munit_version: &'a str,
+) -> [(&'static str, &'a str); 4] {
+    [
+        ("mule.version", runtime_version),
+        ("munit.version", munit_version),
+        ("mule.maven.plugin.version", plugin_version),
+        ("app.runtime", runtime_version),
+    ]
+}
+
+fn parse_error(path: &Path) -> impl FnOnce(roxmltree::Error) -> MigrateError + '_ {
+    move |source| MigrateError::Xml {
+        path: path.to_path_buf(),
+        source,
+    }
+}
 
-// Placeholder for XML operations
 pub fn update_pom_xml(
-    path: &str,
+    path: &Path,
     runtime_version: &str,
     plugin_version: &str,
     munit_version: &str,
     dry_run: bool,
-    backup: bool,
-) {
-    log::info!("Reading pom.xml from {path}");
-    let mut xml_data = fs::read_to_string(path).expect("Failed to read pom.xml");
-    let mut changed = false;
-
-    // Helper to update only the value inside a property tag
-    fn update_property_value(content: &mut String, property_name: &str, new_value: &str) -> bool {
-        let pattern = format!(r#"(<{property_name}>)([^<]*)(</{property_name}>)"#);
-        let re = Regex::new(&pattern).unwrap();
-        let mut did_change = false;
-        *content = re
-            .replace_all(content, |caps: &regex::Captures| {
-                let old_value = caps.get(2).map(|m| m.as_str()).unwrap_or("");
-                if old_value.trim() != new_value {
-                    did_change = true;
-                    log::info!(
-                        "  Updating property '{}': '{}' -> '{}'",
-                        property_name,
-                        old_value.trim(),
-                        new_value
-                    );
-                    format!("{}{}{}", &caps[1], new_value, &caps[3])
-                } else {
-                    log::info!("  Property '{property_name}' already has value '{new_value}'");
-                    caps[0].to_string()
-                }
-            })
-            .to_string();
-        did_change
-    }
+    backup: Option<&Snapshot>,
+) -> Result<(), MigrateError> {
+    log::info!("Reading pom.xml from {}", path.display());
+    let xml_data = read_file(path)?;
 
     // Update mule.version, munit.version, mule.maven.plugin.version, app.runtime in properties
     log::info!("Checking properties in pom.xml:");
-    changed |= update_property_value(&mut xml_data, "mule.version", runtime_version);
-    changed |= update_property_value(&mut xml_data, "munit.version", munit_version);
-    changed |= update_property_value(&mut xml_data, "mule.maven.plugin.version", plugin_version);
-    changed |= update_property_value(&mut xml_data, "app.runtime", runtime_version);
+    let updates = pom_property_updates(runtime_version, plugin_version, munit_version);
+    let (new_data, updated_props) =
+        update_pom_properties(&xml_data, &updates).map_err(parse_error(path))?;
+    for prop in &updated_props {
+        log::info!("  Updating property {prop}");
+    }
 
-    if changed {
-        if backup {
-            let backup_path = format!("{path}.bak");
-            fs::copy(path, &backup_path).expect("Failed to create backup");
-            log::info!("Backup created: {backup_path}");
+    if !updated_props.is_empty() {
+        if let Some(snapshot) = backup {
+            backup_file(snapshot, path)?;
+            log::info!("Backup saved to {}", snapshot.dir().display());
         }
         if dry_run {
             log::info!("[DRY-RUN] Would update pom.xml with the above changes");
         } else {
             log::info!("Writing updated pom.xml...");
-            fs::write(path, xml_data).expect("Failed to write pom.xml");
+            write_file(path, &new_data)?;
             log::info!("✅ Successfully updated pom.xml");
         }
     } else {
         log::info!("✅ No changes needed for pom.xml - all values are already up to date");
     }
+    Ok(())
 }
 
-pub fn update_pom_xml_summary(
-    path: &str,
-    runtime_version: &str,
-    plugin_version: &str,
-    munit_version: &str,
-    dry_run: bool,
-    backup: bool,
-) -> (bool, Vec<String>) {
-    let mut xml_data = fs::read_to_string(path).expect("Failed to read pom.xml");
-    let mut changed = false;
-    let mut updated_props = Vec::new();
+/// Computes the updated pom.xml content without touching the file: the Mule
+/// version properties, the configured dependency/plugin versions, the
+/// mule-maven-plugin declaration and, if enabled, the connector versions
+/// required by the catalog.
+///
+/// Returns the new content and a description of each change.
+pub fn plan_pom_xml_update(
+    content: &str,
+    config: &MigrationConfig,
+) -> Result<(String, Vec<String>), roxmltree::Error> {
+    let updates = pom_property_updates(
+        &config.app_runtime_version,
+        &config.mule_maven_plugin_version,
+        &config.munit_version,
+    );
+    let (new_data, mut changes) = update_pom_properties(content, &updates)?;
+    let (mut new_data, updated_deps) =
+        update_pom_dependency_versions(&new_data, &config.dependencies)?;
+    changes.extend(updated_deps);
+
+    // An explicitly configured plugin version takes precedence
+    let configured = config
+        .dependencies
+        .iter()
+        .any(|d| maven_plugin::is_mule_maven_plugin(&d.group_id, &d.artifact_id));
+    if !configured {
+        let (updated, plugin_changes) = maven_plugin::plan_mule_maven_plugin_update(
+            &new_data,
+            &config.mule_maven_plugin_version,
+            config.add_mule_maven_plugin,
+        )?;
+        new_data = updated;
+        changes.extend(plugin_changes);
+    }
+    let (updated, configuration_changes) =
+        maven_plugin::plan_configuration_update(&new_data, &config.maven_plugin.configuration)?;
+    new_data = updated;
+    changes.extend(configuration_changes);
+
+    let vars = template::project_variables(config, Some(&new_data), Path::new(""));
+    let deployment: Vec<(&str, String)> = config
+        .maven_plugin
+        .deployment
+        .elements()
+        .into_iter()
+        .map(|(name, value)| (name, template::render(value, &vars)))
+        .collect();
+    let deployment: Vec<(&str, &str)> = deployment.iter().map(|(n, v)| (*n, v.as_str())).collect();
+    let (updated, deployment_changes) =
+        maven_plugin::plan_deployment_update(&new_data, &deployment)?;
+    new_data = updated;
+    changes.extend(deployment_changes);
+
+    let (updated, repository_changes) = update_pom_repositories(&new_data, &config.repositories)?;
+    new_data = updated;
+    changes.extend(repository_changes);
+
+    if config.use_connector_catalog {
+        let mut catalog = catalog::builtin_catalog();
+        catalog.extend(config.connector_catalog.iter().cloned());
+        // Explicitly configured dependency versions take precedence
+        let minimums: Vec<_> = catalog::minimum_versions(
+            &catalog,
+            &config.app_runtime_version,
+            &config.mule_artifact.java_specification_versions,
+        )
+        .into_iter()
+        .filter(|(g, a, _)| {
+            !config
+                .dependencies
+                .iter()
+                .any(|d| d.group_id == *g && d.artifact_id == *a)
+        })
+        .collect();
+        let (upgraded, updated_connectors) = upgrade_pom_connector_versions(&new_data, &minimums)?;
+        new_data = upgraded;
+        changes.extend(updated_connectors);
+    }
+
+    if config.migrate_munit {
+        let (migrated, munit_changes) = munit::plan_munit_pom_update(
+            &new_data,
+            &config.munit_version,
+            &config.app_runtime_version,
+        )?;
+        new_data = migrated;
+        changes.extend(munit_changes);
+    }
+
+    let java = match &config.maven_compiler_version {
+        Some(v) => java_compat::highest_java_version(std::slice::from_ref(v)),
+        None => {
+            java_compat::highest_java_version(&config.mule_artifact.java_specification_versions)
+        }
+    };
+    if let Some(java) = java {
+        let (updated, java_changes) =
+            plan_java_version_update(&new_data, java, &config.munit_jvm_args)?;
+        new_data = updated;
+        changes.extend(java_changes);
+    }
+    Ok((new_data, changes))
+}
+
+/// Collects the `<url>` elements of the repositories pom.xml declares, with
+/// a label for each: `<repositories>`, `<pluginRepositories>` and
+/// `<distributionManagement>`, at the top level and in profiles.
+fn repository_urls<'a, 'input>(project: Node<'a, 'input>) -> Vec<(String, Node<'a, 'input>)> {
+    let mut scopes = vec![(None, project)];
+    if let Some(profiles) = child_element(project, "profiles") {
+        for profile in child_elements(profiles, "profile") {
+            let id = child_text(profile, "id").unwrap_or("<unnamed>").to_string();
+            scopes.push((Some(id), profile));
+        }
+    }
+    let mut urls = Vec::new();
+    for (profile, scope) in scopes {
+        let mut repositories = Vec::new();
+        for (list, item) in [
+            ("repositories", "repository"),
+            ("pluginRepositories", "pluginRepository"),
+        ] {
+            if let Some(container) = child_element(scope, list) {
+                repositories.extend(child_elements(container, item));
+            }
+        }
+        if let Some(distribution) = child_element(scope, "distributionManagement") {
+            repositories.extend(
+                ["repository", "snapshotRepository"]
+                    .into_iter()
+                    .filter_map(|name| child_element(distribution, name)),
+            );
+        }
+        for repository in repositories {
+            let Some(url) = child_element(repository, "url") else {
+                continue;
+            };
+            let mut label = match child_text(repository, "id") {
+                Some(id) => format!("{} '{id}'", repository.tag_name().name()),
+                None => repository.tag_name().name().to_string(),
+            };
+            if let Some(profile) = &profile {
+                label.push_str(&format!(" (profile '{profile}')"));
+            }
+            urls.push((label, url));
+        }
+    }
+    urls
+}
+
+/// Replaces repository URLs in pom.xml according to `mappings`. A mapping
+/// applies to its `from` URL and to URLs below it; the first matching
+/// mapping wins.
+///
+/// Returns the new content and a description of each updated URL.
+pub fn update_pom_repositories(
+    content: &str,
+    mappings: &[RepositoryMapping],
+) -> Result<(String, Vec<String>), roxmltree::Error> {
+    if mappings.is_empty() {
+        return Ok((content.to_string(), Vec::new()));
+    }
+    let doc = Document::parse(content)?;
+    let mut edits = Vec::new();
+    let mut changes = Vec::new();
+    for (label, element) in repository_urls(doc.root_element()) {
+        let old = element.text().unwrap_or("").trim();
+        let new = mappings.iter().find_map(|m| {
+            let from = m.from.trim_end_matches('/');
+            let rest = old.strip_prefix(from)?;
+            (rest.is_empty() || rest.starts_with('/'))
+                .then(|| format!("{}{rest}", m.to.trim_end_matches('/')))
+        });
+        if let Some(new) = new.filter(|new| new != old) {
+            edits.push(set_element_text(content, element, &new));
+            changes.push(format!("{label} url: '{old}' -> '{new}'"));
+        }
+    }
+    Ok((apply_edits(content, edits), changes))
+}
+
+/// Checks that edited pom.xml content is still a well-formed pom.
+///
+/// # Errors
+/// Returns a description of the problem.
+pub fn verify_pom(content: &str) -> Result<(), String> {
+    let doc = Document::parse(content).map_err(|e| format!("not well-formed XML: {e}"))?;
+    let root = doc.root_element().tag_name().name();
+    if root != "project" {
+        return Err(format!("root element is <{root}> instead of <project>"));
+    }
+    Ok(())
+}
+
+/// Describes the first structural difference between two versions of an
+/// XML document: an element renamed, added or removed, or an attribute
+/// added or removed. Text and attribute values may differ freely.
+///
+/// Returns `None` if the structure is the same or `before` does not parse.
+pub fn structural_difference(before: &str, after: &str) -> Option<String> {
+    fn compare(a: Node, b: Node, path: &str) -> Option<String> {
+        if a.tag_name() != b.tag_name() {
+            return Some(format!("{path} became <{}>", b.tag_name().name()));
+        }
+        let names = |n: Node| {
+            let mut names: Vec<_> = n.attributes().map(|a| a.name().to_string()).collect();
+            names.sort();
+            names
+        };
+        if names(a) != names(b) {
+            return Some(format!("{path}: attributes changed"));
+        }
+        let a_children: Vec<Node> = a.children().filter(|n| n.is_element()).collect();
+        let b_children: Vec<Node> = b.children().filter(|n| n.is_element()).collect();
+        for (x, y) in a_children.iter().zip(&b_children) {
+            let child_path = format!("{path}/{}", x.tag_name().name());
+            if let Some(diff) = compare(*x, *y, &child_path) {
+                return Some(diff);
+            }
+        }
+        (a_children.len() != b_children.len()).then(|| {
+            format!(
+                "{path}: {} child element(s) became {}",
+                a_children.len(),
+                b_children.len()
+            )
+        })
+    }
+    let before = Document::parse(before).ok()?;
+    let after = Document::parse(after).ok()?;
+    let root = before.root_element();
+    compare(
+        root,
+        after.root_element(),
+        &format!("/{}", root.tag_name().name()),
+    )
+}
+
+/// Compiler properties holding the Java level of custom Java code.
+const COMPILER_PROPERTIES: [&str; 3] = [
+    "maven.compiler.source",
+    "maven.compiler.target",
+    "maven.compiler.release",
+];
 
-    fn update_property_value(
-        content: &mut String,
-        property_name: &str,
-        new_value: &str,
-        updated_props: &mut Vec<String>,
-    ) -> bool {
-        let pattern = format!(r#"(<{property_name}>)([^<]*)(</{property_name}>)"#);
-        let re = Regex::new(&pattern).unwrap();
-        let mut did_change = false;
-        *content = re
-            .replace_all(content, |caps: &regex::Captures| {
-                let old_value = caps.get(2).map(|m| m.as_str()).unwrap_or("");
-                if old_value.trim() != new_value {
-                    did_change = true;
-                    updated_props.push(format!(
-                        "{}: '{}' -> '{}'",
-                        property_name,
-                        old_value.trim(),
-                        new_value
-                    ));
-                    format!("{}{}{}", &caps[1], new_value, &caps[3])
+/// Changes the project's own `<version>` as `bump` says. Increments
+/// (`major`, `minor`, `patch`) are only made with `allow_increments`, so a
+/// re-run that migrates nothing does not bump again.
+///
+/// Returns the new content and a description of the change, or a message
+/// if the pom has no `<version>` of its own or it is not a version.
+pub fn plan_project_version_update(
+    content: &str,
+    bump: &VersionBump,
+    allow_increments: bool,
+) -> Result<(String, Vec<String>), String> {
+    if !allow_increments && !matches!(bump, VersionBump::Set(_)) {
+        return Ok((content.to_string(), Vec::new()));
+    }
+    let doc = Document::parse(content).map_err(|e| e.to_string())?;
+    let element = child_element(doc.root_element(), "version")
+        .ok_or("pom.xml has no <version> of its own, it inherits the parent's")?;
+    let current = element.text().unwrap_or("").trim();
+    let new = bump
+        .apply(current)
+        .ok_or_else(|| format!("project version '{current}' is not a version to increment"))?;
+    if new == current {
+        return Ok((content.to_string(), Vec::new()));
+    }
+    let edit = set_element_text(content, element, &new);
+    Ok((
+        apply_edits(content, vec![edit]),
+        vec![format!("project version: '{current}' -> '{new}'")],
+    ))
+}
+
+/// Plugins running MUnit tests in a JVM of their own.
+const MUNIT_PLUGINS: [&str; 2] = ["munit-maven-plugin", "munit-extensions-maven-plugin"];
+
+/// Aligns pom.xml with the target Java version `java` (a feature version,
+/// 8 for Java 1.8): the declared `maven.compiler.source`/`target`/`release`
+/// properties are set to it, and the `jvm_args` missing from the test JVM
+/// arguments of the MUnit plugins are added, to `<argLines>` or a single
+/// `<argLine>` (an `<argLines>` block is created if there is neither).
+///
+/// Returns the new content and a description of each change.
+pub fn plan_java_version_update(
+    content: &str,
+    java: u32,
+    jvm_args: &[String],
+) -> Result<(String, Vec<String>), roxmltree::Error> {
+    let doc = Document::parse(content)?;
+    let project = doc.root_element();
+    let unit = indent_unit(content, &doc);
+    let mut edits = Vec::new();
+    let mut changes = Vec::new();
+
+    for (scope, props) in property_scopes(project) {
+        for name in COMPILER_PROPERTIES {
+            for element in child_elements(props, name) {
+                let old = element.text().unwrap_or("").trim();
+                // 1.8 and 8 are the same level; keep the style of the pom
+                if old.starts_with("${")
+                    || old.strip_prefix("1.").unwrap_or(old) == java.to_string()
+                {
+                    continue;
+                }
+                let new = if java <= 8 && name != "maven.compiler.release" {
+                    format!("1.{java}")
                 } else {
-                    caps[0].to_string()
+                    java.to_string()
+                };
+                edits.push(set_element_text(content, element, &new));
+                let label = match &scope {
+                    Some(id) => format!("{name} (profile '{id}')"),
+                    None => name.to_string(),
+                };
+                changes.push(format!("{label}: '{old}' -> '{new}'"));
+            }
+        }
+    }
+
+    if !jvm_args.is_empty() {
+        let declarations = artifact_declarations(project);
+        for artifact_id in MUNIT_PLUGINS {
+            let plugins: Vec<Node> = declarations
+                .iter()
+                .copied()
+                .filter(|d| {
+                    d.tag_name().name() == "plugin"
+                        && child_text(*d, "artifactId") == Some(artifact_id)
+                })
+                .collect();
+            // Like the mule-maven-plugin configuration edits, prefer the
+            // declaration that already has a configuration
+            let Some(plugin) = plugins
+                .iter()
+                .find(|p| child_element(**p, "configuration").is_some())
+                .or(plugins.first())
+                .copied()
+            else {
+                continue;
+            };
+            let configuration = child_element(plugin, "configuration");
+            let arg_lines = configuration.and_then(|c| child_element(c, "argLines"));
+            let arg_line = configuration.and_then(|c| child_element(c, "argLine"));
+            let existing: Vec<&str> = match (arg_lines, arg_line) {
+                (Some(lines), _) => child_elements(lines, "argLine")
+                    .filter_map(|l| l.text())
+                    .collect(),
+                (None, Some(line)) => line.text().into_iter().collect(),
+                (None, None) => Vec::new(),
+            };
+            let missing: Vec<&str> = jvm_args
+                .iter()
+                .map(String::as_str)
+                .filter(|arg| !existing.iter().any(|e| e.contains(arg)))
+                .collect();
+            if missing.is_empty() {
+                continue;
+            }
+            let lines: Vec<String> = missing
+                .iter()
+                .map(|arg| format!("<argLine>{}</argLine>", escape_text(arg)))
+                .collect();
+            let lines_block = || {
+                let body = lines.join(&format!("\n{unit}"));
+                format!("<argLines>\n{unit}{body}\n</argLines>")
+            };
+            edits.push(match (configuration, arg_lines, arg_line) {
+                (_, Some(block), _) => insert_child(content, unit, block, &lines.join("\n")),
+                (_, None, Some(line)) => {
+                    let old = line.text().unwrap_or("").trim();
+                    let joined = missing.join(" ");
+                    let new = if old.is_empty() {
+                        joined
+                    } else {
+                        format!("{old} {joined}")
+                    };
+                    set_element_text(content, line, &new)
                 }
-            })
-            .to_string();
-        did_change
+                (Some(configuration), None, None) => {
+                    insert_child(content, unit, configuration, &lines_block())
+                }
+                (None, _, _) => {
+                    let body = lines_block().replace('\n', &format!("\n{unit}"));
+                    let snippet = format!("<configuration>\n{unit}{body}\n</configuration>");
+                    insert_child(content, unit, plugin, &snippet)
+                }
+            });
+            changes.extend(
+                missing
+                    .iter()
+                    .map(|arg| format!("{artifact_id} argLine: added '{arg}'")),
+            );
+        }
     }
+    Ok((apply_edits(content, edits), changes))
+}
 
-    changed |= update_property_value(
-        &mut xml_data,
-        "mule.version",
-        runtime_version,
-        &mut updated_props,
-    );
-    changed |= update_property_value(
-        &mut xml_data,
-        "munit.version",
-        munit_version,
-        &mut updated_props,
-    );
-    changed |= update_property_value(
-        &mut xml_data,
-        "mule.maven.plugin.version",
-        plugin_version,
-        &mut updated_props,
-    );
-    changed |= update_property_value(
-        &mut xml_data,
-        "app.runtime",
-        runtime_version,
-        &mut updated_props,
-    );
+pub fn update_pom_xml_summary(
+    path: &Path,
+    runtime_version: &str,
+    plugin_version: &str,
+    munit_version: &str,
+    dependencies: &[DependencyVersion],
+    dry_run: bool,
+    backup: Option<&Snapshot>,
+) -> Result<(bool, Vec<String>), MigrateError> {
+    let xml_data = read_file(path)?;
+    let updates = pom_property_updates(runtime_version, plugin_version, munit_version);
+    let (new_data, mut updated_props) =
+        update_pom_properties(&xml_data, &updates).map_err(parse_error(path))?;
+    let (new_data, updated_deps) =
+        update_pom_dependency_versions(&new_data, dependencies).map_err(parse_error(path))?;
+    updated_props.extend(updated_deps);
+    let changed = !updated_props.is_empty();
 
     if changed {
-        if backup {
-            let backup_path = format!("{path}.bak");
-            fs::copy(path, &backup_path).expect("Failed to create backup");
+        if let Some(snapshot) = backup {
+            backup_file(snapshot, path)?;
         }
         if !dry_run {
-            fs::write(path, xml_data).expect("Failed to write pom.xml");
+            write_file(path, &new_data)?;
         }
     }
-    (changed, updated_props)
+    Ok((changed, updated_props))
 }
 
 #[cfg(test)]
@@ -158,14 +1050,9 @@ mod tests {
         let xml = r#"<project><properties><mule.version>4.3.0</mule.version><munit.version>3.2.0</munit.version><mule.maven.plugin.version>4.1.0</mule.maven.plugin.version><app.runtime>4.2.2</app.runtime></properties></project>"#;
         let mut file = File::create(&file_path).unwrap();
         file.write_all(xml.as_bytes()).unwrap();
-        let (changed, props) = update_pom_xml_summary(
-            file_path.to_str().unwrap(),
-            "4.9.4",
-            "4.3.1",
-            "3.4.0",
-            false,
-            false,
-        );
+        let (changed, props) =
+            update_pom_xml_summary(&file_path, "4.9.4", "4.3.1", "3.4.0", &[], false, None)
+                .unwrap();
         assert!(changed);
         assert!(props.iter().any(|p| p.contains("mule.version")));
         assert!(props.iter().any(|p| p.contains("munit.version")));
@@ -182,15 +1069,309 @@ mod tests {
         let xml = r#"<project><properties><mule.version>4.9.4</mule.version><munit.version>3.4.0</munit.version><mule.maven.plugin.version>4.3.1</mule.maven.plugin.version><app.runtime>4.9.4</app.runtime></properties></project>"#;
         let mut file = File::create(&file_path).unwrap();
         file.write_all(xml.as_bytes()).unwrap();
-        let (changed, props) = update_pom_xml_summary(
-            file_path.to_str().unwrap(),
-            "4.9.4",
-            "4.3.1",
-            "3.4.0",
-            false,
-            false,
-        );
+        let (changed, props) =
+            update_pom_xml_summary(&file_path, "4.9.4", "4.3.1", "3.4.0", &[], false, None)
+                .unwrap();
         assert!(!changed);
         assert!(props.is_empty());
     }
+
+    #[test]
+    fn test_update_pom_properties_preserves_comments_and_formatting() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<project xmlns="http://maven.apache.org/POM/4.0.0">
+    <!-- <app.runtime>4.1.0</app.runtime> -->
+    <properties>
+        <app.runtime>4.4.0</app.runtime>   <!-- keep me -->
+        <munit.version/>
+    </properties>
+    <build>
+        <plugins>
+            <plugin><configuration><app.runtime>x</app.runtime></configuration></plugin>
+        </plugins>
+    </build>
+</project>
+"#;
+        let (out, props) =
+            update_pom_properties(xml, &[("app.runtime", "4.9.4"), ("munit.version", "3.4.0")])
+                .unwrap();
+        assert_eq!(props.len(), 2);
+        let expected = xml
+            .replace(
+                "<app.runtime>4.4.0</app.runtime>",
+                "<app.runtime>4.9.4</app.runtime>",
+            )
+            .replace("<munit.version/>", "<munit.version>3.4.0</munit.version>");
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_insert_pom_properties() {
+        let pom = "<project>\n    <artifactId>app</artifactId>\n    <properties>\n        <a>1</a>\n    </properties>\n</project>\n";
+        assert_eq!(
+            insert_pom_properties(pom, &[("app.runtime", "4.9.4"), ("munit.version", "3.4.0")])
+                .unwrap(),
+            "<project>\n    <artifactId>app</artifactId>\n    <properties>\n        <a>1</a>\n        <app.runtime>4.9.4</app.runtime>\n        <munit.version>3.4.0</munit.version>\n    </properties>\n</project>\n"
+        );
+        let pom = "<project>\n  <artifactId>app</artifactId>\n</project>\n";
+        assert_eq!(
+            insert_pom_properties(pom, &[("app.runtime", "4.9.4")]).unwrap(),
+            "<project>\n  <artifactId>app</artifactId>\n  <properties>\n    <app.runtime>4.9.4</app.runtime>\n  </properties>\n</project>\n"
+        );
+        let pom = "<project>\n  <artifactId>app</artifactId>\n  <build/>\n</project>\n";
+        assert_eq!(
+            insert_pom_properties(pom, &[("app.runtime", "4.9.4")]).unwrap(),
+            "<project>\n  <artifactId>app</artifactId>\n  <properties>\n    <app.runtime>4.9.4</app.runtime>\n  </properties>\n  <build/>\n</project>\n"
+        );
+    }
+
+    #[test]
+    fn test_update_pom_properties_in_profiles() {
+        let xml = r#"<project>
+  <properties><app.runtime>4.4.0</app.runtime></properties>
+  <profiles>
+    <profile>
+      <id>legacy</id>
+      <properties><app.runtime>4.3.0</app.runtime></properties>
+    </profile>
+  </profiles>
+</project>"#;
+        let (out, props) = update_pom_properties(xml, &[("app.runtime", "4.9.4")]).unwrap();
+        assert_eq!(out.matches("<app.runtime>4.9.4</app.runtime>").count(), 2);
+        assert!(props
+            .iter()
+            .any(|p| p.starts_with("app.runtime (profile 'legacy')")));
+    }
+
+    #[test]
+    fn test_update_pom_dependency_versions() {
+        let xml = r#"<project>
+  <dependencies>
+    <dependency>
+      <groupId>org.mule.connectors</groupId>
+      <artifactId>mule-http-connector</artifactId>
+      <version>1.5.0</version>
+      <classifier>mule-plugin</classifier>
+    </dependency>
+    <dependency>
+      <groupId>org.mule.connectors</groupId>
+      <artifactId>mule-db-connector</artifactId>
+      <version>${db.version}</version>
+    </dependency>
+  </dependencies>
+  <build>
+    <plugins>
+      <plugin>
+        <artifactId>maven-clean-plugin</artifactId>
+        <version>3.0.0</version>
+      </plugin>
+    </plugins>
+  </build>
+</project>"#;
+        let rules = vec![
+            DependencyVersion {
+                group_id: "org.mule.connectors".to_string(),
+                artifact_id: "mule-http-connector".to_string(),
+                version: "1.10.3".to_string(),
+            },
+            DependencyVersion {
+                group_id: "org.mule.connectors".to_string(),
+                artifact_id: "mule-db-connector".to_string(),
+                version: "1.14.0".to_string(),
+            },
+            DependencyVersion {
+                group_id: "org.apache.maven.plugins".to_string(),
+                artifact_id: "maven-clean-plugin".to_string(),
+                version: "3.3.2".to_string(),
+            },
+        ];
+        let (out, updated) = update_pom_dependency_versions(xml, &rules).unwrap();
+        assert_eq!(updated.len(), 2);
+        assert!(out.contains("<version>1.10.3</version>"));
+        assert!(out.contains("<version>${db.version}</version>"));
+        assert!(out.contains("<version>3.3.2</version>"));
+        assert!(updated
+            .iter()
+            .any(|u| u.starts_with("plugin org.apache.maven.plugins:maven-clean-plugin")));
+    }
+
+    #[test]
+    fn test_upgrade_pom_connector_versions_only_raises() {
+        let xml = r#"<project><dependencies>
+  <dependency><groupId>org.mule.connectors</groupId><artifactId>mule-http-connector</artifactId><version>1.5.0</version></dependency>
+  <dependency><groupId>org.mule.connectors</groupId><artifactId>mule-db-connector</artifactId><version>1.15.0</version></dependency>
+</dependencies></project>"#;
+        let minimums = vec![
+            (
+                "org.mule.connectors".to_string(),
+                "mule-http-connector".to_string(),
+                "1.8.0".to_string(),
+            ),
+            (
+                "org.mule.connectors".to_string(),
+                "mule-db-connector".to_string(),
+                "1.14.0".to_string(),
+            ),
+        ];
+        let (out, updated) = upgrade_pom_connector_versions(xml, &minimums).unwrap();
+        assert_eq!(
+            updated,
+            vec!["connector org.mule.connectors:mule-http-connector: '1.5.0' -> '1.8.0'"]
+        );
+        assert!(out.contains("<version>1.15.0</version>"));
+    }
+
+    #[test]
+    fn test_plan_java_version_update() {
+        let pom = r#"<project>
+  <properties>
+    <maven.compiler.source>1.8</maven.compiler.source>
+    <maven.compiler.target>1.8</maven.compiler.target>
+  </properties>
+  <build>
+    <plugins>
+      <plugin>
+        <groupId>com.mulesoft.munit</groupId>
+        <artifactId>munit-extensions-maven-plugin</artifactId>
+        <configuration>
+          <argLines>
+            <argLine>-Xmx1g</argLine>
+          </argLines>
+        </configuration>
+      </plugin>
+      <plugin>
+        <groupId>com.mulesoft.munit.tools</groupId>
+        <artifactId>munit-maven-plugin</artifactId>
+      </plugin>
+    </plugins>
+  </build>
+</project>"#;
+        let args = vec![
+            "-Xmx1g".to_string(),
+            "--add-opens=java.base/java.lang=ALL-UNNAMED".to_string(),
+        ];
+        let (out, changes) = plan_java_version_update(pom, 17, &args).unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                "maven.compiler.source: '1.8' -> '17'",
+                "maven.compiler.target: '1.8' -> '17'",
+                "munit-maven-plugin argLine: added '-Xmx1g'",
+                "munit-maven-plugin argLine: added '--add-opens=java.base/java.lang=ALL-UNNAMED'",
+                "munit-extensions-maven-plugin argLine: added '--add-opens=java.base/java.lang=ALL-UNNAMED'",
+            ]
+        );
+        assert!(out.contains(
+            "            <argLine>-Xmx1g</argLine>\n            <argLine>--add-opens=java.base/java.lang=ALL-UNNAMED</argLine>\n          </argLines>"
+        ));
+        assert!(out.contains(
+            "        <artifactId>munit-maven-plugin</artifactId>\n        <configuration>\n          <argLines>\n            <argLine>-Xmx1g</argLine>"
+        ));
+        // Already aligned: nothing left to do
+        let (again, changes) = plan_java_version_update(&out, 17, &args).unwrap();
+        assert_eq!(again, out);
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn test_plan_project_version_update() {
+        use crate::version::BumpKind;
+        let pom = "<project>\n  <parent><version>2.0.0</version></parent>\n  <version>1.4.2-SNAPSHOT</version>\n</project>";
+        let minor = VersionBump::Bump(BumpKind::Minor);
+        let (out, changes) = plan_project_version_update(pom, &minor, true).unwrap();
+        assert_eq!(
+            out,
+            "<project>\n  <parent><version>2.0.0</version></parent>\n  <version>1.5.0-SNAPSHOT</version>\n</project>"
+        );
+        assert_eq!(
+            changes,
+            vec!["project version: '1.4.2-SNAPSHOT' -> '1.5.0-SNAPSHOT'"]
+        );
+        let (_, changes) = plan_project_version_update(pom, &minor, false).unwrap();
+        assert!(changes.is_empty());
+        assert!(plan_project_version_update(
+            "<project><version>${revision}</version></project>",
+            &minor,
+            true
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_update_pom_repositories() {
+        let pom = r#"<project>
+  <repositories>
+    <repository>
+      <id>mulesoft-releases</id>
+      <url>https://repository.mulesoft.org/releases/</url>
+    </repository>
+    <repository>
+      <id>anypoint-exchange</id>
+      <url>https://maven.anypoint.mulesoft.com/api/v3/maven</url>
+    </repository>
+  </repositories>
+  <distributionManagement>
+    <repository>
+      <id>releases</id>
+      <url>https://repository.mulesoft.org/releases-ee</url>
+    </repository>
+  </distributionManagement>
+  <profiles>
+    <profile>
+      <id>ci</id>
+      <pluginRepositories>
+        <pluginRepository>
+          <id>mulesoft-plugins</id>
+          <url>https://repository.mulesoft.org/releases/plugins</url>
+        </pluginRepository>
+      </pluginRepositories>
+    </profile>
+  </profiles>
+</project>"#;
+        let mappings = vec![
+            RepositoryMapping {
+                from: "https://repository.mulesoft.org/releases".to_string(),
+                to: "https://repository.mulesoft.org/nexus/content/repositories/releases/"
+                    .to_string(),
+            },
+            RepositoryMapping {
+                from: "https://maven.anypoint.mulesoft.com/api/v3/maven".to_string(),
+                to: "https://maven.anypoint.mulesoft.com/api/v3/maven".to_string(),
+            },
+        ];
+        let (out, changes) = update_pom_repositories(pom, &mappings).unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                "repository 'mulesoft-releases' url: 'https://repository.mulesoft.org/releases/' -> 'https://repository.mulesoft.org/nexus/content/repositories/releases/'",
+                "pluginRepository 'mulesoft-plugins' (profile 'ci') url: 'https://repository.mulesoft.org/releases/plugins' -> 'https://repository.mulesoft.org/nexus/content/repositories/releases/plugins'",
+            ]
+        );
+        // Only whole path segments match
+        assert!(out.contains("<url>https://repository.mulesoft.org/releases-ee</url>"));
+    }
+
+    #[test]
+    fn test_verify_pom_edits() {
+        let before = r#"<project><properties><app.runtime>4.4.0</app.runtime></properties><dependencies><dependency><artifactId>a</artifactId></dependency></dependencies></project>"#;
+        // Text-only changes are fine
+        let after = before.replace("4.4.0", "4.9.4");
+        assert_eq!(verify_pom(&after), Ok(()));
+        assert_eq!(structural_difference(before, &after), None);
+
+        let renamed = before.replace("<artifactId>a</artifactId>", "<artifact>a</artifact>");
+        assert_eq!(
+            structural_difference(before, &renamed),
+            Some("/project/dependencies/dependency/artifactId became <artifact>".to_string())
+        );
+        let removed = before.replace("<dependency><artifactId>a</artifactId></dependency>", "");
+        assert_eq!(
+            structural_difference(before, &removed),
+            Some("/project/dependencies: 1 child element(s) became 0".to_string())
+        );
+        let broken = before.replace("</properties>", "");
+        assert!(verify_pom(&broken)
+            .unwrap_err()
+            .starts_with("not well-formed XML"));
+    }
 }
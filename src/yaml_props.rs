@@ -0,0 +1,203 @@
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// Default files searched for YAML properties.
+pub const DEFAULT_YAML_FILES: &str = "src/main/resources/**/*.{yaml,yml}";
+
+/// Sets a property in Mule YAML property files.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct YamlPropertyRule {
+    /// Dotted key path, e.g. `http.listener.port`. Nested mappings and keys
+    /// that contain dots themselves (`"http.port": 8081`) both match.
+    pub key: String,
+    /// Scalar value to set.
+    pub value: String,
+    /// Glob (relative to the project root) of the files to update.
+    #[serde(default = "default_yaml_files")]
+    pub files: String,
+}
+
+fn default_yaml_files() -> String {
+    DEFAULT_YAML_FILES.to_string()
+}
+
+/// Splits a `key: value # comment` line into (indent, key, value range).
+fn parse_entry(line: &str) -> Option<(usize, &str, std::ops::Range<usize>)> {
+    let indent = line.len() - line.trim_start().len();
+    let rest = &line[indent..];
+    if rest.is_empty() || rest.starts_with('#') || rest.starts_with('-') {
+        return None;
+    }
+    let (key, colon) = match rest.chars().next()? {
+        quote @ ('"' | '\'') => {
+            let end = rest[1..].find(quote)? + 1;
+            (&rest[1..end], rest[end + 1..].find(':')? + end + 1)
+        }
+        _ => {
+            let colon = rest
+                .find(": ")
+                .or_else(|| rest.strip_suffix(':').map(|r| r.len()))?;
+            (rest[..colon].trim_end(), colon)
+        }
+    };
+    let after = indent + colon + 1;
+    let value_start = after + (line[after..].len() - line[after..].trim_start().len());
+    Some((indent, key, value_start..value_end(line, value_start)))
+}
+
+/// End of a scalar value: before a trailing ` # comment` outside quotes.
+fn value_end(line: &str, start: usize) -> usize {
+    let value = &line[start..];
+    let mut quote = None;
+    let mut prev_space = true;
+    for (i, c) in value.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') if i == 0 => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '#') if prev_space => return start + value[..i].trim_end().len(),
+            _ => {}
+        }
+        prev_space = c.is_whitespace();
+    }
+    start + value.trim_end().len()
+}
+
+fn double_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Renders `value` in the quoting style of `old`, quoting plain values that
+/// would not parse back as the same string.
+fn quote_like(old: &str, value: &str) -> String {
+    match old.chars().next() {
+        Some('"') => double_quote(value),
+        Some('\'') => format!("'{}'", value.replace('\'', "''")),
+        _ if value.is_empty()
+            || value.contains(": ")
+            || value.contains(" #")
+            || value.starts_with(|c: char| "!&*{}[]|>%@`\"'#,?:-".contains(c)) =>
+        {
+            double_quote(value)
+        }
+        _ => value.to_string(),
+    }
+}
+
+/// Unquotes a scalar for comparison.
+fn unquote(raw: &str) -> &str {
+    raw.strip_prefix('"')
+        .and_then(|r| r.strip_suffix('"'))
+        .or_else(|| raw.strip_prefix('\'').and_then(|r| r.strip_suffix('\'')))
+        .unwrap_or(raw)
+}
+
+/// Sets scalar properties in YAML content line by line, so comments,
+/// ordering and formatting are preserved. Block scalars and sequences are
+/// left untouched.
+///
+/// Returns the new content, a description of each change and the keys that
+/// were not found as single-line scalars.
+pub fn plan_yaml_update<'a>(
+    content: &str,
+    updates: &[(&'a str, &str)],
+) -> (String, Vec<String>, Vec<&'a str>) {
+    let mut stack: Vec<(usize, String)> = Vec::new();
+    let mut found = vec![false; updates.len()];
+    let mut changes = Vec::new();
+    let mut out = String::with_capacity(content.len());
+    // Indent of the key owning the block scalar (`|`/`>`) being skipped
+    let mut block_indent = None;
+    for line in content.split_inclusive('\n') {
+        let body = line.trim_end_matches(['\n', '\r']);
+        let line_indent = body.len() - body.trim_start().len();
+        if let Some(indent) = block_indent {
+            if body.trim().is_empty() || line_indent > indent {
+                out.push_str(line);
+                continue;
+            }
+            block_indent = None;
+        }
+        let Some((indent, key, range)) = parse_entry(body) else {
+            out.push_str(line);
+            continue;
+        };
+        while stack.last().is_some_and(|(i, _)| *i >= indent) {
+            stack.pop();
+        }
+        let path = stack
+            .iter()
+            .map(|(_, k)| k.as_str())
+            .chain([key])
+            .collect::<Vec<_>>()
+            .join(".");
+        let raw = &body[range.clone()];
+        if raw.is_empty() {
+            stack.push((indent, key.to_string()));
+            out.push_str(line);
+            continue;
+        }
+        if raw.starts_with(['|', '>']) {
+            block_indent = Some(indent);
+            out.push_str(line);
+            continue;
+        }
+        match updates.iter().position(|(k, _)| *k == path) {
+            Some(i) => {
+                found[i] = true;
+                let new_value = updates[i].1;
+                if unquote(raw) == new_value {
+                    out.push_str(line);
+                    continue;
+                }
+                let rendered = quote_like(raw, new_value);
+                changes.push(format!("{path}: '{}' -> '{new_value}'", unquote(raw)));
+                out.push_str(&body[..range.start]);
+                out.push_str(&rendered);
+                out.push_str(&line[range.end..]);
+            }
+            None => out.push_str(line),
+        }
+    }
+    let missing = updates
+        .iter()
+        .zip(found)
+        .filter(|(_, f)| !f)
+        .map(|((k, _), _)| *k)
+        .collect();
+    (out, changes, missing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_yaml_update_preserves_comments() {
+        let yaml = "# HTTP settings\nhttp:\n  listener:\n    host: \"0.0.0.0\" # all interfaces\n    port: 8081\n\"db.url\": jdbc:old\nsecure:\n  key: |\n    port: 1\n";
+        let (updated, changes, missing) = plan_yaml_update(
+            yaml,
+            &[
+                ("http.listener.port", "8082"),
+                ("http.listener.host", "localhost"),
+                ("db.url", "jdbc:new"),
+                ("secure.key", "x"),
+                ("http.timeout", "30"),
+            ],
+        );
+        assert_eq!(
+            updated,
+            "# HTTP settings\nhttp:\n  listener:\n    host: \"localhost\" # all interfaces\n    port: 8082\n\"db.url\": jdbc:new\nsecure:\n  key: |\n    port: 1\n"
+        );
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[0], "http.listener.host: '0.0.0.0' -> 'localhost'");
+        assert_eq!(missing, vec!["secure.key", "http.timeout"]);
+    }
+
+    #[test]
+    fn test_quote_like() {
+        assert_eq!(quote_like("8081", "8082"), "8082");
+        assert_eq!(quote_like("'a'", "it's"), "'it''s'");
+        assert_eq!(quote_like("x", "*ref"), "\"*ref\"");
+    }
+}